@@ -58,6 +58,8 @@ pub struct Operating {
     pub db_path: String,
     pub keys_path: Option<String>,
     pub chain: ChainType,
+    pub pruning: String,
+    pub pruning_history: u64,
 }
 
 #[derive(Deserialize)]
@@ -85,6 +87,7 @@ pub struct Network {
     pub min_peers: usize,
     pub max_peers: usize,
     pub sync: bool,
+    pub sync_mode: String,
     pub parcel_relay: bool,
     pub discovery: bool,
     pub discovery_type: String,
@@ -111,6 +114,7 @@ fn default_enable_devel_api() -> bool {
 pub struct Snapshot {
     pub disable: bool,
     pub path: String,
+    pub retention: usize,
 }
 
 #[derive(Deserialize)]
@@ -221,6 +225,12 @@ impl Operating {
         if let Some(chain) = matches.value_of("chain") {
             self.chain = chain.parse()?;
         }
+        if let Some(pruning) = matches.value_of("pruning") {
+            self.pruning = pruning.to_string();
+        }
+        if let Some(pruning_history) = matches.value_of("pruning-history") {
+            self.pruning_history = pruning_history.parse().map_err(|_| "Invalid pruning-history")?;
+        }
         Ok(())
     }
 }
@@ -288,6 +298,9 @@ impl Network {
         if matches.is_present("no-sync") {
             self.sync = false;
         }
+        if let Some(sync_mode) = matches.value_of("sync-mode") {
+            self.sync_mode = sync_mode.to_string();
+        }
         if matches.is_present("no-parcel-relay") {
             self.parcel_relay = false;
         }
@@ -337,6 +350,12 @@ impl Snapshot {
         if let Some(snapshot_path) = matches.value_of("snapshot-path") {
             self.path = snapshot_path.to_string();
         }
+        if let Some(retention) = matches.value_of("snapshot-retention") {
+            self.retention = retention.parse().map_err(|_| "Invalid snapshot-retention")?;
+        }
+        if self.retention == 0 {
+            return Err("snapshot-retention must be at least 1".to_string())
+        }
         Ok(())
     }
 }
@@ -27,6 +27,7 @@ extern crate serde_derive;
 
 extern crate app_dirs;
 extern crate codechain_core as ccore;
+extern crate codechain_crypto as ccrypto;
 extern crate codechain_discovery as cdiscovery;
 extern crate codechain_key as ckey;
 extern crate codechain_keystore as ckeystore;
@@ -38,6 +39,7 @@ extern crate codechain_rpc as crpc;
 extern crate codechain_state as cstate;
 extern crate codechain_sync as csync;
 extern crate codechain_types as ctypes;
+extern crate codechain_vm as cvm;
 extern crate ctrlc;
 extern crate env_logger;
 extern crate fdlimit;
@@ -52,6 +54,8 @@ mod config;
 mod constants;
 mod rpc;
 mod rpc_apis;
+mod script_command;
+mod snapshot_command;
 
 use std::fs;
 use std::path::Path;
@@ -60,8 +64,8 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use app_dirs::AppInfo;
 use ccore::{
-    AccountProvider, Client, ClientService, EngineType, Miner, MinerOptions, MinerService, ShardValidator,
-    ShardValidatorConfig, Spec, Stratum, StratumConfig, StratumError,
+    AccountProvider, Client, ClientConfig, ClientService, EngineType, Miner, MinerOptions, MinerService,
+    ShardValidator, ShardValidatorConfig, Spec, Stratum, StratumConfig, StratumError,
 };
 use cdiscovery::{KademliaConfig, KademliaExtension, UnstructuredConfig, UnstructuredExtension};
 use ckey::Password;
@@ -80,6 +84,8 @@ use primitives::H256;
 use self::account_command::run_account_command;
 use self::config::load_config;
 use self::rpc::{rpc_http_start, rpc_ipc_start};
+use self::script_command::run_script_command;
+use self::snapshot_command::run_snapshot_command;
 
 pub const APP_INFO: AppInfo = AppInfo {
     name: "codechain",
@@ -127,7 +133,11 @@ pub fn discovery_start(service: &NetworkService, cfg: &config::Network) -> Resul
 pub fn client_start(cfg: &config::Config, spec: &Spec, miner: Arc<Miner>) -> Result<ClientService, String> {
     info!("Starting client");
     let client_path = Path::new(&cfg.operating.db_path);
-    let client_config = Default::default();
+    let client_config = ClientConfig {
+        pruning: cfg.operating.pruning.parse()?,
+        history: cfg.operating.pruning_history,
+        ..Default::default()
+    };
     let service = ClientService::start(client_config, &spec, &client_path, miner)
         .map_err(|e| format!("Client service error: {}", e))?;
 
@@ -188,6 +198,10 @@ fn run_subcommand(matches: ArgMatches) -> Result<(), String> {
     let subcommand = matches.subcommand.unwrap();
     if subcommand.name == "account" {
         run_account_command(subcommand.matches)
+    } else if subcommand.name == "script" {
+        run_script_command(subcommand.matches)
+    } else if subcommand.name == "snapshot" {
+        run_snapshot_command(subcommand.matches)
     } else {
         Err("Invalid subcommand".to_string())
     }
@@ -287,6 +301,17 @@ fn run_node(matches: ArgMatches) -> Result<(), String> {
     let config = load_config(&matches)?;
 
     let spec = config.operating.chain.spec()?;
+    if !config.snapshot.disable {
+        // The state of a snapshot is written a period after its block, and the writing must end before the next
+        // checkpoint. The history isn't much larger than that, since its states are kept in memory.
+        let min_history = 2 * spec.params().snapshot_period;
+        if config.operating.pruning != "archive" && config.operating.pruning_history < min_history {
+            return Err(format!(
+                "pruning-history must be at least {} to write snapshots. Otherwise use --no-snapshot",
+                min_history
+            ))
+        }
+    }
 
     let instance_id = config.operating.instance_id.unwrap_or(
         SystemTime::now()
@@ -328,7 +353,17 @@ fn run_node(matches: ArgMatches) -> Result<(), String> {
             }
 
             if config.network.sync {
-                let sync = BlockSyncExtension::new(client.client());
+                let snapshot_dir = if config.snapshot.disable {
+                    None
+                } else {
+                    Some(config.snapshot.path.clone())
+                };
+                let snapshot_sync = match config.network.sync_mode.as_ref() {
+                    "full" => false,
+                    "snapshot" => true,
+                    sync_mode => return Err(format!("Unknown sync mode {}", sync_mode)),
+                };
+                let sync = BlockSyncExtension::new(client.client(), snapshot_dir, snapshot_sync);
                 service.register_extension(sync.clone())?;
                 client.client().add_notify(sync.clone());
             }
@@ -385,7 +420,12 @@ fn run_node(matches: ArgMatches) -> Result<(), String> {
 
     let _snapshot_service = {
         if !config.snapshot.disable {
-            let service = SnapshotService::new(client.client(), config.snapshot.path, spec.params().snapshot_period);
+            let service = SnapshotService::new(
+                client.client(),
+                config.snapshot.path,
+                spec.params().snapshot_period,
+                config.snapshot.retention,
+            );
             client.client().add_notify(service.clone());
             Some(service)
         } else {
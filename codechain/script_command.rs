@@ -0,0 +1,66 @@
+// Copyright 2018 Kodebox, Inc.
+// This file is part of CodeChain.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::fs;
+
+use ccrypto::blake256;
+use ckey::hex::{FromHex, ToHex};
+use clap::ArgMatches;
+use cvm::{assemble, disassemble, AssemblerError, EncoderError};
+
+pub fn run_script_command(matches: ArgMatches) -> Result<(), String> {
+    if matches.subcommand.is_none() {
+        println!("{}", matches.usage());
+        return Ok(())
+    }
+
+    match matches.subcommand() {
+        ("assemble", Some(matches)) => {
+            let source_path = matches.value_of("SOURCE_PATH").expect("SOURCE_PATH arg is required and its index is 1");
+            let source = fs::read_to_string(source_path).map_err(|e| format!("{}", e))?;
+            let bytecode = assemble(&source).map_err(|e| match e {
+                AssemblerError::Encoder(EncoderError::BlobTooLong(len)) => {
+                    format!("The blob of {} bytes is too long to be encoded", len)
+                }
+                e => format!("{:?}", e),
+            })?;
+            println!("0x{}", bytecode.to_hex());
+            Ok(())
+        }
+        ("disassemble", Some(matches)) => {
+            let bytecode = read_bytecode(matches)?;
+            let source = disassemble(&bytecode).map_err(|e| format!("{:?}", e))?;
+            println!("{}", source);
+            Ok(())
+        }
+        ("hash", Some(matches)) => {
+            let bytecode = read_bytecode(matches)?;
+            println!("0x{}", blake256(&bytecode).to_hex());
+            Ok(())
+        }
+        _ => Err("Invalid subcommand".to_string()),
+    }
+}
+
+fn read_bytecode(matches: &ArgMatches) -> Result<Vec<u8>, String> {
+    let val = matches.value_of("BYTECODE").expect("BYTECODE arg is required and its index is 1");
+    let hex = if val.starts_with("0x") {
+        &val[2..]
+    } else {
+        &val[..]
+    };
+    hex.from_hex().map_err(|e| format!("{}", e))
+}
@@ -0,0 +1,123 @@
+// Copyright 2018 Kodebox, Inc.
+// This file is part of CodeChain.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::str::FromStr;
+
+use ccore::{BlockChainClient, BlockId, ChainInfo, ClientService, Miner, MinerOptions};
+use clap::ArgMatches;
+use clogger::{self, LoggerConfig};
+use csync::{create_snapshot, restore_snapshot, Snapshot, SnapshotBlock};
+use primitives::H256;
+
+use super::client_start;
+use super::config::{load_config, Config};
+
+pub fn run_snapshot_command(matches: ArgMatches) -> Result<(), String> {
+    if matches.subcommand.is_none() {
+        println!("{}", matches.usage());
+        return Ok(())
+    }
+
+    clogger::init(&LoggerConfig::new(0)).expect("Logger must be successfully initialized");
+
+    let config = load_config(&matches)?;
+    let root_dir = config.snapshot.path.clone();
+
+    match matches.subcommand() {
+        ("list", _) => {
+            let snapshots = Snapshot::list(&root_dir).map_err(|e| format!("{}", e))?;
+            let mut manifests: Vec<_> =
+                snapshots.iter().map(|snapshot| (snapshot.block_hash(), snapshot.read_manifest())).collect();
+            manifests
+                .sort_unstable_by_key(|(_, manifest)| manifest.as_ref().ok().map(|manifest| manifest.block_number));
+            for (hash, manifest) in manifests {
+                match manifest {
+                    Ok(manifest) => println!(
+                        "#{} 0x{:x} ({} chunks, {} bytes)",
+                        manifest.block_number,
+                        hash,
+                        manifest.chunks.len(),
+                        manifest.chunks_size()
+                    ),
+                    Err(e) => println!("0x{:x} ({})", hash, e),
+                }
+            }
+            Ok(())
+        }
+        ("verify", Some(matches)) => {
+            let snapshot = Snapshot::new(&root_dir, &read_block_hash(matches)?);
+            snapshot.verify().map_err(|e| format!("{}", e))?;
+            println!("0x{:x} is valid", snapshot.block_hash());
+            Ok(())
+        }
+        ("restore", Some(matches)) => {
+            let snapshot = Snapshot::new(&root_dir, &read_block_hash(matches)?);
+            // Verify all the chunks before writing anything into the database.
+            snapshot.verify().map_err(|e| format!("{}", e))?;
+
+            let service = start_client(&config)?;
+            let client = service.client();
+            if client.chain_info().best_block_number != 0 {
+                return Err(format!("{} already has blocks. Restore into a fresh database", config.operating.db_path))
+            }
+            let hash = restore_snapshot(&client, &snapshot).map_err(|e| format!("{}", e))?;
+            let number = client.block_number(BlockId::Hash(hash)).expect("Restored block must exist");
+            println!("Restored #{} 0x{:x}", number, hash);
+            Ok(())
+        }
+        ("create", Some(matches)) => {
+            let service = start_client(&config)?;
+            let client = service.client();
+            let hash = client.block_hash(read_block_id(matches)?).ok_or("The block is not found")?;
+            let snapshot_block = SnapshotBlock::from_client(&*client, &hash).map_err(|e| format!("{}", e))?;
+            let state_db = client.state_db();
+            let snapshot =
+                create_snapshot(state_db.as_hashdb(), &root_dir, &snapshot_block).map_err(|e| format!("{}", e))?;
+            println!("Created #{} 0x{:x}", snapshot_block.block.header.number(), snapshot.block_hash());
+            Ok(())
+        }
+        _ => Err("Invalid subcommand".to_string()),
+    }
+}
+
+fn start_client(config: &Config) -> Result<ClientService, String> {
+    let spec = config.operating.chain.spec()?;
+    let miner = Miner::new(MinerOptions::default(), &spec, None);
+    client_start(config, &spec, miner)
+}
+
+fn read_block_hash(matches: &ArgMatches) -> Result<H256, String> {
+    let val = matches.value_of("BLOCK_HASH").expect("BLOCK_HASH arg is required and its index is 1");
+    parse_hash(val)
+}
+
+fn read_block_id(matches: &ArgMatches) -> Result<BlockId, String> {
+    let val = matches.value_of("at").expect("at arg is required");
+    if val.starts_with("0x") {
+        Ok(BlockId::Hash(parse_hash(val)?))
+    } else {
+        Ok(BlockId::Number(val.parse().map_err(|_| "Invalid block number")?))
+    }
+}
+
+fn parse_hash(val: &str) -> Result<H256, String> {
+    let hex = if val.starts_with("0x") {
+        &val[2..]
+    } else {
+        &val[..]
+    };
+    H256::from_str(hex).map_err(|_| "Invalid block hash".to_string())
+}
@@ -155,7 +155,15 @@ impl<'x> OpenBlock<'x> {
             return Err(StateError::Parcel(ParcelError::ParcelAlreadyImported).into())
         }
 
-        let outcomes = self.block.state.apply(&parcel, parcel.sender(), &parcel.public_key())?;
+        let outcomes = self.block.state.apply(
+            &parcel,
+            parcel.sender(),
+            &parcel.public_key(),
+            parcel.fee_payer(),
+            self.block.header.number(),
+            self.block.header.timestamp(),
+            self.engine.params().max_script_cost,
+        )?;
 
         self.block.parcels_set.insert(h.unwrap_or_else(|| parcel.hash()));
         self.block.parcels.push(parcel.into());
@@ -171,6 +179,19 @@ impl<'x> OpenBlock<'x> {
                     .invoices
                     .push(invoices.into_iter().map(|outcome| outcome.invoice).collect::<Vec<Invoice>>().into());
             }
+            ParcelOutcome::Batch(outcomes) => {
+                let invoices = outcomes
+                    .into_iter()
+                    .map(|outcome| match outcome {
+                        ParcelOutcome::Single {
+                            invoice,
+                            ..
+                        } => invoice,
+                        _ => unreachable!("Only the actions that have a single invoice can be batched"),
+                    })
+                    .collect::<Vec<Invoice>>();
+                self.block.invoices.push(invoices.into());
+            }
         }
         Ok(())
     }
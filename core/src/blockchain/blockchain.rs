@@ -23,7 +23,7 @@ use ctypes::transaction::Transaction;
 use ctypes::BlockNumber;
 use kvdb::{DBTransaction, KeyValueDB};
 use parking_lot::RwLock;
-use primitives::H256;
+use primitives::{H256, U256};
 use rlp::RlpStream;
 
 use super::super::blockchain_info::BlockChainInfo;
@@ -124,6 +124,27 @@ impl BlockChain {
         ImportRoute::new(&hash, &location)
     }
 
+    /// Inserts the block whose state is restored from a snapshot and makes it the best block.
+    /// Unlike `insert_block`, its ancestors don't need to be known.
+    /// Its invoices are unknown because its parcels are never executed.
+    pub fn insert_bootstrap_block(&self, batch: &mut DBTransaction, bytes: &[u8], total_score: U256) -> ImportRoute {
+        let block = BlockView::new(bytes);
+        let header = block.header_view();
+        let hash = header.hash();
+
+        assert!(self.pending_best_block_hash.read().is_none());
+
+        let location = BlockLocation::CanonChain;
+        self.headerchain.insert_bootstrap_header(batch, &header, total_score);
+        self.body_db.insert_body(batch, &block, &location);
+        self.invoice_db.insert_invoice(batch, &hash, Vec::new());
+
+        batch.put(db::COL_EXTRA, BEST_BLOCK_KEY, &hash);
+        *self.pending_best_block_hash.write() = Some(hash);
+
+        ImportRoute::new(&hash, &location)
+    }
+
     /// Apply pending insertion updates
     pub fn commit(&self) {
         self.headerchain.commit();
@@ -21,7 +21,7 @@ use std::sync::Arc;
 use ctypes::BlockNumber;
 use kvdb::{DBTransaction, KeyValueDB};
 use parking_lot::RwLock;
-use primitives::{Bytes, H256};
+use primitives::{Bytes, H256, U256};
 use rlp_compress::{blocks_swapper, compress, decompress};
 
 use super::super::db::{self, CacheUpdatePolicy, Readable, Writable};
@@ -135,6 +135,44 @@ impl HeaderChain {
         Some(location)
     }
 
+    /// Inserts the header whose ancestors may be unknown and makes it the best header.
+    /// The total score can't be calculated without the ancestors, so it's given.
+    /// If the header is already known, does nothing.
+    pub fn insert_bootstrap_header(&self, batch: &mut DBTransaction, header: &HeaderView, total_score: U256) {
+        let hash = header.hash();
+
+        if self.is_known_header(&hash) {
+            return
+        }
+
+        assert!(self.pending_best_header_hash.read().is_none());
+
+        let compressed_header = compress(header.rlp().as_raw(), blocks_swapper());
+        batch.put(db::COL_HEADERS, &hash, &compressed_header);
+
+        let mut new_hashes = HashMap::new();
+        new_hashes.insert(header.number(), hash);
+        let mut new_details = HashMap::new();
+        new_details.insert(
+            hash,
+            BlockDetails {
+                number: header.number(),
+                total_score,
+                parent: header.parent_hash(),
+                children: vec![],
+            },
+        );
+
+        batch.put(db::COL_EXTRA, BEST_HEADER_KEY, &hash);
+        *self.pending_best_header_hash.write() = Some(hash);
+
+        let mut pending_hashes = self.pending_hashes.write();
+        let mut pending_details = self.pending_details.write();
+
+        batch.extend_with_cache(db::COL_EXTRA, &mut *pending_details, new_details, CacheUpdatePolicy::Overwrite);
+        batch.extend_with_cache(db::COL_EXTRA, &mut *pending_hashes, new_hashes, CacheUpdatePolicy::Overwrite);
+    }
+
     /// Apply pending insertion updates
     pub fn commit(&self) {
         let mut pending_best_header_hash = self.pending_best_header_hash.write();
@@ -25,17 +25,19 @@ use cmerkle::{Result as TrieResult, TrieFactory, TrieSpec};
 use cnetwork::NodeId;
 use cstate::{
     ActionHandler, Asset, AssetAddress, AssetScheme, AssetSchemeAddress, StateDB, TopBackend, TopLevelState,
-    TopStateInfo,
+    TopStateInfo, World,
 };
 use ctypes::invoice::ParcelInvoice;
 use ctypes::parcel::ChangeShard;
 use ctypes::transaction::Transaction;
-use ctypes::{BlockNumber, ShardId};
+use ctypes::{BlockNumber, ShardId, WorldId};
+use cvm::ScriptTrace;
 use journaldb;
 use kvdb::{DBTransaction, KeyValueDB};
-use parking_lot::{Mutex, RwLock};
+use parking_lot::{Mutex, RwLock, RwLockReadGuard};
 use primitives::{Bytes, H256, U256};
 use rlp::{Encodable, UntrustedRlp};
+use unexpected::Mismatch;
 
 use super::super::block::{enact, ClosedBlock, Drain, IsBlock, LockedBlock, OpenBlock, SealedBlock};
 use super::super::blockchain::{
@@ -45,7 +47,7 @@ use super::super::blockchain::{
 use super::super::consensus::epoch::Transition as EpochTransition;
 use super::super::consensus::CodeChainEngine;
 use super::super::encoded;
-use super::super::error::{BlockImportError, Error, ImportError, SpecError};
+use super::super::error::{BlockError, BlockImportError, Error, ImportError, SpecError};
 use super::super::header::Header;
 use super::super::miner::{Miner, MinerService};
 use super::super::parcel::{LocalizedParcel, SignedParcel, UnverifiedParcel};
@@ -63,6 +65,7 @@ use super::{
 };
 
 const MAX_MEM_POOL_SIZE: usize = 4096;
+const PRUNING_KEY: &[u8] = b"pruning";
 
 pub struct Client {
     engine: Arc<CodeChainEngine>,
@@ -101,7 +104,10 @@ impl Client {
 
         let trie_factory = TrieFactory::new(trie_spec);
 
-        let journal_db = journaldb::new(db.clone(), journaldb::Algorithm::Archive, ::db::COL_STATE);
+        let journal_db = journaldb::new(db.clone(), config.pruning, ::db::COL_STATE);
+        if !journal_db.is_empty() {
+            check_pruning(&*db, config.pruning)?;
+        }
         let mut state_db = StateDB::new(journal_db, config.state_cache_size, spec.custom_handlers.clone());
         if !spec.check_genesis_root(state_db.as_hashdb()) {
             return Err(SpecError::InvalidState.into())
@@ -111,6 +117,7 @@ impl Client {
             state_db = spec.ensure_genesis_state(state_db, &trie_factory)?;
             let mut batch = DBTransaction::new();
             state_db.journal_under(&mut batch, 0, &spec.genesis_header().hash())?;
+            batch.put(::db::COL_EXTRA, PRUNING_KEY, config.pruning.as_internal_name_str().as_bytes());
             db.write(batch).map_err(ClientError::Database)?;
         }
 
@@ -169,6 +176,51 @@ impl Client {
         self.importer.import_verified_blocks(self)
     }
 
+    /// Imports the block whose state is restored from a snapshot and makes it the best block.
+    /// The parcels of the block aren't executed, so the state of the block must be in the database already.
+    /// Its ancestors don't need to be known, but the parent header is needed to verify the parcels root.
+    pub fn import_bootstrap_block(
+        &self,
+        bytes: &[u8],
+        parent: &Header,
+        total_score: U256,
+    ) -> Result<H256, BlockImportError> {
+        let header = BlockView::new(bytes).header_view();
+        let hash = header.hash();
+        let route = {
+            let _import_lock = self.importer.import_lock.lock();
+            let chain = self.chain.read();
+            if chain.is_known(&hash) {
+                return Err(BlockImportError::Import(ImportError::AlreadyInChain))
+            }
+            if parent.hash() != header.parent_hash() {
+                return Err(BlockImportError::Block(BlockError::InvalidParentHash(Mismatch {
+                    expected: header.parent_hash(),
+                    found: parent.hash(),
+                })))
+            }
+            verification::verify_parcels_root(bytes, &header.parcels_root(), *parent.parcels_root())?;
+            if !self.state_db.read().as_hashdb().contains(&header.state_root()) {
+                return Err(BlockImportError::Import(ImportError::StateNotFound))
+            }
+
+            let mut batch = DBTransaction::new();
+            let route = chain.insert_bootstrap_block(&mut batch, bytes, total_score);
+            self.db.read().write_buffered(batch);
+            chain.commit();
+            route
+        };
+        cinfo!(CLIENT, "Imported the bootstrap block #{} ({})", header.number(), hash);
+
+        let (enacted, retracted) = self.importer.calculate_enacted_retracted(&[route]);
+        self.importer.miner.chain_new_blocks(self, &[hash], &[], &enacted, &retracted);
+        self.notify(|notify| {
+            notify.new_blocks(vec![hash], vec![], enacted.clone(), retracted.clone(), vec![], 0);
+        });
+        self.db.read().flush().expect("DB flush failed.");
+        Ok(hash)
+    }
+
     fn block_hash(chain: &BlockChain, id: BlockId) -> Option<H256> {
         match id {
             BlockId::Hash(hash) => Some(hash),
@@ -250,6 +302,30 @@ impl Client {
             TopLevelState::from_existing(db, root, self.trie_factory.clone()).ok()
         })
     }
+
+    /// Get a reference to the `StateDB`.
+    pub fn state_db(&self) -> RwLockReadGuard<StateDB> {
+        self.state_db.read()
+    }
+}
+
+/// Fails if the database was created with another pruning algorithm, which can't read the state of the database.
+/// The databases created before the algorithm was written are archives.
+fn check_pruning(db: &KeyValueDB, requested: journaldb::Algorithm) -> Result<(), ClientError> {
+    let database = match db.get(::db::COL_EXTRA, PRUNING_KEY).map_err(ClientError::Database)? {
+        Some(name) => journaldb::Algorithm::all_types()
+            .into_iter()
+            .find(|algorithm| algorithm.as_internal_name_str().as_bytes() == &*name)
+            .ok_or_else(|| ClientError::UnknownPruning(String::from_utf8_lossy(&name).into_owned()))?,
+        None => journaldb::Algorithm::Archive,
+    };
+    if database != requested {
+        return Err(ClientError::IncompatiblePruning {
+            database,
+            requested,
+        })
+    }
+    Ok(())
 }
 
 impl DatabaseClient for Client {
@@ -301,14 +377,43 @@ impl AssetClient for Client {
                     Some(Transaction::AssetTransfer {
                         outputs,
                         ..
+                    }) => index < outputs.len() && shard_id == outputs[index].shard_id,
+                    Some(Transaction::AssetIncreaseSupply {
+                        asset_type,
+                        ..
                     }) => {
-                        index < outputs.len()
-                            && shard_id
-                                == AssetSchemeAddress::from_hash(outputs[index].asset_type)
-                                    .expect("An asset type must be able to create an AssetSchemeAddress")
-                                    .shard_id()
+                        // A failed transaction may have an asset type that is not an asset scheme address.
+                        index == 0
+                            && AssetSchemeAddress::from_hash(asset_type)
+                                .map_or(false, |address| address.shard_id() == shard_id)
                     }
-                    None => false,
+                    Some(Transaction::AssetCompose {
+                        shard_id: asset_compose_shard_id,
+                        ..
+                    }) => index == 0 && shard_id == asset_compose_shard_id,
+                    Some(Transaction::AssetDecompose {
+                        outputs,
+                        ..
+                    }) => index < outputs.len() && shard_id == outputs[index].shard_id,
+                    Some(Transaction::CreateWorld {
+                        ..
+                    })
+                    | Some(Transaction::SetWorldOwners {
+                        ..
+                    })
+                    | Some(Transaction::AssetChangeRegistrar {
+                        ..
+                    })
+                    | Some(Transaction::AssetFreeze {
+                        ..
+                    })
+                    | Some(Transaction::AssetUnfreeze {
+                        ..
+                    })
+                    | Some(Transaction::AssetUnwrapCCC {
+                        ..
+                    })
+                    | None => false,
                 };
 
                 if !is_output_valid {
@@ -329,13 +434,50 @@ impl AssetClient for Client {
 }
 
 impl ExecuteClient for Client {
-    fn execute_transactions(&self, transactions: &[Transaction]) -> Result<Vec<ChangeShard>, Error> {
+    fn execute_transactions(&self, transactions: &[Transaction], sender: &Address) -> Result<Vec<ChangeShard>, Error> {
         let state = Client::state_at(&self, BlockId::Latest).expect("Latest state MUST exist");
         let mut shard_ids: Vec<ShardId> = transactions.iter().flat_map(Transaction::related_shards).collect();
         shard_ids.sort_unstable();
         shard_ids.dedup();
 
-        Ok(shard_ids.iter().flat_map(|shard_id| state.apply_transactions(transactions, *shard_id)).collect())
+        // The transactions are executed as if they were included in the next block.
+        let best_header = self.best_block_header();
+        let block_number = best_header.number() + 1;
+        let block_timestamp = best_header.timestamp();
+        let max_script_cost = self.common_params().max_script_cost;
+        Ok(shard_ids
+            .iter()
+            .flat_map(|shard_id| {
+                state.apply_transactions(
+                    transactions,
+                    *shard_id,
+                    sender,
+                    block_number,
+                    block_timestamp,
+                    max_script_cost,
+                )
+            })
+            .collect())
+    }
+
+    fn trace_transfer_input(
+        &self,
+        transaction: &Transaction,
+        input_index: usize,
+        block_id: BlockId,
+    ) -> Result<Option<ScriptTrace>, Error> {
+        let (state, header) = match (Client::state_at(&self, block_id), self.block_header(block_id)) {
+            (Some(state), Some(header)) => (state, header),
+            _ => return Ok(None),
+        };
+        let max_script_cost = self.common_params().max_script_cost;
+        Ok(state.trace_transfer_input(
+            transaction,
+            input_index,
+            header.number() + 1,
+            header.timestamp(),
+            max_script_cost,
+        )?)
     }
 }
 
@@ -516,6 +658,14 @@ impl BlockChainClient for Client {
     fn custom_handlers(&self) -> Vec<Arc<ActionHandler>> {
         self.state_db.read().custom_handlers().to_vec()
     }
+
+    fn custom_action_data(&self, handler_id: u64, query: &[u8], state: StateOrBlock) -> Option<Bytes> {
+        let handler = self.state_db.read().custom_handler(handler_id)?;
+        match state {
+            StateOrBlock::State(s) => handler.query(query, &*s).ok()?,
+            StateOrBlock::Block(id) => handler.query(query, &self.state_at(id)?).ok()?,
+        }
+    }
 }
 
 pub struct Importer {
@@ -536,6 +686,9 @@ pub struct Importer {
 
     /// CodeChain engine to be used during import
     pub engine: Arc<CodeChainEngine>,
+
+    /// The number of the recent blocks whose states are kept when the state is pruned
+    history: u64,
 }
 
 impl Importer {
@@ -566,6 +719,7 @@ impl Importer {
             header_queue,
             miner,
             engine,
+            history: config.history,
         })
     }
 
@@ -698,6 +852,16 @@ impl Importer {
         self.check_epoch_end_signal(&header, &chain, &mut batch);
 
         state.journal_under(&mut batch, number, hash).expect("DB commit failed");
+        // A pruning database keeps the nodes of the last `history` blocks in memory, so the memory use grows with it.
+        if number >= self.history {
+            let ancient_number = number - self.history;
+            match chain.block_hash(ancient_number) {
+                Some(ancient_hash) => {
+                    state.mark_canonical(&mut batch, ancient_number, &ancient_hash).expect("DB commit failed");
+                }
+                None => cdebug!(CLIENT, "Missing expected hash for block {}", ancient_number),
+            }
+        }
         let route = chain.insert_block(&mut batch, block_data, invoices.clone());
 
         let is_canon = route.enacted.last().map_or(false, |h| h == hash);
@@ -993,6 +1157,39 @@ impl Shard for Client {
         };
         state.shard_root(shard_id).ok()?
     }
+
+    fn shard_owners(&self, shard_id: ShardId, state: StateOrBlock) -> Option<Vec<Address>> {
+        let state = match state {
+            StateOrBlock::State(s) => s,
+            StateOrBlock::Block(id) => Box::new(self.state_at(id)?),
+        };
+        state.shard_owners(shard_id).ok()?
+    }
+
+    fn shard_users(&self, shard_id: ShardId, state: StateOrBlock) -> Option<Vec<Address>> {
+        let state = match state {
+            StateOrBlock::State(s) => s,
+            StateOrBlock::Block(id) => Box::new(self.state_at(id)?),
+        };
+        state.shard_users(shard_id).ok()?
+    }
+
+    fn number_of_worlds(&self, shard_id: ShardId, state: StateOrBlock) -> Option<WorldId> {
+        let state = match state {
+            StateOrBlock::State(s) => s,
+            StateOrBlock::Block(id) => Box::new(self.state_at(id)?),
+        };
+        let shard_metadata = state.shard_metadata(shard_id).ok()??;
+        Some(*shard_metadata.number_of_worlds())
+    }
+
+    fn world(&self, shard_id: ShardId, world_id: WorldId, state: StateOrBlock) -> Option<World> {
+        let state = match state {
+            StateOrBlock::State(s) => s,
+            StateOrBlock::Block(id) => Box::new(self.state_at(id)?),
+        };
+        state.world(shard_id, world_id).ok()?
+    }
 }
 
 impl ReopenBlock for Client {
@@ -17,6 +17,7 @@
 use std::path::Path;
 use std::str::FromStr;
 
+use journaldb;
 use kvdb_rocksdb::CompactionProfile;
 
 use super::super::verification::{QueueConfig, VerifierType};
@@ -69,6 +70,10 @@ pub struct ClientConfig {
     pub queue: QueueConfig,
     /// Fat DB enabled?
     pub fat_db: bool,
+    /// The JournalDB algorithm of the state
+    pub pruning: journaldb::Algorithm,
+    /// The number of the recent blocks whose states are kept when the state is pruned
+    pub history: u64,
     /// RocksDB column cache-size if not default
     pub db_cache_size: Option<usize>,
     /// State db compaction profile
@@ -88,6 +93,8 @@ impl Default for ClientConfig {
         Self {
             queue: Default::default(),
             fat_db: false,
+            pruning: journaldb::Algorithm::Archive,
+            history: 64,
             db_cache_size: Default::default(),
             db_compaction: Default::default(),
             db_wal: true,
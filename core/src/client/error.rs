@@ -16,6 +16,7 @@
 
 use std::fmt::{Display, Error as FmtError, Formatter};
 
+use journaldb;
 use kvdb;
 use util_error::UtilError;
 
@@ -26,6 +27,13 @@ pub enum Error {
     Database(kvdb::Error),
     /// Util error
     Util(UtilError),
+    /// The database was created with another pruning algorithm
+    IncompatiblePruning {
+        database: journaldb::Algorithm,
+        requested: journaldb::Algorithm,
+    },
+    /// The database was created with a pruning algorithm that this version doesn't know
+    UnknownPruning(String),
 }
 
 impl From<UtilError> for Error {
@@ -48,6 +56,15 @@ impl Display for Error {
         match self {
             Error::Util(err) => write!(f, "{}", err),
             Error::Database(s) => write!(f, "Database error: {}", s),
+            Error::IncompatiblePruning {
+                database,
+                requested,
+            } => write!(
+                f,
+                "The database was created with the {} pruning, and it can't be opened with the {} pruning",
+                database, requested
+            ),
+            Error::UnknownPruning(name) => write!(f, "The database was created with the unknown {} pruning", name),
         }
     }
 }
@@ -32,11 +32,12 @@ use std::sync::Arc;
 use ckey::{Address, Public};
 use cmerkle::Result as TrieResult;
 use cnetwork::NodeId;
-use cstate::{ActionHandler, Asset, AssetScheme, AssetSchemeAddress, TopStateInfo};
+use cstate::{ActionHandler, Asset, AssetScheme, AssetSchemeAddress, TopStateInfo, World};
 use ctypes::invoice::{Invoice, ParcelInvoice};
 use ctypes::parcel::ChangeShard;
 use ctypes::transaction::Transaction;
-use ctypes::{BlockNumber, ShardId};
+use ctypes::{BlockNumber, ShardId, WorldId};
+use cvm::ScriptTrace;
 use kvdb::KeyValueDB;
 use primitives::{Bytes, H256, U256};
 
@@ -163,6 +164,14 @@ pub trait Shard {
     fn number_of_shards(&self, state: StateOrBlock) -> Option<ShardId>;
 
     fn shard_root(&self, shard_id: ShardId, state: StateOrBlock) -> Option<H256>;
+
+    fn shard_owners(&self, shard_id: ShardId, state: StateOrBlock) -> Option<Vec<Address>>;
+
+    fn shard_users(&self, shard_id: ShardId, state: StateOrBlock) -> Option<Vec<Address>>;
+
+    fn number_of_worlds(&self, shard_id: ShardId, state: StateOrBlock) -> Option<WorldId>;
+
+    fn world(&self, shard_id: ShardId, world_id: WorldId, state: StateOrBlock) -> Option<World>;
 }
 
 /// Provides methods to access account info
@@ -219,6 +228,9 @@ pub trait BlockChainClient: Sync + Send + AccountData + BlockChain + ImportBlock
     fn transaction_invoice(&self, id: TransactionId) -> Option<Invoice>;
 
     fn custom_handlers(&self) -> Vec<Arc<ActionHandler>>;
+
+    /// Asks the custom action handler of `handler_id` to read its data at the given state.
+    fn custom_action_data(&self, handler_id: u64, query: &[u8], state: StateOrBlock) -> Option<Bytes>;
 }
 
 /// Result of import block operation.
@@ -269,5 +281,18 @@ pub trait AssetClient {
 }
 
 pub trait ExecuteClient {
-    fn execute_transactions(&self, transactions: &[Transaction]) -> Result<Vec<ChangeShard>, CoreError>;
+    fn execute_transactions(
+        &self,
+        transactions: &[Transaction],
+        sender: &Address,
+    ) -> Result<Vec<ChangeShard>, CoreError>;
+
+    /// Traces the scripts of the `input_index`-th input of an asset transfer
+    /// as if the transfer were included in the block right after `block_id`.
+    fn trace_transfer_input(
+        &self,
+        transaction: &Transaction,
+        input_index: usize,
+        block_id: BlockId,
+    ) -> Result<Option<ScriptTrace>, CoreError>;
 }
@@ -199,6 +199,7 @@ impl TestBlockChainClient {
                     nonce: U256::zero(),
                     fee: U256::from(10),
                     network_id: 0u64,
+                    expiration: None,
                     action: Action::ChangeShardState {
                         transactions: vec![],
                         changes: vec![],
@@ -267,6 +268,7 @@ impl TestBlockChainClient {
             nonce: U256::zero(),
             fee: U256::from(10),
             network_id: 0u64,
+            expiration: None,
             action: Action::ChangeShardState {
                 transactions,
                 changes: vec![],
@@ -519,6 +521,10 @@ impl BlockChainClient for TestBlockChainClient {
     fn custom_handlers(&self) -> Vec<Arc<ActionHandler>> {
         unimplemented!()
     }
+
+    fn custom_action_data(&self, _handler_id: u64, _query: &[u8], _state: StateOrBlock) -> Option<Bytes> {
+        unimplemented!()
+    }
 }
 
 impl super::EngineClient for TestBlockChainClient {
@@ -54,13 +54,22 @@ impl CodeChainMachine {
     }
 
     /// Does basic verification of the parcel.
-    pub fn verify_parcel_basic(&self, p: &UnverifiedParcel, _header: &Header) -> Result<(), Error> {
+    pub fn verify_parcel_basic(&self, p: &UnverifiedParcel, header: &Header) -> Result<(), Error> {
         if p.fee < self.params.min_parcel_cost {
             return Err(StateError::Parcel(ParcelError::InsufficientFee {
                 minimal: self.params.min_parcel_cost,
                 got: p.fee,
             }).into())
         }
+        if let Some(expiration) = p.expiration {
+            if expiration.is_expired(header.number(), header.timestamp()) {
+                return Err(StateError::Parcel(ParcelError::Expired {
+                    expiration,
+                    block_number: header.number(),
+                    timestamp: header.timestamp(),
+                }).into())
+            }
+        }
         p.verify_basic(self.params()).map_err(StateError::from)?;
 
         Ok(())
@@ -68,7 +77,11 @@ impl CodeChainMachine {
 
     /// Verify a particular parcel is valid, regardless of order.
     pub fn verify_parcel_unordered(&self, p: UnverifiedParcel, _header: &Header) -> Result<SignedParcel, Error> {
-        Ok(SignedParcel::new(p)?)
+        let p = SignedParcel::new(p)?;
+        if p.is_sponsored() && p.fee_payer() == p.sender() {
+            return Err(StateError::Parcel(ParcelError::InvalidFeePayer).into())
+        }
+        Ok(p)
     }
 
     /// Does verification of the parcel against the parent state.
@@ -37,6 +37,8 @@ pub enum ImportError {
     AlreadyQueued,
     /// Already marked as bad from a previous import (could mean parent is bad).
     KnownBad,
+    /// The state of the block isn't in the database.
+    StateNotFound,
 }
 
 impl fmt::Display for ImportError {
@@ -45,6 +47,7 @@ impl fmt::Display for ImportError {
             ImportError::AlreadyInChain => "block already in chain",
             ImportError::AlreadyQueued => "block already in the block queue",
             ImportError::KnownBad => "block known to be bad",
+            ImportError::StateNotFound => "state of the block not found",
         };
 
         f.write_fmt(format_args!("Block import error ({})", msg))
@@ -120,6 +123,7 @@ pub enum BlockError {
 pub enum SpecError {
     InvalidCommonParams,
     InvalidState,
+    DuplicatedActionHandlerId(u64),
 }
 
 impl fmt::Display for SpecError {
@@ -128,6 +132,7 @@ impl fmt::Display for SpecError {
         let msg: String = match self {
             InvalidCommonParams => "Common params are not matched with gensis block".into(),
             InvalidState => "Genesis state is not same with spec".into(),
+            DuplicatedActionHandlerId(id) => format!("Action handler id {} is registered more than once", id),
         };
         f.write_fmt(format_args!("Spec file error ({})", msg))
     }
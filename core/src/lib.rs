@@ -27,6 +27,7 @@ extern crate codechain_network as cnetwork;
 extern crate codechain_state as cstate;
 extern crate codechain_stratum as cstratum;
 extern crate codechain_types as ctypes;
+extern crate codechain_vm as cvm;
 extern crate cuckoo;
 extern crate hashdb;
 extern crate heapsize;
@@ -80,8 +81,9 @@ mod tests;
 pub use account_provider::{AccountProvider, SignError as AccountProviderError};
 pub use block::Block;
 pub use client::{
-    AssetClient, Balance, BlockChainClient, BlockInfo, ChainInfo, ChainNotify, Client, DatabaseClient, EngineClient,
-    EngineInfo, ExecuteClient, ImportBlock, MiningBlockChainClient, Nonce, RegularKey, Shard, TestBlockChainClient,
+    AssetClient, Balance, BlockChainClient, BlockInfo, ChainInfo, ChainNotify, Client, ClientConfig, DatabaseClient,
+    EngineClient, EngineInfo, ExecuteClient, ImportBlock, MiningBlockChainClient, Nonce, RegularKey, Shard,
+    TestBlockChainClient,
 };
 pub use consensus::EngineType;
 pub use db::COL_STATE;
@@ -223,6 +223,7 @@ mod tests {
                 signatures: vec![],
             },
             network_id: 0u64,
+            expiration: None,
         };
         SignedParcel::new_with_sign(parcel, keypair.private())
     }
@@ -189,18 +189,35 @@ impl MemPoolItem {
         self.parcel.nonce
     }
 
-    fn sender(&self) -> &Address {
-        self.parcel.sender()
+    /// The account whose nonce orders the parcel and whose balance pays the cost.
+    fn fee_payer(&self) -> &Address {
+        self.parcel.fee_payer()
     }
 
     fn cost(&self) -> U256 {
-        match &self.parcel.action {
-            Action::Payment {
-                amount,
-                ..
-            } => self.parcel.fee + *amount,
-            _ => self.parcel.fee,
+        // The sender of a sponsored parcel pays the amount, which the fee payer's balance doesn't need to cover.
+        if self.parcel.is_sponsored() {
+            return self.parcel.fee
         }
+        self.parcel.fee + spent_balance(&self.parcel.action)
+    }
+}
+
+/// The balance of the sender that the action spends besides the fee.
+fn spent_balance(action: &Action) -> U256 {
+    match action {
+        Action::Payment {
+            amount,
+            ..
+        } => *amount,
+        Action::WrapCCC {
+            amount,
+            ..
+        } => U256::from(*amount),
+        Action::Batch {
+            actions,
+        } => actions.iter().map(spent_balance).fold(U256::zero(), |sum, amount| sum.saturating_add(amount)),
+        _ => U256::zero(),
     }
 }
 
@@ -268,7 +285,7 @@ impl ParcelSet {
                         "All parcels in `self.by_priority` and `self.by_address` are kept in sync with `by_hash`.",
                     )
                 })
-                .map(|parcel| (*parcel.sender(), parcel.nonce()))
+                .map(|parcel| (*parcel.fee_payer(), parcel.nonce()))
                 .collect()
         };
 
@@ -438,6 +455,7 @@ impl MemPool {
     }
 
     /// Add signed parcel to pool to be verified and imported.
+    /// `timestamp` is the timestamp of the best block.
     ///
     /// NOTE details_provider methods should be cheap to compute
     /// otherwise it might open up an attack vector.
@@ -446,6 +464,7 @@ impl MemPool {
         parcel: SignedParcel,
         origin: ParcelOrigin,
         time: PoolingInstant,
+        timestamp: u64,
         fetch_account: &F,
     ) -> Result<ParcelImportResult, ParcelError>
     where
@@ -454,7 +473,7 @@ impl MemPool {
             let hash = parcel.hash();
             let closed_parcel = parcel.clone();
 
-            let result = self.add_internal(parcel, origin, time, fetch_account);
+            let result = self.add_internal(parcel, origin, time, timestamp, fetch_account);
             match result {
                 Ok(ParcelImportResult::Current) => {
                     self.local_parcels.mark_pending(hash);
@@ -472,12 +491,13 @@ impl MemPool {
             }
             result
         } else {
-            self.add_internal(parcel, origin, time, fetch_account)
+            self.add_internal(parcel, origin, time, timestamp, fetch_account)
         }
     }
 
-    /// Checks the current nonce for all parcels' senders in the pool and removes the old parcels.
-    pub fn remove_old<F>(&mut self, fetch_account: &F, current_time: PoolingInstant)
+    /// Checks the current nonce for all parcels' senders in the pool and removes the old and the expired parcels.
+    /// `current_timestamp` is the timestamp of the best block.
+    pub fn remove_old<F>(&mut self, fetch_account: &F, current_time: PoolingInstant, current_timestamp: u64)
     where
         F: Fn(&Address) -> AccountDetails, {
         let senders = self
@@ -506,7 +526,7 @@ impl MemPool {
                 }
 
                 if time_diff > balance_check {
-                    return match senders.get(&parcel.sender()) {
+                    return match senders.get(&parcel.fee_payer()) {
                         Some(details) if parcel.cost() > details.balance => Some(*hash),
                         _ => None,
                     }
@@ -515,8 +535,18 @@ impl MemPool {
                 None
             })
             .collect::<Vec<_>>();
+        // Clear parcels that can't be included in the next block, whose timestamp is later than the best block's
+        let expired = self
+            .by_hash
+            .iter()
+            .filter(|&(_, ref parcel)| parcel.parcel.is_expired(current_time + 1, current_timestamp + 1))
+            .map(|(hash, _)| *hash)
+            .collect::<Vec<_>>();
         let fetch_nonce =
             |a: &Address| senders.get(a).expect("We fetch details for all senders from both current and future").nonce;
+        for hash in expired {
+            self.remove(&hash, &fetch_nonce, RemovalReason::Invalid);
+        }
         for hash in invalid {
             self.remove(&hash, &fetch_nonce, RemovalReason::Invalid);
         }
@@ -538,7 +568,7 @@ impl MemPool {
         }
 
         let parcel = parcel.expect("None is tested in early-exit condition above; qed");
-        let sender = *parcel.sender();
+        let sender = *parcel.fee_payer();
         let nonce = parcel.nonce();
         let current_nonce = fetch_nonce(&sender);
 
@@ -669,21 +699,29 @@ impl MemPool {
         parcel: SignedParcel,
         origin: ParcelOrigin,
         time: PoolingInstant,
+        timestamp: u64,
         fetch_account: &F,
     ) -> Result<ParcelImportResult, ParcelError>
     where
         F: Fn(&Address) -> AccountDetails, {
-        if origin != ParcelOrigin::Local && parcel.fee < self.minimal_fee {
+        // A batch pays the minimal fee for each of its actions.
+        let minimal_fee = match &parcel.action {
+            Action::Batch {
+                actions,
+            } => self.minimal_fee * U256::from(cmp::max(actions.len(), 1)),
+            _ => self.minimal_fee,
+        };
+        if origin != ParcelOrigin::Local && parcel.fee < minimal_fee {
             ctrace!(
                 MEM_POOL,
                 "Dropping parcel below minimal fee: {:?} (gp: {} < {})",
                 parcel.hash(),
                 parcel.fee,
-                self.minimal_fee
+                minimal_fee
             );
 
             return Err(ParcelError::InsufficientFee {
-                minimal: self.minimal_fee,
+                minimal: minimal_fee,
                 got: parcel.fee,
             })
         }
@@ -704,7 +742,7 @@ impl MemPool {
             })
         }
 
-        let client_account = fetch_account(&parcel.sender());
+        let client_account = fetch_account(&parcel.fee_payer());
         if client_account.balance < parcel.fee {
             ctrace!(
                 MEM_POOL,
@@ -715,11 +753,23 @@ impl MemPool {
             );
 
             return Err(ParcelError::InsufficientBalance {
-                address: *parcel.sender(),
+                address: *parcel.fee_payer(),
                 cost: parcel.fee,
                 balance: client_account.balance,
             })
         }
+        // The parcel will be included in the next block, whose timestamp is later than the best block's.
+        if let Some(expiration) = parcel.expiration {
+            if expiration.is_expired(time + 1, timestamp + 1) {
+                ctrace!(MEM_POOL, "Dropping expired parcel: {:?} (expiration: {})", parcel.hash(), expiration);
+
+                return Err(ParcelError::Expired {
+                    expiration,
+                    block_number: time + 1,
+                    timestamp: timestamp + 1,
+                })
+            }
+        }
         parcel.check_low_s()?;
         // No invalid parcels beyond this point.
         let id = self.next_parcel_id;
@@ -747,7 +797,7 @@ impl MemPool {
             return Err(ParcelError::ParcelAlreadyImported)
         }
 
-        let address = *parcel.sender();
+        let address = *parcel.fee_payer();
         let nonce = parcel.nonce();
         let hash = parcel.hash();
 
@@ -997,7 +1047,7 @@ impl MemPool {
     ) -> bool {
         let order = ParcelOrder::for_parcel(&parcel, base_nonce);
         let hash = parcel.hash();
-        let address = *parcel.sender();
+        let address = *parcel.fee_payer();
         let nonce = parcel.nonce();
 
         let old_hash = by_hash.insert(hash, parcel);
@@ -1116,8 +1166,8 @@ pub mod test {
     use std::cmp::Ordering;
 
     use ckey::{Generator, Random};
-    use ctypes::parcel::{ChangeShard, Parcel};
-    use ctypes::transaction::{AssetMintOutput, Transaction};
+    use ctypes::parcel::{ChangeShard, Expiration, Parcel};
+    use ctypes::transaction::{AssetMintOutput, AssetOutPoint, AssetTransferInput, Transaction};
 
     use super::*;
 
@@ -1139,6 +1189,7 @@ pub mod test {
             nonce: U256::zero(),
             fee,
             network_id: 200,
+            expiration: None,
             action: Action::ChangeShardState {
                 transactions: vec![],
                 changes: vec![],
@@ -1160,6 +1211,7 @@ pub mod test {
         let transactions = vec![Transaction::AssetMint {
             network_id: 200,
             shard_id,
+            world_id: 0,
             metadata: "Metadata".to_string(),
             output: AssetMintOutput {
                 lock_script_hash: H256::zero(),
@@ -1173,6 +1225,7 @@ pub mod test {
             nonce: U256::zero(),
             fee,
             network_id: 200,
+            expiration: None,
             action: Action::ChangeShardState {
                 transactions,
                 changes: vec![ChangeShard {
@@ -1199,6 +1252,7 @@ pub mod test {
             Transaction::AssetMint {
                 network_id: 200,
                 shard_id,
+                world_id: 0,
                 metadata: "Metadata".to_string(),
                 output: AssetMintOutput {
                     lock_script_hash: H256::zero(),
@@ -1220,6 +1274,64 @@ pub mod test {
             nonce: U256::zero(),
             fee,
             network_id: 200,
+            expiration: None,
+            action: Action::ChangeShardState {
+                transactions,
+                changes: vec![ChangeShard {
+                    shard_id,
+                    pre_root: H256::zero(),
+                    post_root: H256::zero(),
+                }],
+                signatures: vec![],
+            },
+        };
+        let keypair = Random.generate().unwrap();
+        let signed = SignedParcel::new_with_sign(parcel, keypair.private());
+        let item = MemPoolItem::new(signed, ParcelOrigin::Local, 0, 0);
+
+        assert_eq!(fee, item.cost());
+    }
+
+    #[test]
+    fn compose_and_decompose_transactions_do_not_increase_cost() {
+        let shard_id = 0;
+
+        let fee = U256::from(100);
+        let transactions = vec![
+            Transaction::AssetCompose {
+                network_id: 200,
+                shard_id,
+                world_id: 0,
+                metadata: "Metadata".to_string(),
+                registrar: None,
+                inputs: vec![],
+                lock_script_hash: H256::zero(),
+                parameters: vec![],
+                amount: 1,
+                nonce: 0,
+            },
+            Transaction::AssetDecompose {
+                network_id: 200,
+                input: AssetTransferInput {
+                    prev_out: AssetOutPoint {
+                        transaction_hash: H256::zero(),
+                        index: 0,
+                        asset_type: H256::zero(),
+                        shard_id,
+                        amount: 1,
+                    },
+                    lock_script: vec![],
+                    unlock_script: vec![],
+                },
+                outputs: vec![],
+                nonce: 0,
+            },
+        ];
+        let parcel = Parcel {
+            nonce: U256::zero(),
+            fee,
+            network_id: 200,
+            expiration: None,
             action: Action::ChangeShardState {
                 transactions,
                 changes: vec![ChangeShard {
@@ -1247,6 +1359,7 @@ pub mod test {
             nonce: U256::zero(),
             fee,
             network_id: 200,
+            expiration: None,
             action: Action::Payment {
                 receiver,
                 amount,
@@ -1258,6 +1371,169 @@ pub mod test {
         assert_eq!(fee + amount, item.cost());
     }
 
+    #[test]
+    fn sponsored_parcel_is_ordered_by_fee_payer() {
+        let sender = Random.generate().unwrap();
+        let fee_payer = Random.generate().unwrap();
+        let fee = U256::from(100);
+        let parcel = Parcel {
+            nonce: 3.into(),
+            fee,
+            network_id: 200,
+            expiration: None,
+            action: Action::Payment {
+                receiver: Address::random(),
+                amount: 100000.into(),
+            },
+        };
+        let signed = SignedParcel::new_with_sponsor(parcel, sender.private(), fee_payer.private());
+        assert_eq!(fee, MemPoolItem::new(signed.clone(), ParcelOrigin::Local, 0, 0).cost());
+
+        let fee_payer_address = fee_payer.address();
+        let fetch_account = |address: &Address| -> AccountDetails {
+            if address == &fee_payer_address {
+                AccountDetails {
+                    nonce: 3.into(),
+                    balance: fee,
+                }
+            } else {
+                AccountDetails {
+                    nonce: 0.into(),
+                    balance: 0.into(),
+                }
+            }
+        };
+        let mut mem_pool = MemPool::new();
+        assert_eq!(Ok(ParcelImportResult::Current), mem_pool.add(signed, ParcelOrigin::External, 0, 0, &fetch_account));
+        assert_eq!(Some(3.into()), mem_pool.last_nonce(&fee_payer_address));
+        assert_eq!(None, mem_pool.last_nonce(&sender.address()));
+    }
+
+    #[test]
+    fn wrap_ccc_increases_cost() {
+        let fee = U256::from(100);
+        let amount = 100000;
+        let keypair = Random.generate().unwrap();
+        let parcel = Parcel {
+            nonce: U256::zero(),
+            fee,
+            network_id: 200,
+            expiration: None,
+            action: Action::WrapCCC {
+                shard_id: 0,
+                lock_script_hash: H256::zero(),
+                parameters: vec![],
+                amount,
+            },
+        };
+        let signed = SignedParcel::new_with_sign(parcel, keypair.private());
+        let item = MemPoolItem::new(signed, ParcelOrigin::Local, 0, 0);
+
+        assert_eq!(fee + U256::from(amount), item.cost());
+    }
+
+    #[test]
+    fn batch_pays_minimal_fee_and_amount_of_every_action() {
+        let keypair = Random.generate().unwrap();
+        let payment = |amount: u64| Action::Payment {
+            receiver: Address::random(),
+            amount: amount.into(),
+        };
+        let parcel = |fee: u64| {
+            let parcel = Parcel {
+                nonce: U256::zero(),
+                fee: fee.into(),
+                network_id: 200,
+                expiration: None,
+                action: Action::Batch {
+                    actions: vec![payment(1000), payment(2000), Action::CreateShard],
+                },
+            };
+            SignedParcel::new_with_sign(parcel, keypair.private())
+        };
+        assert_eq!(U256::from(3300), MemPoolItem::new(parcel(300), ParcelOrigin::Local, 0, 0).cost());
+
+        let fetch_account = |_: &Address| -> AccountDetails {
+            AccountDetails {
+                nonce: 0.into(),
+                balance: 1000000.into(),
+            }
+        };
+        let mut mem_pool = MemPool::new();
+        mem_pool.set_minimal_fee(100.into());
+        assert_eq!(
+            Err(ParcelError::InsufficientFee {
+                minimal: 300.into(),
+                got: 299.into(),
+            }),
+            mem_pool.add(parcel(299), ParcelOrigin::External, 0, 0, &fetch_account)
+        );
+        assert_eq!(
+            Ok(ParcelImportResult::Current),
+            mem_pool.add(parcel(300), ParcelOrigin::External, 0, 0, &fetch_account)
+        );
+    }
+
+    #[test]
+    fn expired_parcel_is_rejected_and_purged() {
+        let keypair = Random.generate().unwrap();
+        let fee = U256::from(100);
+        let parcel = |nonce: u64, expiration: Expiration| {
+            let parcel = Parcel {
+                nonce: nonce.into(),
+                fee,
+                network_id: 200,
+                expiration: Some(expiration),
+                action: Action::Payment {
+                    receiver: Address::random(),
+                    amount: 0.into(),
+                },
+            };
+            SignedParcel::new_with_sign(parcel, keypair.private())
+        };
+        let fetch_account = |_: &Address| -> AccountDetails {
+            AccountDetails {
+                nonce: 0.into(),
+                balance: 1000000.into(),
+            }
+        };
+        // The best block is #10 at timestamp 99.
+        let mut mem_pool = MemPool::new();
+        assert_eq!(
+            Err(ParcelError::Expired {
+                expiration: Expiration::BlockNumber(10),
+                block_number: 11,
+                timestamp: 100,
+            }),
+            mem_pool.add(parcel(0, Expiration::BlockNumber(10)), ParcelOrigin::Local, 10, 99, &fetch_account)
+        );
+        assert_eq!(
+            Ok(ParcelImportResult::Current),
+            mem_pool.add(parcel(0, Expiration::BlockNumber(11)), ParcelOrigin::Local, 10, 99, &fetch_account)
+        );
+        assert_eq!(
+            Err(ParcelError::Expired {
+                expiration: Expiration::Timestamp(99),
+                block_number: 11,
+                timestamp: 100,
+            }),
+            mem_pool.add(parcel(1, Expiration::Timestamp(99)), ParcelOrigin::Local, 10, 99, &fetch_account)
+        );
+        assert_eq!(
+            Ok(ParcelImportResult::Current),
+            mem_pool.add(parcel(1, Expiration::Timestamp(100)), ParcelOrigin::Local, 10, 99, &fetch_account)
+        );
+        assert_eq!(
+            Ok(ParcelImportResult::Current),
+            mem_pool.add(parcel(2, Expiration::BlockNumber(12)), ParcelOrigin::Local, 10, 99, &fetch_account)
+        );
+
+        // The best block is #11 at timestamp 100.
+        mem_pool.remove_old(&fetch_account, 11, 100);
+        assert_eq!(0, mem_pool.status().pending);
+        assert_eq!(1, mem_pool.status().future);
+    }
+
     #[test]
     fn test_fee_per_byte_order_simple() {
         let order1 = create_parcel_order(U256::from(1000_000_000), 100);
@@ -1304,6 +1580,7 @@ pub mod test {
             nonce: U256::zero(),
             fee,
             network_id: 200,
+            expiration: None,
             action: Action::ChangeShardState {
                 transactions: vec![transaction; transaction_count],
                 changes: vec![ChangeShard {
@@ -206,6 +206,7 @@ impl Miner {
     ) -> Vec<Result<ParcelImportResult, Error>> {
         let best_block_header = client.best_block_header().decode();
         let insertion_time = client.chain_info().best_block_number;
+        let timestamp = client.chain_info().best_block_timestamp;
         let mut inserted = Vec::with_capacity(parcels.len());
 
         let results = parcels
@@ -249,8 +250,9 @@ impl Miner {
                             }
                         };
                         let hash = parcel.hash();
-                        let result =
-                            mem_pool.add(parcel, origin, insertion_time, &fetch_account).map_err(StateError::from)?;
+                        let result = mem_pool
+                            .add(parcel, origin, insertion_time, timestamp, &fetch_account)
+                            .map_err(StateError::from)?;
 
                         inserted.push(hash);
                         Ok(result)
@@ -610,9 +612,9 @@ impl MinerService for Miner {
                 nonce: chain.latest_nonce(a),
                 balance: chain.latest_balance(a),
             };
-            let time = chain.chain_info().best_block_number;
+            let chain_info = chain.chain_info();
             let mut mem_pool = self.mem_pool.write();
-            mem_pool.remove_old(&fetch_account, time);
+            mem_pool.remove_old(&fetch_account, chain_info.best_block_number, chain_info.best_block_timestamp);
         }
     }
 
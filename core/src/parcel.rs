@@ -17,7 +17,7 @@
 use std::ops::Deref;
 
 use ccrypto::blake256;
-use ckey::{self, public_to_address, recover, sign, Address, Private, Public, Signature};
+use ckey::{self, public_to_address, recover, sign, Address, KeyPair, Private, Public, Signature};
 use ctypes::parcel::{Action, Error as ParcelError, Parcel};
 use ctypes::transaction::Transaction;
 use ctypes::BlockNumber;
@@ -34,6 +34,8 @@ pub struct UnverifiedParcel {
     unsigned: Parcel,
     /// Signature.
     sig: Signature,
+    /// Signature of the account that pays the fee instead of the sender.
+    fee_payer_sig: Option<Signature>,
     /// Hash of the parcel
     hash: H256,
 }
@@ -48,18 +50,22 @@ impl Deref for UnverifiedParcel {
 
 impl rlp::Decodable for UnverifiedParcel {
     fn decode(d: &UntrustedRlp) -> Result<Self, DecoderError> {
-        if d.item_count()? != 5 {
-            return Err(DecoderError::RlpIncorrectListLen)
-        }
+        let fee_payer_sig = match d.item_count()? {
+            6 => None,
+            7 => Some(d.val_at(6)?),
+            _ => return Err(DecoderError::RlpIncorrectListLen),
+        };
         let hash = blake256(d.as_raw());
         Ok(UnverifiedParcel {
             unsigned: Parcel {
                 nonce: d.val_at(0)?,
                 fee: d.val_at(1)?,
                 network_id: d.val_at(2)?,
-                action: d.val_at(3)?,
+                expiration: d.val_at(3)?,
+                action: d.val_at(4)?,
             },
-            sig: d.val_at(4)?,
+            sig: d.val_at(5)?,
+            fee_payer_sig,
             hash,
         })
     }
@@ -76,6 +82,19 @@ impl UnverifiedParcel {
         UnverifiedParcel {
             unsigned: parcel,
             sig: sig.into(),
+            fee_payer_sig: None,
+            hash: 0.into(),
+        }.compute_hash()
+    }
+
+    /// Creates a sponsored parcel whose fee is paid by the signer of `fee_payer_sig`.
+    /// `sig` must be the signature of `Parcel::hash_with_fee_payer`,
+    /// and `fee_payer_sig` must be the signature of `UnverifiedParcel::fee_payer_message`.
+    pub fn new_sponsored(parcel: Parcel, sig: Signature, fee_payer_sig: Signature) -> Self {
+        UnverifiedParcel {
+            unsigned: parcel,
+            sig,
+            fee_payer_sig: Some(fee_payer_sig),
             hash: 0.into(),
         }.compute_hash()
     }
@@ -89,14 +108,40 @@ impl UnverifiedParcel {
 
     /// Append object with a signature into RLP stream
     fn rlp_append_sealed_parcel(&self, s: &mut RlpStream) {
-        s.begin_list(5);
+        match &self.fee_payer_sig {
+            Some(fee_payer_sig) => {
+                s.begin_list(7);
+                self.rlp_append_signed_fields(s);
+                s.append(fee_payer_sig);
+            }
+            None => {
+                s.begin_list(6);
+                self.rlp_append_signed_fields(s);
+            }
+        }
+    }
+
+    fn rlp_append_signed_fields(&self, s: &mut RlpStream) {
         s.append(&self.nonce);
         s.append(&self.fee);
         s.append(&self.network_id);
+        s.append(&self.expiration);
         s.append(&self.action);
         s.append(&self.sig);
     }
 
+    /// The message hash that the fee payer of a sponsored parcel signs, which includes the signature of the sender.
+    pub fn fee_payer_message(&self) -> H256 {
+        let mut stream = RlpStream::new_list(6);
+        self.rlp_append_signed_fields(&mut stream);
+        blake256(stream.as_raw())
+    }
+
+    /// Returns true if the fee is paid by an account other than the sender.
+    pub fn is_sponsored(&self) -> bool {
+        self.fee_payer_sig.is_some()
+    }
+
     /// Reference to unsigned part of this parcel.
     pub fn as_unsigned(&self) -> &Parcel {
         &self.unsigned
@@ -112,14 +157,37 @@ impl UnverifiedParcel {
         Signature::from(self.sig)
     }
 
+    /// The signature of the fee payer if the parcel is sponsored.
+    pub fn fee_payer_signature(&self) -> Option<Signature> {
+        self.fee_payer_sig
+    }
+
     /// Recovers the public key of the sender.
     pub fn recover_public(&self) -> Result<Public, ckey::Error> {
-        Ok(recover(&self.signature(), &self.unsigned.hash())?)
+        let fee_payer = self.recover_fee_payer_public()?.map(|public| public_to_address(&public));
+        self.recover_sender_public(fee_payer.as_ref())
+    }
+
+    /// Recovers the public key of the fee payer. Returns `None` if the parcel is not sponsored.
+    pub fn recover_fee_payer_public(&self) -> Result<Option<Public>, ckey::Error> {
+        match &self.fee_payer_sig {
+            Some(fee_payer_sig) => Ok(Some(recover(fee_payer_sig, &self.fee_payer_message())?)),
+            None => Ok(None),
+        }
+    }
+
+    fn recover_sender_public(&self, fee_payer: Option<&Address>) -> Result<Public, ckey::Error> {
+        let message = match fee_payer {
+            Some(fee_payer) => self.unsigned.hash_with_fee_payer(fee_payer),
+            None => self.unsigned.hash(),
+        };
+        Ok(recover(&self.signature(), &message)?)
     }
 
-    /// Checks whether the signature has a low 's' value.
+    /// Checks whether the signatures have a low 's' value.
     pub fn check_low_s(&self) -> Result<(), ckey::Error> {
-        if !self.signature().is_low_s() {
+        let is_fee_payer_sig_low_s = self.fee_payer_sig.as_ref().map_or(true, Signature::is_low_s);
+        if !self.signature().is_low_s() || !is_fee_payer_sig_low_s {
             Err(ckey::Error::InvalidSignature.into())
         } else {
             Ok(())
@@ -155,7 +223,51 @@ impl UnverifiedParcel {
                                 return Err(ParcelError::InvalidNetworkId)
                             }
                         }
-                        Transaction::AssetTransfer {
+                        Transaction::AssetCompose {
+                            network_id,
+                            metadata,
+                            ..
+                        } => {
+                            if metadata.len() > params.max_metadata_size {
+                                return Err(ParcelError::MetadataTooBig)
+                            }
+                            if network_id != &self.network_id {
+                                return Err(ParcelError::InvalidNetworkId)
+                            }
+                        }
+                        Transaction::CreateWorld {
+                            network_id,
+                            ..
+                        }
+                        | Transaction::SetWorldOwners {
+                            network_id,
+                            ..
+                        }
+                        | Transaction::AssetTransfer {
+                            network_id,
+                            ..
+                        }
+                        | Transaction::AssetChangeRegistrar {
+                            network_id,
+                            ..
+                        }
+                        | Transaction::AssetIncreaseSupply {
+                            network_id,
+                            ..
+                        }
+                        | Transaction::AssetFreeze {
+                            network_id,
+                            ..
+                        }
+                        | Transaction::AssetUnfreeze {
+                            network_id,
+                            ..
+                        }
+                        | Transaction::AssetDecompose {
+                            network_id,
+                            ..
+                        }
+                        | Transaction::AssetUnwrapCCC {
                             network_id,
                             ..
                         } => {
@@ -166,6 +278,16 @@ impl UnverifiedParcel {
                     }
                 }
             }
+            Action::Batch {
+                actions,
+            } => {
+                if actions.is_empty() {
+                    return Err(ParcelError::EmptyBatch)
+                }
+                if actions.iter().any(|action| !action.is_batchable()) {
+                    return Err(ParcelError::InvalidBatchedAction)
+                }
+            }
             _ => {}
         }
         Ok(())
@@ -178,6 +300,7 @@ pub struct SignedParcel {
     parcel: UnverifiedParcel,
     sender: Address,
     public: Public,
+    fee_payer: Option<Address>,
 }
 
 impl HeapSizeOf for SignedParcel {
@@ -206,14 +329,16 @@ impl From<SignedParcel> for UnverifiedParcel {
 }
 
 impl SignedParcel {
-    /// Try to verify parcel and recover sender.
+    /// Try to verify parcel and recover sender and fee payer.
     pub fn new(parcel: UnverifiedParcel) -> Result<Self, ckey::Error> {
-        let public = parcel.recover_public()?;
+        let fee_payer = parcel.recover_fee_payer_public()?.map(|public| public_to_address(&public));
+        let public = parcel.recover_sender_public(fee_payer.as_ref())?;
         let sender = public_to_address(&public);
         Ok(SignedParcel {
             parcel,
             sender,
             public,
+            fee_payer,
         })
     }
 
@@ -223,11 +348,28 @@ impl SignedParcel {
         SignedParcel::new(UnverifiedParcel::new(parcel, sig)).expect("secret is valid so it's recoverable")
     }
 
+    /// Signs the parcel as coming from `sender` and as paid by `fee_payer`.
+    pub fn new_with_sponsor(parcel: Parcel, private: &Private, fee_payer_private: &Private) -> SignedParcel {
+        let fee_payer = KeyPair::from_private(fee_payer_private.clone()).expect("secret is valid; qed").address();
+        let sig = sign(&private, &parcel.hash_with_fee_payer(&fee_payer))
+            .expect("data is valid and context has signing capabilities; qed");
+        let mut parcel = UnverifiedParcel::new(parcel, sig);
+        let fee_payer_sig = sign(&fee_payer_private, &parcel.fee_payer_message())
+            .expect("data is valid and context has signing capabilities; qed");
+        parcel.fee_payer_sig = Some(fee_payer_sig);
+        SignedParcel::new(parcel.compute_hash()).expect("secret is valid so it's recoverable")
+    }
+
     /// Returns parcel sender.
     pub fn sender(&self) -> &Address {
         &self.sender
     }
 
+    /// Returns the account that pays the fee and whose nonce is consumed.
+    pub fn fee_payer(&self) -> &Address {
+        self.fee_payer.as_ref().unwrap_or(&self.sender)
+    }
+
     /// Returns a public key of the sender.
     pub fn public_key(&self) -> Public {
         self.public.clone()
@@ -278,8 +420,9 @@ impl Deref for LocalizedParcel {
 
 #[cfg(test)]
 mod tests {
-    use ckey::{Address, Public, Signature};
-    use ctypes::transaction::AssetMintOutput;
+    use ckey::{Address, Generator, Public, Random, Signature};
+    use ctypes::parcel::Expiration;
+    use ctypes::transaction::{AssetMintOutput, AssetOutPoint, AssetTransferInput, AssetTransferOutput};
     use primitives::H256;
 
     use super::*;
@@ -293,18 +436,120 @@ mod tests {
                     fee: 10.into(),
                     action: Action::CreateShard,
                     network_id: 0xBE,
+                    expiration: None,
                 },
                 sig: Signature::default(),
+                fee_payer_sig: None,
                 hash: H256::default(),
             }.compute_hash()
         );
     }
 
+    #[test]
+    fn parcel_with_expiration_rlp() {
+        rlp_encode_and_decode_test!(
+            UnverifiedParcel {
+                unsigned: Parcel {
+                    nonce: 0.into(),
+                    fee: 10.into(),
+                    action: Action::CreateShard,
+                    network_id: 0xBE,
+                    expiration: Some(Expiration::Timestamp(1_540_000_000)),
+                },
+                sig: Signature::default(),
+                fee_payer_sig: None,
+                hash: H256::default(),
+            }.compute_hash()
+        );
+    }
+
+    #[test]
+    fn sponsored_parcel_rlp() {
+        rlp_encode_and_decode_test!(
+            UnverifiedParcel {
+                unsigned: Parcel {
+                    nonce: 0.into(),
+                    fee: 10.into(),
+                    action: Action::CreateShard,
+                    network_id: 0xBE,
+                    expiration: None,
+                },
+                sig: Signature::default(),
+                fee_payer_sig: Some(Signature::default()),
+                hash: H256::default(),
+            }.compute_hash()
+        );
+    }
+
+    #[test]
+    fn recover_sender_and_fee_payer_of_sponsored_parcel() {
+        let sender = Random.generate().unwrap();
+        let fee_payer = Random.generate().unwrap();
+        let parcel = Parcel {
+            nonce: 0.into(),
+            fee: 10.into(),
+            action: Action::CreateShard,
+            network_id: 0xBE,
+            expiration: None,
+        };
+        let signed = SignedParcel::new_with_sponsor(parcel, sender.private(), fee_payer.private());
+        assert!(signed.is_sponsored());
+        assert_eq!(&sender.address(), signed.sender());
+        assert_eq!(&fee_payer.address(), signed.fee_payer());
+
+        let decoded: UnverifiedParcel = rlp::decode(&rlp::encode(&signed));
+        assert_eq!(Ok(*sender.public()), decoded.recover_public());
+        assert_eq!(Ok(Some(*fee_payer.public())), decoded.recover_fee_payer_public());
+    }
+
+    #[test]
+    fn signature_of_sponsored_parcel_cannot_be_used_by_another_fee_payer() {
+        let sender = Random.generate().unwrap();
+        let fee_payer = Random.generate().unwrap();
+        let another_fee_payer = Random.generate().unwrap();
+        let parcel = Parcel {
+            nonce: 0.into(),
+            fee: 10.into(),
+            action: Action::CreateShard,
+            network_id: 0xBE,
+            expiration: None,
+        };
+        let signed = SignedParcel::new_with_sponsor(parcel, sender.private(), fee_payer.private());
+
+        let (mut stolen, ..) = signed.deconstruct();
+        stolen.fee_payer_sig = Some(sign(another_fee_payer.private(), &stolen.fee_payer_message()).unwrap());
+        let stolen = SignedParcel::new(stolen.compute_hash()).unwrap();
+        assert_eq!(&another_fee_payer.address(), stolen.fee_payer());
+        assert_ne!(&sender.address(), stolen.sender());
+    }
+
+    #[test]
+    fn encode_and_decode_create_world() {
+        rlp_encode_and_decode_test!(Transaction::CreateWorld {
+            network_id: 200,
+            shard_id: 0xc,
+            nonce: 0,
+            owners: vec![Address::random(), Address::random()],
+        });
+    }
+
+    #[test]
+    fn encode_and_decode_set_world_owners() {
+        rlp_encode_and_decode_test!(Transaction::SetWorldOwners {
+            network_id: 200,
+            shard_id: 0xc,
+            world_id: 3,
+            nonce: 2,
+            owners: vec![Address::random()],
+        });
+    }
+
     #[test]
     fn encode_and_decode_asset_mint() {
         rlp_encode_and_decode_test!(Transaction::AssetMint {
             network_id: 200,
             shard_id: 0xc,
+            world_id: 0,
             metadata: "mint test".to_string(),
             output: AssetMintOutput {
                 lock_script_hash: H256::random(),
@@ -321,6 +566,7 @@ mod tests {
         rlp_encode_and_decode_test!(Transaction::AssetMint {
             network_id: 200,
             shard_id: 3,
+            world_id: 1,
             metadata: "mint test".to_string(),
             output: AssetMintOutput {
                 lock_script_hash: H256::random(),
@@ -347,6 +593,130 @@ mod tests {
         });
     }
 
+    #[test]
+    fn encode_and_decode_asset_change_registrar() {
+        rlp_encode_and_decode_test!(Transaction::AssetChangeRegistrar {
+            network_id: 200,
+            asset_type: H256::random(),
+            registrar: Some(Address::random()),
+            nonce: 0,
+        });
+    }
+
+    #[test]
+    fn encode_and_decode_asset_increase_supply() {
+        rlp_encode_and_decode_test!(Transaction::AssetIncreaseSupply {
+            network_id: 200,
+            asset_type: H256::random(),
+            lock_script_hash: H256::random(),
+            parameters: vec![vec![1, 2, 3]],
+            amount: 100,
+            nonce: 0,
+        });
+    }
+
+    #[test]
+    fn encode_and_decode_asset_freeze() {
+        rlp_encode_and_decode_test!(Transaction::AssetFreeze {
+            network_id: 200,
+            outpoint: AssetOutPoint {
+                transaction_hash: H256::random(),
+                index: 1,
+                asset_type: H256::random(),
+                shard_id: 0,
+                amount: 30,
+            },
+            nonce: 0,
+        });
+    }
+
+    #[test]
+    fn encode_and_decode_asset_unfreeze() {
+        rlp_encode_and_decode_test!(Transaction::AssetUnfreeze {
+            network_id: 200,
+            outpoint: AssetOutPoint {
+                transaction_hash: H256::random(),
+                index: 1,
+                asset_type: H256::random(),
+                shard_id: 0,
+                amount: 30,
+            },
+            nonce: 0,
+        });
+    }
+
+    #[test]
+    fn encode_and_decode_asset_compose() {
+        rlp_encode_and_decode_test!(Transaction::AssetCompose {
+            network_id: 200,
+            shard_id: 3,
+            world_id: 1,
+            metadata: "compose test".to_string(),
+            registrar: Some(Address::random()),
+            inputs: vec![AssetTransferInput {
+                prev_out: AssetOutPoint {
+                    transaction_hash: H256::random(),
+                    index: 0,
+                    asset_type: H256::random(),
+                    shard_id: 0,
+                    amount: 30,
+                },
+                lock_script: vec![0x30, 0x01],
+                unlock_script: vec![],
+            }],
+            lock_script_hash: H256::random(),
+            parameters: vec![vec![1, 2, 3]],
+            amount: 1,
+            nonce: 0,
+        });
+    }
+
+    #[test]
+    fn encode_and_decode_asset_decompose() {
+        rlp_encode_and_decode_test!(Transaction::AssetDecompose {
+            network_id: 200,
+            input: AssetTransferInput {
+                prev_out: AssetOutPoint {
+                    transaction_hash: H256::random(),
+                    index: 0,
+                    asset_type: H256::random(),
+                    shard_id: 0,
+                    amount: 1,
+                },
+                lock_script: vec![0x30, 0x01],
+                unlock_script: vec![],
+            },
+            outputs: vec![AssetTransferOutput {
+                lock_script_hash: H256::random(),
+                parameters: vec![],
+                asset_type: H256::random(),
+                shard_id: 0,
+                amount: 30,
+            }],
+            nonce: 0,
+        });
+    }
+
+    #[test]
+    fn encode_and_decode_asset_unwrap_ccc() {
+        rlp_encode_and_decode_test!(Transaction::AssetUnwrapCCC {
+            network_id: 200,
+            burn: AssetTransferInput {
+                prev_out: AssetOutPoint {
+                    transaction_hash: H256::random(),
+                    index: 0,
+                    asset_type: H256::random(),
+                    shard_id: 0,
+                    amount: 30,
+                },
+                lock_script: vec![0x30, 0x01],
+                unlock_script: vec![],
+            },
+            receiver: Address::random(),
+            nonce: 0,
+        });
+    }
+
     #[test]
     fn encode_and_decode_payment_action() {
         rlp_encode_and_decode_test!(Action::Payment {
@@ -363,12 +733,14 @@ mod tests {
                     nonce: 30.into(),
                     fee: 40.into(),
                     network_id: 50,
+                    expiration: None,
                     action: Action::Payment {
                         receiver: Address::random(),
                         amount: 300.into(),
                     },
                 },
                 sig: Signature::default(),
+                fee_payer_sig: None,
                 hash: H256::default(),
             }.compute_hash()
         );
@@ -382,11 +754,13 @@ mod tests {
                     nonce: 30.into(),
                     fee: 40.into(),
                     network_id: 50,
+                    expiration: None,
                     action: Action::SetRegularKey {
                         key: Public::random(),
                     },
                 },
                 sig: Signature::default(),
+                fee_payer_sig: None,
                 hash: H256::default(),
             }.compute_hash()
         );
@@ -400,9 +774,138 @@ mod tests {
                     nonce: 30.into(),
                     fee: 40.into(),
                     network_id: 50,
+                    expiration: None,
                     action: Action::CreateShard,
                 },
                 sig: Signature::default(),
+                fee_payer_sig: None,
+                hash: H256::default(),
+            }.compute_hash()
+        );
+    }
+
+    #[test]
+    fn encode_and_decode_set_shard_owners_parcel() {
+        rlp_encode_and_decode_test!(
+            UnverifiedParcel {
+                unsigned: Parcel {
+                    nonce: 30.into(),
+                    fee: 40.into(),
+                    network_id: 50,
+                    expiration: None,
+                    action: Action::SetShardOwners {
+                        shard_id: 1,
+                        owners: vec![Address::random(), Address::random()],
+                    },
+                },
+                sig: Signature::default(),
+                fee_payer_sig: None,
+                hash: H256::default(),
+            }.compute_hash()
+        );
+    }
+
+    #[test]
+    fn encode_and_decode_set_shard_users_parcel() {
+        rlp_encode_and_decode_test!(
+            UnverifiedParcel {
+                unsigned: Parcel {
+                    nonce: 30.into(),
+                    fee: 40.into(),
+                    network_id: 50,
+                    expiration: None,
+                    action: Action::SetShardUsers {
+                        shard_id: 1,
+                        users: vec![Address::random()],
+                    },
+                },
+                sig: Signature::default(),
+                fee_payer_sig: None,
+                hash: H256::default(),
+            }.compute_hash()
+        );
+    }
+
+    #[test]
+    fn encode_and_decode_wrap_ccc_parcel() {
+        rlp_encode_and_decode_test!(
+            UnverifiedParcel {
+                unsigned: Parcel {
+                    nonce: 30.into(),
+                    fee: 40.into(),
+                    network_id: 50,
+                    expiration: None,
+                    action: Action::WrapCCC {
+                        shard_id: 1,
+                        lock_script_hash: H256::random(),
+                        parameters: vec![vec![1, 2, 3]],
+                        amount: 300,
+                    },
+                },
+                sig: Signature::default(),
+                fee_payer_sig: None,
+                hash: H256::default(),
+            }.compute_hash()
+        );
+    }
+
+    #[test]
+    fn encode_and_decode_batch_parcel() {
+        rlp_encode_and_decode_test!(
+            UnverifiedParcel {
+                unsigned: Parcel {
+                    nonce: 30.into(),
+                    fee: 40.into(),
+                    network_id: 50,
+                    expiration: None,
+                    action: Action::Batch {
+                        actions: vec![
+                            Action::Payment {
+                                receiver: Address::random(),
+                                amount: 300.into(),
+                            },
+                            Action::CreateShard,
+                            Action::SetShardUsers {
+                                shard_id: 1,
+                                users: vec![Address::random()],
+                            },
+                        ],
+                    },
+                },
+                sig: Signature::default(),
+                fee_payer_sig: None,
+                hash: H256::default(),
+            }.compute_hash()
+        );
+    }
+
+    #[test]
+    fn decoding_nested_batch_fails() {
+        let nested = Action::Batch {
+            actions: vec![Action::Batch {
+                actions: vec![Action::CreateShard],
+            }],
+        };
+        let encoded = rlp::encode(&nested);
+        assert_eq!(Err(DecoderError::Custom("Nested batch")), UntrustedRlp::new(&encoded).as_val::<Action>());
+    }
+
+    #[test]
+    fn encode_and_decode_custom_parcel() {
+        rlp_encode_and_decode_test!(
+            UnverifiedParcel {
+                unsigned: Parcel {
+                    nonce: 30.into(),
+                    fee: 40.into(),
+                    network_id: 50,
+                    expiration: None,
+                    action: Action::Custom {
+                        handler_id: 2,
+                        bytes: vec![0xc5, 0x01, 0x83, 0x6b, 0x65, 0x79],
+                    },
+                },
+                sig: Signature::default(),
+                fee_payer_sig: None,
                 hash: H256::default(),
             }.compute_hash()
         );
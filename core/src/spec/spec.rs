@@ -22,8 +22,8 @@ use cjson;
 use ckey::Address;
 use cmerkle::TrieFactory;
 use cstate::{
-    ActionHandler, Backend, Metadata, MetadataAddress, Shard, ShardAddress, ShardMetadataAddress, StateDB, StateResult,
-    WorldAddress,
+    ActionHandler, Backend, HitHandler, Metadata, MetadataAddress, RegistryHandler, Shard, ShardAddress,
+    ShardMetadataAddress, StateDB, StateResult, WorldAddress,
 };
 use ctypes::ShardId;
 use hashdb::HashDB;
@@ -55,6 +55,8 @@ pub struct CommonParams {
     pub max_body_size: usize,
     /// Snapshot creation period in unit of block numbers.
     pub snapshot_period: u64,
+    /// Maximum total cost of the script instructions executed to spend an asset.
+    pub max_script_cost: u64,
     /// Flag whether to use shard validator.
     pub use_shard_validator: bool,
 }
@@ -68,6 +70,7 @@ impl From<cjson::spec::Params> for CommonParams {
             min_parcel_cost: p.min_parcel_cost.into(),
             max_body_size: p.max_body_size.into(),
             snapshot_period: p.snapshot_period.into(),
+            max_script_cost: p.max_script_cost.into(),
             use_shard_validator: p.use_shard_validator.into(),
         }
     }
@@ -229,7 +232,7 @@ impl Spec {
                 let mut t = trie_factory.from_existing(db.as_hashdb_mut(), &mut root)?;
                 let address = ShardAddress::new(shard_id);
 
-                let shard = Shard::new(shard_root, owner);
+                let shard = Shard::new(shard_root, vec![owner], vec![]);
                 let r = t.insert(&*address, &shard.rlp_bytes());
                 debug_assert_eq!(Ok(None), r);
                 r?;
@@ -386,15 +389,28 @@ impl Spec {
     }
 }
 
+fn custom_handlers(handlers: Vec<cjson::spec::ActionHandler>) -> Result<Vec<Arc<ActionHandler>>, Error> {
+    let mut custom_handlers: Vec<Arc<ActionHandler>> = Vec::with_capacity(handlers.len());
+    for handler in handlers {
+        let id: u64 = handler.id.into();
+        if custom_handlers.iter().any(|h| h.handler_id() == id) {
+            return Err(SpecError::DuplicatedActionHandlerId(id).into())
+        }
+        custom_handlers.push(match handler.kind {
+            cjson::spec::ActionHandlerKind::Hit => Arc::new(HitHandler::new(id)),
+            cjson::spec::ActionHandlerKind::Registry => Arc::new(RegistryHandler::new(id)),
+        });
+    }
+    Ok(custom_handlers)
+}
+
 /// Load from JSON object.
 fn load_from(s: cjson::spec::Spec) -> Result<Spec, Error> {
     let g = Genesis::from(s.genesis);
     let GenericSeal(seal_rlp) = g.seal.into();
     let params = CommonParams::from(s.params);
     let engine = Spec::engine(s.engine, params);
-    let custom_handlers = match &engine {
-        _ => vec![],
-    };
+    let custom_handlers = custom_handlers(s.action_handlers.unwrap_or_else(Vec::new))?;
 
     let mut s = Spec {
         name: s.name.clone().into(),
@@ -445,4 +461,26 @@ mod tests {
         let result = genesis_header.extra_data();
         assert_eq!(&hash_of_common_params, result);
     }
+
+    #[test]
+    fn action_handler_ids_must_be_unique() {
+        let handler = |id: u64, kind| cjson::spec::ActionHandler {
+            id: cjson::uint::Uint(id.into()),
+            kind,
+        };
+        let handlers = custom_handlers(vec![
+            handler(1, cjson::spec::ActionHandlerKind::Hit),
+            handler(2, cjson::spec::ActionHandlerKind::Registry),
+        ]).unwrap();
+        assert_eq!(vec![1, 2], handlers.iter().map(|h| h.handler_id()).collect::<Vec<_>>());
+
+        let result = custom_handlers(vec![
+            handler(1, cjson::spec::ActionHandlerKind::Hit),
+            handler(1, cjson::spec::ActionHandlerKind::Registry),
+        ]);
+        match result {
+            Err(Error::Spec(SpecError::DuplicatedActionHandlerId(1))) => {}
+            _ => panic!("Duplicated handler ids must be rejected"),
+        }
+    }
 }
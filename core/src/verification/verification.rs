@@ -115,7 +115,7 @@ pub fn verify_header_params(header: &Header, engine: &CodeChainEngine) -> Result
 }
 
 /// Verify block data against header: parcels root
-fn verify_parcels_root(block: &[u8], parcels_root: &H256, parent_parcels_root: H256) -> Result<(), Error> {
+pub fn verify_parcels_root(block: &[u8], parcels_root: &H256, parent_parcels_root: H256) -> Result<(), Error> {
     let block = UntrustedRlp::new(block);
     let parcel = block.at(1)?;
     let expected_root = skewed_merkle_root(parent_parcels_root, parcel.iter().map(|r| r.as_raw()));
@@ -0,0 +1,70 @@
+// Copyright 2018 Kodebox, Inc.
+// This file is part of CodeChain.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use super::super::uint::Uint;
+
+/// Custom action handler.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct ActionHandler {
+    /// The id that `Custom` actions specify to be handled by this handler.
+    pub id: Uint,
+    pub kind: ActionHandlerKind,
+}
+
+/// Kind of custom action handler.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ActionHandlerKind {
+    Hit,
+    Registry,
+}
+
+#[cfg(test)]
+mod tests {
+    use primitives::U256;
+    use serde_json;
+
+    use super::*;
+
+    #[test]
+    fn action_handler_deserialization() {
+        let s = r#"[
+            { "id": 1, "kind": "hit" },
+            { "id": "0x2", "kind": "registry" }
+        ]"#;
+        let handlers: Vec<ActionHandler> = serde_json::from_str(s).unwrap();
+        assert_eq!(
+            vec![
+                ActionHandler {
+                    id: Uint(U256::from(1)),
+                    kind: ActionHandlerKind::Hit,
+                },
+                ActionHandler {
+                    id: Uint(U256::from(2)),
+                    kind: ActionHandlerKind::Registry,
+                },
+            ],
+            handlers
+        );
+    }
+
+    #[test]
+    fn unknown_kind_must_fail() {
+        let s = r#"{ "id": 1, "kind": "unknown" }"#;
+        let result: Result<ActionHandler, _> = serde_json::from_str(s);
+        assert!(result.is_err());
+    }
+}
@@ -33,6 +33,8 @@ pub struct Params {
     pub max_body_size: Uint,
     /// Snapshot creation period in unit of block numbers.
     pub snapshot_period: Uint,
+    /// Maximum total cost of the script instructions executed to spend an asset.
+    pub max_script_cost: Uint,
     pub use_shard_validator: bool,
 }
 
@@ -53,6 +55,7 @@ mod tests {
             "minParcelCost" : "10",
             "maxBodySize" : 4194304,
             "snapshotPeriod": 16384,
+            "maxScriptCost": 10000,
             "useShardValidator": true
         }"#;
 
@@ -63,6 +66,7 @@ mod tests {
         assert_eq!(deserialized.min_parcel_cost, Uint(U256::from(10)));
         assert_eq!(deserialized.max_body_size, Uint(4194304.into()));
         assert_eq!(deserialized.snapshot_period, Uint(16384.into()));
+        assert_eq!(deserialized.max_script_cost, Uint(10000.into()));
         assert_eq!(deserialized.use_shard_validator, true);
     }
 }
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use super::{Accounts, Engine, Genesis, Params, Shards};
+use super::{Accounts, ActionHandler, Engine, Genesis, Params, Shards};
 use serde_json;
 use serde_json::Error;
 use std::io::Read;
@@ -38,6 +38,8 @@ pub struct Spec {
     pub shards: Shards,
     /// Boot nodes.
     pub nodes: Option<Vec<String>>,
+    /// Custom action handlers.
+    pub action_handlers: Option<Vec<ActionHandler>>,
 }
 
 impl Spec {
@@ -80,6 +82,7 @@ mod tests {
                 "minParcelCost" : "10",
                 "maxBodySize": 4194304,
                 "snapshotPeriod": 16384,
+                "maxScriptCost": 10000,
                 "useShardValidator": true
             },
             "genesis": {
@@ -108,7 +111,11 @@ mod tests {
                 "102e61f5d8f9bc71d0ad4a084df4e65e05ce0e1c": { "balance": "1606938044258990275541962092341162602522202993782792835301376", "nonce": "1048576" }
             },
             "shards": {
-            }
+            },
+            "actionHandlers": [
+                { "id": 1, "kind": "hit" },
+                { "id": 2, "kind": "registry" }
+            ]
         }"#;
         let _deserialized: Spec = serde_json::from_str(s).unwrap();
         // TODO: validate all fields
@@ -59,6 +59,11 @@ pub use private::Private;
 pub use random::Random;
 pub use rustc_serialize::hex;
 #[cfg(feature = "schnorr")]
+pub use schnorr::{
+    aggregate_schnorr_private_share, aggregate_schnorr_publics, recover_schnorr, sign_schnorr, verify_schnorr,
+    verify_schnorr_address, SchnorrSignature, SCHNORR_SIGNATURE_LENGTH,
+};
+#[cfg(all(feature = "schnorr", not(feature = "ecdsa")))]
 pub use schnorr::{
     recover_schnorr as recover, sign_schnorr as sign, verify_schnorr as verify,
     verify_schnorr_address as verify_address, SchnorrSignature as Signature,
@@ -20,7 +20,8 @@ use std::hash::{Hash, Hasher};
 use std::ops::{Deref, DerefMut};
 use std::str::FromStr;
 
-use primitives::H512;
+use crypto::{blake256, blake256_with_key};
+use primitives::{H256, H512};
 use rlp::{Decodable, DecoderError, Encodable, RlpStream, UntrustedRlp};
 use rustc_hex::{FromHex, ToHex};
 use secp256k1::{key, schnorr, Error as SecpError, Message as SecpMessage};
@@ -192,13 +193,7 @@ pub fn sign_schnorr(private: &Private, message: &Message) -> Result<SchnorrSigna
 
 pub fn verify_schnorr(public: &Public, signature: &SchnorrSignature, message: &Message) -> Result<bool, Error> {
     let context = &SECP256K1;
-    let pdata: [u8; 65] = {
-        let mut temp = [4u8; 65];
-        temp[1..65].copy_from_slice(&**public);
-        temp
-    };
-
-    let publ = key::PublicKey::from_slice(context, &pdata)?;
+    let publ = to_secp_public(public)?;
     let sig = schnorr::Signature::deserialize(&signature.0);
     match context.verify_schnorr(&SecpMessage::from_slice(&message[..])?, &sig, &publ) {
         Ok(_) => Ok(true),
@@ -207,6 +202,54 @@ pub fn verify_schnorr(public: &Public, signature: &SchnorrSignature, message: &M
     }
 }
 
+/// Aggregates public keys into `sum(H(L, P_i) * P_i)`, where `L` is the hash of all the public keys in order.
+///
+/// Each key is weighted by a coefficient bound to the whole set as in MuSig,
+/// so a signer can't choose its key to cancel out the keys of the others.
+pub fn aggregate_schnorr_publics(publics: &[Public]) -> Result<Public, Error> {
+    let context = &SECP256K1;
+    let hash = aggregation_hash(publics);
+    let mut aggregated: Option<key::PublicKey> = None;
+    for public in publics {
+        let mut weighted = to_secp_public(public)?;
+        weighted.mul_assign(context, &aggregation_coefficient(&hash, public)?)?;
+        aggregated = match aggregated {
+            Some(mut sum) => {
+                sum.add_assign(context, &weighted)?;
+                Some(sum)
+            }
+            None => Some(weighted),
+        };
+    }
+    aggregated.map(|aggregated| from_secp_public(&aggregated)).ok_or(Error::InvalidPublic)
+}
+
+/// Returns `H(L, P) * private`, the share of `private` in the private key of `aggregate_schnorr_publics(publics)`.
+/// The aggregated signature must be made with the sum of the shares of all the signers.
+pub fn aggregate_schnorr_private_share(publics: &[Public], private: &Private) -> Result<Private, Error> {
+    let context = &SECP256K1;
+    let mut share = key::SecretKey::from_slice(context, private)?;
+    let public = from_secp_public(&key::PublicKey::from_secret_key(context, &share)?);
+    if !publics.contains(&public) {
+        return Err(Error::InvalidPublic)
+    }
+    share.mul_assign(context, &aggregation_coefficient(&aggregation_hash(publics), &public)?)?;
+    Ok(Private::from(share))
+}
+
+/// The hash of all the aggregated public keys, which is `L` in MuSig.
+fn aggregation_hash(publics: &[Public]) -> H256 {
+    let mut concatenated = Vec::with_capacity(publics.len() * 64);
+    for public in publics {
+        concatenated.extend_from_slice(public);
+    }
+    blake256(concatenated)
+}
+
+fn aggregation_coefficient(hash: &H256, public: &Public) -> Result<key::SecretKey, Error> {
+    Ok(key::SecretKey::from_slice(&SECP256K1, &blake256_with_key(public, hash))?)
+}
+
 pub fn verify_schnorr_address(
     address: &Address,
     signature: &SchnorrSignature,
@@ -222,19 +265,37 @@ pub fn recover_schnorr(signature: &SchnorrSignature, message: &Message) -> Resul
 
     let sig = schnorr::Signature::deserialize(&signature.0);
     let pubkey = context.recover_schnorr(&SecpMessage::from_slice(&message[..])?, &sig)?;
-    let serialized = pubkey.serialize_vec(context, false);
+    Ok(from_secp_public(&pubkey))
+}
+
+fn to_secp_public(public: &Public) -> Result<key::PublicKey, Error> {
+    let pdata: [u8; 65] = {
+        let mut temp = [4u8; 65];
+        temp[1..65].copy_from_slice(&**public);
+        temp
+    };
+    Ok(key::PublicKey::from_slice(&SECP256K1, &pdata)?)
+}
+
+fn from_secp_public(pubkey: &key::PublicKey) -> Public {
+    let serialized = pubkey.serialize_vec(&SECP256K1, false);
 
     let mut public = Public::default();
     public.copy_from_slice(&serialized[1..65]);
-    Ok(public)
+    public
 }
 
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
 
-    use super::super::{Generator, Message, Random};
-    use super::{recover_schnorr, sign_schnorr, verify_schnorr, verify_schnorr_address, SchnorrSignature};
+    use secp256k1::key;
+
+    use super::super::{Generator, Message, Private, Random, SECP256K1};
+    use super::{
+        aggregate_schnorr_private_share, aggregate_schnorr_publics, from_secp_public, recover_schnorr, sign_schnorr,
+        to_secp_public, verify_schnorr, verify_schnorr_address, SchnorrSignature,
+    };
 
     #[test]
     fn signature_to_and_from_str() {
@@ -269,4 +330,57 @@ mod tests {
         let signature = sign_schnorr(keypair.private(), &message).unwrap();
         assert!(verify_schnorr_address(&keypair.address(), &signature, &message).unwrap());
     }
+
+    #[test]
+    fn sign_with_sum_of_shares_and_verify_aggregated_public() {
+        let keypairs = vec![Random.generate().unwrap(), Random.generate().unwrap(), Random.generate().unwrap()];
+        let publics: Vec<_> = keypairs.iter().map(|keypair| *keypair.public()).collect();
+        let mut shares = keypairs.iter().map(|keypair| {
+            let share = aggregate_schnorr_private_share(&publics, keypair.private()).unwrap();
+            key::SecretKey::from_slice(&SECP256K1, &share).unwrap()
+        });
+        let mut sum = shares.next().unwrap();
+        for share in shares {
+            sum.add_assign(&SECP256K1, &share).unwrap();
+        }
+        let message = Message::default();
+        let signature = sign_schnorr(&Private::from(sum), &message).unwrap();
+
+        let aggregated = aggregate_schnorr_publics(&publics).unwrap();
+        assert!(verify_schnorr(&aggregated, &signature, &message).unwrap());
+        assert!(!verify_schnorr(&aggregate_schnorr_publics(&publics[..2]).unwrap(), &signature, &message).unwrap());
+    }
+
+    #[test]
+    fn rogue_key_cannot_sign_for_aggregated_public() {
+        let honest = Random.generate().unwrap();
+        let attacker = Random.generate().unwrap();
+
+        // The rogue key is the public key of the attacker minus the honest one,
+        // so that the plain sum of the public keys is the public key of the attacker.
+        let mut rogue = to_secp_public(honest.public()).unwrap();
+        rogue.mul_assign(&SECP256K1, &key::MINUS_ONE_KEY).unwrap();
+        rogue.add_assign(&SECP256K1, &to_secp_public(attacker.public()).unwrap()).unwrap();
+        let rogue = from_secp_public(&rogue);
+        let mut sum = to_secp_public(honest.public()).unwrap();
+        sum.add_assign(&SECP256K1, &to_secp_public(&rogue).unwrap()).unwrap();
+        assert_eq!(attacker.public(), &from_secp_public(&sum));
+
+        let message = Message::default();
+        let signature = sign_schnorr(attacker.private(), &message).unwrap();
+        let aggregated = aggregate_schnorr_publics(&[*honest.public(), rogue]).unwrap();
+        assert!(!verify_schnorr(&aggregated, &signature, &message).unwrap());
+    }
+
+    #[test]
+    fn share_of_unknown_public() {
+        let keypair = Random.generate().unwrap();
+        let other = Random.generate().unwrap();
+        assert!(aggregate_schnorr_private_share(&[*other.public()], keypair.private()).is_err());
+    }
+
+    #[test]
+    fn aggregate_no_public() {
+        assert!(aggregate_schnorr_publics(&[]).is_err());
+    }
 }
@@ -21,6 +21,7 @@ extern crate codechain_key as ckey;
 extern crate codechain_network as cnetwork;
 extern crate codechain_state as cstate;
 extern crate codechain_types as ctypes;
+extern crate codechain_vm as cvm;
 extern crate jsonrpc_core;
 extern crate jsonrpc_http_server;
 extern crate jsonrpc_ipc_server;
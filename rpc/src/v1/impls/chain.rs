@@ -21,11 +21,11 @@ use ccore::{
     SignedParcel, UnverifiedParcel,
 };
 use ckey::{Address, Public};
-use cstate::{Asset, AssetScheme, AssetSchemeAddress};
+use cstate::{Asset, AssetScheme, AssetSchemeAddress, World};
 use ctypes::invoice::{Invoice, ParcelInvoice};
 use ctypes::parcel::{Action, ChangeShard};
 use ctypes::transaction::Transaction;
-use ctypes::{BlockNumber, ShardId};
+use ctypes::{BlockNumber, ShardId, WorldId};
 use primitives::{H160, H256, U256};
 use rlp::{DecoderError, UntrustedRlp};
 
@@ -33,7 +33,7 @@ use jsonrpc_core::Result;
 
 use super::super::errors;
 use super::super::traits::Chain;
-use super::super::types::{Block, BlockNumberAndHash, Bytes, Parcel};
+use super::super::types::{Block, BlockNumberAndHash, Bytes, Parcel, ScriptTrace};
 
 pub struct ChainClient<C, M>
 where
@@ -67,8 +67,12 @@ where
             .map_err(errors::rlp)
             .and_then(|parcel: UnverifiedParcel| {
                 match &parcel.as_unsigned().action {
-                    Action::Custom(bytes) => {
-                        if !self.client.custom_handlers().iter().any(|c| c.is_target(bytes)) {
+                    Action::Custom {
+                        handler_id,
+                        bytes,
+                    } => {
+                        let handlers = self.client.custom_handlers();
+                        if !handlers.iter().any(|h| h.handler_id() == *handler_id && h.is_valid(bytes)) {
                             return Err(errors::rlp(DecoderError::Custom("Invalid custom action!")))
                         }
                     }
@@ -103,8 +107,13 @@ where
         Ok(self.client.transaction_invoice(transaction_hash.into()))
     }
 
-    fn get_asset_scheme_by_hash(&self, transaction_hash: H256, shard_id: ShardId) -> Result<Option<AssetScheme>> {
-        let address = AssetSchemeAddress::new(transaction_hash, shard_id);
+    fn get_asset_scheme_by_hash(
+        &self,
+        transaction_hash: H256,
+        shard_id: ShardId,
+        world_id: WorldId,
+    ) -> Result<Option<AssetScheme>> {
+        let address = AssetSchemeAddress::new(transaction_hash, shard_id, world_id);
         self.get_asset_scheme_by_type(address.into())
     }
 
@@ -157,6 +166,36 @@ where
         Ok(self.client.shard_root(shard_id, block_id.into()))
     }
 
+    fn get_shard_owners(&self, shard_id: ShardId, block_number: Option<u64>) -> Result<Option<Vec<Address>>> {
+        let block_id = block_number.map(BlockId::Number).unwrap_or(BlockId::Latest);
+        Ok(self.client.shard_owners(shard_id, block_id.into()))
+    }
+
+    fn get_shard_users(&self, shard_id: ShardId, block_number: Option<u64>) -> Result<Option<Vec<Address>>> {
+        let block_id = block_number.map(BlockId::Number).unwrap_or(BlockId::Latest);
+        Ok(self.client.shard_users(shard_id, block_id.into()))
+    }
+
+    fn get_number_of_worlds(&self, shard_id: ShardId, block_number: Option<u64>) -> Result<Option<WorldId>> {
+        let block_id = block_number.map(BlockId::Number).unwrap_or(BlockId::Latest);
+        Ok(self.client.number_of_worlds(shard_id, block_id.into()))
+    }
+
+    fn get_world(&self, shard_id: ShardId, world_id: WorldId, block_number: Option<u64>) -> Result<Option<World>> {
+        let block_id = block_number.map(BlockId::Number).unwrap_or(BlockId::Latest);
+        Ok(self.client.world(shard_id, world_id, block_id.into()))
+    }
+
+    fn get_custom_action_data(
+        &self,
+        handler_id: u64,
+        query: Bytes,
+        block_number: Option<u64>,
+    ) -> Result<Option<Bytes>> {
+        let block_id = block_number.map(BlockId::Number).unwrap_or(BlockId::Latest);
+        Ok(self.client.custom_action_data(handler_id, &query.into_vec(), block_id.into()).map(Bytes::new))
+    }
+
     fn get_best_block_number(&self) -> Result<BlockNumber> {
         Ok(self.client.chain_info().best_block_number)
     }
@@ -196,10 +235,22 @@ where
         Ok(self.client.common_params().network_id)
     }
 
-    fn execute_change_shard_state(&self, raw: Bytes) -> Result<Vec<ChangeShard>> {
+    fn execute_change_shard_state(&self, raw: Bytes, sender: Address) -> Result<Vec<ChangeShard>> {
         let transactions: Vec<Transaction> =
             UntrustedRlp::new(&raw.into_vec()).as_list().map_err(errors::rlp).map(Into::into)?;
 
-        Ok(self.client.execute_transactions(&transactions).map_err(errors::core)?)
+        Ok(self.client.execute_transactions(&transactions, &sender).map_err(errors::core)?)
+    }
+
+    fn trace_transaction_scripts(
+        &self,
+        raw: Bytes,
+        input_index: usize,
+        block_number: Option<u64>,
+    ) -> Result<Option<ScriptTrace>> {
+        let transaction: Transaction = UntrustedRlp::new(&raw.into_vec()).as_val().map_err(errors::rlp)?;
+        let block_id = block_number.map(BlockId::Number).unwrap_or(BlockId::Latest);
+        let trace = self.client.trace_transfer_input(&transaction, input_index, block_id).map_err(errors::core)?;
+        Ok(trace.map(From::from))
     }
 }
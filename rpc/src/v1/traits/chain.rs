@@ -15,16 +15,16 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use ckey::{Address, Public};
-use cstate::{Asset, AssetScheme};
+use cstate::{Asset, AssetScheme, World};
 use ctypes::invoice::{Invoice, ParcelInvoice};
 use ctypes::parcel::ChangeShard;
 use ctypes::transaction::Transaction;
-use ctypes::{BlockNumber, ShardId};
+use ctypes::{BlockNumber, ShardId, WorldId};
 use primitives::{H160, H256, U256};
 
 use jsonrpc_core::Result;
 
-use super::super::types::{Block, BlockNumberAndHash, Bytes, Parcel};
+use super::super::types::{Block, BlockNumberAndHash, Bytes, Parcel, ScriptTrace};
 
 build_rpc_trait! {
     pub trait Chain {
@@ -50,7 +50,7 @@ build_rpc_trait! {
 
         /// Gets asset scheme with given transaction hash.
         # [rpc(name = "chain_getAssetSchemeByHash")]
-        fn get_asset_scheme_by_hash(&self, H256, ShardId) -> Result<Option<AssetScheme>>;
+        fn get_asset_scheme_by_hash(&self, H256, ShardId, WorldId) -> Result<Option<AssetScheme>>;
 
         /// Gets asset scheme with given asset type.
         # [rpc(name = "chain_getAssetSchemeByType")]
@@ -84,6 +84,26 @@ build_rpc_trait! {
         # [rpc(name = "chain_getShardRoot")]
         fn get_shard_root(&self, ShardId, Option<u64>) -> Result<Option<H256>>;
 
+        /// Gets shard owners
+        # [rpc(name = "chain_getShardOwners")]
+        fn get_shard_owners(&self, ShardId, Option<u64>) -> Result<Option<Vec<Address>>>;
+
+        /// Gets shard users
+        # [rpc(name = "chain_getShardUsers")]
+        fn get_shard_users(&self, ShardId, Option<u64>) -> Result<Option<Vec<Address>>>;
+
+        /// Gets the number of worlds in the shard
+        # [rpc(name = "chain_getNumberOfWorlds")]
+        fn get_number_of_worlds(&self, ShardId, Option<u64>) -> Result<Option<WorldId>>;
+
+        /// Gets world with given shard id and world id
+        # [rpc(name = "chain_getWorld")]
+        fn get_world(&self, ShardId, WorldId, Option<u64>) -> Result<Option<World>>;
+
+        /// Asks the custom action handler with given id to read its data
+        # [rpc(name = "chain_getCustomActionData")]
+        fn get_custom_action_data(&self, u64, Bytes, Option<u64>) -> Result<Option<Bytes>>;
+
         /// Gets number of best block.
         # [rpc(name = "chain_getBestBlockNumber")]
         fn get_best_block_number(&self) -> Result<BlockNumber>;
@@ -118,6 +138,10 @@ build_rpc_trait! {
 
         /// Execute Transactions
         # [rpc(name = "chain_executeTransactions")]
-        fn execute_change_shard_state(&self, Bytes) -> Result<Vec<ChangeShard>>;
+        fn execute_change_shard_state(&self, Bytes, Address) -> Result<Vec<ChangeShard>>;
+
+        /// Traces the scripts of an input of the asset transfer step by step.
+        # [rpc(name = "chain_traceTransactionScripts")]
+        fn trace_transaction_scripts(&self, Bytes, usize, Option<u64>) -> Result<Option<ScriptTrace>>;
     }
 }
@@ -74,9 +74,11 @@ impl From<CoreBlock> for Block {
                         nonce: unverified.as_unsigned().nonce.clone(),
                         fee: unverified.as_unsigned().fee.clone(),
                         network_id: unverified.as_unsigned().network_id,
+                        expiration: unverified.as_unsigned().expiration,
                         action: unverified.as_unsigned().action.clone(),
                         hash: unverified.hash(),
                         sig: sig.into(),
+                        fee_payer_sig: unverified.fee_payer_signature(),
                     }
                 })
                 .collect(),
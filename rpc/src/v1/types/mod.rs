@@ -17,10 +17,12 @@
 mod block;
 mod bytes;
 mod parcel;
+mod script_trace;
 mod work;
 
 pub use self::block::Block;
 pub use self::block::BlockNumberAndHash;
 pub use self::bytes::Bytes;
 pub use self::parcel::Parcel;
+pub use self::script_trace::{ScriptTrace, TraceStep};
 pub use self::work::Work;
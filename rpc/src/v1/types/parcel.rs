@@ -16,7 +16,7 @@
 
 use ccore::{LocalizedParcel, SignedParcel};
 use ckey::Signature;
-use ctypes::parcel::Action;
+use ctypes::parcel::{Action, Expiration};
 use primitives::{H256, U256};
 
 #[derive(Debug, Serialize)]
@@ -28,9 +28,11 @@ pub struct Parcel {
     pub nonce: U256,
     pub fee: U256,
     pub network_id: u64,
+    pub expiration: Option<Expiration>,
     pub action: Action,
     pub hash: H256,
     pub sig: Signature,
+    pub fee_payer_sig: Option<Signature>,
 }
 
 impl From<LocalizedParcel> for Parcel {
@@ -43,9 +45,11 @@ impl From<LocalizedParcel> for Parcel {
             nonce: p.nonce,
             fee: p.fee,
             network_id: p.network_id,
+            expiration: p.expiration,
             action: p.action.clone(),
             hash: p.hash(),
             sig: sig.into(),
+            fee_payer_sig: p.fee_payer_signature(),
         }
     }
 }
@@ -60,9 +64,11 @@ impl From<SignedParcel> for Parcel {
             nonce: p.nonce,
             fee: p.fee,
             network_id: p.network_id,
+            expiration: p.expiration,
             action: p.action.clone(),
             hash: p.hash(),
             sig: sig.into(),
+            fee_payer_sig: p.fee_payer_signature(),
         }
     }
 }
@@ -0,0 +1,67 @@
+// Copyright 2018 Kodebox, Inc.
+// This file is part of CodeChain.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use cvm::{Frame, ScriptResult, ScriptTrace as VMScriptTrace, TraceStep as VMTraceStep};
+
+use super::Bytes;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceStep {
+    pub frame: String,
+    pub pc: usize,
+    pub instruction: String,
+    pub stack: Vec<Bytes>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptTrace {
+    pub steps: Vec<TraceStep>,
+    pub result: Option<String>,
+    pub error: Option<String>,
+}
+
+impl From<VMTraceStep> for TraceStep {
+    fn from(step: VMTraceStep) -> Self {
+        let frame = match step.frame {
+            Frame::Unlock => "unlock",
+            Frame::Lock => "lock",
+        };
+        Self {
+            frame: frame.to_string(),
+            pc: step.pc,
+            instruction: step.instruction.to_string(),
+            stack: step.stack.into_iter().map(Bytes::new).collect(),
+        }
+    }
+}
+
+impl From<VMScriptTrace> for ScriptTrace {
+    fn from(trace: VMScriptTrace) -> Self {
+        let (result, error) = match trace.result {
+            Ok(ScriptResult::Unlocked) => (Some("unlocked".to_string()), None),
+            Ok(ScriptResult::Fail) => (Some("fail".to_string()), None),
+            Ok(ScriptResult::Burnt) => (Some("burnt".to_string()), None),
+            Err(err) => (None, Some(format!("{:?}", err))),
+        };
+        Self {
+            steps: trace.steps.into_iter().map(From::from).collect(),
+            result,
+            error,
+        }
+    }
+}
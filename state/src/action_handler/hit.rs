@@ -14,73 +14,80 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use ccrypto::blake256;
+use ckey::Address;
 use cmerkle::TrieMut;
 use ctypes::invoice::Invoice;
 use ctypes::parcel::Outcome;
-use primitives::{Bytes, H256};
+use primitives::Bytes;
 use rlp::{self, Decodable, DecoderError, Encodable, UntrustedRlp};
 
-use super::super::{StateResult, TopLevelState, TopState, TopStateInfo};
+use super::super::{ActionDataAddress, StateResult, TopLevelState, TopState, TopStateInfo};
 use super::ActionHandler;
 
-const ACTION_ID: u8 = 0;
-
 pub struct HitAction {
     increase: u8,
 }
 
 impl Decodable for HitAction {
     fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
-        if rlp.item_count()? != 2 {
+        if rlp.item_count()? != 1 {
             return Err(DecoderError::RlpIncorrectListLen)
         }
-        if rlp.val_at::<u8>(0)? != ACTION_ID {
-            return Err(DecoderError::Custom("Unknown message id detected"))
-        }
         Ok(Self {
-            increase: rlp.val_at(1)?,
+            increase: rlp.val_at(0)?,
         })
     }
 }
 
+/// Counts the hits that the actions have given.
 #[derive(Clone)]
-pub struct HitHandler {}
+pub struct HitHandler {
+    handler_id: u64,
+}
 
 impl HitHandler {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(handler_id: u64) -> Self {
+        Self {
+            handler_id,
+        }
     }
 
-    fn address(&self) -> H256 {
-        let mut hash: H256 = blake256(&b"metadata hit");
-        hash[0] = b'M';
-        hash
+    fn address(&self) -> ActionDataAddress {
+        ActionDataAddress::new(self.handler_id, b"hit count")
     }
 }
 
 impl ActionHandler for HitHandler {
+    fn handler_id(&self) -> u64 {
+        self.handler_id
+    }
+
     fn init(&self, state: &mut TrieMut) -> StateResult<()> {
-        let r = state.insert(&self.address(), &1u32.rlp_bytes());
+        let data: Bytes = 1u32.rlp_bytes().to_vec();
+        let r = state.insert(&self.address(), &data.rlp_bytes());
         debug_assert_eq!(Ok(None), r);
         r?;
         Ok(())
     }
 
-    fn is_target(&self, bytes: &Bytes) -> bool {
+    fn is_valid(&self, bytes: &[u8]) -> bool {
         HitAction::decode(&UntrustedRlp::new(bytes)).is_ok()
     }
 
-    /// `bytes` must be valid encoding of HitAction
-    fn execute(&self, bytes: &Bytes, state: &mut TopLevelState) -> Option<StateResult<Outcome>> {
-        HitAction::decode(&UntrustedRlp::new(bytes)).ok().map(|action| {
-            let prev_counter: u32 = rlp::decode(&state.action_data(&self.address())?);
-            let increase = action.increase as u32;
-            state.update_action_data(&self.address(), (prev_counter + increase).rlp_bytes().to_vec())?;
-            Ok(Outcome::Single {
-                invoice: Invoice::Success,
-                error: None,
-            })
+    fn execute(&self, bytes: &[u8], state: &mut TopLevelState, _sender: &Address) -> StateResult<Outcome> {
+        let action =
+            HitAction::decode(&UntrustedRlp::new(bytes)).expect("The action must be verified before it is executed");
+        let prev_counter: u32 = state.action_data(&self.address())?.map_or(0, |data| rlp::decode(&data));
+        let increase = action.increase as u32;
+        state.update_action_data(&self.address(), (prev_counter + increase).rlp_bytes().to_vec())?;
+        Ok(Outcome::Single {
+            invoice: Invoice::Success,
+            error: None,
         })
     }
+
+    /// Returns the RLP of the hit count regardless of `query`.
+    fn query(&self, _query: &[u8], state: &TopStateInfo) -> StateResult<Option<Bytes>> {
+        Ok(state.action_data(&self.address())?)
+    }
 }
@@ -15,17 +15,29 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 mod hit;
+mod registry;
 
+use ckey::Address;
 use cmerkle::TrieMut;
 use ctypes::parcel::Outcome;
 use primitives::Bytes;
 
-use super::{StateResult, TopLevelState};
+use super::{StateResult, TopLevelState, TopStateInfo};
 
+/// A handler of `Action::Custom`. The chain spec registers a handler with its handler id,
+/// and the handler keeps its data under `ActionDataAddress`es of the id.
 pub trait ActionHandler: Send + Sync {
+    /// The id that `Action::Custom` specifies to be handled by this handler.
+    fn handler_id(&self) -> u64;
+    /// Writes the initial data of the handler into the genesis state.
     fn init(&self, state: &mut TrieMut) -> StateResult<()>;
-    fn is_target(&self, bytes: &Bytes) -> bool;
-    fn execute(&self, bytes: &Bytes, state: &mut TopLevelState) -> Option<StateResult<Outcome>>;
+    /// Returns true if `bytes` is a valid action of this handler.
+    fn is_valid(&self, bytes: &[u8]) -> bool;
+    /// Applies the action on behalf of `sender`. `bytes` must be a valid action of this handler.
+    fn execute(&self, bytes: &[u8], state: &mut TopLevelState, sender: &Address) -> StateResult<Outcome>;
+    /// Reads the data that `query` asks for. Returns `None` if there's no such data.
+    fn query(&self, query: &[u8], state: &TopStateInfo) -> StateResult<Option<Bytes>>;
 }
 
 pub use self::hit::HitHandler;
+pub use self::registry::RegistryHandler;
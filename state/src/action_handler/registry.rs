@@ -0,0 +1,293 @@
+// Copyright 2018 Kodebox, Inc.
+// This file is part of CodeChain.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use ckey::Address;
+use cmerkle::TrieMut;
+use ctypes::invoice::Invoice;
+use ctypes::parcel::{Error as ParcelError, Outcome};
+use primitives::Bytes;
+use rlp::{self, Decodable, DecoderError, Encodable, RlpStream, UntrustedRlp};
+
+use super::super::{ActionDataAddress, StateResult, TopLevelState, TopState, TopStateInfo};
+use super::ActionHandler;
+
+const SET: u8 = 1;
+const REMOVE: u8 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryAction {
+    Set {
+        key: Bytes,
+        value: Bytes,
+    },
+    Remove {
+        key: Bytes,
+    },
+}
+
+impl RegistryAction {
+    fn key(&self) -> &Bytes {
+        match self {
+            RegistryAction::Set {
+                key,
+                ..
+            } => key,
+            RegistryAction::Remove {
+                key,
+            } => key,
+        }
+    }
+}
+
+impl Encodable for RegistryAction {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        match self {
+            RegistryAction::Set {
+                key,
+                value,
+            } => {
+                s.begin_list(3).append(&SET).append(key).append(value);
+            }
+            RegistryAction::Remove {
+                key,
+            } => {
+                s.begin_list(2).append(&REMOVE).append(key);
+            }
+        }
+    }
+}
+
+impl Decodable for RegistryAction {
+    fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+        match rlp.val_at(0)? {
+            SET => {
+                if rlp.item_count()? != 3 {
+                    return Err(DecoderError::RlpIncorrectListLen)
+                }
+                Ok(RegistryAction::Set {
+                    key: rlp.val_at(1)?,
+                    value: rlp.val_at(2)?,
+                })
+            }
+            REMOVE => {
+                if rlp.item_count()? != 2 {
+                    return Err(DecoderError::RlpIncorrectListLen)
+                }
+                Ok(RegistryAction::Remove {
+                    key: rlp.val_at(1)?,
+                })
+            }
+            _ => Err(DecoderError::Custom("Unexpected registry action")),
+        }
+    }
+}
+
+/// A value in the registry and the address that set it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistryEntry {
+    pub owner: Address,
+    pub value: Bytes,
+}
+
+impl Encodable for RegistryEntry {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(2).append(&self.owner).append(&self.value);
+    }
+}
+
+impl Decodable for RegistryEntry {
+    fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+        if rlp.item_count()? != 2 {
+            return Err(DecoderError::RlpIncorrectListLen)
+        }
+        Ok(Self {
+            owner: rlp.val_at(0)?,
+            value: rlp.val_at(1)?,
+        })
+    }
+}
+
+/// A key-value registry. The address that sets a key first owns it,
+/// and only the owner can change or remove the value of the key.
+#[derive(Clone)]
+pub struct RegistryHandler {
+    handler_id: u64,
+}
+
+impl RegistryHandler {
+    pub fn new(handler_id: u64) -> Self {
+        Self {
+            handler_id,
+        }
+    }
+
+    fn address(&self, key: &[u8]) -> ActionDataAddress {
+        ActionDataAddress::new(self.handler_id, key)
+    }
+}
+
+impl ActionHandler for RegistryHandler {
+    fn handler_id(&self) -> u64 {
+        self.handler_id
+    }
+
+    fn init(&self, _state: &mut TrieMut) -> StateResult<()> {
+        Ok(())
+    }
+
+    fn is_valid(&self, bytes: &[u8]) -> bool {
+        RegistryAction::decode(&UntrustedRlp::new(bytes)).is_ok()
+    }
+
+    fn execute(&self, bytes: &[u8], state: &mut TopLevelState, sender: &Address) -> StateResult<Outcome> {
+        let action = RegistryAction::decode(&UntrustedRlp::new(bytes))
+            .expect("The action must be verified before it is executed");
+        let address = self.address(action.key());
+        if let Some(data) = state.action_data(&address)? {
+            let entry: RegistryEntry = rlp::decode(&data);
+            if entry.owner != *sender {
+                return Ok(Outcome::Single {
+                    invoice: Invoice::Failed,
+                    error: Some(ParcelError::InsufficientPermission),
+                })
+            }
+        }
+        match action {
+            RegistryAction::Set {
+                value,
+                ..
+            } => {
+                let entry = RegistryEntry {
+                    owner: *sender,
+                    value,
+                };
+                state.update_action_data(&address, entry.rlp_bytes().to_vec())?;
+            }
+            RegistryAction::Remove {
+                ..
+            } => state.remove_action_data(&address),
+        }
+        Ok(Outcome::Single {
+            invoice: Invoice::Success,
+            error: None,
+        })
+    }
+
+    /// Returns the RLP of the `RegistryEntry` whose key is `query`.
+    fn query(&self, query: &[u8], state: &TopStateInfo) -> StateResult<Option<Bytes>> {
+        Ok(state.action_data(&self.address(query))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ckey::Address;
+
+    use super::super::super::tests::helpers::get_temp_state;
+    use super::*;
+
+    fn set(key: &[u8], value: &[u8]) -> Bytes {
+        RegistryAction::Set {
+            key: key.to_vec(),
+            value: value.to_vec(),
+        }.rlp_bytes().to_vec()
+    }
+
+    fn remove(key: &[u8]) -> Bytes {
+        RegistryAction::Remove {
+            key: key.to_vec(),
+        }.rlp_bytes().to_vec()
+    }
+
+    fn success() -> Outcome {
+        Outcome::Single {
+            invoice: Invoice::Success,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn encode_and_decode_registry_action() {
+        let set = RegistryAction::Set {
+            key: b"key".to_vec(),
+            value: b"value".to_vec(),
+        };
+        assert_eq!(set, rlp::decode(&set.rlp_bytes()));
+        let remove = RegistryAction::Remove {
+            key: b"key".to_vec(),
+        };
+        assert_eq!(remove, rlp::decode(&remove.rlp_bytes()));
+    }
+
+    #[test]
+    fn is_valid() {
+        let handler = RegistryHandler::new(2);
+        assert!(handler.is_valid(&set(b"key", b"value")));
+        assert!(handler.is_valid(&remove(b"key")));
+        assert!(!handler.is_valid(&[0xc1, 0x03]));
+        assert!(!handler.is_valid(b""));
+    }
+
+    #[test]
+    fn owner_sets_and_removes_the_value() {
+        let handler = RegistryHandler::new(2);
+        let owner = Address::random();
+        let mut state = get_temp_state();
+
+        assert_eq!(Ok(None), handler.query(b"key", &state));
+        assert_eq!(Ok(success()), handler.execute(&set(b"key", b"value"), &mut state, &owner));
+        let entry = RegistryEntry {
+            owner,
+            value: b"value".to_vec(),
+        };
+        assert_eq!(Ok(Some(entry.rlp_bytes().to_vec())), handler.query(b"key", &state));
+
+        assert_eq!(Ok(success()), handler.execute(&remove(b"key"), &mut state, &owner));
+        assert_eq!(Ok(None), handler.query(b"key", &state));
+    }
+
+    #[test]
+    fn only_owner_can_change_the_value() {
+        let handler = RegistryHandler::new(2);
+        let owner = Address::random();
+        let other = Address::random();
+        let mut state = get_temp_state();
+        assert_eq!(Ok(success()), handler.execute(&set(b"key", b"value"), &mut state, &owner));
+
+        let failed = || Outcome::Single {
+            invoice: Invoice::Failed,
+            error: Some(ParcelError::InsufficientPermission),
+        };
+        assert_eq!(Ok(failed()), handler.execute(&set(b"key", b"other"), &mut state, &other));
+        assert_eq!(Ok(failed()), handler.execute(&remove(b"key"), &mut state, &other));
+
+        let entry = RegistryEntry {
+            owner,
+            value: b"value".to_vec(),
+        };
+        assert_eq!(Ok(Some(entry.rlp_bytes().to_vec())), handler.query(b"key", &state));
+    }
+
+    #[test]
+    fn handlers_with_different_ids_do_not_share_keys() {
+        let handler = RegistryHandler::new(2);
+        let another = RegistryHandler::new(3);
+        let owner = Address::random();
+        let mut state = get_temp_state();
+        assert_eq!(Ok(success()), handler.execute(&set(b"key", b"value"), &mut state, &owner));
+        assert_eq!(Ok(None), another.query(b"key", &state));
+    }
+}
@@ -41,11 +41,12 @@ use std::sync::Arc;
 
 use ckey::Address;
 use hashdb::HashDB;
-use primitives::{Bytes, H256};
+use primitives::Bytes;
 
 use super::{
-    Account, ActionHandler, Asset, AssetAddress, AssetScheme, AssetSchemeAddress, Metadata, MetadataAddress,
-    RegularAccount, RegularAccountAddress, Shard, ShardAddress, World, WorldAddress,
+    Account, ActionDataAddress, ActionHandler, Asset, AssetAddress, AssetScheme, AssetSchemeAddress, Metadata,
+    MetadataAddress, RegularAccount, RegularAccountAddress, Shard, ShardAddress, ShardMetadata, ShardMetadataAddress,
+    World, WorldAddress,
 };
 
 
@@ -69,7 +70,7 @@ pub trait TopBackend: Send {
     );
     fn add_to_metadata_cache(&mut self, address: MetadataAddress, item: Option<Metadata>, modified: bool);
     fn add_to_shard_cache(&mut self, address: ShardAddress, item: Option<Shard>, modified: bool);
-    fn add_to_action_data_cache(&mut self, address: H256, item: Option<Bytes>, modified: bool);
+    fn add_to_action_data_cache(&mut self, address: ActionDataAddress, item: Option<Bytes>, modified: bool);
 
     /// Get basic copy of the cached account. Not required to include storage.
     /// Returns 'None' if cache is disabled or if the account is not cached.
@@ -77,7 +78,7 @@ pub trait TopBackend: Send {
     fn get_cached_regular_account(&self, addr: &RegularAccountAddress) -> Option<Option<RegularAccount>>;
     fn get_cached_metadata(&self, addr: &MetadataAddress) -> Option<Option<Metadata>>;
     fn get_cached_shard(&self, addr: &ShardAddress) -> Option<Option<Shard>>;
-    fn get_cached_action_data(&self, key: &H256) -> Option<Option<Bytes>>;
+    fn get_cached_action_data(&self, addr: &ActionDataAddress) -> Option<Option<Bytes>>;
 
     /// Get value from a cached account.
     /// `None` is passed to the closure if the account entry cached
@@ -92,9 +93,19 @@ pub trait TopBackend: Send {
         F: FnOnce(Option<&mut RegularAccount>) -> U;
 
     fn custom_handlers(&self) -> &[Arc<ActionHandler>];
+
+    fn custom_handler(&self, handler_id: u64) -> Option<Arc<ActionHandler>> {
+        self.custom_handlers().iter().find(|handler| handler.handler_id() == handler_id).cloned()
+    }
 }
 
 pub trait ShardBackend: Send {
+    fn add_to_shard_metadata_cache(
+        &mut self,
+        address: ShardMetadataAddress,
+        item: Option<ShardMetadata>,
+        modified: bool,
+    );
     fn add_to_world_cache(&mut self, address: WorldAddress, item: Option<World>, modified: bool);
 
     /// Add an asset entry to the cache.
@@ -102,6 +113,7 @@ pub trait ShardBackend: Send {
     /// Add an asset entry to the cache.
     fn add_to_asset_cache(&mut self, addr: AssetAddress, asset: Option<Asset>, modified: bool);
 
+    fn get_cached_shard_metadata(&self, hash: &ShardMetadataAddress) -> Option<Option<ShardMetadata>>;
     fn get_cached_world(&self, hash: &WorldAddress) -> Option<Option<World>>;
 
     /// Get basic copy of the cached account. Not required to include storage.
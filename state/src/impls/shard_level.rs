@@ -15,6 +15,7 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use std::cell::RefMut;
+use std::collections::BTreeMap;
 use std::fmt;
 
 use ccrypto::{Blake, BLAKE_NULL_RLP};
@@ -22,11 +23,11 @@ use ckey::Address;
 use cmerkle::{self, Result as TrieResult, Trie, TrieError, TrieFactory};
 use ctypes::invoice::Invoice;
 use ctypes::transaction::{
-    AssetMintOutput, AssetTransferInput, AssetTransferOutput, Error as TransactionError, Outcome as TransactionOutcome,
-    Transaction,
+    AssetMintOutput, AssetOutPoint, AssetTransferInput, AssetTransferOutput, Error as TransactionError,
+    Outcome as TransactionOutcome, Transaction,
 };
-use ctypes::ShardId;
-use cvm::{decode, execute, ScriptResult, VMConfig};
+use ctypes::{BlockNumber, ShardId, WorldId};
+use cvm::{decode, execute, trace, ChainTimeInfo, Instruction, ScriptResult, ScriptTrace, VMConfig};
 use primitives::{Bytes, H256};
 use rlp::Encodable;
 use unexpected::Mismatch;
@@ -35,13 +36,18 @@ use super::super::backend::{Backend, ShardBackend};
 use super::super::checkpoint::{CheckpointId, StateWithCheckpoint};
 use super::super::item::cache::Cache;
 use super::super::traits::{ShardState, ShardStateInfo, StateWithCache};
-use super::super::{Asset, AssetAddress, AssetScheme, AssetSchemeAddress, ShardMetadata, ShardMetadataAddress};
+use super::super::{
+    Asset, AssetAddress, AssetScheme, AssetSchemeAddress, PooledAsset, ShardMetadata, ShardMetadataAddress, World,
+    WorldAddress,
+};
 use super::super::{StateDB, StateError, StateResult};
 
 
 pub struct ShardLevelState<B> {
     db: B,
     root: H256,
+    metadata: Cache<ShardMetadata>,
+    world: Cache<World>,
     asset_scheme: Cache<AssetScheme>,
     asset: Cache<Asset>,
     id_of_checkpoints: Vec<CheckpointId>,
@@ -68,6 +74,8 @@ impl<B: Backend + ShardBackend> ShardLevelState<B> {
         Ok(ShardLevelState {
             db,
             root,
+            metadata: Cache::new(),
+            world: Cache::new(),
             asset_scheme: Cache::new(),
             asset: Cache::new(),
             id_of_checkpoints: Default::default(),
@@ -90,6 +98,8 @@ impl<B: Backend + ShardBackend> ShardLevelState<B> {
         Ok(ShardLevelState {
             db,
             root,
+            metadata: Cache::new(),
+            world: Cache::new(),
             asset_scheme: Cache::new(),
             asset: Cache::new(),
             id_of_checkpoints: Default::default(),
@@ -104,10 +114,30 @@ impl<B: Backend + ShardBackend> ShardLevelState<B> {
         (self.root, self.db)
     }
 
-    fn apply_internal(&mut self, transaction: &Transaction) -> StateResult<()> {
+    fn apply_internal(
+        &mut self,
+        transaction: &Transaction,
+        sender: &Address,
+        shard_owners: &[Address],
+        block_number: BlockNumber,
+        block_timestamp: u64,
+        max_script_cost: u64,
+    ) -> StateResult<()> {
         debug_assert_eq!(Ok(()), transaction.verify());
         match transaction {
+            Transaction::CreateWorld {
+                nonce,
+                owners,
+                ..
+            } => self.create_world(shard_owners, nonce, owners, sender),
+            Transaction::SetWorldOwners {
+                world_id,
+                nonce,
+                owners,
+                ..
+            } => self.set_world_owners(*world_id, nonce, owners, sender),
             Transaction::AssetMint {
+                world_id,
                 metadata,
                 registrar,
                 output:
@@ -117,26 +147,235 @@ impl<B: Backend + ShardBackend> ShardLevelState<B> {
                         parameters,
                     },
                 ..
-            } => Ok(self.mint_asset(transaction.hash(), metadata, lock_script_hash, parameters, amount, registrar)?),
+            } => self.mint_asset(
+                transaction.hash(),
+                *world_id,
+                metadata,
+                lock_script_hash,
+                parameters,
+                amount,
+                registrar,
+                sender,
+                block_number,
+            ),
             Transaction::AssetTransfer {
                 burns,
                 inputs,
                 outputs,
                 ..
-            } => self.transfer_asset(&transaction, burns, inputs, outputs),
+            } => self.transfer_asset(
+                &transaction,
+                burns,
+                inputs,
+                outputs,
+                block_number,
+                block_timestamp,
+                max_script_cost,
+            ),
+            Transaction::AssetChangeRegistrar {
+                asset_type,
+                registrar,
+                ..
+            } => self.change_registrar(asset_type, registrar, sender),
+            Transaction::AssetIncreaseSupply {
+                asset_type,
+                lock_script_hash,
+                parameters,
+                amount,
+                ..
+            } => self.increase_supply(
+                transaction.hash(),
+                asset_type,
+                lock_script_hash,
+                parameters,
+                *amount,
+                sender,
+                block_number,
+            ),
+            Transaction::AssetFreeze {
+                outpoint,
+                ..
+            } => self.set_frozen(outpoint, true, sender),
+            Transaction::AssetUnfreeze {
+                outpoint,
+                ..
+            } => self.set_frozen(outpoint, false, sender),
+            Transaction::AssetCompose {
+                world_id,
+                metadata,
+                registrar,
+                inputs,
+                lock_script_hash,
+                parameters,
+                amount,
+                ..
+            } => self.compose_asset(
+                &transaction,
+                *world_id,
+                metadata,
+                registrar,
+                inputs,
+                lock_script_hash,
+                parameters,
+                *amount,
+                sender,
+                block_number,
+                block_timestamp,
+                max_script_cost,
+            ),
+            Transaction::AssetDecompose {
+                input,
+                outputs,
+                ..
+            } => self.decompose_asset(&transaction, input, outputs, block_number, block_timestamp, max_script_cost),
+            Transaction::AssetUnwrapCCC {
+                burn,
+                ..
+            } => self.unwrap_ccc(&transaction, burn, block_number, block_timestamp, max_script_cost),
+        }
+    }
+
+    /// Runs the scripts of the `input_index`-th input of an asset transfer, recording every executed instruction.
+    /// Returns `None` if the transaction is not a transfer or it doesn't have such an input.
+    pub fn trace_transfer_input(
+        &self,
+        transaction: &Transaction,
+        input_index: usize,
+        block_number: BlockNumber,
+        block_timestamp: u64,
+        max_script_cost: u64,
+    ) -> StateResult<Option<ScriptTrace>> {
+        let input = match transaction {
+            Transaction::AssetTransfer {
+                inputs,
+                ..
+            } => match inputs.get(input_index) {
+                Some(input) => input,
+                None => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        let (_, asset) = self.spent_asset(input)?;
+        let (unlock_script, lock_script) = decode_scripts(input)?;
+
+        let chain_time = ChainTimeInfo {
+            block_number,
+            block_timestamp,
+            output_block_number: asset.created_at(),
+        };
+        Ok(Some(trace(
+            &unlock_script,
+            &asset.parameters(),
+            &lock_script,
+            transaction,
+            input,
+            chain_time,
+            VMConfig {
+                max_cost: max_script_cost,
+                ..VMConfig::default()
+            },
+        )))
+    }
+
+    /// Creates an asset of the wrapped CCC and increases its supply.
+    /// The caller must take the same amount of CCC from the balance of the sender.
+    pub fn wrap_ccc(
+        &mut self,
+        parcel_hash: H256,
+        lock_script_hash: &H256,
+        parameters: &[Bytes],
+        amount: u64,
+        block_number: BlockNumber,
+    ) -> StateResult<()> {
+        let asset_scheme_address = AssetSchemeAddress::new_with_zero_suffix(self.shard_id);
+        {
+            let mut asset_scheme =
+                self.require_asset_scheme(&asset_scheme_address, || AssetScheme::new(String::new(), 0, None))?;
+            if !asset_scheme.increase_amount(amount) {
+                return Err(TransactionError::CannotIncreaseSupply(asset_scheme_address.clone().into()).into())
+            }
+            ctrace!(TX, "The supply of the wrapped CCC is increased to {}", asset_scheme.amount());
+        }
+
+        let asset_address = AssetAddress::new(parcel_hash, 0, self.shard_id);
+        let asset = self.require_asset(&asset_address, || {
+            Asset::new(asset_scheme_address.into(), *lock_script_hash, parameters.to_vec(), amount, block_number)
+        })?;
+        ctrace!(TX, "{:?} is generated on {:?}", asset, asset_address);
+        Ok(())
+    }
+
+    fn create_world(
+        &mut self,
+        shard_owners: &[Address],
+        nonce: &u64,
+        owners: &[Address],
+        sender: &Address,
+    ) -> StateResult<()> {
+        if !shard_owners.contains(sender) {
+            return Err(TransactionError::InsufficientPermission.into())
+        }
+
+        let mut metadata = self.require_metadata()?;
+        if metadata.nonce() != nonce {
+            return Err(TransactionError::InvalidShardNonce(Mismatch {
+                expected: *metadata.nonce(),
+                found: *nonce,
+            }).into())
+        }
+        metadata.inc_nonce();
+        let world_id = metadata.increase_number_of_worlds();
+
+        let world_address = WorldAddress::new(self.shard_id, world_id);
+        let world = self.require_world(&world_address, || World::new(owners.to_vec()))?;
+        ctrace!(TX, "{:?} is created on {:?}", world, world_address);
+        Ok(())
+    }
+
+    fn set_world_owners(
+        &mut self,
+        world_id: WorldId,
+        nonce: &u64,
+        owners: &[Address],
+        sender: &Address,
+    ) -> StateResult<()> {
+        let world_address = WorldAddress::new(self.shard_id, world_id);
+        let mut world = self.world(world_id)?.ok_or(TransactionError::InvalidWorldId(world_id))?;
+        if !world.world_owners().contains(sender) {
+            return Err(TransactionError::InsufficientPermission.into())
         }
+        if world.nonce() != nonce {
+            return Err(TransactionError::InvalidWorldNonce(Mismatch {
+                expected: *world.nonce(),
+                found: *nonce,
+            }).into())
+        }
+        world.set_owners(owners.to_vec());
+        world.inc_nonce();
+
+        ctrace!(TX, "{:?} is changed to {:?}", world_address, world);
+        *self.require_world(&world_address, || unreachable!("The world must exist"))? = world;
+        Ok(())
     }
 
     fn mint_asset(
         &mut self,
         transaction_hash: H256,
+        world_id: WorldId,
         metadata: &String,
         lock_script_hash: &H256,
         parameters: &Vec<Bytes>,
         amount: &Option<u64>,
         registrar: &Option<Address>,
+        sender: &Address,
+        block_number: BlockNumber,
     ) -> StateResult<()> {
-        let asset_scheme_address = AssetSchemeAddress::new(transaction_hash, self.shard_id);
+        let world = self.world(world_id)?.ok_or(TransactionError::InvalidWorldId(world_id))?;
+        if !world.world_owners().contains(sender) {
+            return Err(TransactionError::InsufficientPermission.into())
+        }
+
+        let asset_scheme_address = AssetSchemeAddress::new(transaction_hash, self.shard_id, world_id);
         let amount = amount.unwrap_or(::std::u64::MAX);
         let asset_scheme = self.require_asset_scheme(&asset_scheme_address, || {
             AssetScheme::new(metadata.clone(), amount, registrar.clone())
@@ -145,67 +384,37 @@ impl<B: Backend + ShardBackend> ShardLevelState<B> {
 
         let asset_address = AssetAddress::new(transaction_hash, 0, self.shard_id);
         let asset = self.require_asset(&asset_address, || {
-            Asset::new(asset_scheme_address.into(), *lock_script_hash, parameters.clone(), amount)
+            Asset::new(asset_scheme_address.into(), *lock_script_hash, parameters.clone(), amount, block_number)
         });
         ctrace!(TX, "{:?} is generated on {:?}", asset, asset_address);
         Ok(())
     }
 
+    /// Spends the inputs and creates the outputs that are in this shard.
+    /// The other shards that a cross-shard transfer is related to apply the rest of it.
     fn transfer_asset(
         &mut self,
         transaction: &Transaction,
         burns: &[AssetTransferInput],
         inputs: &[AssetTransferInput],
         outputs: &[AssetTransferOutput],
+        block_number: BlockNumber,
+        block_timestamp: u64,
+        max_script_cost: u64,
     ) -> StateResult<()> {
-        for (input, burn) in inputs.iter().map(|input| (input, false)).chain(burns.iter().map(|input| (input, true))) {
-            let (address_hash, asset) = {
-                let index = input.prev_out.index;
-                let address = AssetAddress::new(input.prev_out.transaction_hash, index, self.shard_id);
-                match self.asset(&address)? {
-                    Some(asset) => (address.into(), asset),
-                    None => return Err(TransactionError::AssetNotFound(address.into()).into()),
-                }
-            };
-
-            if *asset.lock_script_hash() != Blake::blake(&input.lock_script) {
-                let mismatch = Mismatch {
-                    expected: *asset.lock_script_hash(),
-                    found: Blake::blake(&input.lock_script),
-                };
-                return Err(TransactionError::ScriptHashMismatch(mismatch).into())
-            }
-
-            let script_result = match (decode(&input.lock_script), decode(&input.unlock_script)) {
-                (Ok(lock_script), Ok(unlock_script)) => {
-                    // FIXME : apply parameters to vm
-                    execute(
-                        &unlock_script,
-                        &asset.parameters(),
-                        &lock_script,
-                        transaction.hash_without_script(),
-                        VMConfig::default(),
-                    )
-                }
-                // FIXME : Deliver full decode error
-                _ => return Err(TransactionError::InvalidScript.into()),
-            };
-
-            match script_result {
-                Ok(result) => match (result, burn) {
-                    (ScriptResult::Unlocked, false) => {}
-                    (ScriptResult::Burnt, true) => {}
-                    _ => return Err(TransactionError::FailedToUnlock(address_hash).into()),
-                },
-                Err(err) => {
-                    ctrace!(TX, "Cannot run unlock/lock script {:?}", err);
-                    return Err(TransactionError::FailedToUnlock(address_hash).into())
-                }
-            }
+        let shard_id = self.shard_id;
+        let is_in_shard = |input: &&AssetTransferInput| input.related_shard() == shard_id;
+        for (input, burn) in inputs
+            .iter()
+            .filter(is_in_shard)
+            .map(|input| (input, false))
+            .chain(burns.iter().filter(is_in_shard).map(|input| (input, true)))
+        {
+            self.unlock_input(transaction, input, burn, block_number, block_timestamp, max_script_cost)?;
         }
 
         let mut deleted_asset = Vec::with_capacity(inputs.len());
-        for input in inputs {
+        for input in inputs.iter().filter(is_in_shard) {
             let index = input.prev_out.index;
             let amount = input.prev_out.amount;
             let address = AssetAddress::new(input.prev_out.transaction_hash, index, self.shard_id);
@@ -213,9 +422,12 @@ impl<B: Backend + ShardBackend> ShardLevelState<B> {
             let asset_type = input.prev_out.asset_type.clone();
             let asset_scheme_address = AssetSchemeAddress::from_hash(asset_type)
                 .ok_or(TransactionError::AssetSchemeNotFound(asset_type.into()))?;
-            let _asset_scheme = self
-                .asset_scheme((&asset_scheme_address).into())?
-                .ok_or(TransactionError::AssetSchemeNotFound(asset_scheme_address.into()))?;
+            // The asset scheme of an asset that has moved from another shard is not in this shard.
+            if asset_scheme_address.shard_id() == self.shard_id {
+                let _asset_scheme = self
+                    .asset_scheme((&asset_scheme_address).into())?
+                    .ok_or(TransactionError::AssetSchemeNotFound(asset_scheme_address.into()))?;
+            }
 
             match self.asset(&address)? {
                 Some(asset) => {
@@ -238,10 +450,15 @@ impl<B: Backend + ShardBackend> ShardLevelState<B> {
             deleted_asset.push((hash, amount));
         }
         let mut created_asset = Vec::with_capacity(outputs.len());
-        for (index, output) in outputs.iter().enumerate() {
+        for (index, output) in outputs.iter().enumerate().filter(|(_, output)| output.shard_id == self.shard_id) {
             let asset_address = AssetAddress::new(transaction.hash(), index, self.shard_id);
-            let asset =
-                Asset::new(output.asset_type, output.lock_script_hash, output.parameters.clone(), output.amount);
+            let asset = Asset::new(
+                output.asset_type,
+                output.lock_script_hash,
+                output.parameters.clone(),
+                output.amount,
+                block_number,
+            );
             self.require_asset(&asset_address, || asset)?;
             created_asset.push((asset_address, output.amount));
         }
@@ -250,10 +467,331 @@ impl<B: Backend + ShardBackend> ShardLevelState<B> {
         Ok(())
     }
 
+    fn compose_asset(
+        &mut self,
+        transaction: &Transaction,
+        world_id: WorldId,
+        metadata: &String,
+        registrar: &Option<Address>,
+        inputs: &[AssetTransferInput],
+        lock_script_hash: &H256,
+        parameters: &Vec<Bytes>,
+        amount: u64,
+        sender: &Address,
+        block_number: BlockNumber,
+        block_timestamp: u64,
+        max_script_cost: u64,
+    ) -> StateResult<()> {
+        let world = self.world(world_id)?.ok_or(TransactionError::InvalidWorldId(world_id))?;
+        if !world.world_owners().contains(sender) {
+            return Err(TransactionError::InsufficientPermission.into())
+        }
+
+        let mut pool: BTreeMap<H256, u64> = BTreeMap::new();
+        for input in inputs {
+            let (address_hash, asset) =
+                self.unlock_input(transaction, input, false, block_number, block_timestamp, max_script_cost)?;
+            if *asset.amount() != input.prev_out.amount {
+                return Err(TransactionError::InvalidAssetAmount {
+                    address: address_hash,
+                    expected: *asset.amount(),
+                    got: input.prev_out.amount,
+                }.into())
+            }
+            let pooled_amount = pool.entry(*asset.asset_type()).or_insert(0);
+            *pooled_amount =
+                pooled_amount.checked_add(*asset.amount()).ok_or(TransactionError::InconsistentTransactionInOut)?;
+
+            let address = AssetAddress::new(input.prev_out.transaction_hash, input.prev_out.index, self.shard_id);
+            self.kill_asset(&address);
+        }
+        let pool: Vec<_> = pool
+            .into_iter()
+            .map(|(asset_type, amount)| PooledAsset {
+                asset_type,
+                amount,
+            })
+            .collect();
+
+        let transaction_hash = transaction.hash();
+        let asset_scheme_address = AssetSchemeAddress::new(transaction_hash, self.shard_id, world_id);
+        let asset_scheme = self.require_asset_scheme(&asset_scheme_address, || {
+            AssetScheme::new_with_pool(metadata.clone(), amount, registrar.clone(), pool)
+        })?;
+        ctrace!(TX, "{:?} is composed on {:?}", asset_scheme, asset_scheme_address);
+
+        let asset_address = AssetAddress::new(transaction_hash, 0, self.shard_id);
+        let asset = self.require_asset(&asset_address, || {
+            Asset::new(asset_scheme_address.into(), *lock_script_hash, parameters.clone(), amount, block_number)
+        })?;
+        ctrace!(TX, "{:?} is generated on {:?}", asset, asset_address);
+        Ok(())
+    }
+
+    /// Spends the composite asset if it is in this shard, and creates the outputs that are in this shard.
+    fn decompose_asset(
+        &mut self,
+        transaction: &Transaction,
+        input: &AssetTransferInput,
+        outputs: &[AssetTransferOutput],
+        block_number: BlockNumber,
+        block_timestamp: u64,
+        max_script_cost: u64,
+    ) -> StateResult<()> {
+        if input.related_shard() == self.shard_id {
+            let (address_hash, asset) =
+                self.unlock_input(transaction, input, false, block_number, block_timestamp, max_script_cost)?;
+            let asset_scheme_address = AssetSchemeAddress::from_hash(*asset.asset_type())
+                .ok_or(TransactionError::InvalidAssetType(*asset.asset_type()))?;
+            let asset_scheme = self
+                .asset_scheme(&asset_scheme_address)?
+                .ok_or(TransactionError::AssetSchemeNotFound(*asset.asset_type()))?;
+            if !asset_scheme.is_composite()
+                || asset.amount() != asset_scheme.amount()
+                || *asset.amount() != input.prev_out.amount
+            {
+                return Err(TransactionError::InvalidDecomposedInput(address_hash).into())
+            }
+
+            let mut sum: BTreeMap<H256, u64> = BTreeMap::new();
+            for output in outputs {
+                let amount = sum.entry(output.asset_type).or_insert(0);
+                *amount = amount.checked_add(output.amount).ok_or(TransactionError::InvalidDecomposedOutput)?;
+            }
+            let pool: BTreeMap<H256, u64> =
+                asset_scheme.pool().iter().map(|pooled| (pooled.asset_type, pooled.amount)).collect();
+            if sum != pool {
+                return Err(TransactionError::InvalidDecomposedOutput.into())
+            }
+
+            let address = AssetAddress::new(input.prev_out.transaction_hash, input.prev_out.index, self.shard_id);
+            self.kill_asset(&address);
+            self.asset_scheme.remove(&asset_scheme_address);
+            ctrace!(TX, "{:?} is decomposed", asset_scheme_address);
+        }
+
+        for (index, output) in outputs.iter().enumerate().filter(|(_, output)| output.shard_id == self.shard_id) {
+            let asset_address = AssetAddress::new(transaction.hash(), index, self.shard_id);
+            let asset = Asset::new(
+                output.asset_type,
+                output.lock_script_hash,
+                output.parameters.clone(),
+                output.amount,
+                block_number,
+            );
+            self.require_asset(&asset_address, || asset)?;
+        }
+        Ok(())
+    }
+
+    /// Burns an asset of the wrapped CCC and decreases its supply.
+    /// The top level state gives the CCC back when the transaction succeeds.
+    fn unwrap_ccc(
+        &mut self,
+        transaction: &Transaction,
+        burn: &AssetTransferInput,
+        block_number: BlockNumber,
+        block_timestamp: u64,
+        max_script_cost: u64,
+    ) -> StateResult<()> {
+        let (address_hash, asset) =
+            self.unlock_input(transaction, burn, true, block_number, block_timestamp, max_script_cost)?;
+        let asset_scheme_address = AssetSchemeAddress::new_with_zero_suffix(self.shard_id);
+        let wrapped_ccc_type: H256 = asset_scheme_address.clone().into();
+        if burn.prev_out.asset_type != wrapped_ccc_type || *asset.asset_type() != wrapped_ccc_type {
+            return Err(TransactionError::InvalidAssetType(burn.prev_out.asset_type).into())
+        }
+        if *asset.amount() != burn.prev_out.amount {
+            return Err(TransactionError::InvalidAssetAmount {
+                address: address_hash,
+                expected: *asset.amount(),
+                got: burn.prev_out.amount,
+            }.into())
+        }
+
+        {
+            let mut asset_scheme = self
+                .require_asset_scheme(&asset_scheme_address, || unreachable!("The wrapped CCC must have its scheme"))?;
+            if !asset_scheme.decrease_amount(*asset.amount()) {
+                return Err(TransactionError::InvalidAssetAmount {
+                    address: wrapped_ccc_type,
+                    expected: *asset_scheme.amount(),
+                    got: *asset.amount(),
+                }.into())
+            }
+            ctrace!(TX, "The supply of the wrapped CCC is decreased to {}", asset_scheme.amount());
+        }
+
+        let address = AssetAddress::new(burn.prev_out.transaction_hash, burn.prev_out.index, self.shard_id);
+        self.kill_asset(&address);
+        Ok(())
+    }
+
+    /// Returns the address of the asset scheme of `asset_type` after checking that `sender` is its registrar.
+    fn registrar_scheme_address(&self, asset_type: &H256, sender: &Address) -> StateResult<AssetSchemeAddress> {
+        let asset_scheme_address =
+            AssetSchemeAddress::from_hash(*asset_type).ok_or(TransactionError::InvalidAssetType(*asset_type))?;
+        let asset_scheme =
+            self.asset_scheme(&asset_scheme_address)?.ok_or(TransactionError::AssetSchemeNotFound(*asset_type))?;
+        if asset_scheme.registrar() != &Some(*sender) {
+            return Err(TransactionError::InsufficientPermission.into())
+        }
+        Ok(asset_scheme_address)
+    }
+
+    fn change_registrar(
+        &mut self,
+        asset_type: &H256,
+        registrar: &Option<Address>,
+        sender: &Address,
+    ) -> StateResult<()> {
+        let asset_scheme_address = self.registrar_scheme_address(asset_type, sender)?;
+        let mut asset_scheme =
+            self.require_asset_scheme(&asset_scheme_address, || unreachable!("The asset scheme must exist"))?;
+        asset_scheme.set_registrar(*registrar);
+        ctrace!(TX, "The registrar of {:?} is changed to {:?}", asset_scheme_address, registrar);
+        Ok(())
+    }
+
+    fn increase_supply(
+        &mut self,
+        transaction_hash: H256,
+        asset_type: &H256,
+        lock_script_hash: &H256,
+        parameters: &Vec<Bytes>,
+        amount: u64,
+        sender: &Address,
+        block_number: BlockNumber,
+    ) -> StateResult<()> {
+        let asset_scheme_address = self.registrar_scheme_address(asset_type, sender)?;
+        {
+            let mut asset_scheme =
+                self.require_asset_scheme(&asset_scheme_address, || unreachable!("The asset scheme must exist"))?;
+            if !asset_scheme.increase_amount(amount) {
+                return Err(TransactionError::CannotIncreaseSupply(*asset_type).into())
+            }
+            ctrace!(TX, "The supply of {:?} is increased to {}", asset_scheme_address, asset_scheme.amount());
+        }
+
+        let asset_address = AssetAddress::new(transaction_hash, 0, self.shard_id);
+        let asset = self.require_asset(&asset_address, || {
+            Asset::new(*asset_type, *lock_script_hash, parameters.clone(), amount, block_number)
+        })?;
+        ctrace!(TX, "{:?} is generated on {:?}", asset, asset_address);
+        Ok(())
+    }
+
+    fn set_frozen(&mut self, outpoint: &AssetOutPoint, frozen: bool, sender: &Address) -> StateResult<()> {
+        self.registrar_scheme_address(&outpoint.asset_type, sender)?;
+
+        let address = AssetAddress::new(outpoint.transaction_hash, outpoint.index, self.shard_id);
+        let asset = self.asset(&address)?.ok_or_else(|| TransactionError::AssetNotFound(address.clone().into()))?;
+        if asset.asset_type() != &outpoint.asset_type {
+            return Err(TransactionError::InvalidAssetType(outpoint.asset_type).into())
+        }
+        self.require_asset(&address, || unreachable!("The asset must exist"))?.set_frozen(frozen);
+        ctrace!(
+            TX,
+            "{:?} is {}",
+            address,
+            if frozen {
+                "frozen"
+            } else {
+                "unfrozen"
+            }
+        );
+        Ok(())
+    }
+
+    /// Runs the scripts of the input and returns the asset that it spends.
+    /// The scripts must burn the asset if `burn` is true, and unlock it otherwise.
+    fn unlock_input(
+        &self,
+        transaction: &Transaction,
+        input: &AssetTransferInput,
+        burn: bool,
+        block_number: BlockNumber,
+        block_timestamp: u64,
+        max_script_cost: u64,
+    ) -> StateResult<(H256, Asset)> {
+        let (address_hash, asset) = self.spent_asset(input)?;
+        if asset.is_frozen() {
+            return Err(TransactionError::AssetFrozen(address_hash).into())
+        }
+        let (unlock_script, lock_script) = decode_scripts(input)?;
+
+        let chain_time = ChainTimeInfo {
+            block_number,
+            block_timestamp,
+            output_block_number: asset.created_at(),
+        };
+        // FIXME : apply parameters to vm
+        let script_result = execute(
+            &unlock_script,
+            &asset.parameters(),
+            &lock_script,
+            transaction,
+            input,
+            chain_time,
+            VMConfig {
+                max_cost: max_script_cost,
+                ..VMConfig::default()
+            },
+        );
+
+        match script_result {
+            Ok(result) => match (result, burn) {
+                (ScriptResult::Unlocked, false) => {}
+                (ScriptResult::Burnt, true) => {}
+                _ => return Err(TransactionError::FailedToUnlock(address_hash).into()),
+            },
+            Err(err) => {
+                ctrace!(TX, "Cannot run unlock/lock script {:?}", err);
+                return Err(TransactionError::FailedToUnlock(address_hash).into())
+            }
+        }
+        Ok((address_hash, asset))
+    }
+
+    /// Returns the asset spent by the input after checking the hash of its lock script.
+    fn spent_asset(&self, input: &AssetTransferInput) -> StateResult<(H256, Asset)> {
+        let index = input.prev_out.index;
+        let address = AssetAddress::new(input.prev_out.transaction_hash, index, self.shard_id);
+        let asset = match self.asset(&address)? {
+            Some(asset) => asset,
+            None => return Err(TransactionError::AssetNotFound(address.into()).into()),
+        };
+
+        if *asset.lock_script_hash() != Blake::blake(&input.lock_script) {
+            let mismatch = Mismatch {
+                expected: *asset.lock_script_hash(),
+                found: Blake::blake(&input.lock_script),
+            };
+            return Err(TransactionError::ScriptHashMismatch(mismatch).into())
+        }
+        Ok((address.into(), asset))
+    }
+
     fn kill_asset(&mut self, account: &AssetAddress) {
         self.asset.remove(account);
     }
 
+    fn require_metadata<'a>(&'a self) -> cmerkle::Result<RefMut<'a, ShardMetadata>> {
+        let default = || ShardMetadata::new(0);
+        let db = self.trie_factory.readonly(self.db.as_hashdb(), &self.root)?;
+        let address = ShardMetadataAddress::new(self.shard_id);
+        let from_db = || self.db.get_cached_shard_metadata(&address);
+        self.metadata.require_item_or_from(&address, default, db, from_db)
+    }
+
+    fn require_world<'a, F>(&'a self, a: &WorldAddress, default: F) -> cmerkle::Result<RefMut<'a, World>>
+    where
+        F: FnOnce() -> World, {
+        let db = self.trie_factory.readonly(self.db.as_hashdb(), &self.root)?;
+        let from_db = || self.db.get_cached_world(a);
+        self.world.require_item_or_from(a, default, db, from_db)
+    }
+
     fn require_asset_scheme<'a, F>(
         &'a self,
         a: &AssetSchemeAddress,
@@ -280,6 +818,19 @@ impl<B: Backend + ShardBackend> ShardStateInfo for ShardLevelState<B> {
         &self.root
     }
 
+    fn metadata(&self) -> cmerkle::Result<Option<ShardMetadata>> {
+        let address = ShardMetadataAddress::new(self.shard_id);
+        let trie = self.trie_factory.readonly(self.db.as_hashdb(), &self.root)?;
+        self.metadata
+            .ensure_cached(&address, &|metadata| metadata.cloned(), trie, |a| self.db.get_cached_shard_metadata(a))
+    }
+
+    fn world(&self, world_id: WorldId) -> cmerkle::Result<Option<World>> {
+        let address = WorldAddress::new(self.shard_id, world_id);
+        let trie = self.trie_factory.readonly(self.db.as_hashdb(), &self.root)?;
+        self.world.ensure_cached(&address, &|world| world.cloned(), trie, |a| self.db.get_cached_world(a))
+    }
+
     fn asset_scheme(&self, a: &AssetSchemeAddress) -> cmerkle::Result<Option<AssetScheme>> {
         let cached_asset = self.db.get_cached_asset_scheme(&a).and_then(|asset_scheme| asset_scheme);
         if cached_asset.is_some() {
@@ -291,19 +842,26 @@ impl<B: Backend + ShardBackend> ShardStateInfo for ShardLevelState<B> {
     }
 
     fn asset(&self, a: &AssetAddress) -> cmerkle::Result<Option<Asset>> {
-        let cached_asset = self.db.get_cached_asset(&a).and_then(|asset| asset);
-        if cached_asset.is_some() {
-            return Ok(cached_asset)
-        }
-
+        // The local cache must be read first, so that an asset spent by this block isn't found again.
         let trie = self.trie_factory.readonly(self.db.as_hashdb(), &self.root)?;
-        Ok(trie.get_with(a.as_ref(), ::rlp::decode::<Asset>)?)
+        self.asset.ensure_cached(a, &|asset| asset.cloned(), trie, |a| self.db.get_cached_asset(a))
+    }
+}
+
+/// Decodes the unlock script and the lock script of the input.
+fn decode_scripts(input: &AssetTransferInput) -> StateResult<(Vec<Instruction>, Vec<Instruction>)> {
+    match (decode(&input.unlock_script), decode(&input.lock_script)) {
+        (Ok(unlock_script), Ok(lock_script)) => Ok((unlock_script, lock_script)),
+        // FIXME : Deliver full decode error
+        _ => Err(TransactionError::InvalidScript.into()),
     }
 }
 
 impl<B> StateWithCheckpoint for ShardLevelState<B> {
     fn create_checkpoint(&mut self, id: CheckpointId) {
         self.id_of_checkpoints.push(id);
+        self.metadata.checkpoint();
+        self.world.checkpoint();
         self.asset_scheme.checkpoint();
         self.asset.checkpoint();
     }
@@ -312,6 +870,8 @@ impl<B> StateWithCheckpoint for ShardLevelState<B> {
         let expected = self.id_of_checkpoints.pop().expect("The checkpoint must exist");
         assert_eq!(expected, id);
 
+        self.metadata.discard_checkpoint();
+        self.world.discard_checkpoint();
         self.asset_scheme.discard_checkpoint();
         self.asset.discard_checkpoint();
     }
@@ -320,6 +880,8 @@ impl<B> StateWithCheckpoint for ShardLevelState<B> {
         let expected = self.id_of_checkpoints.pop().expect("The checkpoint must exist");
         assert_eq!(expected, id);
 
+        self.metadata.revert_to_checkpoint();
+        self.world.revert_to_checkpoint();
         self.asset_scheme.revert_to_checkpoint();
         self.asset.revert_to_checkpoint();
     }
@@ -328,6 +890,8 @@ impl<B> StateWithCheckpoint for ShardLevelState<B> {
 impl<B: Backend + ShardBackend> StateWithCache for ShardLevelState<B> {
     fn commit(&mut self) -> TrieResult<()> {
         let mut trie = self.trie_factory.from_existing(self.db.as_hashdb_mut(), &mut self.root)?;
+        self.metadata.commit(&mut trie)?;
+        self.world.commit(&mut trie)?;
         self.asset_scheme.commit(&mut trie)?;
         self.asset.commit(&mut trie)?;
         Ok(())
@@ -335,6 +899,12 @@ impl<B: Backend + ShardBackend> StateWithCache for ShardLevelState<B> {
 
     fn propagate_to_global_cache(&mut self) {
         let ref mut db = self.db;
+        self.metadata.propagate_to_global_cache(|address, item, modified| {
+            db.add_to_shard_metadata_cache(address, item, modified);
+        });
+        self.world.propagate_to_global_cache(|address, item, modified| {
+            db.add_to_world_cache(address, item, modified);
+        });
         self.asset_scheme.propagate_to_global_cache(|address, item, modified| {
             db.add_to_asset_scheme_cache(address, item, modified);
         });
@@ -344,6 +914,8 @@ impl<B: Backend + ShardBackend> StateWithCache for ShardLevelState<B> {
     }
 
     fn clear(&mut self) {
+        self.metadata.clear();
+        self.world.clear();
         self.asset_scheme.clear();
         self.asset.clear();
     }
@@ -351,6 +923,8 @@ impl<B: Backend + ShardBackend> StateWithCache for ShardLevelState<B> {
 
 impl<B: ShardBackend> fmt::Debug for ShardLevelState<B> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "metadata: {:?}", self.metadata)?;
+        writeln!(f, "world: {:?}", self.world)?;
         write!(f, "asset_scheme: {:?} asset: {:?}", self.asset_scheme, self.asset)
     }
 }
@@ -363,6 +937,8 @@ impl Clone for ShardLevelState<StateDB> {
             db: self.db.clone(),
             root: self.root.clone(),
             id_of_checkpoints: self.id_of_checkpoints.clone(),
+            metadata: self.metadata.clone(),
+            world: self.world.clone(),
             asset_scheme: self.asset_scheme.clone(),
             asset: self.asset.clone(),
             trie_factory: self.trie_factory.clone(),
@@ -374,11 +950,20 @@ impl Clone for ShardLevelState<StateDB> {
 const TRANSACTION_CHECKPOINT: CheckpointId = 456;
 
 impl<B: Backend + ShardBackend> ShardState<B> for ShardLevelState<B> {
-    fn apply(&mut self, transaction: &Transaction) -> StateResult<TransactionOutcome> {
+    fn apply(
+        &mut self,
+        transaction: &Transaction,
+        sender: &Address,
+        shard_owners: &[Address],
+        block_number: BlockNumber,
+        block_timestamp: u64,
+        max_script_cost: u64,
+    ) -> StateResult<TransactionOutcome> {
         ctrace!(TX, "Execute {:?}(TxHash:{:?})", transaction, transaction.hash());
 
         self.create_checkpoint(TRANSACTION_CHECKPOINT);
-        let result = self.apply_internal(transaction);
+        let result =
+            self.apply_internal(transaction, sender, shard_owners, block_number, block_timestamp, max_script_cost);
         match result {
             Ok(_) => {
                 cinfo!(TX, "Tx({}) is applied", transaction.hash());
@@ -408,7 +993,7 @@ impl<B: Backend + ShardBackend> ShardState<B> for ShardLevelState<B> {
 #[cfg(test)]
 mod tests {
     use super::super::super::tests::helpers::get_temp_state_db;
-    use ctypes::transaction::{AssetOutPoint, AssetTransferInput, AssetTransferOutput};
+    use ctypes::transaction::{AssetTransferInput, AssetTransferOutput};
 
     use super::*;
 
@@ -420,60 +1005,251 @@ mod tests {
         ShardLevelState::try_new(shard_id, state_db, Default::default()).unwrap()
     }
 
+    /// Returns a shard state that has a world whose only owner is `owner`, who also owns the shard.
+    fn get_temp_shard_state_with_world(shard_id: ShardId, owner: &Address) -> ShardLevelState<StateDB> {
+        let mut state = get_temp_shard_state(shard_id);
+        let create_world = Transaction::CreateWorld {
+            network_id: 200,
+            shard_id,
+            nonce: 0,
+            owners: vec![*owner],
+        };
+        assert_eq!(
+            Invoice::Success,
+            state.apply(&create_world, owner, &[*owner], 0, 0, ::std::u64::MAX).unwrap().invoice
+        );
+        state
+    }
+
     #[test]
-    fn mint_permissioned_asset() {
+    fn create_world() {
         let shard_id = 0;
         let mut state = get_temp_shard_state(shard_id);
+        let shard_owner = Address::random();
+        let owners = vec![Address::random(), Address::random()];
 
-        let metadata = "metadata".to_string();
-        let lock_script_hash = H256::random();
-        let parameters = vec![];
-        let amount = 100;
-        let registrar = Some(Address::random());
-        let transaction = Transaction::AssetMint {
+        assert_eq!(Ok(None), state.world(0));
+        let transaction = Transaction::CreateWorld {
             network_id: 200,
             shard_id,
-            metadata: metadata.clone(),
-            output: AssetMintOutput {
-                lock_script_hash,
-                parameters: parameters.clone(),
-                amount: Some(amount),
-            },
-            registrar,
             nonce: 0,
+            owners: owners.clone(),
         };
-
-        let result = state.apply(&transaction);
         assert_eq!(
             Ok(TransactionOutcome {
                 invoice: Invoice::Success,
                 error: None,
             }),
-            result
+            state.apply(&transaction, &shard_owner, &[shard_owner], 0, 0, ::std::u64::MAX)
         );
 
-        let transaction_hash = transaction.hash();
-        let asset_scheme_address = AssetSchemeAddress::new(transaction_hash, shard_id);
-        let asset_scheme = state.asset_scheme(&asset_scheme_address);
-        assert_eq!(Ok(Some(AssetScheme::new(metadata.clone(), amount, registrar))), asset_scheme);
-
-        let asset_address = AssetAddress::new(transaction_hash, 0, shard_id);
-        let asset = state.asset(&asset_address);
-        assert_eq!(Ok(Some(Asset::new(asset_scheme_address.into(), lock_script_hash, parameters, amount))), asset);
+        assert_eq!(Ok(Some(World::new(owners))), state.world(0));
+        let metadata = state.metadata().unwrap().unwrap();
+        assert_eq!(&1, metadata.number_of_worlds());
+        assert_eq!(&1, metadata.nonce());
     }
 
     #[test]
-    fn mint_infinite_asset() {
+    fn only_shard_owner_can_create_world() {
+        let shard_id = 0;
+        let mut state = get_temp_shard_state(shard_id);
+        let shard_owner = Address::random();
+        let sender = Address::random();
+
+        let transaction = Transaction::CreateWorld {
+            network_id: 200,
+            shard_id,
+            nonce: 0,
+            owners: vec![sender],
+        };
+        assert_eq!(
+            Ok(TransactionOutcome {
+                invoice: Invoice::Failed,
+                error: Some(TransactionError::InsufficientPermission),
+            }),
+            state.apply(&transaction, &sender, &[shard_owner], 0, 0, ::std::u64::MAX)
+        );
+        assert_eq!(Ok(None), state.world(0));
+    }
+
+    #[test]
+    fn create_world_with_invalid_nonce() {
         let shard_id = 0;
         let mut state = get_temp_shard_state(shard_id);
+        let shard_owner = Address::random();
+
+        let transaction = Transaction::CreateWorld {
+            network_id: 200,
+            shard_id,
+            nonce: 1,
+            owners: vec![],
+        };
+        assert_eq!(
+            Ok(TransactionOutcome {
+                invoice: Invoice::Failed,
+                error: Some(TransactionError::InvalidShardNonce(Mismatch {
+                    expected: 0,
+                    found: 1,
+                })),
+            }),
+            state.apply(&transaction, &shard_owner, &[shard_owner], 0, 0, ::std::u64::MAX)
+        );
+    }
+
+    #[test]
+    fn set_world_owners() {
+        let shard_id = 0;
+        let sender = Address::random();
+        let mut state = get_temp_shard_state_with_world(shard_id, &sender);
+
+        let new_owners = vec![Address::random()];
+        let transaction = Transaction::SetWorldOwners {
+            network_id: 200,
+            shard_id,
+            world_id: 0,
+            nonce: 0,
+            owners: new_owners.clone(),
+        };
+        assert_eq!(
+            Invoice::Success,
+            state.apply(&transaction, &sender, &[sender], 0, 0, ::std::u64::MAX).unwrap().invoice
+        );
+        assert_eq!(Ok(Some(World::new_with_nonce(new_owners, 1))), state.world(0));
+
+        // The sender is not an owner anymore.
+        let transaction = Transaction::SetWorldOwners {
+            network_id: 200,
+            shard_id,
+            world_id: 0,
+            nonce: 1,
+            owners: vec![sender],
+        };
+        assert_eq!(
+            Ok(TransactionOutcome {
+                invoice: Invoice::Failed,
+                error: Some(TransactionError::InsufficientPermission),
+            }),
+            state.apply(&transaction, &sender, &[sender], 0, 0, ::std::u64::MAX)
+        );
+    }
+
+    #[test]
+    fn set_owners_of_invalid_world() {
+        let shard_id = 0;
+        let sender = Address::random();
+        let mut state = get_temp_shard_state_with_world(shard_id, &sender);
+
+        let transaction = Transaction::SetWorldOwners {
+            network_id: 200,
+            shard_id,
+            world_id: 1,
+            nonce: 0,
+            owners: vec![],
+        };
+        assert_eq!(
+            Ok(TransactionOutcome {
+                invoice: Invoice::Failed,
+                error: Some(TransactionError::InvalidWorldId(1)),
+            }),
+            state.apply(&transaction, &sender, &[sender], 0, 0, ::std::u64::MAX)
+        );
+    }
+
+    #[test]
+    fn only_world_owner_can_mint() {
+        let shard_id = 0;
+        let owner = Address::random();
+        let mut state = get_temp_shard_state_with_world(shard_id, &owner);
+
+        let mint = |world_id| Transaction::AssetMint {
+            network_id: 200,
+            shard_id,
+            world_id,
+            metadata: "metadata".to_string(),
+            output: AssetMintOutput {
+                lock_script_hash: H256::random(),
+                parameters: vec![],
+                amount: Some(30),
+            },
+            registrar: None,
+            nonce: 0,
+        };
+
+        let sender = Address::random();
+        assert_eq!(
+            Ok(TransactionOutcome {
+                invoice: Invoice::Failed,
+                error: Some(TransactionError::InsufficientPermission),
+            }),
+            state.apply(&mint(0), &sender, &[owner], 0, 0, ::std::u64::MAX)
+        );
+        assert_eq!(
+            Ok(TransactionOutcome {
+                invoice: Invoice::Failed,
+                error: Some(TransactionError::InvalidWorldId(1)),
+            }),
+            state.apply(&mint(1), &owner, &[owner], 0, 0, ::std::u64::MAX)
+        );
+    }
+
+    #[test]
+    fn mint_permissioned_asset() {
+        let shard_id = 0;
+        let sender = Address::random();
+        let mut state = get_temp_shard_state_with_world(shard_id, &sender);
 
         let metadata = "metadata".to_string();
         let lock_script_hash = H256::random();
         let parameters = vec![];
+        let amount = 100;
         let registrar = Some(Address::random());
         let transaction = Transaction::AssetMint {
             network_id: 200,
             shard_id,
+            world_id: 0,
+            metadata: metadata.clone(),
+            output: AssetMintOutput {
+                lock_script_hash,
+                parameters: parameters.clone(),
+                amount: Some(amount),
+            },
+            registrar,
+            nonce: 0,
+        };
+
+        let result = state.apply(&transaction, &sender, &[sender], 0, 0, ::std::u64::MAX);
+        assert_eq!(
+            Ok(TransactionOutcome {
+                invoice: Invoice::Success,
+                error: None,
+            }),
+            result
+        );
+
+        let transaction_hash = transaction.hash();
+        let asset_scheme_address = AssetSchemeAddress::new(transaction_hash, shard_id, 0);
+        let asset_scheme = state.asset_scheme(&asset_scheme_address);
+        assert_eq!(Ok(Some(AssetScheme::new(metadata.clone(), amount, registrar))), asset_scheme);
+
+        let asset_address = AssetAddress::new(transaction_hash, 0, shard_id);
+        let asset = state.asset(&asset_address);
+        assert_eq!(Ok(Some(Asset::new(asset_scheme_address.into(), lock_script_hash, parameters, amount, 0))), asset);
+    }
+
+    #[test]
+    fn mint_infinite_asset() {
+        let shard_id = 0;
+        let sender = Address::random();
+        let mut state = get_temp_shard_state_with_world(shard_id, &sender);
+
+        let metadata = "metadata".to_string();
+        let lock_script_hash = H256::random();
+        let parameters = vec![];
+        let registrar = Some(Address::random());
+        let transaction = Transaction::AssetMint {
+            network_id: 200,
+            shard_id,
+            world_id: 0,
             metadata: metadata.clone(),
             output: AssetMintOutput {
                 lock_script_hash,
@@ -484,7 +1260,7 @@ mod tests {
             nonce: 0,
         };
 
-        let result = state.apply(&transaction);
+        let result = state.apply(&transaction, &sender, &[sender], 0, 0, ::std::u64::MAX);
         assert_eq!(
             Ok(TransactionOutcome {
                 invoice: Invoice::Success,
@@ -494,14 +1270,14 @@ mod tests {
         );
 
         let transaction_hash = transaction.hash();
-        let asset_scheme_address = AssetSchemeAddress::new(transaction_hash, shard_id);
+        let asset_scheme_address = AssetSchemeAddress::new(transaction_hash, shard_id, 0);
         let asset_scheme = state.asset_scheme(&asset_scheme_address);
         assert_eq!(Ok(Some(AssetScheme::new(metadata.clone(), ::std::u64::MAX, registrar))), asset_scheme);
 
         let asset_address = AssetAddress::new(transaction_hash, 0, shard_id);
         let asset = state.asset(&asset_address);
         assert_eq!(
-            Ok(Some(Asset::new(asset_scheme_address.into(), lock_script_hash, parameters, ::std::u64::MAX))),
+            Ok(Some(Asset::new(asset_scheme_address.into(), lock_script_hash, parameters, ::std::u64::MAX, 0))),
             asset
         );
     }
@@ -509,7 +1285,8 @@ mod tests {
     #[test]
     fn mint_and_transfer() {
         let shard_id = 0;
-        let mut state = get_temp_shard_state(shard_id);
+        let sender = Address::random();
+        let mut state = get_temp_shard_state_with_world(shard_id, &sender);
 
         let metadata = "metadata".to_string();
         let lock_script_hash = H256::from("07feab4c39250abf60b77d7589a5b61fdf409bd837e936376381d19db1e1f050");
@@ -518,6 +1295,7 @@ mod tests {
         let mint = Transaction::AssetMint {
             network_id: 200,
             shard_id,
+            world_id: 0,
             metadata: metadata.clone(),
             output: AssetMintOutput {
                 lock_script_hash,
@@ -536,10 +1314,10 @@ mod tests {
                 invoice: Invoice::Success,
                 error: None,
             }),
-            state.apply(&mint)
+            state.apply(&mint, &sender, &[sender], 0, 0, ::std::u64::MAX)
         );
 
-        let asset_scheme_address = AssetSchemeAddress::new(mint_hash, shard_id);
+        let asset_scheme_address = AssetSchemeAddress::new(mint_hash, shard_id, 0);
         let asset_scheme = state.asset_scheme(&asset_scheme_address);
         let asset_type = asset_scheme_address.into();
 
@@ -547,7 +1325,7 @@ mod tests {
 
         let asset_address = AssetAddress::new(mint_hash, 0, shard_id);
         let asset = state.asset(&asset_address);
-        assert_eq!(Ok(Some(Asset::new(asset_type, lock_script_hash, vec![], amount))), asset);
+        assert_eq!(Ok(Some(Asset::new(asset_type, lock_script_hash, vec![], amount, 0))), asset);
 
         let random_lock_script_hash = H256::random();
         let transfer = Transaction::AssetTransfer {
@@ -558,6 +1336,7 @@ mod tests {
                     transaction_hash: mint_hash,
                     index: 0,
                     asset_type,
+                    shard_id,
                     amount: 30,
                 },
                 lock_script: vec![0x30, 0x01],
@@ -568,18 +1347,21 @@ mod tests {
                     lock_script_hash,
                     parameters: vec![vec![1]],
                     asset_type,
+                    shard_id,
                     amount: 10,
                 },
                 AssetTransferOutput {
                     lock_script_hash,
                     parameters: vec![],
                     asset_type,
+                    shard_id,
                     amount: 5,
                 },
                 AssetTransferOutput {
                     lock_script_hash: random_lock_script_hash,
                     parameters: vec![],
                     asset_type,
+                    shard_id,
                     amount: 15,
                 },
             ],
@@ -592,26 +1374,404 @@ mod tests {
                 invoice: Invoice::Success,
                 error: None,
             }),
-            state.apply(&transfer)
+            state.apply(&transfer, &sender, &[sender], 0, 0, ::std::u64::MAX)
         );
 
         let asset0_address = AssetAddress::new(transfer_hash, 0, shard_id);
         let asset0 = state.asset(&asset0_address);
-        assert_eq!(Ok(Some(Asset::new(asset_type, lock_script_hash, vec![vec![1]], 10))), asset0);
+        assert_eq!(Ok(Some(Asset::new(asset_type, lock_script_hash, vec![vec![1]], 10, 0))), asset0);
 
         let asset1_address = AssetAddress::new(transfer_hash, 1, shard_id);
         let asset1 = state.asset(&asset1_address);
-        assert_eq!(Ok(Some(Asset::new(asset_type, lock_script_hash, vec![], 5))), asset1);
+        assert_eq!(Ok(Some(Asset::new(asset_type, lock_script_hash, vec![], 5, 0))), asset1);
 
         let asset2_address = AssetAddress::new(transfer_hash, 2, shard_id);
         let asset2 = state.asset(&asset2_address);
-        assert_eq!(Ok(Some(Asset::new(asset_type, random_lock_script_hash, vec![], 15))), asset2);
+        assert_eq!(Ok(Some(Asset::new(asset_type, random_lock_script_hash, vec![], 15, 0))), asset2);
+    }
+
+    /// Mints an asset whose lock script is `[0x30, 0x01]` and returns the hash of the mint and the asset type.
+    fn mint_with_registrar(
+        state: &mut ShardLevelState<StateDB>,
+        shard_id: ShardId,
+        sender: &Address,
+        registrar: Address,
+        amount: Option<u64>,
+    ) -> (H256, H256) {
+        let mint = Transaction::AssetMint {
+            network_id: 200,
+            shard_id,
+            world_id: 0,
+            metadata: "metadata".to_string(),
+            output: AssetMintOutput {
+                lock_script_hash: H256::from("07feab4c39250abf60b77d7589a5b61fdf409bd837e936376381d19db1e1f050"),
+                parameters: vec![],
+                amount,
+            },
+            registrar: Some(registrar),
+            nonce: 0,
+        };
+        assert_eq!(Invoice::Success, state.apply(&mint, sender, &[*sender], 0, 0, ::std::u64::MAX).unwrap().invoice);
+        let asset_type = AssetSchemeAddress::new(mint.hash(), shard_id, 0).into();
+        (mint.hash(), asset_type)
+    }
+
+    #[test]
+    fn change_registrar() {
+        let shard_id = 0;
+        let sender = Address::random();
+        let mut state = get_temp_shard_state_with_world(shard_id, &sender);
+
+        let registrar = Address::random();
+        let (_, asset_type) = mint_with_registrar(&mut state, shard_id, &sender, registrar, Some(100));
+        let asset_scheme_address = AssetSchemeAddress::from_hash(asset_type).unwrap();
+
+        let new_registrar = Address::random();
+        let change = Transaction::AssetChangeRegistrar {
+            network_id: 200,
+            asset_type,
+            registrar: Some(new_registrar),
+            nonce: 0,
+        };
+        assert_eq!(
+            Ok(TransactionOutcome {
+                invoice: Invoice::Failed,
+                error: Some(TransactionError::InsufficientPermission),
+            }),
+            state.apply(&change, &sender, &[sender], 0, 0, ::std::u64::MAX)
+        );
+        assert_eq!(
+            Ok(TransactionOutcome {
+                invoice: Invoice::Success,
+                error: None,
+            }),
+            state.apply(&change, &registrar, &[sender], 0, 0, ::std::u64::MAX)
+        );
+        assert_eq!(
+            Ok(Some(AssetScheme::new("metadata".to_string(), 100, Some(new_registrar)))),
+            state.asset_scheme(&asset_scheme_address)
+        );
+    }
+
+    #[test]
+    fn increase_supply() {
+        let shard_id = 0;
+        let sender = Address::random();
+        let mut state = get_temp_shard_state_with_world(shard_id, &sender);
+
+        let registrar = Address::random();
+        let (_, asset_type) = mint_with_registrar(&mut state, shard_id, &sender, registrar, Some(100));
+        let asset_scheme_address = AssetSchemeAddress::from_hash(asset_type).unwrap();
+
+        let lock_script_hash = H256::random();
+        let increase = Transaction::AssetIncreaseSupply {
+            network_id: 200,
+            asset_type,
+            lock_script_hash,
+            parameters: vec![],
+            amount: 50,
+            nonce: 0,
+        };
+        assert_eq!(
+            Ok(TransactionOutcome {
+                invoice: Invoice::Failed,
+                error: Some(TransactionError::InsufficientPermission),
+            }),
+            state.apply(&increase, &sender, &[sender], 3, 0, ::std::u64::MAX)
+        );
+        assert_eq!(
+            Ok(TransactionOutcome {
+                invoice: Invoice::Success,
+                error: None,
+            }),
+            state.apply(&increase, &registrar, &[sender], 3, 0, ::std::u64::MAX)
+        );
+
+        assert_eq!(
+            Ok(Some(AssetScheme::new("metadata".to_string(), 150, Some(registrar)))),
+            state.asset_scheme(&asset_scheme_address)
+        );
+        let asset_address = AssetAddress::new(increase.hash(), 0, shard_id);
+        assert_eq!(Ok(Some(Asset::new(asset_type, lock_script_hash, vec![], 50, 3))), state.asset(&asset_address));
+    }
+
+    #[test]
+    fn cannot_increase_infinite_supply() {
+        let shard_id = 0;
+        let sender = Address::random();
+        let mut state = get_temp_shard_state_with_world(shard_id, &sender);
+
+        let registrar = Address::random();
+        let (_, asset_type) = mint_with_registrar(&mut state, shard_id, &sender, registrar, None);
+
+        let increase = Transaction::AssetIncreaseSupply {
+            network_id: 200,
+            asset_type,
+            lock_script_hash: H256::random(),
+            parameters: vec![],
+            amount: 1,
+            nonce: 0,
+        };
+        assert_eq!(
+            Ok(TransactionOutcome {
+                invoice: Invoice::Failed,
+                error: Some(TransactionError::CannotIncreaseSupply(asset_type)),
+            }),
+            state.apply(&increase, &registrar, &[sender], 0, 0, ::std::u64::MAX)
+        );
+    }
+
+    #[test]
+    fn frozen_asset_cannot_be_transferred() {
+        let shard_id = 0;
+        let sender = Address::random();
+        let mut state = get_temp_shard_state_with_world(shard_id, &sender);
+
+        let registrar = Address::random();
+        let (mint_hash, asset_type) = mint_with_registrar(&mut state, shard_id, &sender, registrar, Some(30));
+        let outpoint = AssetOutPoint {
+            transaction_hash: mint_hash,
+            index: 0,
+            asset_type,
+            shard_id,
+            amount: 30,
+        };
+
+        let freeze = Transaction::AssetFreeze {
+            network_id: 200,
+            outpoint: outpoint.clone(),
+            nonce: 0,
+        };
+        assert_eq!(
+            Ok(TransactionOutcome {
+                invoice: Invoice::Failed,
+                error: Some(TransactionError::InsufficientPermission),
+            }),
+            state.apply(&freeze, &sender, &[sender], 0, 0, ::std::u64::MAX)
+        );
+        assert_eq!(
+            Invoice::Success,
+            state.apply(&freeze, &registrar, &[sender], 0, 0, ::std::u64::MAX).unwrap().invoice
+        );
+
+        let transfer = Transaction::AssetTransfer {
+            network_id: 200,
+            burns: vec![],
+            inputs: vec![AssetTransferInput {
+                prev_out: outpoint.clone(),
+                lock_script: vec![0x30, 0x01],
+                unlock_script: vec![],
+            }],
+            outputs: vec![AssetTransferOutput {
+                lock_script_hash: H256::random(),
+                parameters: vec![],
+                asset_type,
+                shard_id,
+                amount: 30,
+            }],
+            nonce: 0,
+        };
+        let asset_address = AssetAddress::new(mint_hash, 0, shard_id);
+        assert_eq!(
+            Ok(TransactionOutcome {
+                invoice: Invoice::Failed,
+                error: Some(TransactionError::AssetFrozen(asset_address.clone().into())),
+            }),
+            state.apply(&transfer, &sender, &[sender], 0, 0, ::std::u64::MAX)
+        );
+
+        let unfreeze = Transaction::AssetUnfreeze {
+            network_id: 200,
+            outpoint,
+            nonce: 0,
+        };
+        assert_eq!(
+            Invoice::Success,
+            state.apply(&unfreeze, &registrar, &[sender], 0, 0, ::std::u64::MAX).unwrap().invoice
+        );
+        assert_eq!(
+            Ok(TransactionOutcome {
+                invoice: Invoice::Success,
+                error: None,
+            }),
+            state.apply(&transfer, &sender, &[sender], 0, 0, ::std::u64::MAX)
+        );
+        assert_eq!(Ok(None), state.asset(&asset_address));
+    }
+
+    fn unlocking_input(transaction_hash: H256, asset_type: H256, amount: u64) -> AssetTransferInput {
+        AssetTransferInput {
+            prev_out: AssetOutPoint {
+                transaction_hash,
+                index: 0,
+                asset_type,
+                shard_id: 0,
+                amount,
+            },
+            lock_script: vec![0x30, 0x01],
+            unlock_script: vec![],
+        }
+    }
+
+    #[test]
+    fn compose_and_decompose() {
+        let shard_id = 0;
+        let sender = Address::random();
+        let mut state = get_temp_shard_state_with_world(shard_id, &sender);
+
+        let (mint_hash1, asset_type1) = mint_with_registrar(&mut state, shard_id, &sender, Address::random(), Some(10));
+        let (mint_hash2, asset_type2) = mint_with_registrar(&mut state, shard_id, &sender, Address::random(), Some(20));
+
+        let lock_script_hash = H256::from("07feab4c39250abf60b77d7589a5b61fdf409bd837e936376381d19db1e1f050");
+        let compose = Transaction::AssetCompose {
+            network_id: 200,
+            shard_id,
+            world_id: 0,
+            metadata: "composite".to_string(),
+            registrar: None,
+            inputs: vec![unlocking_input(mint_hash1, asset_type1, 10), unlocking_input(mint_hash2, asset_type2, 20)],
+            lock_script_hash,
+            parameters: vec![],
+            amount: 1,
+            nonce: 0,
+        };
+        assert_eq!(
+            Ok(TransactionOutcome {
+                invoice: Invoice::Success,
+                error: None,
+            }),
+            state.apply(&compose, &sender, &[sender], 0, 0, ::std::u64::MAX)
+        );
+        assert_eq!(Ok(None), state.asset(&AssetAddress::new(mint_hash1, 0, shard_id)));
+        assert_eq!(Ok(None), state.asset(&AssetAddress::new(mint_hash2, 0, shard_id)));
+
+        let composite_scheme_address = AssetSchemeAddress::new(compose.hash(), shard_id, 0);
+        let composite_type: H256 = composite_scheme_address.clone().into();
+        let mut pool = vec![
+            PooledAsset {
+                asset_type: asset_type1,
+                amount: 10,
+            },
+            PooledAsset {
+                asset_type: asset_type2,
+                amount: 20,
+            },
+        ];
+        pool.sort_by_key(|pooled| pooled.asset_type);
+        assert_eq!(
+            Ok(Some(AssetScheme::new_with_pool("composite".to_string(), 1, None, pool))),
+            state.asset_scheme(&composite_scheme_address)
+        );
+        let composite_address = AssetAddress::new(compose.hash(), 0, shard_id);
+        assert_eq!(
+            Ok(Some(Asset::new(composite_type, lock_script_hash, vec![], 1, 0))),
+            state.asset(&composite_address)
+        );
+
+        let output = |asset_type, amount| AssetTransferOutput {
+            lock_script_hash,
+            parameters: vec![],
+            asset_type,
+            shard_id,
+            amount,
+        };
+        let invalid_decompose = Transaction::AssetDecompose {
+            network_id: 200,
+            input: unlocking_input(compose.hash(), composite_type, 1),
+            outputs: vec![output(asset_type1, 10), output(asset_type2, 19)],
+            nonce: 0,
+        };
+        assert_eq!(
+            Ok(TransactionOutcome {
+                invoice: Invoice::Failed,
+                error: Some(TransactionError::InvalidDecomposedOutput),
+            }),
+            state.apply(&invalid_decompose, &sender, &[sender], 0, 0, ::std::u64::MAX)
+        );
+
+        let decompose = Transaction::AssetDecompose {
+            network_id: 200,
+            input: unlocking_input(compose.hash(), composite_type, 1),
+            outputs: vec![output(asset_type2, 20), output(asset_type1, 10)],
+            nonce: 0,
+        };
+        assert_eq!(
+            Ok(TransactionOutcome {
+                invoice: Invoice::Success,
+                error: None,
+            }),
+            state.apply(&decompose, &sender, &[sender], 0, 0, ::std::u64::MAX)
+        );
+        assert_eq!(Ok(None), state.asset(&composite_address));
+        assert_eq!(Ok(None), state.asset_scheme(&composite_scheme_address));
+        assert_eq!(
+            Ok(Some(Asset::new(asset_type2, lock_script_hash, vec![], 20, 0))),
+            state.asset(&AssetAddress::new(decompose.hash(), 0, shard_id))
+        );
+        assert_eq!(
+            Ok(Some(Asset::new(asset_type1, lock_script_hash, vec![], 10, 0))),
+            state.asset(&AssetAddress::new(decompose.hash(), 1, shard_id))
+        );
+    }
+
+    #[test]
+    fn cannot_compose_the_same_asset_twice() {
+        let shard_id = 0;
+        let sender = Address::random();
+        let mut state = get_temp_shard_state_with_world(shard_id, &sender);
+
+        let (mint_hash, asset_type) = mint_with_registrar(&mut state, shard_id, &sender, Address::random(), Some(10));
+        let compose = Transaction::AssetCompose {
+            network_id: 200,
+            shard_id,
+            world_id: 0,
+            metadata: "composite".to_string(),
+            registrar: None,
+            inputs: vec![unlocking_input(mint_hash, asset_type, 10), unlocking_input(mint_hash, asset_type, 10)],
+            lock_script_hash: H256::random(),
+            parameters: vec![],
+            amount: 1,
+            nonce: 0,
+        };
+        let asset_address = AssetAddress::new(mint_hash, 0, shard_id);
+        assert_eq!(
+            Ok(TransactionOutcome {
+                invoice: Invoice::Failed,
+                error: Some(TransactionError::AssetNotFound(asset_address.clone().into())),
+            }),
+            state.apply(&compose, &sender, &[sender], 0, 0, ::std::u64::MAX)
+        );
+        assert_eq!(Ok(None), state.asset_scheme(&AssetSchemeAddress::new(compose.hash(), shard_id, 0)));
+        assert!(state.asset(&asset_address).unwrap().is_some());
+    }
+
+    #[test]
+    fn cannot_decompose_non_composite_asset() {
+        let shard_id = 0;
+        let sender = Address::random();
+        let mut state = get_temp_shard_state_with_world(shard_id, &sender);
+
+        let (mint_hash, asset_type) = mint_with_registrar(&mut state, shard_id, &sender, Address::random(), Some(10));
+        let decompose = Transaction::AssetDecompose {
+            network_id: 200,
+            input: unlocking_input(mint_hash, asset_type, 10),
+            outputs: vec![],
+            nonce: 0,
+        };
+        assert_eq!(
+            Ok(TransactionOutcome {
+                invoice: Invoice::Failed,
+                error: Some(TransactionError::InvalidDecomposedInput(AssetAddress::new(mint_hash, 0, shard_id).into())),
+            }),
+            state.apply(&decompose, &sender, &[sender], 0, 0, ::std::u64::MAX)
+        );
     }
 
     #[test]
     fn mint_and_failed_transfer_and_successful_transfer() {
         let shard_id = 0;
-        let mut state = get_temp_shard_state(shard_id);
+        let sender = Address::random();
+        let mut state = get_temp_shard_state_with_world(shard_id, &sender);
 
         let metadata = "metadata".to_string();
         let lock_script_hash = H256::from("07feab4c39250abf60b77d7589a5b61fdf409bd837e936376381d19db1e1f050");
@@ -620,6 +1780,7 @@ mod tests {
         let mint = Transaction::AssetMint {
             network_id: 200,
             shard_id,
+            world_id: 0,
             metadata: metadata.clone(),
             output: AssetMintOutput {
                 lock_script_hash,
@@ -638,10 +1799,10 @@ mod tests {
                 invoice: Invoice::Success,
                 error: None,
             }),
-            state.apply(&mint)
+            state.apply(&mint, &sender, &[sender], 0, 0, ::std::u64::MAX)
         );
 
-        let asset_scheme_address = AssetSchemeAddress::new(mint_hash, shard_id);
+        let asset_scheme_address = AssetSchemeAddress::new(mint_hash, shard_id, 0);
         let asset_scheme = state.asset_scheme(&asset_scheme_address);
         let asset_type = asset_scheme_address.into();
 
@@ -649,7 +1810,7 @@ mod tests {
 
         let asset_address = AssetAddress::new(mint_hash, 0, shard_id);
         let asset = state.asset(&asset_address);
-        assert_eq!(Ok(Some(Asset::new(asset_type, lock_script_hash, vec![], amount))), asset);
+        assert_eq!(Ok(Some(Asset::new(asset_type, lock_script_hash, vec![], amount, 0))), asset);
 
         let failed_transfer = Transaction::AssetTransfer {
             network_id,
@@ -659,6 +1820,7 @@ mod tests {
                     transaction_hash: mint_hash,
                     index: 0,
                     asset_type,
+                    shard_id,
                     amount: 30,
                 },
                 lock_script: vec![0x30],
@@ -668,12 +1830,13 @@ mod tests {
                 lock_script_hash,
                 parameters: vec![vec![1]],
                 asset_type,
+                shard_id,
                 amount: 30,
             }],
             nonce: 0,
         };
 
-        let failed_outcome = state.apply(&failed_transfer).unwrap();
+        let failed_outcome = state.apply(&failed_transfer, &sender, &[sender], 0, 0, ::std::u64::MAX).unwrap();
         assert_eq!(Invoice::Failed, failed_outcome.invoice);
         assert_ne!(None, failed_outcome.error);
 
@@ -686,6 +1849,7 @@ mod tests {
                     transaction_hash: mint_hash,
                     index: 0,
                     asset_type,
+                    shard_id,
                     amount: 30,
                 },
                 lock_script: vec![0x30, 0x01],
@@ -696,18 +1860,21 @@ mod tests {
                     lock_script_hash,
                     parameters: vec![vec![1]],
                     asset_type,
+                    shard_id,
                     amount: 10,
                 },
                 AssetTransferOutput {
                     lock_script_hash,
                     parameters: vec![],
                     asset_type,
+                    shard_id,
                     amount: 5,
                 },
                 AssetTransferOutput {
                     lock_script_hash: random_lock_script_hash,
                     parameters: vec![],
                     asset_type,
+                    shard_id,
                     amount: 15,
                 },
             ],
@@ -720,19 +1887,159 @@ mod tests {
                 invoice: Invoice::Success,
                 error: None,
             }),
-            state.apply(&successful_transfer)
+            state.apply(&successful_transfer, &sender, &[sender], 0, 0, ::std::u64::MAX)
         );
 
         let asset0_address = AssetAddress::new(successful_transfer_hash, 0, shard_id);
         let asset0 = state.asset(&asset0_address);
-        assert_eq!(Ok(Some(Asset::new(asset_type, lock_script_hash, vec![vec![1]], 10))), asset0);
+        assert_eq!(Ok(Some(Asset::new(asset_type, lock_script_hash, vec![vec![1]], 10, 0))), asset0);
 
         let asset1_address = AssetAddress::new(successful_transfer_hash, 1, shard_id);
         let asset1 = state.asset(&asset1_address);
-        assert_eq!(Ok(Some(Asset::new(asset_type, lock_script_hash, vec![], 5))), asset1);
+        assert_eq!(Ok(Some(Asset::new(asset_type, lock_script_hash, vec![], 5, 0))), asset1);
 
         let asset2_address = AssetAddress::new(successful_transfer_hash, 2, shard_id);
         let asset2 = state.asset(&asset2_address);
-        assert_eq!(Ok(Some(Asset::new(asset_type, random_lock_script_hash, vec![], 15))), asset2);
+        assert_eq!(Ok(Some(Asset::new(asset_type, random_lock_script_hash, vec![], 15, 0))), asset2);
+    }
+
+    #[test]
+    fn transfer_timelocked_asset() {
+        let shard_id = 0;
+        let sender = Address::random();
+        let mut state = get_temp_shard_state_with_world(shard_id, &sender);
+
+        // PUSH 10 CHKTIMELOCK BLOCK
+        let lock_script = vec![0x30, 0x0a, 0xb0, 0x01];
+        let lock_script_hash = Blake::blake(&lock_script);
+        let amount = 30;
+        let mint = Transaction::AssetMint {
+            network_id: 200,
+            shard_id,
+            world_id: 0,
+            metadata: "metadata".to_string(),
+            output: AssetMintOutput {
+                lock_script_hash,
+                parameters: vec![],
+                amount: Some(amount),
+            },
+            registrar: None,
+            nonce: 0,
+        };
+        let mint_hash = mint.hash();
+        assert_eq!(
+            Ok(TransactionOutcome {
+                invoice: Invoice::Success,
+                error: None,
+            }),
+            state.apply(&mint, &sender, &[sender], 3, 0, ::std::u64::MAX)
+        );
+
+        let asset_type = AssetSchemeAddress::new(mint_hash, shard_id, 0).into();
+        let asset_address = AssetAddress::new(mint_hash, 0, shard_id);
+        assert_eq!(3, state.asset(&asset_address).unwrap().unwrap().created_at());
+
+        let output_lock_script_hash = H256::random();
+        let transfer = Transaction::AssetTransfer {
+            network_id: 200,
+            burns: vec![],
+            inputs: vec![AssetTransferInput {
+                prev_out: AssetOutPoint {
+                    transaction_hash: mint_hash,
+                    index: 0,
+                    asset_type,
+                    shard_id,
+                    amount,
+                },
+                lock_script,
+                unlock_script: vec![],
+            }],
+            outputs: vec![AssetTransferOutput {
+                lock_script_hash: output_lock_script_hash,
+                parameters: vec![],
+                asset_type,
+                shard_id,
+                amount,
+            }],
+            nonce: 0,
+        };
+
+        let failed_outcome = state.apply(&transfer, &sender, &[sender], 9, 0, ::std::u64::MAX).unwrap();
+        assert_eq!(Invoice::Failed, failed_outcome.invoice);
+
+        assert_eq!(
+            Ok(TransactionOutcome {
+                invoice: Invoice::Success,
+                error: None,
+            }),
+            state.apply(&transfer, &sender, &[sender], 10, 0, ::std::u64::MAX)
+        );
+        let transferred_address = AssetAddress::new(transfer.hash(), 0, shard_id);
+        assert_eq!(
+            Ok(Some(Asset::new(asset_type, output_lock_script_hash, vec![], amount, 10))),
+            state.asset(&transferred_address)
+        );
+    }
+
+    #[test]
+    fn trace_transfer_input() {
+        let shard_id = 0;
+        let sender = Address::random();
+        let mut state = get_temp_shard_state_with_world(shard_id, &sender);
+
+        // PUSH 10 CHKTIMELOCK BLOCK
+        let lock_script = vec![0x30, 0x0a, 0xb0, 0x01];
+        let lock_script_hash = Blake::blake(&lock_script);
+        let amount = 30;
+        let mint = Transaction::AssetMint {
+            network_id: 200,
+            shard_id,
+            world_id: 0,
+            metadata: "metadata".to_string(),
+            output: AssetMintOutput {
+                lock_script_hash,
+                parameters: vec![],
+                amount: Some(amount),
+            },
+            registrar: None,
+            nonce: 0,
+        };
+        let mint_hash = mint.hash();
+        assert_eq!(Ok(None), state.trace_transfer_input(&mint, 0, 3, 0, ::std::u64::MAX));
+        assert_eq!(Invoice::Success, state.apply(&mint, &sender, &[sender], 3, 0, ::std::u64::MAX).unwrap().invoice);
+
+        let asset_type = AssetSchemeAddress::new(mint_hash, shard_id, 0).into();
+        let transfer = Transaction::AssetTransfer {
+            network_id: 200,
+            burns: vec![],
+            inputs: vec![AssetTransferInput {
+                prev_out: AssetOutPoint {
+                    transaction_hash: mint_hash,
+                    index: 0,
+                    asset_type,
+                    shard_id,
+                    amount,
+                },
+                lock_script,
+                unlock_script: vec![],
+            }],
+            outputs: vec![AssetTransferOutput {
+                lock_script_hash: H256::random(),
+                parameters: vec![],
+                asset_type,
+                shard_id,
+                amount,
+            }],
+            nonce: 0,
+        };
+        assert_eq!(Ok(None), state.trace_transfer_input(&transfer, 1, 9, 0, ::std::u64::MAX));
+
+        let trace = state.trace_transfer_input(&transfer, 0, 9, 0, ::std::u64::MAX).unwrap().unwrap();
+        assert_eq!(Ok(ScriptResult::Fail), trace.result);
+        let instructions: Vec<_> = trace.steps.into_iter().map(|step| step.instruction).collect();
+        assert_eq!(vec![Instruction::Push(10), Instruction::ChkTimelock(1)], instructions);
+
+        let trace = state.trace_transfer_input(&transfer, 0, 10, 0, ::std::u64::MAX).unwrap().unwrap();
+        assert_eq!(Ok(ScriptResult::Unlocked), trace.result);
     }
 }
@@ -44,9 +44,9 @@ use cmerkle::{Result as TrieResult, Trie, TrieError, TrieFactory};
 use ctypes::invoice::Invoice;
 use ctypes::parcel::{Action, ChangeShard, Error as ParcelError, Outcome as ParcelOutcome, Parcel};
 use ctypes::transaction::{Error as TransactionError, Outcome as TransactionOutcome, Transaction};
-use ctypes::ShardId;
+use ctypes::{BlockNumber, ShardId, WorldId};
+use cvm::ScriptTrace;
 use primitives::{Bytes, H256, U256};
-use rlp::NULL_RLP;
 use unexpected::Mismatch;
 
 use super::super::backend::TopBackend;
@@ -54,8 +54,8 @@ use super::super::checkpoint::{CheckpointId, StateWithCheckpoint};
 use super::super::item::cache::{Cache, CacheableItem};
 use super::super::traits::{ShardState, ShardStateInfo, StateWithCache, TopState, TopStateInfo};
 use super::super::{
-    Account, Asset, AssetAddress, AssetScheme, AssetSchemeAddress, Metadata, MetadataAddress, RegularAccount,
-    RegularAccountAddress, Shard, ShardAddress, ShardLevelState,
+    Account, ActionDataAddress, Asset, AssetAddress, AssetScheme, AssetSchemeAddress, Metadata, MetadataAddress,
+    RegularAccount, RegularAccountAddress, Shard, ShardAddress, ShardLevelState, ShardMetadata, World,
 };
 use super::super::{StateDB, StateError, StateResult};
 
@@ -143,16 +143,28 @@ impl TopStateInfo for TopLevelState {
         Ok(db.get_with(&shard_address, ::rlp::decode::<Shard>)?.map(|s| s.root().clone()))
     }
 
-    fn shard_owner(&self, shard_id: ShardId) -> TrieResult<Option<Address>> {
+    fn shard_owners(&self, shard_id: ShardId) -> TrieResult<Option<Vec<Address>>> {
         let shard_address = ShardAddress::new(shard_id);
-        let owner = self.db.get_cached_shard(&shard_address).and_then(|s| s).map(|s| s.owner().clone());
-        if owner.is_some() {
-            return Ok(owner)
+        let owners = self.db.get_cached_shard(&shard_address).and_then(|s| s).map(|s| s.owners().to_vec());
+        if owners.is_some() {
+            return Ok(owners)
         }
 
         // because of lexical borrow of self.db
         let db = self.trie_factory.readonly(self.db.as_hashdb(), &self.root)?;
-        Ok(db.get_with(&shard_address, ::rlp::decode::<Shard>)?.map(|s| s.owner().clone()))
+        Ok(db.get_with(&shard_address, ::rlp::decode::<Shard>)?.map(|s| s.owners().to_vec()))
+    }
+
+    fn shard_users(&self, shard_id: ShardId) -> TrieResult<Option<Vec<Address>>> {
+        let shard_address = ShardAddress::new(shard_id);
+        let users = self.db.get_cached_shard(&shard_address).and_then(|s| s).map(|s| s.users().to_vec());
+        if users.is_some() {
+            return Ok(users)
+        }
+
+        // because of lexical borrow of self.db
+        let db = self.trie_factory.readonly(self.db.as_hashdb(), &self.root)?;
+        Ok(db.get_with(&shard_address, ::rlp::decode::<Shard>)?.map(|s| s.users().to_vec()))
     }
 
     fn asset_scheme(
@@ -177,9 +189,32 @@ impl TopStateInfo for TopLevelState {
         shard_level_state.asset(asset_address)
     }
 
-    fn action_data(&self, key: &H256) -> TrieResult<Bytes> {
-        let action_data = self.require_action_data(key)?;
-        Ok(action_data.clone())
+    fn shard_metadata(&self, shard_id: ShardId) -> TrieResult<Option<ShardMetadata>> {
+        let shard_root = match self.shard_root(shard_id)? {
+            Some(shard_root) => shard_root,
+            None => return Ok(None),
+        };
+        // FIXME: Make it mutable borrow db instead of cloning.
+        let shard_level_state =
+            ShardLevelState::from_existing(shard_id, self.db.clone(), shard_root, self.trie_factory)?;
+        shard_level_state.metadata()
+    }
+
+    fn world(&self, shard_id: ShardId, world_id: WorldId) -> TrieResult<Option<World>> {
+        let shard_root = match self.shard_root(shard_id)? {
+            Some(shard_root) => shard_root,
+            None => return Ok(None),
+        };
+        // FIXME: Make it mutable borrow db instead of cloning.
+        let shard_level_state =
+            ShardLevelState::from_existing(shard_id, self.db.clone(), shard_root, self.trie_factory)?;
+        shard_level_state.world(world_id)
+    }
+
+    fn action_data(&self, a: &ActionDataAddress) -> TrieResult<Option<Bytes>> {
+        let db = self.trie_factory.readonly(self.db.as_hashdb(), &self.root)?;
+        let from_global_cache = |a| self.db.get_cached_action_data(a);
+        self.action_data.ensure_cached(a, &|data: Option<&Bytes>| data.cloned(), db, from_global_cache)
     }
 }
 
@@ -313,24 +348,33 @@ impl TopLevelState {
     }
 
     /// Execute a given parcel, charging parcel fee.
+    /// The fee and the nonce are taken from `fee_payer`, which is the same as `sender` unless the parcel is sponsored.
     /// This will change the state accordingly.
     pub fn apply(
         &mut self,
         parcel: &Parcel,
+        sender: &Address,
+        sender_public: &Public,
         fee_payer: &Address,
-        fee_payer_public: &Public,
+        block_number: BlockNumber,
+        block_timestamp: u64,
+        max_script_cost: u64,
     ) -> StateResult<ParcelOutcome> {
-        // Change the address to a master address if it is a regular key.
-        let fee_payer = if self.regular_account_exists_and_not_null(fee_payer)? {
-            let regular_account = self.require_regular_account_from_address(fee_payer)?;
-            public_to_address(&regular_account.master_account())
-        } else {
-            fee_payer.clone()
-        };
+        let sender = self.master_address(sender)?;
+        let fee_payer = self.master_address(fee_payer)?;
 
         self.create_checkpoint(PARCEL_FEE_CHECKPOINT);
 
-        match self.apply_internal(parcel, &fee_payer, fee_payer_public) {
+        let result = self.apply_internal(
+            parcel,
+            &sender,
+            sender_public,
+            &fee_payer,
+            block_number,
+            block_timestamp,
+            max_script_cost,
+        );
+        match result {
             Err(StateError::Transaction(_)) => unreachable!(),
             Err(err) => {
                 self.revert_to_checkpoint(PARCEL_FEE_CHECKPOINT);
@@ -347,9 +391,23 @@ impl TopLevelState {
     fn apply_internal(
         &mut self,
         parcel: &Parcel,
+        sender: &Address,
+        sender_public: &Public,
         fee_payer: &Address,
-        fee_payer_public: &Public,
+        block_number: BlockNumber,
+        block_timestamp: u64,
+        max_script_cost: u64,
     ) -> StateResult<ParcelOutcome> {
+        if let Some(expiration) = parcel.expiration {
+            if expiration.is_expired(block_number, block_timestamp) {
+                return Err(ParcelError::Expired {
+                    expiration,
+                    block_number,
+                    timestamp: block_timestamp,
+                }.into())
+            }
+        }
+
         let nonce = self.nonce(fee_payer)?;
 
         if parcel.nonce != nonce {
@@ -374,14 +432,41 @@ impl TopLevelState {
 
         // The failed parcel also must pay the fee and increase nonce.
         self.create_checkpoint(PARCEL_ACTION_CHECKPOINT);
+        // The checkpoint doesn't cover the shard tries, which are committed to the database as soon as they change.
+        // A failed batch restores the database, so that the nodes that its shard tries replaced aren't removed.
+        let db_before_batch = match parcel.action {
+            Action::Batch {
+                ..
+            } => Some(self.db.clone()),
+            _ => None,
+        };
 
-        match self.apply_action(&parcel.action, &parcel.network_id, fee_payer, fee_payer_public) {
+        match self.apply_action(
+            &parcel.action,
+            parcel.hash(),
+            &parcel.network_id,
+            sender,
+            sender_public,
+            block_number,
+            block_timestamp,
+            max_script_cost,
+        ) {
             Ok(outcome) => {
-                self.discard_checkpoint(PARCEL_ACTION_CHECKPOINT);
+                match db_before_batch {
+                    // A failed batch must not leave the changes of the actions that were applied before the failure.
+                    Some(db) if !outcome.is_success() => {
+                        self.revert_to_checkpoint(PARCEL_ACTION_CHECKPOINT);
+                        self.db = db;
+                    }
+                    _ => self.discard_checkpoint(PARCEL_ACTION_CHECKPOINT),
+                }
                 Ok(outcome)
             }
             Err(err) => {
                 self.revert_to_checkpoint(PARCEL_ACTION_CHECKPOINT);
+                if let Some(db) = db_before_batch {
+                    self.db = db;
+                }
                 Err(err)
             }
         }
@@ -390,9 +475,13 @@ impl TopLevelState {
     fn apply_action(
         &mut self,
         action: &Action,
+        parcel_hash: H256,
         network_id: &u64,
-        fee_payer: &Address,
-        fee_payer_public: &Public,
+        sender: &Address,
+        sender_public: &Public,
+        block_number: BlockNumber,
+        block_timestamp: u64,
+        max_script_cost: u64,
     ) -> StateResult<ParcelOutcome> {
         match action {
             Action::ChangeShardState {
@@ -412,22 +501,56 @@ impl TopLevelState {
                             found: transaction_network_id,
                         }).into())
                     }
+                    match t {
+                        Transaction::AssetMint {
+                            ..
+                        }
+                        | Transaction::AssetCompose {
+                            ..
+                        }
+                        | Transaction::AssetIncreaseSupply {
+                            ..
+                        } => {
+                            for shard_id in t.related_shards() {
+                                self.check_mint_permission(shard_id, sender)?;
+                            }
+                        }
+                        _ => {}
+                    }
                 }
 
-                let first_result = self.apply_transactions_with_check(&transactions, &changes[0])?;
-
-                for change in changes.iter().skip(1) {
-                    let result = self.apply_transactions_with_check(&transactions, change)?;
-                    if result != first_result {
-                        return Err(ParcelError::InconsistentShardOutcomes.into())
-                    }
+                let mut unwrapping_shards: Vec<ShardId> = transactions
+                    .iter()
+                    .filter_map(|t| match t {
+                        Transaction::AssetUnwrapCCC {
+                            burn,
+                            ..
+                        } => Some(burn.related_shard()),
+                        _ => None,
+                    })
+                    .collect();
+                unwrapping_shards.sort_unstable();
+                unwrapping_shards.dedup();
+                let mut wrapped_ccc_supplies = Vec::with_capacity(unwrapping_shards.len());
+                for shard_id in unwrapping_shards {
+                    wrapped_ccc_supplies.push((shard_id, self.wrapped_ccc_supply(shard_id)?));
                 }
-                Ok(ParcelOutcome::Transactions(first_result))
+
+                let results = self.apply_transactions_with_check(
+                    &transactions,
+                    &changes,
+                    sender,
+                    block_number,
+                    block_timestamp,
+                    max_script_cost,
+                )?;
+                self.give_back_unwrapped_ccc(transactions, &results, wrapped_ccc_supplies)?;
+                Ok(ParcelOutcome::Transactions(results))
             }
             Action::Payment {
                 receiver,
                 amount,
-            } => match self.transfer_balance(fee_payer, receiver, amount) {
+            } => match self.transfer_balance(sender, receiver, amount) {
                 Ok(()) => Ok(ParcelOutcome::Single {
                     invoice: Invoice::Success,
                     error: None,
@@ -444,7 +567,7 @@ impl TopLevelState {
             },
             Action::SetRegularKey {
                 key,
-            } => match self.set_regular_key(fee_payer_public, key) {
+            } => match self.set_regular_key(sender_public, key) {
                 Ok(()) => Ok(ParcelOutcome::Single {
                     invoice: Invoice::Success,
                     error: None,
@@ -453,56 +576,257 @@ impl TopLevelState {
             },
             Action::CreateShard => {
                 let shard_creation_cost = 1.into(); // FIXME: Make shard creation cost configurable
-                self.create_shard(&shard_creation_cost, fee_payer)?;
+                self.create_shard(&shard_creation_cost, sender)?;
+                Ok(ParcelOutcome::Single {
+                    invoice: Invoice::Success,
+                    error: None,
+                })
+            }
+            Action::SetShardOwners {
+                shard_id,
+                owners,
+            } => {
+                let shard_owners =
+                    self.shard_owners(*shard_id)?.ok_or_else(|| ParcelError::InvalidShardId(*shard_id))?;
+                if !shard_owners.contains(sender) {
+                    return Err(ParcelError::InsufficientPermission.into())
+                }
+                if !owners.contains(sender) {
+                    return Err(ParcelError::NewOwnersMustContainSender.into())
+                }
+                self.set_shard_owners(*shard_id, owners.clone())?;
+                Ok(ParcelOutcome::Single {
+                    invoice: Invoice::Success,
+                    error: None,
+                })
+            }
+            Action::SetShardUsers {
+                shard_id,
+                users,
+            } => {
+                let shard_owners =
+                    self.shard_owners(*shard_id)?.ok_or_else(|| ParcelError::InvalidShardId(*shard_id))?;
+                if !shard_owners.contains(sender) {
+                    return Err(ParcelError::InsufficientPermission.into())
+                }
+                self.set_shard_users(*shard_id, users.clone())?;
                 Ok(ParcelOutcome::Single {
                     invoice: Invoice::Success,
                     error: None,
                 })
             }
-            Action::Custom(bytes) => {
-                let handlers = self.db.custom_handlers().to_vec();
-                for h in handlers {
-                    if let Some(result) = h.execute(bytes, self) {
-                        return result
+            Action::WrapCCC {
+                shard_id,
+                lock_script_hash,
+                parameters,
+                amount,
+            } => {
+                match self.wrap_ccc(parcel_hash, *shard_id, lock_script_hash, parameters, *amount, sender, block_number)
+                {
+                    Ok(()) => Ok(ParcelOutcome::Single {
+                        invoice: Invoice::Success,
+                        error: None,
+                    }),
+                    Err(StateError::Parcel(
+                        err @ ParcelError::InsufficientBalance {
+                            ..
+                        },
+                    )) => Ok(ParcelOutcome::Single {
+                        invoice: Invoice::Failed,
+                        error: Some(err),
+                    }),
+                    Err(err) => Err(err),
+                }
+            }
+            Action::Batch {
+                actions,
+            } => {
+                if actions.is_empty() {
+                    return Err(ParcelError::EmptyBatch.into())
+                }
+                if actions.iter().any(|action| !action.is_batchable()) {
+                    return Err(ParcelError::InvalidBatchedAction.into())
+                }
+                let mut outcomes = Vec::with_capacity(actions.len());
+                for action in actions {
+                    let outcome = self.apply_action(
+                        action,
+                        parcel_hash,
+                        network_id,
+                        sender,
+                        sender_public,
+                        block_number,
+                        block_timestamp,
+                        max_script_cost,
+                    )?;
+                    let is_success = outcome.is_success();
+                    outcomes.push(outcome);
+                    if !is_success {
+                        break
                     }
                 }
-                panic!("Unknown custom parcel accepted!")
+                if outcomes.iter().all(ParcelOutcome::is_success) {
+                    return Ok(ParcelOutcome::Batch(outcomes))
+                }
+                // The changes are reverted in apply_internal, so every action in the batch fails.
+                let mut errors: Vec<Option<ParcelError>> = outcomes
+                    .into_iter()
+                    .map(|outcome| match outcome {
+                        ParcelOutcome::Single {
+                            error,
+                            ..
+                        } => error,
+                        _ => None,
+                    })
+                    .collect();
+                errors.resize(actions.len(), None);
+                Ok(ParcelOutcome::Batch(
+                    errors
+                        .into_iter()
+                        .map(|error| ParcelOutcome::Single {
+                            invoice: Invoice::Failed,
+                            error,
+                        })
+                        .collect(),
+                ))
+            }
+            Action::Custom {
+                handler_id,
+                bytes,
+            } => {
+                let handler = self.db.custom_handler(*handler_id).ok_or(ParcelError::InvalidCustomAction)?;
+                if !handler.is_valid(bytes) {
+                    return Err(ParcelError::InvalidCustomAction.into())
+                }
+                handler.execute(bytes, self, sender)
             }
         }
     }
 
+    /// Applies the transactions to every shard in `changes` and checks the roots of the shards.
+    ///
+    /// A transaction that is related to several shards settles in all of them or in none of them:
+    /// each shard applies the part of the transaction that is in it, and the transaction fails in every shard
+    /// if it fails in any of them. Since a failed transaction changes the states that the following transactions see,
+    /// the transactions are applied again without the failed ones until no more transactions fail.
     fn apply_transactions_with_check(
         &mut self,
         transactions: &[Transaction],
-        change: &ChangeShard,
+        changes: &[ChangeShard],
+        sender: &Address,
+        block_number: BlockNumber,
+        block_timestamp: u64,
+        max_script_cost: u64,
     ) -> StateResult<Vec<TransactionOutcome>> {
-        let shard_id = change.shard_id;
-
-        let shard_root = self.shard_root(shard_id)?.ok_or_else(|| ParcelError::InvalidShardId(shard_id))?;
-        if !change.pre_root.is_zero() && shard_root != change.pre_root {
-            return Err(ParcelError::InvalidShardRoot(Mismatch {
-                expected: shard_root,
-                found: change.pre_root,
-            }).into())
+        let mut shard_roots = Vec::with_capacity(changes.len());
+        for (index, change) in changes.iter().enumerate() {
+            let shard_id = change.shard_id;
+            if changes[..index].iter().any(|change| change.shard_id == shard_id) {
+                return Err(ParcelError::DuplicatedShardChange(shard_id).into())
+            }
+            let shard_root = self.shard_root(shard_id)?.ok_or_else(|| ParcelError::InvalidShardId(shard_id))?;
+            if !change.pre_root.is_zero() && shard_root != change.pre_root {
+                return Err(ParcelError::InvalidShardRoot(Mismatch {
+                    expected: shard_root,
+                    found: change.pre_root,
+                }).into())
+            }
+            shard_roots.push(shard_root);
+        }
+        for shard_id in transactions.iter().flat_map(Transaction::related_shards) {
+            if changes.iter().all(|change| change.shard_id != shard_id) {
+                return Err(ParcelError::ShardChangeMissing(shard_id).into())
+            }
         }
 
-        let (new_shard_root, db, results) = self.apply_transactions_internal(transactions, shard_id, shard_root)?;
-        if !change.post_root.is_zero() && change.post_root != new_shard_root {
-            return Err(ParcelError::InvalidShardRoot(Mismatch {
-                expected: new_shard_root,
-                found: change.post_root,
-            }).into())
+        let mut errors: Vec<Option<TransactionError>> = vec![None; transactions.len()];
+        let (db, new_shard_roots) = loop {
+            let failed: Vec<bool> = errors.iter().map(Option::is_some).collect();
+            let mut db = self.db.clone();
+            let mut new_shard_roots = Vec::with_capacity(changes.len());
+            let mut has_new_failure = false;
+            for (change, shard_root) in changes.iter().zip(shard_roots.iter()) {
+                let (new_shard_root, new_db, outcomes) = self.apply_transactions_internal(
+                    transactions,
+                    &failed,
+                    change.shard_id,
+                    *shard_root,
+                    db,
+                    sender,
+                    block_number,
+                    block_timestamp,
+                    max_script_cost,
+                )?;
+                db = new_db;
+                new_shard_roots.push(new_shard_root);
+
+                for (error, outcome) in errors.iter_mut().zip(outcomes) {
+                    if let Some(TransactionOutcome {
+                        error: Some(new_error),
+                        ..
+                    }) = outcome
+                    {
+                        if error.is_none() {
+                            *error = Some(new_error);
+                            has_new_failure = true;
+                        }
+                    }
+                }
+            }
+            if !has_new_failure {
+                break (db, new_shard_roots)
+            }
+        };
+
+        for (change, new_shard_root) in changes.iter().zip(new_shard_roots.iter()) {
+            if !change.post_root.is_zero() && change.post_root != *new_shard_root {
+                return Err(ParcelError::InvalidShardRoot(Mismatch {
+                    expected: *new_shard_root,
+                    found: change.post_root,
+                }).into())
+            }
         }
 
         self.db = db;
-
-        self.set_shard_root(shard_id, &shard_root, &new_shard_root)?;
-        Ok(results)
+        for ((change, shard_root), new_shard_root) in changes.iter().zip(shard_roots).zip(new_shard_roots) {
+            self.set_shard_root(change.shard_id, &shard_root, &new_shard_root)?;
+        }
+        Ok(errors
+            .into_iter()
+            .map(|error| match error {
+                Some(error) => TransactionOutcome {
+                    invoice: Invoice::Failed,
+                    error: Some(error),
+                },
+                None => TransactionOutcome {
+                    invoice: Invoice::Success,
+                    error: None,
+                },
+            })
+            .collect())
     }
 
-    pub fn apply_transactions(&self, transactions: &[Transaction], shard_id: ShardId) -> StateResult<ChangeShard> {
+    pub fn apply_transactions(
+        &self,
+        transactions: &[Transaction],
+        shard_id: ShardId,
+        sender: &Address,
+        block_number: BlockNumber,
+        block_timestamp: u64,
+        max_script_cost: u64,
+    ) -> StateResult<ChangeShard> {
         let pre_root = self.shard_root(shard_id)?.ok_or_else(|| ParcelError::InvalidShardId(shard_id))?;
-        let (post_root, ..) = self.apply_transactions_internal(transactions, shard_id, pre_root)?;
+        let (post_root, ..) = self.apply_transactions_internal(
+            transactions,
+            &vec![false; transactions.len()],
+            shard_id,
+            pre_root,
+            self.db.clone(),
+            sender,
+            block_number,
+            block_timestamp,
+            max_script_cost,
+        )?;
         Ok(ChangeShard {
             shard_id,
             pre_root,
@@ -510,26 +834,179 @@ impl TopLevelState {
         })
     }
 
+    /// Applies the transactions that are related to the shard, skipping the ones that are known to fail.
+    /// The outcome of a skipped transaction is `None`.
     fn apply_transactions_internal(
         &self,
         transactions: &[Transaction],
+        failed: &[bool],
         shard_id: ShardId,
         shard_root: H256,
-    ) -> StateResult<(H256, StateDB, Vec<TransactionOutcome>)> {
-        // FIXME: Make it mutable borrow db instead of cloning.
-        let mut shard_level_state =
-            ShardLevelState::from_existing(shard_id, self.db.clone(), shard_root, self.trie_factory)?;
+        db: StateDB,
+        sender: &Address,
+        block_number: BlockNumber,
+        block_timestamp: u64,
+        max_script_cost: u64,
+    ) -> StateResult<(H256, StateDB, Vec<Option<TransactionOutcome>>)> {
+        let shard_owners = self.shard_owners(shard_id)?.ok_or_else(|| ParcelError::InvalidShardId(shard_id))?;
+        let mut shard_level_state = ShardLevelState::from_existing(shard_id, db, shard_root, self.trie_factory)?;
 
         let mut results = Vec::with_capacity(transactions.len());
-        for t in transactions {
-            let result = shard_level_state.apply(t)?;
-            results.push(result);
+        for (t, failed) in transactions.iter().zip(failed) {
+            if *failed || !t.related_shards().contains(&shard_id) {
+                results.push(None);
+                continue
+            }
+            let result =
+                shard_level_state.apply(t, sender, &shard_owners, block_number, block_timestamp, max_script_cost)?;
+            results.push(Some(result));
         }
 
         let (new_root, db) = shard_level_state.drop();
         Ok((new_root, db, results))
     }
 
+    /// Traces the scripts of the `input_index`-th input of an asset transfer on the shard that the input belongs to.
+    pub fn trace_transfer_input(
+        &self,
+        transaction: &Transaction,
+        input_index: usize,
+        block_number: BlockNumber,
+        block_timestamp: u64,
+        max_script_cost: u64,
+    ) -> StateResult<Option<ScriptTrace>> {
+        let shard_id = match transaction {
+            Transaction::AssetTransfer {
+                inputs,
+                ..
+            } => match inputs.get(input_index) {
+                Some(input) => input.related_shard(),
+                None => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        let shard_root = self.shard_root(shard_id)?.ok_or_else(|| ParcelError::InvalidShardId(shard_id))?;
+        let shard_level_state =
+            ShardLevelState::from_existing(shard_id, self.db.clone(), shard_root, self.trie_factory)?;
+        shard_level_state.trace_transfer_input(transaction, input_index, block_number, block_timestamp, max_script_cost)
+    }
+
+    /// Takes `amount` of CCC from the balance of `sender` and creates an asset of the wrapped CCC in the shard.
+    /// The asset is the first output of a transaction whose hash is the hash of the parcel.
+    fn wrap_ccc(
+        &mut self,
+        parcel_hash: H256,
+        shard_id: ShardId,
+        lock_script_hash: &H256,
+        parameters: &[Bytes],
+        amount: u64,
+        sender: &Address,
+        block_number: BlockNumber,
+    ) -> StateResult<()> {
+        let shard_root = self.shard_root(shard_id)?.ok_or_else(|| ParcelError::InvalidShardId(shard_id))?;
+        let balance = self.balance(sender)?;
+        let cost = U256::from(amount);
+        if balance < cost {
+            return Err(ParcelError::InsufficientBalance {
+                address: *sender,
+                cost,
+                balance,
+            }.into())
+        }
+        let supply = self.wrapped_ccc_supply(shard_id)?;
+
+        let mut shard_level_state =
+            ShardLevelState::from_existing(shard_id, self.db.clone(), shard_root, self.trie_factory)?;
+        shard_level_state.wrap_ccc(parcel_hash, lock_script_hash, parameters, amount, block_number).map_err(|err| {
+            match err {
+                StateError::Transaction(err) => ParcelError::InvalidTransaction(err).into(),
+                err => err,
+            }
+        })?;
+        shard_level_state.commit()?;
+
+        // The wrapped CCC must be issued as much as CCC is taken.
+        // It's checked before the database is replaced, since the error doesn't revert the database.
+        let asset_scheme_address = AssetSchemeAddress::new_with_zero_suffix(shard_id);
+        let new_supply =
+            shard_level_state.asset_scheme(&asset_scheme_address)?.map(|scheme| *scheme.amount()).unwrap_or(0);
+        if supply.checked_add(amount) != Some(new_supply) {
+            return Err(ParcelError::InconsistentWrappedCCCSupply(shard_id).into())
+        }
+
+        let (new_shard_root, db) = shard_level_state.drop();
+        self.db = db;
+        self.set_shard_root(shard_id, &shard_root, &new_shard_root)?;
+        self.sub_balance(sender, &cost)?;
+        Ok(())
+    }
+
+    /// Gives the CCC that the successful `AssetUnwrapCCC` transactions have burnt back to their receivers.
+    /// `supplies` are the supplies of the wrapped CCC in the shards before the transactions are applied.
+    fn give_back_unwrapped_ccc(
+        &mut self,
+        transactions: &[Transaction],
+        outcomes: &[TransactionOutcome],
+        supplies: Vec<(ShardId, u64)>,
+    ) -> StateResult<()> {
+        for (shard_id, supply) in supplies {
+            let unwrapped: Vec<(Address, u64)> = transactions
+                .iter()
+                .zip(outcomes)
+                .filter_map(|(t, outcome)| match t {
+                    Transaction::AssetUnwrapCCC {
+                        burn,
+                        receiver,
+                        ..
+                    } if outcome.invoice == Invoice::Success && burn.related_shard() == shard_id => {
+                        Some((*receiver, burn.prev_out.amount))
+                    }
+                    _ => None,
+                })
+                .collect();
+            let total: u64 = unwrapped.iter().map(|(_, amount)| amount).sum();
+            // The supply of the wrapped CCC must decrease as much as CCC is given back.
+            if supply.checked_sub(total) != Some(self.wrapped_ccc_supply(shard_id)?) {
+                return Err(ParcelError::InconsistentWrappedCCCSupply(shard_id).into())
+            }
+            for (receiver, amount) in unwrapped {
+                self.add_balance(&receiver, &U256::from(amount))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn wrapped_ccc_supply(&self, shard_id: ShardId) -> TrieResult<u64> {
+        let asset_scheme_address = AssetSchemeAddress::new_with_zero_suffix(shard_id);
+        Ok(self.asset_scheme(shard_id, &asset_scheme_address)?.map(|asset_scheme| *asset_scheme.amount()).unwrap_or(0))
+    }
+
+    /// Returns the master address if `address` is a regular key.
+    fn master_address(&self, address: &Address) -> TrieResult<Address> {
+        if self.regular_account_exists_and_not_null(address)? {
+            let regular_account = self.require_regular_account_from_address(address)?;
+            Ok(public_to_address(&regular_account.master_account()))
+        } else {
+            Ok(*address)
+        }
+    }
+
+    /// Only the owners and the users of the shard can create asset schemes or supply in it, unless it has no users.
+    fn check_mint_permission(&self, shard_id: ShardId, sender: &Address) -> StateResult<()> {
+        let users = match self.shard_users(shard_id)? {
+            Some(users) => users,
+            None => return Ok(()),
+        };
+        if users.is_empty() || users.contains(sender) {
+            return Ok(())
+        }
+        let owners = self.shard_owners(shard_id)?.unwrap_or_default();
+        if owners.contains(sender) {
+            return Ok(())
+        }
+        Err(ParcelError::InsufficientPermission.into())
+    }
+
     fn create_shard_level_state(&mut self, fee_payer: &Address) -> StateResult<()> {
         let (shard_id, shard_root, db) = {
             let mut metadata = self.require_metadata()?;
@@ -549,7 +1026,7 @@ impl TopLevelState {
         ctrace!(STATE, "shard created({}, {:?})", shard_id, shard_root);
 
         self.set_shard_root(shard_id, &BLAKE_NULL_RLP, &shard_root)?;
-        self.set_shard_owner(shard_id, &Address::zero(), *fee_payer)?;
+        self.set_shard_owners(shard_id, vec![*fee_payer])?;
         Ok(())
     }
 
@@ -625,18 +1102,18 @@ impl TopLevelState {
     }
 
     fn require_shard<'a>(&'a self, shard_id: ShardId) -> TrieResult<RefMut<'a, Shard>> {
-        let default = || Shard::new(BLAKE_NULL_RLP, Address::zero());
+        let default = || Shard::new(BLAKE_NULL_RLP, vec![], vec![]);
         let db = self.trie_factory.readonly(self.db.as_hashdb(), &self.root)?;
         let shard_address = ShardAddress::new(shard_id);
         let from_db = || self.db.get_cached_shard(&shard_address);
         self.shard.require_item_or_from(&shard_address, default, db, from_db)
     }
 
-    fn require_action_data<'a>(&'a self, key: &H256) -> TrieResult<RefMut<'a, Bytes>> {
-        let default = || NULL_RLP.to_vec();
+    fn require_action_data<'a>(&'a self, a: &ActionDataAddress) -> TrieResult<RefMut<'a, Bytes>> {
+        let default = || Bytes::new();
         let db = self.trie_factory.readonly(self.db.as_hashdb(), &self.root)?;
-        let from_db = || self.db.get_cached_action_data(key);
-        self.action_data.require_item_or_from(key, default, db, from_db)
+        let from_db = || self.db.get_cached_action_data(a);
+        self.action_data.require_item_or_from(a, default, db, from_db)
     }
 }
 
@@ -795,18 +1272,27 @@ impl TopState<StateDB> for TopLevelState {
         Ok(())
     }
 
-    fn set_shard_owner(&mut self, shard_id: ShardId, old_owner: &Address, new_owner: Address) -> StateResult<()> {
+    fn set_shard_owners(&mut self, shard_id: ShardId, new_owners: Vec<Address>) -> StateResult<()> {
+        let mut shard = self.require_shard(shard_id)?;
+        shard.set_owners(new_owners);
+        Ok(())
+    }
+
+    fn set_shard_users(&mut self, shard_id: ShardId, new_users: Vec<Address>) -> StateResult<()> {
         let mut shard = self.require_shard(shard_id)?;
-        assert_eq!(old_owner, shard.owner());
-        shard.set_owner(new_owner);
+        shard.set_users(new_users);
         Ok(())
     }
 
-    fn update_action_data(&mut self, key: &H256, data: Bytes) -> StateResult<()> {
-        let mut action_data = self.require_action_data(key)?;
+    fn update_action_data(&mut self, a: &ActionDataAddress, data: Bytes) -> StateResult<()> {
+        let mut action_data = self.require_action_data(a)?;
         *action_data = data;
         Ok(())
     }
+
+    fn remove_action_data(&mut self, a: &ActionDataAddress) {
+        self.action_data.remove(a);
+    }
 }
 
 #[cfg(test)]
@@ -1044,8 +1530,9 @@ mod tests_state {
 
 #[cfg(test)]
 mod tests_parcel {
+    use ccrypto::Blake;
     use ckey::{Address, Generator, Random};
-    use ctypes::parcel::Parcel;
+    use ctypes::parcel::{Expiration, Parcel};
     use ctypes::transaction::{AssetMintOutput, AssetOutPoint, AssetTransferInput, AssetTransferOutput, Transaction};
     use primitives::U256;
 
@@ -1057,6 +1544,15 @@ mod tests_parcel {
         (keypair.address(), keypair.public().clone())
     }
 
+    fn create_world(network_id: u64, shard_id: ShardId, owner: Address) -> Transaction {
+        Transaction::CreateWorld {
+            network_id,
+            shard_id,
+            nonce: 0,
+            owners: vec![owner],
+        }
+    }
+
     #[test]
     fn apply_empty_parcel() {
         let (sender, sender_public) = address();
@@ -1069,6 +1565,7 @@ mod tests_parcel {
             fee: 5.into(),
             nonce: 0.into(),
             network_id: 0xCA,
+            expiration: None,
             action: Action::ChangeShardState {
                 transactions: vec![],
                 changes: vec![],
@@ -1078,7 +1575,7 @@ mod tests_parcel {
 
         assert_eq!(Ok(()), state.add_balance(&sender, &20.into()));
 
-        let result = state.apply(&parcel, &sender, &sender_public);
+        let result = state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX);
 
         assert_eq!(Ok(ParcelOutcome::Transactions(vec![])), result);
         assert_eq!(Ok(15.into()), state.balance(&sender));
@@ -1093,6 +1590,7 @@ mod tests_parcel {
             nonce: 2.into(),
             fee: 5.into(),
             network_id: 0xCA,
+            expiration: None,
             action: Action::ChangeShardState {
                 transactions: vec![],
                 changes: vec![],
@@ -1102,7 +1600,7 @@ mod tests_parcel {
         let (sender, sender_public) = address();
         assert_eq!(Ok(()), state.add_balance(&sender, &20.into()));
 
-        let result = state.apply(&parcel, &sender, &sender_public);
+        let result = state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX);
         assert_eq!(
             Err(StateError::Parcel(ParcelError::InvalidNonce {
                 expected: 0.into(),
@@ -1115,6 +1613,101 @@ mod tests_parcel {
         assert_eq!(Ok(0.into()), state.nonce(&sender));
     }
 
+    #[test]
+    fn should_apply_error_for_expired_parcel() {
+        let mut state = get_temp_state();
+
+        let parcel = Parcel {
+            nonce: 0.into(),
+            fee: 5.into(),
+            network_id: 0xCA,
+            expiration: Some(Expiration::BlockNumber(10)),
+            action: Action::ChangeShardState {
+                transactions: vec![],
+                changes: vec![],
+                signatures: vec![],
+            },
+        };
+        let (sender, sender_public) = address();
+        assert_eq!(Ok(()), state.add_balance(&sender, &20.into()));
+
+        let result = state.apply(&parcel, &sender, &sender_public, &sender, 11, 0, ::std::u64::MAX);
+        assert_eq!(
+            Err(StateError::Parcel(ParcelError::Expired {
+                expiration: Expiration::BlockNumber(10),
+                block_number: 11,
+                timestamp: 0,
+            })),
+            result
+        );
+        assert_eq!(Ok(20.into()), state.balance(&sender));
+        assert_eq!(Ok(0.into()), state.nonce(&sender));
+
+        let result = state.apply(&parcel, &sender, &sender_public, &sender, 10, 0, ::std::u64::MAX);
+        assert_eq!(Ok(ParcelOutcome::Transactions(vec![])), result);
+        assert_eq!(Ok(15.into()), state.balance(&sender));
+        assert_eq!(Ok(1.into()), state.nonce(&sender));
+    }
+
+    #[test]
+    fn should_apply_error_for_parcel_expired_by_timestamp() {
+        let mut state = get_temp_state();
+
+        let parcel = Parcel {
+            nonce: 0.into(),
+            fee: 5.into(),
+            network_id: 0xCA,
+            expiration: Some(Expiration::Timestamp(100)),
+            action: Action::ChangeShardState {
+                transactions: vec![],
+                changes: vec![],
+                signatures: vec![],
+            },
+        };
+        let (sender, sender_public) = address();
+        assert_eq!(Ok(()), state.add_balance(&sender, &20.into()));
+
+        let result = state.apply(&parcel, &sender, &sender_public, &sender, 0, 101, ::std::u64::MAX);
+        assert_eq!(
+            Err(StateError::Parcel(ParcelError::Expired {
+                expiration: Expiration::Timestamp(100),
+                block_number: 0,
+                timestamp: 101,
+            })),
+            result
+        );
+        assert_eq!(Ok(20.into()), state.balance(&sender));
+        assert_eq!(Ok(0.into()), state.nonce(&sender));
+
+        let result = state.apply(&parcel, &sender, &sender_public, &sender, 0, 100, ::std::u64::MAX);
+        assert_eq!(Ok(ParcelOutcome::Transactions(vec![])), result);
+        assert_eq!(Ok(15.into()), state.balance(&sender));
+        assert_eq!(Ok(1.into()), state.nonce(&sender));
+    }
+
+    #[test]
+    fn custom_action_of_unknown_handler_is_rejected() {
+        let mut state = get_temp_state();
+
+        let parcel = Parcel {
+            nonce: 0.into(),
+            fee: 5.into(),
+            network_id: 0xCA,
+            expiration: None,
+            action: Action::Custom {
+                handler_id: 1,
+                bytes: vec![0xc1, 0x01],
+            },
+        };
+        let (sender, sender_public) = address();
+        assert_eq!(Ok(()), state.add_balance(&sender, &20.into()));
+
+        let result = state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX);
+        assert_eq!(Err(StateError::Parcel(ParcelError::InvalidCustomAction)), result);
+        assert_eq!(Ok(20.into()), state.balance(&sender));
+        assert_eq!(Ok(0.into()), state.nonce(&sender));
+    }
+
     #[test]
     fn should_apply_error_for_not_enough_cash() {
         let mut state = get_temp_state();
@@ -1122,6 +1715,7 @@ mod tests_parcel {
             fee: 5.into(),
             nonce: 0.into(),
             network_id: 0xCA,
+            expiration: None,
             action: Action::ChangeShardState {
                 transactions: vec![],
                 changes: vec![],
@@ -1131,7 +1725,7 @@ mod tests_parcel {
         let (sender, sender_public) = address();
         assert_eq!(Ok(()), state.add_balance(&sender, &4.into()));
 
-        let result = state.apply(&parcel, &sender, &sender_public);
+        let result = state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX);
         assert_eq!(
             Err(StateError::Parcel(ParcelError::InsufficientBalance {
                 address: sender,
@@ -1157,6 +1751,7 @@ mod tests_parcel {
             },
             nonce: 0.into(),
             network_id: 0xCA,
+            expiration: None,
         };
         let (sender, sender_public) = address();
         assert_eq!(Ok(()), state.add_balance(&sender, &20.into()));
@@ -1166,7 +1761,7 @@ mod tests_parcel {
                 invoice: Invoice::Success,
                 error: None,
             }),
-            state.apply(&parcel, &sender, &sender_public)
+            state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX)
         );
 
         assert_eq!(Ok(10.into()), state.balance(&receiver));
@@ -1175,56 +1770,246 @@ mod tests_parcel {
     }
 
     #[test]
-    fn should_apply_set_regular_key() {
+    fn fee_payer_pays_fee_of_sponsored_parcel() {
         let mut state = get_temp_state();
-        let key = 1u64.into();
+        let receiver = 1u64.into();
 
         let parcel = Parcel {
             fee: 5.into(),
-            action: Action::SetRegularKey {
-                key,
+            action: Action::Payment {
+                receiver,
+                amount: 10.into(),
             },
-            nonce: 0.into(),
+            nonce: 3.into(),
             network_id: 0xCA,
+            expiration: None,
         };
         let (sender, sender_public) = address();
-        assert_eq!(Ok(()), state.add_balance(&sender, &5.into()));
+        let (fee_payer, _) = address();
+        assert_eq!(Ok(()), state.add_balance(&sender, &20.into()));
+        assert_eq!(Ok(()), state.add_balance(&fee_payer, &20.into()));
+        for _ in 0..3 {
+            assert_eq!(Ok(()), state.inc_nonce(&fee_payer));
+        }
 
-        assert_eq!(state.regular_key(&sender), Ok(None));
         assert_eq!(
             Ok(ParcelOutcome::Single {
                 invoice: Invoice::Success,
-                error: None
+                error: None,
             }),
-            state.apply(&parcel, &sender, &sender_public)
+            state.apply(&parcel, &sender, &sender_public, &fee_payer, 0, 0, ::std::u64::MAX)
         );
-        assert_eq!(Ok(Some(key)), state.regular_key(&sender));
+
+        assert_eq!(Ok(10.into()), state.balance(&receiver));
+        assert_eq!(Ok(10.into()), state.balance(&sender));
+        assert_eq!(Ok(0.into()), state.nonce(&sender));
+        assert_eq!(Ok(15.into()), state.balance(&fee_payer));
+        assert_eq!(Ok(4.into()), state.nonce(&fee_payer));
     }
 
     #[test]
-    fn should_use_master_balance_when_signed_with_regular_key() {
+    fn apply_batch_of_payments() {
         let mut state = get_temp_state();
-        let regular_keypair = Random.generate().unwrap();
-        let key = regular_keypair.public();
+        let receiver1 = 1u64.into();
+        let receiver2 = 2u64.into();
 
         let parcel = Parcel {
             fee: 5.into(),
-            action: Action::SetRegularKey {
-                key: key.clone(),
+            action: Action::Batch {
+                actions: vec![
+                    Action::Payment {
+                        receiver: receiver1,
+                        amount: 10.into(),
+                    },
+                    Action::Payment {
+                        receiver: receiver2,
+                        amount: 20.into(),
+                    },
+                ],
             },
             nonce: 0.into(),
             network_id: 0xCA,
+            expiration: None,
         };
         let (sender, sender_public) = address();
-        assert_eq!(Ok(()), state.add_balance(&sender, &15.into()));
+        assert_eq!(Ok(()), state.add_balance(&sender, &100.into()));
 
-        assert_eq!(state.regular_key(&sender), Ok(None));
+        let success = || ParcelOutcome::Single {
+            invoice: Invoice::Success,
+            error: None,
+        };
         assert_eq!(
-            Ok(ParcelOutcome::Single {
+            Ok(ParcelOutcome::Batch(vec![success(), success()])),
+            state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX)
+        );
+
+        assert_eq!(Ok(10.into()), state.balance(&receiver1));
+        assert_eq!(Ok(20.into()), state.balance(&receiver2));
+        assert_eq!(Ok(65.into()), state.balance(&sender));
+        assert_eq!(Ok(1.into()), state.nonce(&sender));
+    }
+
+    #[test]
+    fn failed_batch_reverts_every_action() {
+        let mut state = get_temp_state();
+        let receiver1 = 1u64.into();
+        let receiver2 = 2u64.into();
+
+        let parcel = Parcel {
+            fee: 5.into(),
+            action: Action::Batch {
+                actions: vec![
+                    Action::Payment {
+                        receiver: receiver1,
+                        amount: 10.into(),
+                    },
+                    Action::Payment {
+                        receiver: receiver2,
+                        amount: 100.into(),
+                    },
+                    Action::CreateShard,
+                ],
+            },
+            nonce: 0.into(),
+            network_id: 0xCA,
+            expiration: None,
+        };
+        let (sender, sender_public) = address();
+        assert_eq!(Ok(()), state.add_balance(&sender, &100.into()));
+
+        assert_eq!(
+            Ok(ParcelOutcome::Batch(vec![
+                ParcelOutcome::Single {
+                    invoice: Invoice::Failed,
+                    error: None,
+                },
+                ParcelOutcome::Single {
+                    invoice: Invoice::Failed,
+                    error: Some(ParcelError::InsufficientBalance {
+                        address: sender,
+                        balance: 85.into(),
+                        cost: 100.into(),
+                    }),
+                },
+                ParcelOutcome::Single {
+                    invoice: Invoice::Failed,
+                    error: None,
+                },
+            ])),
+            state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX)
+        );
+
+        assert_eq!(Ok(0.into()), state.balance(&receiver1));
+        assert_eq!(Ok(0.into()), state.balance(&receiver2));
+        assert_eq!(Ok(95.into()), state.balance(&sender));
+        assert_eq!(Ok(1.into()), state.nonce(&sender));
+    }
+
+    #[test]
+    fn batch_cannot_contain_change_shard_state() {
+        let mut state = get_temp_state();
+
+        let parcel = Parcel {
+            fee: 5.into(),
+            action: Action::Batch {
+                actions: vec![Action::ChangeShardState {
+                    transactions: vec![],
+                    changes: vec![],
+                    signatures: vec![],
+                }],
+            },
+            nonce: 0.into(),
+            network_id: 0xCA,
+            expiration: None,
+        };
+        let (sender, sender_public) = address();
+        assert_eq!(Ok(()), state.add_balance(&sender, &100.into()));
+
+        assert_eq!(
+            Err(StateError::Parcel(ParcelError::InvalidBatchedAction)),
+            state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX)
+        );
+        assert_eq!(Ok(100.into()), state.balance(&sender));
+        assert_eq!(Ok(0.into()), state.nonce(&sender));
+    }
+
+    #[test]
+    fn batch_cannot_be_empty() {
+        let mut state = get_temp_state();
+
+        let parcel = Parcel {
+            fee: 5.into(),
+            action: Action::Batch {
+                actions: vec![],
+            },
+            nonce: 0.into(),
+            network_id: 0xCA,
+            expiration: None,
+        };
+        let (sender, sender_public) = address();
+        assert_eq!(Ok(()), state.add_balance(&sender, &100.into()));
+
+        assert_eq!(
+            Err(StateError::Parcel(ParcelError::EmptyBatch)),
+            state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX)
+        );
+        assert_eq!(Ok(100.into()), state.balance(&sender));
+        assert_eq!(Ok(0.into()), state.nonce(&sender));
+    }
+
+    #[test]
+    fn should_apply_set_regular_key() {
+        let mut state = get_temp_state();
+        let key = 1u64.into();
+
+        let parcel = Parcel {
+            fee: 5.into(),
+            action: Action::SetRegularKey {
+                key,
+            },
+            nonce: 0.into(),
+            network_id: 0xCA,
+            expiration: None,
+        };
+        let (sender, sender_public) = address();
+        assert_eq!(Ok(()), state.add_balance(&sender, &5.into()));
+
+        assert_eq!(state.regular_key(&sender), Ok(None));
+        assert_eq!(
+            Ok(ParcelOutcome::Single {
+                invoice: Invoice::Success,
+                error: None
+            }),
+            state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX)
+        );
+        assert_eq!(Ok(Some(key)), state.regular_key(&sender));
+    }
+
+    #[test]
+    fn should_use_master_balance_when_signed_with_regular_key() {
+        let mut state = get_temp_state();
+        let regular_keypair = Random.generate().unwrap();
+        let key = regular_keypair.public();
+
+        let parcel = Parcel {
+            fee: 5.into(),
+            action: Action::SetRegularKey {
+                key: key.clone(),
+            },
+            nonce: 0.into(),
+            network_id: 0xCA,
+            expiration: None,
+        };
+        let (sender, sender_public) = address();
+        assert_eq!(Ok(()), state.add_balance(&sender, &15.into()));
+
+        assert_eq!(state.regular_key(&sender), Ok(None));
+        assert_eq!(
+            Ok(ParcelOutcome::Single {
                 invoice: Invoice::Success,
                 error: None
             }),
-            state.apply(&parcel, &sender, &sender_public)
+            state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX)
         );
         assert_eq!(Ok(Some(*key)), state.regular_key(&sender));
 
@@ -1233,6 +2018,7 @@ mod tests_parcel {
             fee: 5.into(),
             nonce: 1.into(),
             network_id: 0xCA,
+            expiration: None,
         };
 
         assert_eq!(
@@ -1240,10 +2026,18 @@ mod tests_parcel {
                 invoice: Invoice::Success,
                 error: None
             }),
-            state.apply(&parcel, &regular_keypair.address(), regular_keypair.public())
+            state.apply(
+                &parcel,
+                &regular_keypair.address(),
+                regular_keypair.public(),
+                &regular_keypair.address(),
+                0,
+                0,
+                ::std::u64::MAX
+            )
         );
         assert_eq!(Ok(4.into()), state.balance(&sender));
-        assert_eq!(Ok(Some(sender)), state.shard_owner(0));
+        assert_eq!(Ok(Some(vec![sender])), state.shard_owners(0));
     }
 
     #[test]
@@ -1259,6 +2053,7 @@ mod tests_parcel {
             },
             nonce: 0.into(),
             network_id: 0xCA,
+            expiration: None,
         };
         let (sender, sender_public) = address();
         assert_eq!(Ok(()), state.add_balance(&sender, &15.into()));
@@ -1269,7 +2064,7 @@ mod tests_parcel {
                 invoice: Invoice::Success,
                 error: None
             }),
-            state.apply(&parcel, &sender, &sender_public)
+            state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX)
         );
         assert_eq!(Ok(Some(*key)), state.regular_key(&sender));
 
@@ -1280,11 +2075,12 @@ mod tests_parcel {
             },
             nonce: 0.into(),
             network_id: 0xCA,
+            expiration: None,
         };
         let (sender2, sender_public2) = address();
         assert_eq!(Ok(()), state.add_balance(&sender2, &15.into()));
 
-        let result = state.apply(&parcel, &sender2, &sender_public2);
+        let result = state.apply(&parcel, &sender2, &sender_public2, &sender2, 0, 0, ::std::u64::MAX);
         assert_eq!(Err(StateError::Parcel(ParcelError::RegularKeyAlreadyInUse)), result);
         assert_eq!(Ok(None), state.regular_key(&sender2));
     }
@@ -1306,9 +2102,10 @@ mod tests_parcel {
             },
             nonce: 0.into(),
             network_id: 0xCA,
+            expiration: None,
         };
 
-        let result = state.apply(&parcel, &sender, &sender_public);
+        let result = state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX);
         assert_eq!(Err(StateError::Parcel(ParcelError::RegularKeyAlreadyInUseAsMaster)), result);
     }
 
@@ -1330,6 +2127,7 @@ mod tests_parcel {
             },
             nonce: 0.into(),
             network_id: 0xCA,
+            expiration: None,
         };
 
         assert_eq!(Some(regular_public), state.regular_key(&sender).unwrap());
@@ -1339,7 +2137,7 @@ mod tests_parcel {
                 invoice: Invoice::Success,
                 error: None
             }),
-            state.apply(&parcel, &regular_address, &regular_public)
+            state.apply(&parcel, &regular_address, &regular_public, &regular_address, 0, 0, ::std::u64::MAX)
         );
         assert_eq!(Ok(false), state.regular_account_exists_and_not_null(&regular_address));
         assert_eq!(Some(regular_public2), state.regular_key(&sender).unwrap());
@@ -1365,17 +2163,18 @@ mod tests_parcel {
             fee: 5.into(),
             nonce: 0.into(),
             network_id: 0xCA,
+            expiration: None,
         };
         assert_eq!(
             Ok(ParcelOutcome::Single {
                 invoice: Invoice::Success,
                 error: None
             }),
-            state.apply(&parcel, &regular_address, &regular_public)
+            state.apply(&parcel, &regular_address, &regular_public, &regular_address, 0, 0, ::std::u64::MAX)
         );
         assert_eq!(Ok(14.into()), state.balance(&regular_address));
         assert_eq!(Ok(20.into()), state.balance(&sender));
-        assert_eq!(Ok(Some(regular_address)), state.shard_owner(0));
+        assert_eq!(Ok(Some(vec![regular_address])), state.shard_owners(0));
     }
 
     #[test]
@@ -1396,8 +2195,9 @@ mod tests_parcel {
             fee: 5.into(),
             nonce: 0.into(),
             network_id: 0xCA,
+            expiration: None,
         };
-        let result = state.apply(&parcel, &sender, &sender_public);
+        let result = state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX);
         assert_eq!(Err(StateError::Parcel(ParcelError::InvalidTransferDestination)), result);
         assert_eq!(Ok(20.into()), state.balance(&sender));
     }
@@ -1415,6 +2215,7 @@ mod tests_parcel {
             },
             nonce: 0.into(),
             network_id: 0xCA,
+            expiration: None,
         };
         let (sender, sender_public) = address();
         assert_eq!(Ok(()), state.add_balance(&sender, &20.into()));
@@ -1428,7 +2229,7 @@ mod tests_parcel {
                     cost: 30.into(),
                 })
             }),
-            state.apply(&parcel, &sender, &sender_public)
+            state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX)
         );
 
         assert_eq!(Ok(0.into()), state.balance(&receiver));
@@ -1454,6 +2255,7 @@ mod tests_parcel {
         let transaction = Transaction::AssetMint {
             network_id: 0xCA,
             shard_id,
+            world_id: 0,
             metadata: metadata.clone(),
             output: AssetMintOutput {
                 lock_script_hash,
@@ -1464,7 +2266,7 @@ mod tests_parcel {
             nonce: 0,
         };
         let transaction_hash = transaction.hash();
-        let transactions = vec![transaction];
+        let transactions = vec![create_world(0xCA, shard_id, sender), transaction];
         let parcel = Parcel {
             fee: 11.into(),
             action: Action::ChangeShardState {
@@ -1478,28 +2280,36 @@ mod tests_parcel {
             },
             nonce: 0.into(),
             network_id: 0xCA,
+            expiration: None,
         };
 
         assert_eq!(Ok(()), state.add_balance(&sender, &U256::from(69u64)));
 
         assert_eq!(
-            Ok(ParcelOutcome::Transactions(vec![TransactionOutcome {
-                invoice: Invoice::Success,
-                error: None,
-            }])),
-            state.apply(&parcel, &sender, &sender_public)
+            Ok(ParcelOutcome::Transactions(vec![
+                TransactionOutcome {
+                    invoice: Invoice::Success,
+                    error: None,
+                },
+                TransactionOutcome {
+                    invoice: Invoice::Success,
+                    error: None,
+                },
+            ])),
+            state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX)
         );
 
         assert_eq!(state.balance(&sender), Ok(58.into()));
         assert_eq!(state.nonce(&sender), Ok(1.into()));
+        assert_eq!(Ok(Some(World::new(vec![sender]))), state.world(shard_id, 0));
 
-        let asset_scheme_address = AssetSchemeAddress::new(transaction_hash, shard_id);
+        let asset_scheme_address = AssetSchemeAddress::new(transaction_hash, shard_id, 0);
         let asset_scheme = state.asset_scheme(shard_id, &asset_scheme_address);
         assert_eq!(Ok(Some(AssetScheme::new(metadata.clone(), amount, registrar))), asset_scheme);
 
         let asset_address = AssetAddress::new(transaction_hash, 0, shard_id);
         let asset = state.asset(shard_id, &asset_address);
-        assert_eq!(Ok(Some(Asset::new(asset_scheme_address.into(), lock_script_hash, parameters, amount))), asset);
+        assert_eq!(Ok(Some(Asset::new(asset_scheme_address.into(), lock_script_hash, parameters, amount, 0))), asset);
     }
 
     #[test]
@@ -1519,6 +2329,7 @@ mod tests_parcel {
         let transaction = Transaction::AssetMint {
             network_id: 0xCA,
             shard_id,
+            world_id: 0,
             metadata: metadata.clone(),
             output: AssetMintOutput {
                 lock_script_hash,
@@ -1529,7 +2340,7 @@ mod tests_parcel {
             nonce: 0,
         };
         let transaction_hash = transaction.hash();
-        let transactions = vec![transaction];
+        let transactions = vec![create_world(0xCA, shard_id, sender), transaction];
         let parcel = Parcel {
             fee: 5.into(),
             action: Action::ChangeShardState {
@@ -1543,29 +2354,36 @@ mod tests_parcel {
             },
             nonce: 0.into(),
             network_id: 0xCA,
+            expiration: None,
         };
 
         assert_eq!(Ok(()), state.add_balance(&sender, &U256::from(69u64)));
 
         assert_eq!(
-            Ok(ParcelOutcome::Transactions(vec![TransactionOutcome {
-                invoice: Invoice::Success,
-                error: None,
-            }])),
-            state.apply(&parcel, &sender, &sender_public)
+            Ok(ParcelOutcome::Transactions(vec![
+                TransactionOutcome {
+                    invoice: Invoice::Success,
+                    error: None,
+                },
+                TransactionOutcome {
+                    invoice: Invoice::Success,
+                    error: None,
+                },
+            ])),
+            state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX)
         );
 
         assert_eq!(state.balance(&sender), Ok(64.into()));
         assert_eq!(state.nonce(&sender), Ok(1.into()));
 
-        let asset_scheme_address = AssetSchemeAddress::new(transaction_hash, shard_id);
+        let asset_scheme_address = AssetSchemeAddress::new(transaction_hash, shard_id, 0);
         let asset_scheme = state.asset_scheme(shard_id, &asset_scheme_address);
         assert_eq!(Ok(Some(AssetScheme::new(metadata.clone(), ::std::u64::MAX, registrar))), asset_scheme);
 
         let asset_address = AssetAddress::new(transaction_hash, 0, shard_id);
         let asset = state.asset(shard_id, &asset_address);
         assert_eq!(
-            Ok(Some(Asset::new(asset_scheme_address.into(), lock_script_hash, parameters, ::std::u64::MAX))),
+            Ok(Some(Asset::new(asset_scheme_address.into(), lock_script_hash, parameters, ::std::u64::MAX, 0))),
             asset
         );
     }
@@ -1588,6 +2406,7 @@ mod tests_parcel {
         let mint = Transaction::AssetMint {
             network_id,
             shard_id,
+            world_id: 0,
             metadata: metadata.clone(),
             output: AssetMintOutput {
                 lock_script_hash,
@@ -1599,7 +2418,7 @@ mod tests_parcel {
         };
         let mint_hash = mint.hash();
 
-        let asset_scheme_address = AssetSchemeAddress::new(mint_hash, shard_id);
+        let asset_scheme_address = AssetSchemeAddress::new(mint_hash, shard_id, 0);
         let asset_type = asset_scheme_address.clone().into();
         let asset_address = AssetAddress::new(mint_hash, 0, shard_id);
 
@@ -1612,6 +2431,7 @@ mod tests_parcel {
                     transaction_hash: mint_hash,
                     index: 0,
                     asset_type,
+                    shard_id,
                     amount: 30,
                 },
                 lock_script: vec![0x30, 0x01],
@@ -1622,18 +2442,21 @@ mod tests_parcel {
                     lock_script_hash,
                     parameters: vec![vec![1]],
                     asset_type,
+                    shard_id,
                     amount: 10,
                 },
                 AssetTransferOutput {
                     lock_script_hash,
                     parameters: vec![],
                     asset_type,
+                    shard_id,
                     amount: 5,
                 },
                 AssetTransferOutput {
                     lock_script_hash: random_lock_script_hash,
                     parameters: vec![],
                     asset_type,
+                    shard_id,
                     amount: 15,
                 },
             ],
@@ -1642,11 +2465,12 @@ mod tests_parcel {
         let transfer_hash = transfer.hash();
 
 
-        let transactions = vec![mint, transfer];
+        let transactions = vec![create_world(network_id, shard_id, sender), mint, transfer];
         let parcel = Parcel {
             fee: 20.into(),
             nonce: 0.into(),
             network_id,
+            expiration: None,
             action: Action::ChangeShardState {
                 transactions,
                 changes: vec![ChangeShard {
@@ -1670,8 +2494,12 @@ mod tests_parcel {
                     invoice: Invoice::Success,
                     error: None,
                 },
+                TransactionOutcome {
+                    invoice: Invoice::Success,
+                    error: None,
+                },
             ]),
-            state.apply(&parcel, &sender, &sender_public).unwrap()
+            state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX).unwrap()
         );
 
         assert_eq!(state.balance(&sender), Ok(100.into()));
@@ -1685,15 +2513,15 @@ mod tests_parcel {
 
         let asset0_address = AssetAddress::new(transfer_hash, 0, shard_id);
         let asset0 = state.asset(shard_id, &asset0_address);
-        assert_eq!(Ok(Some(Asset::new(asset_type, lock_script_hash, vec![vec![1]], 10))), asset0);
+        assert_eq!(Ok(Some(Asset::new(asset_type, lock_script_hash, vec![vec![1]], 10, 0))), asset0);
 
         let asset1_address = AssetAddress::new(transfer_hash, 1, shard_id);
         let asset1 = state.asset(shard_id, &asset1_address);
-        assert_eq!(Ok(Some(Asset::new(asset_type, lock_script_hash, vec![], 5))), asset1);
+        assert_eq!(Ok(Some(Asset::new(asset_type, lock_script_hash, vec![], 5, 0))), asset1);
 
         let asset2_address = AssetAddress::new(transfer_hash, 2, shard_id);
         let asset2 = state.asset(shard_id, &asset2_address);
-        assert_eq!(Ok(Some(Asset::new(asset_type, random_lock_script_hash, vec![], 15))), asset2);
+        assert_eq!(Ok(Some(Asset::new(asset_type, random_lock_script_hash, vec![], 15, 0))), asset2);
     }
 
     #[test]
@@ -1714,6 +2542,7 @@ mod tests_parcel {
         let mint = Transaction::AssetMint {
             network_id,
             shard_id,
+            world_id: 0,
             metadata: metadata.clone(),
             output: AssetMintOutput {
                 lock_script_hash,
@@ -1728,9 +2557,10 @@ mod tests_parcel {
         let mint_parcel = Parcel {
             fee: 20.into(),
             network_id,
+            expiration: None,
             nonce: 0.into(),
             action: Action::ChangeShardState {
-                transactions: vec![mint],
+                transactions: vec![create_world(network_id, shard_id, sender), mint],
                 changes: vec![ChangeShard {
                     shard_id,
                     pre_root: H256::from("0xa8ed01b49cd63c6a547ac3ce357539aa634fb44331a351e3e98b9f1c3a8e3edf"),
@@ -1743,21 +2573,27 @@ mod tests_parcel {
         assert_eq!(Ok(()), state.add_balance(&sender, &U256::from(120)));
 
         assert_eq!(
-            Ok(ParcelOutcome::Transactions(vec![TransactionOutcome {
-                invoice: Invoice::Success,
-                error: None,
-            }])),
-            state.apply(&mint_parcel, &sender, &sender_public)
+            Ok(ParcelOutcome::Transactions(vec![
+                TransactionOutcome {
+                    invoice: Invoice::Success,
+                    error: None,
+                },
+                TransactionOutcome {
+                    invoice: Invoice::Success,
+                    error: None,
+                },
+            ])),
+            state.apply(&mint_parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX)
         );
         assert_eq!(state.balance(&sender), Ok(100.into()));
         assert_eq!(state.nonce(&sender), Ok(1.into()));
 
-        let asset_scheme_address = AssetSchemeAddress::new(mint_hash, shard_id);
+        let asset_scheme_address = AssetSchemeAddress::new(mint_hash, shard_id, 0);
         let asset_type = asset_scheme_address.clone().into();
         let asset_address = AssetAddress::new(mint_hash, 0, shard_id);
 
         let asset = state.asset(shard_id, &asset_address);
-        assert_eq!(Ok(Some(Asset::new(asset_type, lock_script_hash, vec![], 30))), asset);
+        assert_eq!(Ok(Some(Asset::new(asset_type, lock_script_hash, vec![], 30, 0))), asset);
 
         let random_lock_script_hash = H256::random();
         let transfer = Transaction::AssetTransfer {
@@ -1768,6 +2604,7 @@ mod tests_parcel {
                     transaction_hash: mint_hash,
                     index: 0,
                     asset_type,
+                    shard_id,
                     amount: 30,
                 },
                 lock_script: vec![0x30, 0x01],
@@ -1778,18 +2615,21 @@ mod tests_parcel {
                     lock_script_hash,
                     parameters: vec![vec![1]],
                     asset_type,
+                    shard_id,
                     amount: 10,
                 },
                 AssetTransferOutput {
                     lock_script_hash,
                     parameters: vec![],
                     asset_type,
+                    shard_id,
                     amount: 5,
                 },
                 AssetTransferOutput {
                     lock_script_hash: random_lock_script_hash,
                     parameters: vec![],
                     asset_type,
+                    shard_id,
                     amount: 15,
                 },
             ],
@@ -1800,12 +2640,13 @@ mod tests_parcel {
         let transfer_parcel = Parcel {
             fee: 30.into(),
             network_id,
+            expiration: None,
             nonce: 1.into(),
             action: Action::ChangeShardState {
                 transactions: vec![transfer],
                 changes: vec![ChangeShard {
                     shard_id,
-                    pre_root: H256::from("0x3e56ec350ed779120d7b27472aef08fefa0e72165206275efa0b4c8419ba26db"),
+                    pre_root: state.shard_root(shard_id).unwrap().unwrap(),
                     post_root: H256::zero(),
                 }],
                 signatures: vec![],
@@ -1817,7 +2658,7 @@ mod tests_parcel {
                 invoice: Invoice::Success,
                 error: None,
             }])),
-            state.apply(&transfer_parcel, &sender, &sender_public)
+            state.apply(&transfer_parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX)
         );
 
         assert_eq!(state.balance(&sender), Ok(70.into()));
@@ -1831,15 +2672,15 @@ mod tests_parcel {
 
         let asset0_address = AssetAddress::new(transfer_hash, 0, shard_id);
         let asset0 = state.asset(shard_id, &asset0_address);
-        assert_eq!(Ok(Some(Asset::new(asset_type, lock_script_hash, vec![vec![1]], 10))), asset0);
+        assert_eq!(Ok(Some(Asset::new(asset_type, lock_script_hash, vec![vec![1]], 10, 0))), asset0);
 
         let asset1_address = AssetAddress::new(transfer_hash, 1, shard_id);
         let asset1 = state.asset(shard_id, &asset1_address);
-        assert_eq!(Ok(Some(Asset::new(asset_type, lock_script_hash, vec![], 5))), asset1);
+        assert_eq!(Ok(Some(Asset::new(asset_type, lock_script_hash, vec![], 5, 0))), asset1);
 
         let asset2_address = AssetAddress::new(transfer_hash, 2, shard_id);
         let asset2 = state.asset(shard_id, &asset2_address);
-        assert_eq!(Ok(Some(Asset::new(asset_type, random_lock_script_hash, vec![], 15))), asset2);
+        assert_eq!(Ok(Some(Asset::new(asset_type, random_lock_script_hash, vec![], 15, 0))), asset2);
     }
 
     #[test]
@@ -1864,7 +2705,7 @@ mod tests_parcel {
         let state = get_temp_state();
 
         let shard_id = 3;
-        assert_eq!(Ok(None), state.asset_scheme(shard_id, &AssetSchemeAddress::new(H256::random(), shard_id)));
+        assert_eq!(Ok(None), state.asset_scheme(shard_id, &AssetSchemeAddress::new(H256::random(), shard_id, 0)));
     }
 
     #[test]
@@ -1876,10 +2717,11 @@ mod tests_parcel {
             fee: 5.into(),
             nonce: 0.into(),
             network_id: 0xCA,
+            expiration: None,
         };
         let (sender, sender_public) = address();
         assert_eq!(Ok(()), state.add_balance(&sender, &20.into()));
-        let res = state.apply(&parcel, &sender, &sender_public);
+        let res = state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX);
         assert_eq!(
             Ok(ParcelOutcome::Single {
                 invoice: Invoice::Success,
@@ -1891,7 +2733,239 @@ mod tests_parcel {
         assert_eq!(Ok(1.into()), state.nonce(&sender));
         assert_ne!(Ok(None), state.shard_root(0));
         assert_ne!(Ok(None), state.shard_root(0));
-        assert_eq!(Ok(Some(sender)), state.shard_owner(0));
+        assert_eq!(Ok(Some(vec![sender])), state.shard_owners(0));
+    }
+
+    #[test]
+    fn set_shard_owners() {
+        let (sender, sender_public) = address();
+
+        let mut state = get_temp_state();
+        assert_eq!(Ok(()), state.create_shard_level_state(&sender));
+        assert_eq!(Ok(()), state.commit());
+        assert_eq!(Ok(()), state.add_balance(&sender, &20.into()));
+
+        let owners = vec![Address::random(), sender, Address::random()];
+        let parcel = Parcel {
+            action: Action::SetShardOwners {
+                shard_id: 0,
+                owners: owners.clone(),
+            },
+            fee: 5.into(),
+            nonce: 0.into(),
+            network_id: 0xCA,
+            expiration: None,
+        };
+        assert_eq!(
+            Ok(ParcelOutcome::Single {
+                invoice: Invoice::Success,
+                error: None,
+            }),
+            state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX)
+        );
+        assert_eq!(Ok(15.into()), state.balance(&sender));
+        assert_eq!(Ok(Some(owners)), state.shard_owners(0));
+    }
+
+    #[test]
+    fn new_owners_must_contain_sender() {
+        let (sender, sender_public) = address();
+
+        let mut state = get_temp_state();
+        assert_eq!(Ok(()), state.create_shard_level_state(&sender));
+        assert_eq!(Ok(()), state.commit());
+        assert_eq!(Ok(()), state.add_balance(&sender, &20.into()));
+
+        let parcel = Parcel {
+            action: Action::SetShardOwners {
+                shard_id: 0,
+                owners: vec![Address::random()],
+            },
+            fee: 5.into(),
+            nonce: 0.into(),
+            network_id: 0xCA,
+            expiration: None,
+        };
+        assert_eq!(
+            Err(StateError::Parcel(ParcelError::NewOwnersMustContainSender)),
+            state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX)
+        );
+        assert_eq!(Ok(Some(vec![sender])), state.shard_owners(0));
+    }
+
+    #[test]
+    fn only_owners_can_set_shard_owners_and_users() {
+        let (owner, _) = address();
+        let (sender, sender_public) = address();
+
+        let mut state = get_temp_state();
+        assert_eq!(Ok(()), state.create_shard_level_state(&owner));
+        assert_eq!(Ok(()), state.commit());
+        assert_eq!(Ok(()), state.add_balance(&sender, &20.into()));
+
+        let parcel = Parcel {
+            action: Action::SetShardOwners {
+                shard_id: 0,
+                owners: vec![sender],
+            },
+            fee: 5.into(),
+            nonce: 0.into(),
+            network_id: 0xCA,
+            expiration: None,
+        };
+        assert_eq!(
+            Err(StateError::Parcel(ParcelError::InsufficientPermission)),
+            state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX)
+        );
+
+        let parcel = Parcel {
+            action: Action::SetShardUsers {
+                shard_id: 0,
+                users: vec![sender],
+            },
+            fee: 5.into(),
+            nonce: 0.into(),
+            network_id: 0xCA,
+            expiration: None,
+        };
+        assert_eq!(
+            Err(StateError::Parcel(ParcelError::InsufficientPermission)),
+            state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX)
+        );
+
+        assert_eq!(Ok(Some(vec![owner])), state.shard_owners(0));
+        assert_eq!(Ok(Some(vec![])), state.shard_users(0));
+    }
+
+    #[test]
+    fn only_shard_users_can_mint() {
+        let (owner, owner_public) = address();
+        let (user, user_public) = address();
+        let (stranger, stranger_public) = address();
+
+        let mut state = get_temp_state();
+        assert_eq!(Ok(()), state.create_shard_level_state(&owner));
+        assert_eq!(Ok(()), state.commit());
+        assert_eq!(Ok(()), state.add_balance(&owner, &20.into()));
+        assert_eq!(Ok(()), state.add_balance(&user, &20.into()));
+        assert_eq!(Ok(()), state.add_balance(&stranger, &20.into()));
+
+        let shard_id = 0;
+        let set_users = Parcel {
+            action: Action::SetShardUsers {
+                shard_id,
+                users: vec![user],
+            },
+            fee: 5.into(),
+            nonce: 0.into(),
+            network_id: 0xCA,
+            expiration: None,
+        };
+        assert_eq!(
+            Ok(ParcelOutcome::Single {
+                invoice: Invoice::Success,
+                error: None,
+            }),
+            state.apply(&set_users, &owner, &owner_public, &owner, 0, 0, ::std::u64::MAX)
+        );
+        assert_eq!(Ok(Some(vec![user])), state.shard_users(shard_id));
+
+        let mint_parcel = Parcel {
+            action: Action::ChangeShardState {
+                transactions: vec![Transaction::AssetMint {
+                    network_id: 0xCA,
+                    shard_id,
+                    world_id: 0,
+                    metadata: "metadata".to_string(),
+                    output: AssetMintOutput {
+                        lock_script_hash: H256::random(),
+                        parameters: vec![],
+                        amount: Some(30),
+                    },
+                    registrar: None,
+                    nonce: 0,
+                }],
+                changes: vec![ChangeShard {
+                    shard_id,
+                    pre_root: H256::zero(),
+                    post_root: H256::zero(),
+                }],
+                signatures: vec![],
+            },
+            fee: 5.into(),
+            nonce: 0.into(),
+            network_id: 0xCA,
+            expiration: None,
+        };
+
+        assert_eq!(
+            Err(StateError::Parcel(ParcelError::InsufficientPermission)),
+            state.apply(&mint_parcel, &stranger, &stranger_public, &stranger, 0, 0, ::std::u64::MAX)
+        );
+        assert_eq!(Ok(0.into()), state.nonce(&stranger));
+
+        let compose_parcel = Parcel {
+            action: Action::ChangeShardState {
+                transactions: vec![Transaction::AssetCompose {
+                    network_id: 0xCA,
+                    shard_id,
+                    world_id: 0,
+                    metadata: "metadata".to_string(),
+                    registrar: None,
+                    inputs: vec![],
+                    lock_script_hash: H256::random(),
+                    parameters: vec![],
+                    amount: 1,
+                    nonce: 0,
+                }],
+                changes: vec![ChangeShard {
+                    shard_id,
+                    pre_root: H256::zero(),
+                    post_root: H256::zero(),
+                }],
+                signatures: vec![],
+            },
+            fee: 5.into(),
+            nonce: 0.into(),
+            network_id: 0xCA,
+            expiration: None,
+        };
+        assert_eq!(
+            Err(StateError::Parcel(ParcelError::InsufficientPermission)),
+            state.apply(&compose_parcel, &stranger, &stranger_public, &stranger, 0, 0, ::std::u64::MAX)
+        );
+
+        let increase_supply_parcel = Parcel {
+            action: Action::ChangeShardState {
+                transactions: vec![Transaction::AssetIncreaseSupply {
+                    network_id: 0xCA,
+                    asset_type: AssetSchemeAddress::new_with_zero_suffix(shard_id).into(),
+                    lock_script_hash: H256::random(),
+                    parameters: vec![],
+                    amount: 10,
+                    nonce: 0,
+                }],
+                changes: vec![ChangeShard {
+                    shard_id,
+                    pre_root: H256::zero(),
+                    post_root: H256::zero(),
+                }],
+                signatures: vec![],
+            },
+            fee: 5.into(),
+            nonce: 0.into(),
+            network_id: 0xCA,
+            expiration: None,
+        };
+        assert_eq!(
+            Err(StateError::Parcel(ParcelError::InsufficientPermission)),
+            state.apply(&increase_supply_parcel, &stranger, &stranger_public, &stranger, 0, 0, ::std::u64::MAX)
+        );
+        assert_eq!(Ok(0.into()), state.nonce(&stranger));
+
+        // The permission check passes, though the mint fails because there is no world yet.
+        assert!(state.apply(&mint_parcel, &user, &user_public, &user, 0, 0, ::std::u64::MAX).is_ok());
+        assert_eq!(Ok(1.into()), state.nonce(&user));
     }
 
     #[test]
@@ -1903,10 +2977,11 @@ mod tests_parcel {
             fee: 5.into(),
             nonce: 0.into(),
             network_id: 0xCA,
+            expiration: None,
         };
         let (sender, sender_public) = address();
         assert_eq!(Ok(()), state.add_balance(&sender, &20.into()));
-        let res = state.apply(&parcel, &sender, &sender_public);
+        let res = state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX);
         assert_eq!(
             Ok(ParcelOutcome::Single {
                 invoice: Invoice::Success,
@@ -1916,7 +2991,7 @@ mod tests_parcel {
         );
         assert_eq!(Ok(14.into()), state.balance(&sender));
         assert_eq!(Ok(1.into()), state.nonce(&sender));
-        assert_eq!(Ok(Some(sender)), state.shard_owner(0));
+        assert_eq!(Ok(Some(vec![sender])), state.shard_owners(0));
 
         let shard_id = 3;
         assert_eq!(Ok(None), state.asset(shard_id, &AssetAddress::new(H256::random(), 0, shard_id)));
@@ -1931,10 +3006,11 @@ mod tests_parcel {
             fee: 5.into(),
             nonce: 0.into(),
             network_id: 0xCA,
+            expiration: None,
         };
         let (sender, sender_public) = address();
         assert_eq!(Ok(()), state.add_balance(&sender, &20.into()));
-        let res = state.apply(&parcel, &sender, &sender_public);
+        let res = state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX);
         assert_eq!(
             Ok(ParcelOutcome::Single {
                 invoice: Invoice::Success,
@@ -1944,10 +3020,10 @@ mod tests_parcel {
         );
         assert_eq!(Ok(14.into()), state.balance(&sender));
         assert_eq!(Ok(1.into()), state.nonce(&sender));
-        assert_eq!(Ok(Some(sender)), state.shard_owner(0));
+        assert_eq!(Ok(Some(vec![sender])), state.shard_owners(0));
 
         let shard_id = 3;
-        assert_eq!(Ok(None), state.asset_scheme(shard_id, &AssetSchemeAddress::new(H256::random(), shard_id)));
+        assert_eq!(Ok(None), state.asset_scheme(shard_id, &AssetSchemeAddress::new(H256::random(), shard_id, 0)));
     }
 
     #[test]
@@ -1963,6 +3039,7 @@ mod tests_parcel {
         let transaction = Transaction::AssetMint {
             network_id: 0xCA,
             shard_id,
+            world_id: 0,
             metadata: metadata.clone(),
             output: AssetMintOutput {
                 lock_script_hash,
@@ -1986,12 +3063,13 @@ mod tests_parcel {
                 signatures: vec![],
             },
             network_id: 0xCA,
+            expiration: None,
         };
         let (sender, sender_public) = address();
 
         assert_eq!(Ok(()), state.add_balance(&sender, &U256::from(69u64)));
 
-        let res = state.apply(&parcel, &sender, &sender_public);
+        let res = state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX);
         assert_eq!(Err(StateError::Parcel(ParcelError::InvalidShardId(0))), res);
     }
 
@@ -2002,7 +3080,7 @@ mod tests_parcel {
         let network_id = 0xBeef;
         let shard_id = 100;
 
-        let asset_type = AssetSchemeAddress::new(H256::zero(), shard_id).into();
+        let asset_type = AssetSchemeAddress::new(H256::zero(), shard_id, 0).into();
         let transfer = Transaction::AssetTransfer {
             network_id,
             burns: vec![],
@@ -2011,6 +3089,7 @@ mod tests_parcel {
                     transaction_hash: H256::random(),
                     index: 0,
                     asset_type,
+                    shard_id,
                     amount: 30,
                 },
                 lock_script: vec![0x30, 0x01],
@@ -2021,18 +3100,21 @@ mod tests_parcel {
                     lock_script_hash: H256::random(),
                     parameters: vec![vec![1]],
                     asset_type,
+                    shard_id,
                     amount: 10,
                 },
                 AssetTransferOutput {
                     lock_script_hash: H256::random(),
                     parameters: vec![],
                     asset_type,
+                    shard_id,
                     amount: 5,
                 },
                 AssetTransferOutput {
                     lock_script_hash: H256::random(),
                     parameters: vec![],
                     asset_type,
+                    shard_id,
                     amount: 15,
                 },
             ],
@@ -2042,6 +3124,7 @@ mod tests_parcel {
         let parcel = Parcel {
             fee: 30.into(),
             network_id,
+            expiration: None,
             nonce: 0.into(),
             action: Action::ChangeShardState {
                 transactions: vec![transfer],
@@ -2057,7 +3140,137 @@ mod tests_parcel {
         let (sender, sender_public) = address();
         assert_eq!(Ok(()), state.add_balance(&sender, &U256::from(120)));
 
-        let res = state.apply(&parcel, &sender, &sender_public);
+        let res = state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX);
         assert_eq!(Err(StateError::Parcel(ParcelError::InvalidShardId(100))), res);
     }
+
+    #[test]
+    fn wrap_and_unwrap_ccc() {
+        let (sender, sender_public) = address();
+
+        let mut state = get_temp_state();
+        assert_eq!(Ok(()), state.create_shard_level_state(&sender));
+        assert_eq!(Ok(()), state.commit());
+        assert_eq!(Ok(()), state.add_balance(&sender, &100.into()));
+
+        let shard_id = 0;
+        let network_id = 0xBeef;
+        let burn_script = vec![0x01];
+        let wrap = Parcel {
+            fee: 5.into(),
+            nonce: 0.into(),
+            network_id,
+            expiration: None,
+            action: Action::WrapCCC {
+                shard_id,
+                lock_script_hash: Blake::blake(&burn_script),
+                parameters: vec![],
+                amount: 30,
+            },
+        };
+        assert_eq!(
+            Ok(ParcelOutcome::Single {
+                invoice: Invoice::Success,
+                error: None,
+            }),
+            state.apply(&wrap, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX)
+        );
+        assert_eq!(Ok(65.into()), state.balance(&sender));
+
+        let asset_scheme_address = AssetSchemeAddress::new_with_zero_suffix(shard_id);
+        let asset_type: H256 = asset_scheme_address.clone().into();
+        let asset_address = AssetAddress::new(wrap.hash(), 0, shard_id);
+        assert_eq!(
+            Ok(Some(AssetScheme::new(String::new(), 30, None))),
+            state.asset_scheme(shard_id, &asset_scheme_address)
+        );
+        assert_eq!(
+            Ok(Some(Asset::new(asset_type, Blake::blake(&burn_script), vec![], 30, 0))),
+            state.asset(shard_id, &asset_address)
+        );
+
+        let receiver = Address::random();
+        let unwrap = Transaction::AssetUnwrapCCC {
+            network_id,
+            burn: AssetTransferInput {
+                prev_out: AssetOutPoint {
+                    transaction_hash: wrap.hash(),
+                    index: 0,
+                    asset_type,
+                    shard_id,
+                    amount: 30,
+                },
+                lock_script: burn_script,
+                unlock_script: vec![],
+            },
+            receiver,
+            nonce: 0,
+        };
+        let parcel = Parcel {
+            fee: 5.into(),
+            nonce: 1.into(),
+            network_id,
+            expiration: None,
+            action: Action::ChangeShardState {
+                transactions: vec![unwrap],
+                changes: vec![ChangeShard {
+                    shard_id,
+                    pre_root: H256::zero(),
+                    post_root: H256::zero(),
+                }],
+                signatures: vec![],
+            },
+        };
+        assert_eq!(
+            Ok(ParcelOutcome::Transactions(vec![TransactionOutcome {
+                invoice: Invoice::Success,
+                error: None,
+            }])),
+            state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX)
+        );
+        assert_eq!(Ok(60.into()), state.balance(&sender));
+        assert_eq!(Ok(30.into()), state.balance(&receiver));
+        assert_eq!(Ok(None), state.asset_scheme(shard_id, &asset_scheme_address));
+        assert_eq!(Ok(None), state.asset(shard_id, &asset_address));
+    }
+
+    #[test]
+    fn cannot_wrap_more_ccc_than_balance() {
+        let (sender, sender_public) = address();
+
+        let mut state = get_temp_state();
+        assert_eq!(Ok(()), state.create_shard_level_state(&sender));
+        assert_eq!(Ok(()), state.commit());
+        assert_eq!(Ok(()), state.add_balance(&sender, &20.into()));
+
+        let shard_id = 0;
+        let parcel = Parcel {
+            fee: 5.into(),
+            nonce: 0.into(),
+            network_id: 0xBeef,
+            expiration: None,
+            action: Action::WrapCCC {
+                shard_id,
+                lock_script_hash: H256::random(),
+                parameters: vec![],
+                amount: 30,
+            },
+        };
+        assert_eq!(
+            Ok(ParcelOutcome::Single {
+                invoice: Invoice::Failed,
+                error: Some(ParcelError::InsufficientBalance {
+                    address: sender,
+                    balance: 15.into(),
+                    cost: 30.into(),
+                }),
+            }),
+            state.apply(&parcel, &sender, &sender_public, &sender, 0, 0, ::std::u64::MAX)
+        );
+        assert_eq!(Ok(15.into()), state.balance(&sender));
+        assert_eq!(Ok(1.into()), state.nonce(&sender));
+
+        let asset_scheme_address = AssetSchemeAddress::new_with_zero_suffix(shard_id);
+        assert_eq!(Ok(None), state.asset_scheme(shard_id, &asset_scheme_address));
+    }
 }
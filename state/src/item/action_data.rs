@@ -0,0 +1,98 @@
+// Copyright 2018 Kodebox, Inc.
+// This file is part of CodeChain.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use byteorder::{BigEndian, ByteOrder};
+use ccrypto::blake256;
+use primitives::{Bytes, H256};
+
+use super::cache::CacheableItem;
+
+impl CacheableItem for Bytes {
+    type Address = ActionDataAddress;
+
+    fn is_null(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+const PREFIX: u8 = super::ACTION_DATA_PREFIX;
+
+/// The address of the data of a custom action handler.
+/// The handler id follows the prefix, so the data of a handler is in its own subtree of the state trie.
+#[derive(Clone, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ActionDataAddress(H256);
+
+impl_address!(TOP, ActionDataAddress, PREFIX);
+
+impl ActionDataAddress {
+    pub fn new(handler_id: u64, key: &[u8]) -> Self {
+        let mut address = Self::from_transaction_hash(blake256(key), handler_id);
+        BigEndian::write_u64(&mut address.0[1..9], handler_id);
+        address
+    }
+
+    pub fn handler_id(&self) -> u64 {
+        BigEndian::read_u64(&self.0[1..9])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handler_id_follows_prefix() {
+        let address = ActionDataAddress::new(0x0102, b"key");
+        assert_eq!(address[0..9], [PREFIX, 0, 0, 0, 0, 0, 0, 1, 2]);
+        assert_eq!(0x0102, address.handler_id());
+    }
+
+    #[test]
+    fn different_handler_id_makes_different_address() {
+        let address1 = ActionDataAddress::new(1, b"key");
+        let address2 = ActionDataAddress::new(2, b"key");
+        assert_ne!(address1, address2);
+        assert_ne!(address1[9..], address2[9..]);
+    }
+
+    #[test]
+    fn parse_fail_return_none() {
+        let hash = {
+            let mut hash;
+            loop {
+                hash = H256::random();
+                if hash[0] == PREFIX {
+                    continue
+                }
+                break
+            }
+            hash
+        };
+        let address = ActionDataAddress::from_hash(hash);
+        assert!(address.is_none());
+    }
+
+    #[test]
+    fn parse_return_some() {
+        let hash = {
+            let mut hash = H256::random();
+            hash[0] = PREFIX;
+            hash
+        };
+        let address = ActionDataAddress::from_hash(hash);
+        assert_eq!(Some(ActionDataAddress(hash)), address);
+    }
+}
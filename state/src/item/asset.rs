@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use ctypes::ShardId;
+use ctypes::{BlockNumber, ShardId};
 use primitives::{Bytes, H256};
 use rlp::{Decodable, DecoderError, Encodable, RlpStream, UntrustedRlp};
 
@@ -26,15 +26,25 @@ pub struct Asset {
     lock_script_hash: H256,
     parameters: Vec<Bytes>,
     amount: u64,
+    created_at: BlockNumber,
+    frozen: bool,
 }
 
 impl Asset {
-    pub fn new(asset_type: H256, lock_script_hash: H256, parameters: Vec<Bytes>, amount: u64) -> Self {
+    pub fn new(
+        asset_type: H256,
+        lock_script_hash: H256,
+        parameters: Vec<Bytes>,
+        amount: u64,
+        created_at: BlockNumber,
+    ) -> Self {
         Self {
             asset_type,
             lock_script_hash,
             parameters,
             amount,
+            created_at,
+            frozen: false,
         }
     }
 
@@ -53,6 +63,20 @@ impl Asset {
     pub fn amount(&self) -> &u64 {
         &self.amount
     }
+
+    /// The number of the block in which the asset was created.
+    pub fn created_at(&self) -> BlockNumber {
+        self.created_at
+    }
+
+    /// A frozen asset cannot be spent until the registrar of its asset scheme unfreezes it.
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    pub fn set_frozen(&mut self, frozen: bool) {
+        self.frozen = frozen;
+    }
 }
 
 impl CacheableItem for Asset {
@@ -67,12 +91,14 @@ const PREFIX: u8 = super::ASSET_PREFIX;
 
 impl Encodable for Asset {
     fn rlp_append(&self, s: &mut RlpStream) {
-        s.begin_list(5)
+        s.begin_list(7)
             .append(&PREFIX)
             .append(&self.asset_type)
             .append(&self.lock_script_hash)
             .append(&self.parameters)
-            .append(&self.amount);
+            .append(&self.amount)
+            .append(&self.created_at)
+            .append(&self.frozen);
     }
 }
 
@@ -83,11 +109,20 @@ impl Decodable for Asset {
             cdebug!(STATE, "{} is not an expected prefix for asset", prefix);
             return Err(DecoderError::Custom("Unexpected prefix"))
         }
+        // The assets written before the creation block and the freezing were added don't have them.
+        let (created_at, frozen) = match rlp.item_count()? {
+            5 => (0, false),
+            6 => (rlp.val_at(5)?, false),
+            7 => (rlp.val_at(5)?, rlp.val_at(6)?),
+            _ => return Err(DecoderError::RlpIncorrectListLen),
+        };
         Ok(Self {
             asset_type: rlp.val_at(1)?,
             lock_script_hash: rlp.val_at(2)?,
             parameters: rlp.val_at(3)?,
             amount: rlp.val_at(4)?,
+            created_at,
+            frozen,
         })
     }
 }
@@ -194,4 +229,16 @@ mod tests {
         let asset_address = AssetAddress::from_hash(hash).unwrap();
         assert_eq!(shard_id, asset_address.shard_id());
     }
+
+    #[test]
+    fn decode_asset_without_creation_block() {
+        let asset_type = H256::random();
+        let lock_script_hash = H256::random();
+        let parameters = vec![vec![1, 2, 3]];
+        let mut s = RlpStream::new_list(5);
+        s.append(&PREFIX).append(&asset_type).append(&lock_script_hash).append(&parameters).append(&100u64);
+
+        let asset = ::rlp::decode::<Asset>(&s.out());
+        assert_eq!(Asset::new(asset_type, lock_script_hash, parameters, 100, 0), asset);
+    }
 }
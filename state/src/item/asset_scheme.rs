@@ -14,8 +14,9 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use byteorder::{BigEndian, ByteOrder};
 use ckey::Address;
-use ctypes::ShardId;
+use ctypes::{ShardId, WorldId};
 use primitives::H256;
 use rlp::{Decodable, DecoderError, Encodable, RlpStream, UntrustedRlp};
 
@@ -26,6 +27,15 @@ pub struct AssetScheme {
     metadata: String,
     amount: u64,
     registrar: Option<Address>,
+    pool: Vec<PooledAsset>,
+}
+
+/// Assets of a type that are locked in a composite asset.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PooledAsset {
+    pub asset_type: H256,
+    pub amount: u64,
 }
 
 impl AssetScheme {
@@ -34,6 +44,17 @@ impl AssetScheme {
             metadata,
             amount,
             registrar,
+            pool: Vec::new(),
+        }
+    }
+
+    /// Creates the scheme of a composite asset, which holds the assets in `pool` until it is decomposed.
+    pub fn new_with_pool(metadata: String, amount: u64, registrar: Option<Address>, pool: Vec<PooledAsset>) -> Self {
+        Self {
+            metadata,
+            amount,
+            registrar,
+            pool,
         }
     }
 
@@ -52,13 +73,55 @@ impl AssetScheme {
     pub fn is_permissioned(&self) -> bool {
         self.registrar.is_some()
     }
+
+    pub fn pool(&self) -> &[PooledAsset] {
+        &self.pool
+    }
+
+    pub fn is_composite(&self) -> bool {
+        !self.pool.is_empty()
+    }
+
+    pub fn set_registrar(&mut self, registrar: Option<Address>) {
+        self.registrar = registrar;
+    }
+
+    /// Increases the amount of issued assets. Returns false if the amount is infinite or it would overflow.
+    pub fn increase_amount(&mut self, amount: u64) -> bool {
+        if self.amount == ::std::u64::MAX {
+            return false
+        }
+        match self.amount.checked_add(amount) {
+            Some(new_amount) if new_amount != ::std::u64::MAX => {
+                self.amount = new_amount;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Decreases the amount of issued assets. Returns false if there are fewer assets than `amount`.
+    pub fn decrease_amount(&mut self, amount: u64) -> bool {
+        match self.amount.checked_sub(amount) {
+            Some(new_amount) => {
+                self.amount = new_amount;
+                true
+            }
+            None => false,
+        }
+    }
 }
 
 const PREFIX: u8 = super::ASSET_SCHEME_PREFIX;
 
 impl Encodable for AssetScheme {
     fn rlp_append(&self, s: &mut RlpStream) {
-        s.begin_list(4).append(&PREFIX).append(&self.metadata).append(&self.amount).append(&self.registrar);
+        s.begin_list(5)
+            .append(&PREFIX)
+            .append(&self.metadata)
+            .append(&self.amount)
+            .append(&self.registrar)
+            .append_list(&self.pool);
     }
 }
 
@@ -73,6 +136,25 @@ impl Decodable for AssetScheme {
             metadata: rlp.val_at(1)?,
             amount: rlp.val_at(2)?,
             registrar: rlp.val_at(3)?,
+            pool: rlp.list_at(4)?,
+        })
+    }
+}
+
+impl Encodable for PooledAsset {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(2).append(&self.asset_type).append(&self.amount);
+    }
+}
+
+impl Decodable for PooledAsset {
+    fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+        if rlp.item_count()? != 2 {
+            return Err(DecoderError::RlpIncorrectListLen)
+        }
+        Ok(Self {
+            asset_type: rlp.val_at(0)?,
+            amount: rlp.val_at(1)?,
         })
     }
 }
@@ -80,13 +162,22 @@ impl Decodable for AssetScheme {
 #[derive(Clone, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct AssetSchemeAddress(H256);
 
-impl_address!(SHARD, AssetSchemeAddress, PREFIX);
+impl_address!(WORLD, AssetSchemeAddress, PREFIX);
 
 impl AssetSchemeAddress {
-    pub fn new(transaction_hash: H256, shard_id: ShardId) -> Self {
+    pub fn new(transaction_hash: H256, shard_id: ShardId, world_id: WorldId) -> Self {
         let index = ::std::u64::MAX;
 
-        Self::from_transaction_hash_with_shard_id(transaction_hash, index, shard_id)
+        Self::from_transaction_hash_with_shard_and_world_id(transaction_hash, index, shard_id, world_id)
+    }
+
+    /// Returns the address of the asset scheme of the wrapped CCC in the shard.
+    /// No transaction can create the scheme since the address is not derived from a hash.
+    pub fn new_with_zero_suffix(shard_id: ShardId) -> Self {
+        let mut hash = H256::zero();
+        hash[0..2].clone_from_slice(&[PREFIX, 0]);
+        BigEndian::write_u16(&mut hash[2..4], shard_id);
+        AssetSchemeAddress(hash)
     }
 }
 
@@ -121,22 +212,41 @@ mod tests {
             address
         };
         let shard_id = 0xBEE;
-        let asset_address = AssetSchemeAddress::new(origin, shard_id);
+        let world_id = 0xA;
+        let asset_address = AssetSchemeAddress::new(origin, shard_id, world_id);
         let hash: H256 = asset_address.into();
         assert_ne!(origin, hash);
         assert_eq!(hash[0..2], [PREFIX, 0]);
         assert_eq!(hash[2..4], [0x0B, 0xEE]); // shard id
-        assert_eq!(hash[4..6], [0, 0]); // world id
+        assert_eq!(hash[4..6], [0x00, 0x0A]); // world id
+    }
+
+    #[test]
+    fn wrapped_ccc_scheme_address() {
+        let asset_scheme_address = AssetSchemeAddress::new_with_zero_suffix(0xBEE);
+        assert_eq!(0xBEE, asset_scheme_address.shard_id());
+        assert_eq!(0, asset_scheme_address.world_id());
+        let hash: H256 = asset_scheme_address.into();
+        assert_eq!(hash[0..4], [PREFIX, 0, 0x0B, 0xEE]);
+        assert!(hash[4..].iter().all(|byte| *byte == 0));
     }
 
     #[test]
     fn shard_id() {
         let origin = H256::random();
         let shard_id = 0xCAA;
-        let asset_scheme_address = AssetSchemeAddress::new(origin, shard_id);
+        let asset_scheme_address = AssetSchemeAddress::new(origin, shard_id, 0);
         assert_eq!(shard_id, asset_scheme_address.shard_id());
     }
 
+    #[test]
+    fn world_id() {
+        let origin = H256::random();
+        let world_id = 0xBEE;
+        let asset_scheme_address = AssetSchemeAddress::new(origin, 0, world_id);
+        assert_eq!(world_id, asset_scheme_address.world_id());
+    }
+
     #[test]
     fn shard_id_from_hash() {
         let hash = {
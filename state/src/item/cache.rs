@@ -23,7 +23,6 @@ use std::hash::Hash;
 use std::vec::Vec;
 
 use cmerkle::{self, Result as TrieResult, Trie, TrieKinds, TrieMut};
-use primitives::{Bytes, H256};
 use rlp::{Decodable, Encodable};
 
 pub trait CacheableItem: Clone + fmt::Debug + Decodable + Encodable {
@@ -31,13 +30,6 @@ pub trait CacheableItem: Clone + fmt::Debug + Decodable + Encodable {
     fn is_null(&self) -> bool;
 }
 
-impl CacheableItem for Bytes {
-    type Address = H256;
-    fn is_null(&self) -> bool {
-        self.is_empty()
-    }
-}
-
 #[derive(Eq, PartialEq, Clone, Copy, Debug)]
 /// Account modification state. Used to check if the account was
 /// Modified in between commits and overall.
@@ -18,6 +18,7 @@
 mod address;
 
 pub mod account;
+pub mod action_data;
 pub mod asset;
 pub mod asset_scheme;
 pub mod cache;
@@ -29,6 +30,7 @@ pub mod world;
 
 const ASSET_PREFIX: u8 = 'A' as u8;
 const ADDRESS_PREFIX: u8 = 'C' as u8;
+const ACTION_DATA_PREFIX: u8 = 'D' as u8;
 const SHARD_METADATA_PREFIX: u8 = 'E' as u8;
 const SHARD_PREFIX: u8 = 'H' as u8;
 const METADATA_PREFIX: u8 = 'M' as u8;
@@ -25,14 +25,16 @@ use super::cache::CacheableItem;
 #[derive(Clone, Debug)]
 pub struct Shard {
     root: H256,
-    owner: Address,
+    owners: Vec<Address>,
+    users: Vec<Address>,
 }
 
 impl Shard {
-    pub fn new(shard_root: H256, owner: Address) -> Self {
+    pub fn new(shard_root: H256, owners: Vec<Address>, users: Vec<Address>) -> Self {
         Self {
             root: shard_root,
-            owner,
+            owners,
+            users,
         }
     }
 
@@ -44,12 +46,32 @@ impl Shard {
         self.root = root;
     }
 
-    pub fn owner(&self) -> &Address {
-        &self.owner
+    pub fn owners(&self) -> &[Address] {
+        &self.owners
     }
 
-    pub fn set_owner(&mut self, owner: Address) {
-        self.owner = owner;
+    pub fn set_owners(&mut self, owners: Vec<Address>) {
+        self.owners = owners;
+    }
+
+    /// The addresses that are allowed to mint assets in the shard besides the owners.
+    /// Anyone can mint if it is empty.
+    pub fn users(&self) -> &[Address] {
+        &self.users
+    }
+
+    pub fn set_users(&mut self, users: Vec<Address>) {
+        self.users = users;
+    }
+
+    /// Decodes the value of a leaf in the state trie if it is a shard.
+    pub fn from_leaf_value(value: &[u8]) -> Option<Self> {
+        let rlp = UntrustedRlp::new(value);
+        // The prefix is checked first, so that the other items aren't logged as invalid shards.
+        match rlp.val_at::<u8>(0) {
+            Ok(PREFIX) => rlp.as_val().ok(),
+            _ => None,
+        }
     }
 }
 
@@ -65,13 +87,13 @@ const PREFIX: u8 = super::SHARD_PREFIX;
 
 impl Encodable for Shard {
     fn rlp_append(&self, s: &mut RlpStream) {
-        s.begin_list(3).append(&PREFIX).append(&self.root).append(&self.owner);
+        s.begin_list(4).append(&PREFIX).append(&self.root).append_list(&self.owners).append_list(&self.users);
     }
 }
 
 impl Decodable for Shard {
     fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
-        if rlp.item_count()? != 3 {
+        if rlp.item_count()? != 4 {
             return Err(DecoderError::RlpInvalidLength)
         }
         let prefix = rlp.val_at::<u8>(0)?;
@@ -81,7 +103,8 @@ impl Decodable for Shard {
         }
         Ok(Self {
             root: rlp.val_at(1)?,
-            owner: rlp.val_at(2)?,
+            owners: rlp.list_at(2)?,
+            users: rlp.list_at(3)?,
         })
     }
 }
@@ -110,6 +133,24 @@ mod tests {
         assert_eq!(address2[0], PREFIX);
     }
 
+    #[test]
+    fn rlp_encode_and_decode() {
+        let shard = Shard::new(H256::random(), vec![Address::random(), Address::random()], vec![Address::random()]);
+        let decoded = ::rlp::decode::<Shard>(&shard.rlp_bytes());
+        assert_eq!(shard.root(), decoded.root());
+        assert_eq!(shard.owners(), decoded.owners());
+        assert_eq!(shard.users(), decoded.users());
+    }
+
+    #[test]
+    fn decode_leaf_value_of_shard() {
+        let shard = Shard::new(H256::random(), vec![Address::random()], vec![]);
+        let decoded = Shard::from_leaf_value(&shard.rlp_bytes()).unwrap();
+        assert_eq!(shard.root(), decoded.root());
+        assert!(Shard::from_leaf_value(&::rlp::encode(&H256::random())).is_none());
+        assert!(Shard::from_leaf_value(&::rlp::encode_list::<u8, u8>(&[b'A', 1, 2, 3])).is_none());
+    }
+
     #[test]
     fn parse_fail_return_none() {
         let hash = {
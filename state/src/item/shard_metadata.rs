@@ -48,6 +48,16 @@ impl ShardMetadata {
     pub fn nonce(&self) -> &u64 {
         &self.nonce
     }
+
+    pub fn increase_number_of_worlds(&mut self) -> WorldId {
+        let r = self.number_of_worlds;
+        self.number_of_worlds += 1;
+        r
+    }
+
+    pub fn inc_nonce(&mut self) {
+        self.nonce += 1;
+    }
 }
 
 impl CacheableItem for ShardMetadata {
@@ -22,6 +22,7 @@ use rlp::{Decodable, DecoderError, Encodable, RlpStream, UntrustedRlp};
 use super::cache::CacheableItem;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct World {
     world_owners: Vec<Address>,
     nonce: u64,
@@ -49,6 +50,14 @@ impl World {
     pub fn nonce(&self) -> &u64 {
         &self.nonce
     }
+
+    pub fn set_owners(&mut self, owners: Vec<Address>) {
+        self.world_owners = owners;
+    }
+
+    pub fn inc_nonce(&mut self) {
+        self.nonce += 1;
+    }
 }
 
 impl CacheableItem for World {
@@ -51,15 +51,16 @@ mod traits;
 #[cfg(test)]
 pub mod tests;
 
-pub use action_handler::{ActionHandler, HitHandler};
+pub use action_handler::{ActionHandler, HitHandler, RegistryHandler};
 pub use backend::{Backend, ShardBackend, TopBackend};
 pub use checkpoint::{CheckpointId, StateWithCheckpoint};
 pub use db::StateDB;
 pub use error::Error as StateError;
 pub use impls::{ShardLevelState, TopLevelState};
 pub use item::account::Account;
+pub use item::action_data::ActionDataAddress;
 pub use item::asset::{Asset, AssetAddress};
-pub use item::asset_scheme::{AssetScheme, AssetSchemeAddress};
+pub use item::asset_scheme::{AssetScheme, AssetSchemeAddress, PooledAsset};
 pub use item::cache::{Cache, CacheableItem};
 pub use item::metadata::{Metadata, MetadataAddress};
 pub use item::regular_account::{RegularAccount, RegularAccountAddress};
@@ -27,3 +27,247 @@ pub mod helpers {
         TopLevelState::new(journal_db, Default::default())
     }
 }
+
+mod cross_shard {
+    use ccrypto::Blake;
+    use ckey::{Address, Generator, KeyPair, Random};
+    use ctypes::invoice::Invoice;
+    use ctypes::parcel::{Action, ChangeShard, Error as ParcelError, Outcome as ParcelOutcome, Parcel};
+    use ctypes::transaction::{
+        AssetMintOutput, AssetOutPoint, AssetTransferInput, AssetTransferOutput, Error as TransactionError,
+        Outcome as TransactionOutcome, Transaction,
+    };
+    use ctypes::ShardId;
+    use primitives::{H256, U256};
+    use unexpected::Mismatch;
+
+    use super::super::impls::TopLevelState;
+    use super::super::traits::{StateWithCache, TopState, TopStateInfo};
+    use super::super::{Asset, AssetAddress, AssetSchemeAddress, StateError, StateResult};
+    use super::helpers::get_temp_state;
+
+    const NETWORK_ID: u64 = 200;
+
+    fn lock_script_hash() -> H256 {
+        // The hash of the lock script `[0x30, 0x01]`
+        H256::from("07feab4c39250abf60b77d7589a5b61fdf409bd837e936376381d19db1e1f050")
+    }
+
+    /// Returns a state that has two shards, 0 and 1, owned by `sender`.
+    fn state_with_two_shards(sender: &Address) -> TopLevelState {
+        let mut state = get_temp_state();
+        assert_eq!(Ok(()), state.add_balance(sender, &U256::from(1000)));
+        assert_eq!(Ok(()), state.create_shard(&U256::from(1), sender));
+        assert_eq!(Ok(()), state.create_shard(&U256::from(1), sender));
+        assert_eq!(Ok(()), state.commit());
+        state
+    }
+
+    fn apply(
+        state: &mut TopLevelState,
+        sender: &KeyPair,
+        transactions: Vec<Transaction>,
+        shard_ids: &[ShardId],
+    ) -> StateResult<ParcelOutcome> {
+        let parcel = Parcel {
+            fee: 10.into(),
+            nonce: state.nonce(&sender.address()).unwrap(),
+            network_id: NETWORK_ID,
+            expiration: None,
+            action: Action::ChangeShardState {
+                transactions,
+                changes: shard_ids
+                    .iter()
+                    .map(|shard_id| ChangeShard {
+                        shard_id: *shard_id,
+                        pre_root: H256::zero(),
+                        post_root: H256::zero(),
+                    })
+                    .collect(),
+                signatures: vec![],
+            },
+        };
+        state.apply(&parcel, &sender.address(), sender.public(), &sender.address(), 0, 0, ::std::u64::MAX)
+    }
+
+    /// Mints 30 assets in shard 0 and returns the hash of the mint and the asset type.
+    fn mint_in_shard_0(state: &mut TopLevelState, sender: &KeyPair) -> (H256, H256) {
+        let create_world = Transaction::CreateWorld {
+            network_id: NETWORK_ID,
+            shard_id: 0,
+            nonce: 0,
+            owners: vec![sender.address()],
+        };
+        let mint = Transaction::AssetMint {
+            network_id: NETWORK_ID,
+            shard_id: 0,
+            world_id: 0,
+            metadata: "metadata".to_string(),
+            output: AssetMintOutput {
+                lock_script_hash: lock_script_hash(),
+                parameters: vec![],
+                amount: Some(30),
+            },
+            registrar: None,
+            nonce: 0,
+        };
+        let mint_hash = mint.hash();
+        assert_eq!(
+            Ok(ParcelOutcome::Transactions(vec![success(), success()])),
+            apply(state, sender, vec![create_world, mint], &[0])
+        );
+        (mint_hash, AssetSchemeAddress::new(mint_hash, 0, 0).into())
+    }
+
+    fn transfer(prev_out: AssetOutPoint, lock_script: Vec<u8>, shard_id: ShardId) -> Transaction {
+        let asset_type = prev_out.asset_type;
+        let amount = prev_out.amount;
+        Transaction::AssetTransfer {
+            network_id: NETWORK_ID,
+            burns: vec![],
+            inputs: vec![AssetTransferInput {
+                prev_out,
+                lock_script,
+                unlock_script: vec![],
+            }],
+            outputs: vec![AssetTransferOutput {
+                lock_script_hash: lock_script_hash(),
+                parameters: vec![],
+                asset_type,
+                shard_id,
+                amount,
+            }],
+            nonce: 0,
+        }
+    }
+
+    fn success() -> TransactionOutcome {
+        TransactionOutcome {
+            invoice: Invoice::Success,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn transfer_asset_to_another_shard() {
+        let sender = Random.generate().unwrap();
+        let mut state = state_with_two_shards(&sender.address());
+        let (mint_hash, asset_type) = mint_in_shard_0(&mut state, &sender);
+
+        let to_shard_1 = transfer(
+            AssetOutPoint {
+                transaction_hash: mint_hash,
+                index: 0,
+                asset_type,
+                shard_id: 0,
+                amount: 30,
+            },
+            vec![0x30, 0x01],
+            1,
+        );
+        assert_eq!(vec![0, 1], to_shard_1.related_shards());
+        assert_eq!(
+            Ok(ParcelOutcome::Transactions(vec![success()])),
+            apply(&mut state, &sender, vec![to_shard_1.clone()], &[0, 1])
+        );
+
+        assert_eq!(Ok(None), state.asset(0, &AssetAddress::new(mint_hash, 0, 0)));
+        assert_eq!(Ok(None), state.asset(0, &AssetAddress::new(to_shard_1.hash(), 0, 0)));
+        assert_eq!(
+            Ok(Some(Asset::new(asset_type, lock_script_hash(), vec![], 30, 0))),
+            state.asset(1, &AssetAddress::new(to_shard_1.hash(), 0, 1))
+        );
+
+        // The moved asset is spent in the shard that it is in.
+        let in_shard_1 = transfer(
+            AssetOutPoint {
+                transaction_hash: to_shard_1.hash(),
+                index: 0,
+                asset_type,
+                shard_id: 1,
+                amount: 30,
+            },
+            vec![0x30, 0x01],
+            1,
+        );
+        assert_eq!(
+            Ok(ParcelOutcome::Transactions(vec![success()])),
+            apply(&mut state, &sender, vec![in_shard_1.clone()], &[1])
+        );
+        assert_eq!(Ok(None), state.asset(1, &AssetAddress::new(to_shard_1.hash(), 0, 1)));
+        assert_eq!(
+            Ok(Some(Asset::new(asset_type, lock_script_hash(), vec![], 30, 0))),
+            state.asset(1, &AssetAddress::new(in_shard_1.hash(), 0, 1))
+        );
+    }
+
+    #[test]
+    fn failed_cross_shard_transfer_changes_no_shard() {
+        let sender = Random.generate().unwrap();
+        let mut state = state_with_two_shards(&sender.address());
+        let (mint_hash, asset_type) = mint_in_shard_0(&mut state, &sender);
+        let shard_roots = (state.shard_root(0), state.shard_root(1));
+
+        let prev_out = AssetOutPoint {
+            transaction_hash: mint_hash,
+            index: 0,
+            asset_type,
+            shard_id: 0,
+            amount: 30,
+        };
+        // The lock script is wrong, so the input cannot be spent in shard 0.
+        let invalid = transfer(prev_out.clone(), vec![0x30], 1);
+        let failed = TransactionOutcome {
+            invoice: Invoice::Failed,
+            error: Some(TransactionError::ScriptHashMismatch(Mismatch {
+                expected: lock_script_hash(),
+                found: Blake::blake(&[0x30u8]),
+            })),
+        };
+        assert_eq!(
+            Ok(ParcelOutcome::Transactions(vec![failed.clone()])),
+            apply(&mut state, &sender, vec![invalid.clone()], &[1, 0])
+        );
+        assert_eq!(shard_roots, (state.shard_root(0), state.shard_root(1)));
+        assert_eq!(Ok(None), state.asset(1, &AssetAddress::new(invalid.hash(), 0, 1)));
+
+        // The failed transfer leaves the asset unspent, so a following transaction in the same parcel can spend it.
+        let invalid_again = transfer(prev_out.clone(), vec![0x30], 0);
+        let valid = transfer(prev_out, vec![0x30, 0x01], 1);
+        assert_eq!(
+            Ok(ParcelOutcome::Transactions(vec![failed, success()])),
+            apply(&mut state, &sender, vec![invalid_again, valid.clone()], &[0, 1])
+        );
+        assert_eq!(
+            Ok(Some(Asset::new(asset_type, lock_script_hash(), vec![], 30, 0))),
+            state.asset(1, &AssetAddress::new(valid.hash(), 0, 1))
+        );
+    }
+
+    #[test]
+    fn every_related_shard_must_be_changed() {
+        let sender = Random.generate().unwrap();
+        let mut state = state_with_two_shards(&sender.address());
+        let (mint_hash, asset_type) = mint_in_shard_0(&mut state, &sender);
+
+        let to_shard_1 = transfer(
+            AssetOutPoint {
+                transaction_hash: mint_hash,
+                index: 0,
+                asset_type,
+                shard_id: 0,
+                amount: 30,
+            },
+            vec![0x30, 0x01],
+            1,
+        );
+        assert_eq!(
+            Err(StateError::Parcel(ParcelError::ShardChangeMissing(1))),
+            apply(&mut state, &sender, vec![to_shard_1.clone()], &[0])
+        );
+        assert_eq!(
+            Err(StateError::Parcel(ParcelError::DuplicatedShardChange(0))),
+            apply(&mut state, &sender, vec![to_shard_1], &[0, 1, 0])
+        );
+    }
+}
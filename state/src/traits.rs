@@ -17,11 +17,13 @@
 use ckey::{Address, Public};
 use cmerkle::Result as TrieResult;
 use ctypes::transaction::{Outcome as TransactionOutcome, Transaction};
-use ctypes::ShardId;
+use ctypes::{BlockNumber, ShardId, WorldId};
 use primitives::{Bytes, H256, U256};
 
 use super::backend::{ShardBackend, TopBackend};
-use super::{Asset, AssetAddress, AssetScheme, AssetSchemeAddress, StateResult};
+use super::{
+    ActionDataAddress, Asset, AssetAddress, AssetScheme, AssetSchemeAddress, ShardMetadata, StateResult, World,
+};
 
 
 pub trait TopStateInfo {
@@ -37,18 +39,29 @@ pub trait TopStateInfo {
     fn number_of_shards(&self) -> TrieResult<ShardId>;
 
     fn shard_root(&self, shard_id: ShardId) -> TrieResult<Option<H256>>;
-    fn shard_owner(&self, shard_id: ShardId) -> TrieResult<Option<Address>>;
+    fn shard_owners(&self, shard_id: ShardId) -> TrieResult<Option<Vec<Address>>>;
+    /// Get the addresses that are allowed to mint assets in the shard. Everyone can mint if it is empty.
+    fn shard_users(&self, shard_id: ShardId) -> TrieResult<Option<Vec<Address>>>;
+    /// Get the metadata of the shard. Returns `None` if the shard doesn't exist.
+    fn shard_metadata(&self, shard_id: ShardId) -> TrieResult<Option<ShardMetadata>>;
+
+    /// Get the world in the shard.
+    fn world(&self, shard_id: ShardId, world_id: WorldId) -> TrieResult<Option<World>>;
 
     /// Get the asset scheme.
     fn asset_scheme(&self, shard_id: ShardId, a: &AssetSchemeAddress) -> TrieResult<Option<AssetScheme>>;
     /// Get the asset.
     fn asset(&self, shard_id: ShardId, a: &AssetAddress) -> TrieResult<Option<Asset>>;
 
-    fn action_data(&self, key: &H256) -> TrieResult<Bytes>;
+    /// Get the data of a custom action handler.
+    fn action_data(&self, a: &ActionDataAddress) -> TrieResult<Option<Bytes>>;
 }
 
 pub trait ShardStateInfo {
     fn root(&self) -> &H256;
+    fn metadata(&self) -> TrieResult<Option<ShardMetadata>>;
+    /// Get the world.
+    fn world(&self, world_id: WorldId) -> TrieResult<Option<World>>;
     /// Get the asset scheme.
     fn asset_scheme(&self, a: &AssetSchemeAddress) -> TrieResult<Option<AssetScheme>>;
     /// Get the asset.
@@ -58,7 +71,15 @@ pub trait ShardStateInfo {
 pub trait ShardState<B>
 where
     B: ShardBackend, {
-    fn apply(&mut self, transaction: &Transaction) -> StateResult<TransactionOutcome>;
+    fn apply(
+        &mut self,
+        transaction: &Transaction,
+        sender: &Address,
+        shard_owners: &[Address],
+        block_number: BlockNumber,
+        block_timestamp: u64,
+        max_script_cost: u64,
+    ) -> StateResult<TransactionOutcome>;
 }
 
 pub trait TopState<B>
@@ -92,9 +113,11 @@ where
     fn create_shard(&mut self, shard_creation_cost: &U256, fee_payer: &Address) -> StateResult<()>;
 
     fn set_shard_root(&mut self, shard_id: ShardId, old_root: &H256, new_root: &H256) -> StateResult<()>;
-    fn set_shard_owner(&mut self, shard_id: ShardId, old_owner: &Address, new_owner: Address) -> StateResult<()>;
+    fn set_shard_owners(&mut self, shard_id: ShardId, new_owners: Vec<Address>) -> StateResult<()>;
+    fn set_shard_users(&mut self, shard_id: ShardId, new_users: Vec<Address>) -> StateResult<()>;
 
-    fn update_action_data(&mut self, key: &H256, data: Bytes) -> StateResult<()>;
+    fn update_action_data(&mut self, a: &ActionDataAddress, data: Bytes) -> StateResult<()>;
+    fn remove_action_data(&mut self, a: &ActionDataAddress);
 }
 
 pub trait StateWithCache {
@@ -53,6 +53,10 @@ impl HeaderDownloader {
         self.total_score
     }
 
+    pub fn best_hash(&self) -> H256 {
+        self.best_hash
+    }
+
     pub fn new(client: Arc<BlockChainClient>, total_score: U256, best_hash: H256) -> Self {
         let best_header_hash = client.best_block_header().hash();
         let best_score = client.block_total_score(BlockId::Latest).expect("Best block always exist");
@@ -16,6 +16,8 @@
 
 mod body;
 mod header;
+mod state;
 
 pub use self::body::BodyDownloader;
 pub use self::header::HeaderDownloader;
+pub use self::state::StateDownloader;
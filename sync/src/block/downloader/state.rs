@@ -0,0 +1,311 @@
+// Copyright 2018 Kodebox, Inc.
+// This file is part of CodeChain.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cmp::min;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use cnetwork::NodeId;
+use kvdb::KeyValueDB;
+use primitives::H256;
+
+use super::super::super::snapshot::{Restorer, SnapshotError};
+use super::super::message::RequestMessage;
+
+const MAX_WAIT: u64 = 15;
+/// A peer that failed this many times in a row is considered unable to serve the snapshot.
+const MAX_FAILURES: u32 = 3;
+/// The peer that failed isn't asked for this long, which doubles with every failure in a row.
+const BACKOFF: u64 = 5;
+const MAX_BACKOFF: u64 = 300;
+
+/// Downloads the state of a block from the snapshots of the peers, and then the body of the block.
+/// Every chunk goes to a different peer, and a chunk that a peer doesn't have is requested again.
+pub struct StateDownloader {
+    block_hash: H256,
+    restorer: Restorer,
+
+    head_requested: bool,
+    downloading: HashSet<H256>,
+    /// The pending request to each peer, and when it was sent.
+    requests: HashMap<NodeId, (RequestMessage, Instant)>,
+    /// The number of the failures in a row of the peers that didn't have the snapshot, sent an invalid one
+    /// or didn't respond in time, and when they can be asked again.
+    failures: HashMap<NodeId, (u32, Instant)>,
+}
+
+impl StateDownloader {
+    pub fn new(db: Arc<KeyValueDB>, block_hash: H256, state_root: H256) -> Self {
+        Self {
+            block_hash,
+            restorer: Restorer::new(db, state_root),
+
+            head_requested: false,
+            downloading: HashSet::new(),
+            requests: HashMap::new(),
+            failures: HashMap::new(),
+        }
+    }
+
+    pub fn block_hash(&self) -> H256 {
+        self.block_hash
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.restorer.is_complete()
+    }
+
+    /// Returns true if every peer has failed too many times in a row, e.g. because it removed the snapshot.
+    pub fn is_unavailable(&self, peers: &[NodeId]) -> bool {
+        !peers.is_empty()
+            && peers.iter().all(|peer| self.failures.get(peer).map_or(false, |(count, _)| *count >= MAX_FAILURES))
+    }
+
+    /// Returns the next request to the peer.
+    /// A peer has one pending request at a time, and a peer that failed isn't asked until its backoff ends.
+    pub fn create_request(&mut self, peer: &NodeId) -> Option<RequestMessage> {
+        if self.requests.contains_key(peer) || self.is_backing_off(peer) {
+            return None
+        }
+
+        let request = if self.is_complete() {
+            RequestMessage::Bodies(vec![self.block_hash])
+        } else if !self.restorer.has_head() {
+            if self.head_requested {
+                return None
+            }
+            self.head_requested = true;
+            RequestMessage::StateHead(self.block_hash)
+        } else {
+            let tree_root = *self.restorer.chunks().iter().find(|root| !self.downloading.contains(root))?;
+            self.downloading.insert(tree_root);
+            RequestMessage::StateChunk {
+                block_hash: self.block_hash,
+                tree_root,
+            }
+        };
+        self.requests.insert(*peer, (request.clone(), Instant::now()));
+        Some(request)
+    }
+
+    /// Marks the request to the peer as not sent, so that it can be sent to another peer.
+    pub fn cancel(&mut self, peer: &NodeId) -> Option<RequestMessage> {
+        let (request, _) = self.requests.remove(peer)?;
+        match &request {
+            RequestMessage::StateHead(_) => self.head_requested = false,
+            RequestMessage::StateChunk {
+                tree_root,
+                ..
+            } => {
+                self.downloading.remove(tree_root);
+            }
+            _ => {}
+        }
+        Some(request)
+    }
+
+    /// Cancels the request to the peer, and doesn't ask the peer until its backoff ends.
+    pub fn back_off(&mut self, peer: &NodeId) {
+        self.cancel(peer);
+        let count = self.failures.get(peer).map_or(0, |(count, _)| *count) + 1;
+        let backoff = min(BACKOFF << min(count - 1, 16), MAX_BACKOFF);
+        self.failures.insert(*peer, (count, Instant::now() + Duration::from_secs(backoff)));
+    }
+
+    fn is_backing_off(&self, peer: &NodeId) -> bool {
+        self.failures.get(peer).map_or(false, |(_, until)| Instant::now() < *until)
+    }
+
+    /// Backs off the peers that haven't responded in time, and returns their requests.
+    pub fn back_off_expired(&mut self) -> Vec<(NodeId, RequestMessage)> {
+        let expired: Vec<_> = self
+            .requests
+            .iter()
+            .filter(|(_, (_, time))| (Instant::now() - *time).as_secs() > MAX_WAIT)
+            .map(|(peer, _)| *peer)
+            .collect();
+        expired
+            .into_iter()
+            .filter_map(|peer| {
+                let request = self.requests.get(&peer)?.0.clone();
+                self.back_off(&peer);
+                Some((peer, request))
+            })
+            .collect()
+    }
+
+    /// Imports the response to the head or chunk request of the peer.
+    /// The peer is backed off if the response is invalid.
+    pub fn import(&mut self, peer: &NodeId, compressed: &[u8]) -> Result<(), SnapshotError> {
+        let result = match self.requests.get(peer) {
+            Some((RequestMessage::StateHead(_), _)) => self.restorer.import_head(compressed),
+            Some((
+                RequestMessage::StateChunk {
+                    tree_root,
+                    ..
+                },
+                _,
+            )) => self.restorer.import_chunk(*tree_root, compressed),
+            _ => return Ok(()),
+        };
+        match result {
+            Ok(()) => {
+                self.cancel(peer);
+                self.failures.remove(peer);
+            }
+            Err(_) => self.back_off(peer),
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use kvdb_memorydb;
+    use primitives::Bytes;
+
+    use super::super::super::super::snapshot::Snapshot;
+    use super::super::super::super::tests::helpers::{assert_same_state, node_with_state, write_snapshot_of};
+    use super::*;
+
+    fn peer(port: u16) -> NodeId {
+        NodeId::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), port)
+    }
+
+    fn serve(snapshot: &Snapshot, request: &RequestMessage) -> Bytes {
+        match request {
+            RequestMessage::StateHead(_) => snapshot.read_head().unwrap(),
+            RequestMessage::StateChunk {
+                tree_root,
+                ..
+            } => snapshot.read_chunk(tree_root).unwrap(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn restore_state_from_snapshot_of_peer() {
+        let (server, root) = node_with_state(1000);
+        let snapshot = write_snapshot_of(&server, &root);
+
+        let client: Arc<KeyValueDB> = Arc::new(kvdb_memorydb::create(1));
+        let mut downloader = StateDownloader::new(Arc::clone(&client), snapshot.block_hash(), root);
+        let server_id = peer(3485);
+        while !downloader.is_complete() {
+            let request = downloader.create_request(&server_id).unwrap();
+            downloader.import(&server_id, &serve(&snapshot, &request)).unwrap();
+        }
+        assert_same_state(&server, &client, &root);
+        assert_eq!(Some(RequestMessage::Bodies(vec![snapshot.block_hash()])), downloader.create_request(&server_id));
+    }
+
+    #[test]
+    fn back_off_peer_that_sent_invalid_head() {
+        let (server, root) = node_with_state(100);
+        let snapshot = write_snapshot_of(&server, &root);
+
+        let client: Arc<KeyValueDB> = Arc::new(kvdb_memorydb::create(1));
+        let mut downloader = StateDownloader::new(client, H256::random(), H256::random());
+        let (bad_peer, good_peer) = (peer(3485), peer(3486));
+        let request = downloader.create_request(&bad_peer).unwrap();
+        assert_eq!(None, downloader.create_request(&good_peer));
+        assert!(downloader.import(&bad_peer, &serve(&snapshot, &request)).is_err());
+        assert_eq!(None, downloader.create_request(&bad_peer));
+        assert_eq!(Some(request), downloader.create_request(&good_peer));
+    }
+
+    #[test]
+    fn request_chunks_to_different_peers() {
+        let (server, root) = node_with_state(1000);
+        let snapshot = write_snapshot_of(&server, &root);
+
+        let client: Arc<KeyValueDB> = Arc::new(kvdb_memorydb::create(1));
+        let mut downloader = StateDownloader::new(client, snapshot.block_hash(), root);
+        let peers = [peer(3485), peer(3486), peer(3487)];
+        let head_request = downloader.create_request(&peers[0]).unwrap();
+        downloader.import(&peers[0], &serve(&snapshot, &head_request)).unwrap();
+
+        let first = downloader.create_request(&peers[0]).unwrap();
+        let second = downloader.create_request(&peers[1]).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(None, downloader.create_request(&peers[0]));
+        assert_eq!(Some(first.clone()), downloader.cancel(&peers[0]));
+        assert_eq!(Some(first), downloader.create_request(&peers[2]));
+    }
+
+    #[test]
+    fn backed_off_peer_is_asked_again_after_backoff() {
+        let (server, root) = node_with_state(100);
+        let snapshot = write_snapshot_of(&server, &root);
+
+        let client: Arc<KeyValueDB> = Arc::new(kvdb_memorydb::create(1));
+        let mut downloader = StateDownloader::new(client, snapshot.block_hash(), root);
+        let (empty_peer, other_peer) = (peer(3485), peer(3486));
+        let request = downloader.create_request(&empty_peer).unwrap();
+        downloader.back_off(&empty_peer);
+        assert_eq!(None, downloader.create_request(&empty_peer));
+        assert_eq!(Some(request.clone()), downloader.create_request(&other_peer));
+        assert!(downloader.back_off_expired().is_empty());
+
+        downloader.cancel(&other_peer);
+        downloader.failures.get_mut(&empty_peer).unwrap().1 = Instant::now();
+        assert_eq!(Some(request), downloader.create_request(&empty_peer));
+    }
+
+    #[test]
+    fn backoff_grows_with_failures_in_a_row() {
+        let client: Arc<KeyValueDB> = Arc::new(kvdb_memorydb::create(1));
+        let mut downloader = StateDownloader::new(client, H256::random(), H256::random());
+        let slow_peer = peer(3485);
+        downloader.back_off(&slow_peer);
+        let first = downloader.failures[&slow_peer].1;
+        downloader.back_off(&slow_peer);
+        let (count, second) = downloader.failures[&slow_peer];
+        assert_eq!(2, count);
+        assert!(second - first >= Duration::from_secs(BACKOFF));
+    }
+
+    #[test]
+    fn snapshot_is_unavailable_when_every_peer_fails_in_a_row() {
+        let (server, root) = node_with_state(100);
+        let snapshot = write_snapshot_of(&server, &root);
+
+        let client: Arc<KeyValueDB> = Arc::new(kvdb_memorydb::create(1));
+        let mut downloader = StateDownloader::new(client, snapshot.block_hash(), root);
+        let peers = [peer(3485), peer(3486)];
+        assert!(!downloader.is_unavailable(&[]));
+        for _ in 0..MAX_FAILURES {
+            downloader.back_off(&peers[0]);
+        }
+        assert!(!downloader.is_unavailable(&peers));
+        for _ in 0..MAX_FAILURES - 1 {
+            downloader.back_off(&peers[1]);
+        }
+        assert!(!downloader.is_unavailable(&peers));
+
+        downloader.failures.get_mut(&peers[1]).unwrap().1 = Instant::now();
+        let request = downloader.create_request(&peers[1]).unwrap();
+        downloader.import(&peers[1], &serve(&snapshot, &request)).unwrap();
+        downloader.back_off(&peers[1]);
+        assert!(!downloader.is_unavailable(&peers));
+        downloader.back_off(&peers[1]);
+        downloader.back_off(&peers[1]);
+        assert!(downloader.is_unavailable(&peers));
+    }
+}
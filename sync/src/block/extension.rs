@@ -21,23 +21,29 @@ use std::sync::Arc;
 
 use ccore::encoded::Header as EncodedHeader;
 use ccore::{
-    Block, BlockChainClient, BlockId, BlockImportError, BlockInfo, ChainInfo, ChainNotify, Client, Header, ImportBlock,
-    ImportError, Seal, UnverifiedParcel,
+    Block, BlockChainClient, BlockId, BlockImportError, BlockInfo, ChainInfo, ChainNotify, Client, EngineInfo, Header,
+    ImportBlock, ImportError, Seal, UnverifiedParcel,
 };
 use cnetwork::{Api, NetworkExtension, NodeId, TimerToken};
 use ctypes::parcel::Action;
 use ctypes::BlockNumber;
-use primitives::{H256, U256};
+use primitives::{Bytes, H256, U256};
 use rlp::{Encodable, UntrustedRlp};
 use time::Duration;
 
-use super::downloader::{BodyDownloader, HeaderDownloader};
+use super::super::snapshot::Snapshot;
+use super::downloader::{BodyDownloader, HeaderDownloader, StateDownloader};
 use super::message::{Message, RequestMessage, ResponseMessage};
 
 const SYNC_TIMER_TOKEN: usize = 0;
 const SYNC_TIMER_INTERVAL: i64 = 1000;
 
-const SNAPSHOT_PERIOD: u64 = (1 << 14);
+enum SnapshotSync {
+    /// Waits for the headers of the best peer to choose the snapshot.
+    WaitingForHeaders,
+    /// Downloads the state of the snapshot, and then the body of its block.
+    Restoring(StateDownloader),
+}
 
 pub struct Extension {
     requests: RwLock<HashMap<NodeId, Vec<(u64, RequestMessage)>>>,
@@ -46,10 +52,20 @@ pub struct Extension {
     client: Arc<Client>,
     api: Mutex<Option<Arc<Api>>>,
     last_request: AtomicUsize,
+    /// The directory of the snapshots served to the peers. `None` if the node doesn't make snapshots.
+    snapshot_dir: Option<String>,
+    /// `None` if the node downloads and executes every block from the genesis.
+    snapshot_sync: Mutex<Option<SnapshotSync>>,
 }
 
 impl Extension {
-    pub fn new(client: Arc<Client>) -> Arc<Self> {
+    pub fn new(client: Arc<Client>, snapshot_dir: Option<String>, snapshot_sync: bool) -> Arc<Self> {
+        // Only a fresh node can skip the blocks before the snapshot.
+        let snapshot_sync = if snapshot_sync && client.chain_info().best_block_number == 0 {
+            Some(SnapshotSync::WaitingForHeaders)
+        } else {
+            None
+        };
         Arc::new(Self {
             requests: RwLock::new(HashMap::new()),
             header_downloaders: RwLock::new(HashMap::new()),
@@ -57,6 +73,8 @@ impl Extension {
             client,
             api: Mutex::new(None),
             last_request: AtomicUsize::new(0),
+            snapshot_dir,
+            snapshot_sync: Mutex::new(snapshot_sync),
         })
     }
 
@@ -72,6 +90,13 @@ impl Extension {
         }
     }
 
+    /// Dismisses the request that isn't answered in time, so that its late response is ignored.
+    fn dismiss_expired_request(&self, token: &NodeId, request: &RequestMessage) {
+        if let Some(requests) = self.requests.write().get_mut(token) {
+            requests.retain(|(_, r)| r != request);
+        }
+    }
+
     fn send_request(&self, token: &NodeId, request: RequestMessage) {
         if let Some(requests) = self.requests.write().get_mut(token) {
             let id = self.last_request.fetch_add(1, Ordering::Relaxed) as u64;
@@ -119,6 +144,9 @@ impl NetworkExtension for Extension {
 
     fn on_node_removed(&self, token: &NodeId) {
         self.header_downloaders.write().remove(token);
+        if let Some(SnapshotSync::Restoring(downloader)) = &mut *self.snapshot_sync.lock() {
+            downloader.cancel(token);
+        }
         cinfo!(SYNC, "Peer removed #{}", token);
     }
 
@@ -145,6 +173,19 @@ impl NetworkExtension for Extension {
 
         let total_score = self.client.chain_info().total_score;
         let peer_ids: Vec<_> = self.header_downloaders.read().keys().cloned().collect();
+
+        self.update_snapshot_sync();
+        self.expire_snapshot_requests();
+        let better_peers: Vec<_> = self
+            .header_downloaders
+            .read()
+            .iter()
+            .filter(|(_, peer)| peer.total_score() > total_score)
+            .map(|(id, _)| *id)
+            .collect();
+        self.retarget_snapshot_sync(&better_peers);
+        let is_restoring_snapshot = self.is_restoring_snapshot();
+
         for id in peer_ids {
             if let Some(peer) = self.header_downloaders.write().get_mut(&id) {
                 if let Some(request) = peer.create_request() {
@@ -158,6 +199,12 @@ impl NetworkExtension for Extension {
             } else {
                 U256::zero()
             };
+            if is_restoring_snapshot {
+                if peer_score > total_score {
+                    self.request_snapshot(&id);
+                }
+                continue
+            }
             let have_body_request = {
                 if let Some(request_list) = self.requests.read().get(&id) {
                     request_list.iter().any(|r| match r {
@@ -220,6 +267,10 @@ impl ChainNotify for Extension {
                 peer.mark_as_imported(imported.clone());
             }
         }
+        // The bodies are downloaded after the state of the snapshot is restored.
+        if self.is_restoring_snapshot() {
+            return
+        }
         let mut enacted_headers: Vec<_> = enacted
             .into_iter()
             .map(|hash| self.client.block_header(BlockId::Hash(hash)).expect("Enacted header must exist"))
@@ -298,21 +349,29 @@ impl Extension {
                 ..
             } => true,
             RequestMessage::Bodies(hashes) => hashes.len() != 0,
-            RequestMessage::StateHead(hash) => match self.client.block_number(BlockId::Hash(*hash)) {
-                Some(number) if number % SNAPSHOT_PERIOD == 0 => true,
-                _ => false,
-            },
+            RequestMessage::StateHead(hash) => self.is_snapshot_checkpoint(hash),
             RequestMessage::StateChunk {
                 block_hash,
-                tree_root: _tree_root,
-            } => {
-                let _is_checkpoint = match self.client.block_number(BlockId::Hash(*block_hash)) {
-                    Some(number) if number % SNAPSHOT_PERIOD == 0 => true,
-                    _ => false,
-                };
-                // FIXME:  check tree_root
-                unimplemented!()
-            }
+                ..
+            } => self.is_snapshot_checkpoint(block_hash),
+        }
+    }
+
+    fn is_snapshot_checkpoint(&self, block_hash: &H256) -> bool {
+        let period = self.client.common_params().snapshot_period;
+        match self.client.block_number(BlockId::Hash(*block_hash)) {
+            Some(number) if period != 0 => number % period == 0,
+            _ => false,
+        }
+    }
+
+    /// Returns the snapshot of the block if this node has written it.
+    fn snapshot(&self, block_hash: &H256) -> Option<Snapshot> {
+        let snapshot = Snapshot::new(self.snapshot_dir.as_ref()?, block_hash);
+        if snapshot.exists() {
+            Some(snapshot)
+        } else {
+            None
         }
     }
 
@@ -337,12 +396,41 @@ impl Extension {
         ResponseMessage::Bodies(bodies)
     }
 
-    fn create_state_head_response(&self, _hash: H256) -> ResponseMessage {
-        unimplemented!()
+    fn create_state_head_response(&self, hash: H256) -> ResponseMessage {
+        let head = match self.snapshot(&hash) {
+            Some(snapshot) => snapshot.read_head(),
+            None => return ResponseMessage::StateHead(Vec::new()),
+        };
+        match head {
+            Ok(head) => ResponseMessage::StateHead(head),
+            Err(err) => {
+                cwarn!(SYNC, "Cannot read the snapshot head of {}: {}", hash, err);
+                ResponseMessage::StateHead(Vec::new())
+            }
+        }
     }
 
-    fn create_state_chunk_response(&self, _hash: H256, _tree_root: H256) -> ResponseMessage {
-        unimplemented!()
+    fn create_state_chunk_response(&self, hash: H256, tree_root: H256) -> ResponseMessage {
+        let snapshot = match self.snapshot(&hash) {
+            Some(snapshot) => snapshot,
+            None => return ResponseMessage::StateChunk(Vec::new()),
+        };
+        // The chunks are the subtrees under the grandchildren of the state root and the shard roots.
+        let chunk = snapshot.chunk_roots().and_then(|chunk_roots| {
+            if chunk_roots.contains(&tree_root) {
+                snapshot.read_chunk(&tree_root).map(Some)
+            } else {
+                Ok(None)
+            }
+        });
+        match chunk {
+            Ok(Some(chunk)) => ResponseMessage::StateChunk(chunk),
+            Ok(None) => ResponseMessage::StateChunk(Vec::new()),
+            Err(err) => {
+                cwarn!(SYNC, "Cannot read the snapshot chunk {} of {}: {}", tree_root, hash, err);
+                ResponseMessage::StateChunk(Vec::new())
+            }
+        }
     }
 }
 
@@ -369,9 +457,15 @@ impl Extension {
                         RequestMessage::Bodies(hashes) => hashes,
                         _ => unreachable!(),
                     };
-                    self.on_body_response(from, hashes, bodies)
+                    if self.is_restoring_snapshot() {
+                        self.on_snapshot_body_response(from, hashes, bodies)
+                    } else {
+                        self.on_body_response(from, hashes, bodies)
+                    }
+                }
+                ResponseMessage::StateHead(bytes) | ResponseMessage::StateChunk(bytes) => {
+                    self.on_state_response(from, bytes)
                 }
-                _ => unimplemented!(),
             }
         }
     }
@@ -400,7 +494,14 @@ impl Extension {
                 for body in bodies {
                     for parcel in body {
                         let is_valid = match &parcel.as_unsigned().action {
-                            Action::Custom(bytes) => self.client.custom_handlers().iter().any(|c| c.is_target(bytes)),
+                            Action::Custom {
+                                handler_id,
+                                bytes,
+                            } => self
+                                .client
+                                .custom_handlers()
+                                .iter()
+                                .any(|h| h.handler_id() == *handler_id && h.is_valid(bytes)),
                             _ => true,
                         };
                         if !is_valid {
@@ -410,13 +511,14 @@ impl Extension {
                 }
                 true
             }
-            (RequestMessage::StateHead(..), ResponseMessage::StateHead(..)) => unimplemented!(),
+            // The nodes are verified by the state downloader.
+            (RequestMessage::StateHead(..), ResponseMessage::StateHead(..)) => true,
             (
                 RequestMessage::StateChunk {
                     ..
                 },
                 ResponseMessage::StateChunk(..),
-            ) => unimplemented!(),
+            ) => true,
             _ => false,
         }
     }
@@ -447,6 +549,9 @@ impl Extension {
             }
         }
 
+        if self.is_restoring_snapshot() {
+            return
+        }
         let body_targets = exists
             .iter()
             .filter(|header| self.client.block_body(BlockId::Hash(header.hash())).is_none())
@@ -495,3 +600,159 @@ impl Extension {
         }
     }
 }
+
+impl Extension {
+    fn is_restoring_snapshot(&self) -> bool {
+        self.snapshot_sync.lock().is_some()
+    }
+
+    /// Chooses the snapshot to download once the headers of the best peer are imported.
+    /// Falls back to the full sync if the peers can't have any snapshot yet.
+    fn update_snapshot_sync(&self) {
+        let mut snapshot_sync = self.snapshot_sync.lock();
+        match *snapshot_sync {
+            Some(SnapshotSync::WaitingForHeaders) => {}
+            _ => return,
+        }
+
+        let target = match self.snapshot_target() {
+            Some(target) => target,
+            None => return,
+        };
+        match target {
+            Some(header) => {
+                cinfo!(SYNC, "Restoring the state of the snapshot #{} ({})", header.number(), header.hash());
+                let downloader = StateDownloader::new(self.client.database(), header.hash(), header.state_root());
+                *snapshot_sync = Some(SnapshotSync::Restoring(downloader));
+            }
+            None => {
+                cinfo!(SYNC, "No snapshot to download. Sync from the genesis block");
+                *snapshot_sync = None;
+                drop(snapshot_sync);
+                self.add_body_targets_after(self.client.chain_info().best_block_number);
+            }
+        }
+    }
+
+    /// Returns the header of the latest snapshot that the best peer can have.
+    /// `None` if the headers of the best peer aren't imported yet.
+    fn snapshot_target(&self) -> Option<Option<EncodedHeader>> {
+        let best_hash = self.header_downloaders.read().values().max_by_key(|peer| peer.total_score())?.best_hash();
+        let best_number = self.client.block_number(BlockId::Hash(best_hash))?;
+
+        // The peers write the snapshot of the checkpoint before the latest one.
+        let period = self.client.common_params().snapshot_period;
+        if period != 0 && best_number / period > 1 {
+            Some(self.client.block_header(BlockId::Number((best_number / period - 1) * period)))
+        } else {
+            Some(None)
+        }
+    }
+
+    /// Switches to the newer snapshot if every peer failed to serve the current one too many times in a row,
+    /// e.g. because the peers removed it after writing a newer one.
+    /// Falls back to the full sync if there is no newer snapshot.
+    fn retarget_snapshot_sync(&self, peers: &[NodeId]) {
+        let mut snapshot_sync = self.snapshot_sync.lock();
+        let block_hash = match &*snapshot_sync {
+            Some(SnapshotSync::Restoring(downloader)) if downloader.is_unavailable(peers) => downloader.block_hash(),
+            _ => return,
+        };
+
+        match self.snapshot_target() {
+            Some(Some(ref header)) if header.hash() != block_hash => {
+                cinfo!(SYNC, "Restoring the state of the newer snapshot #{} ({})", header.number(), header.hash());
+                let downloader = StateDownloader::new(self.client.database(), header.hash(), header.state_root());
+                *snapshot_sync = Some(SnapshotSync::Restoring(downloader));
+            }
+            _ => {
+                cinfo!(SYNC, "No peer can serve the snapshot {}. Sync from the genesis block", block_hash);
+                *snapshot_sync = None;
+                drop(snapshot_sync);
+                self.add_body_targets_after(self.client.chain_info().best_block_number);
+            }
+        }
+    }
+
+    /// The headers are requested independently of the snapshot.
+    fn request_snapshot(&self, id: &NodeId) {
+        let request = match &mut *self.snapshot_sync.lock() {
+            Some(SnapshotSync::Restoring(downloader)) => downloader.create_request(id),
+            _ => None,
+        };
+        if let Some(request) = request {
+            self.send_request(id, request);
+        }
+    }
+
+    /// The peers that don't respond to the snapshot requests in time aren't asked for a while.
+    fn expire_snapshot_requests(&self) {
+        let expired = match &mut *self.snapshot_sync.lock() {
+            Some(SnapshotSync::Restoring(downloader)) => downloader.back_off_expired(),
+            _ => return,
+        };
+        for (peer, request) in expired {
+            cinfo!(SYNC, "Peer #{} didn't respond to the snapshot request in time", peer);
+            self.dismiss_expired_request(&peer, &request);
+        }
+    }
+
+    fn on_state_response(&self, from: &NodeId, response: Bytes) {
+        let mut snapshot_sync = self.snapshot_sync.lock();
+        let downloader = match &mut *snapshot_sync {
+            Some(SnapshotSync::Restoring(downloader)) => downloader,
+            _ => return,
+        };
+        // The peer doesn't have the snapshot yet, or removed it. Other peers will be asked.
+        if response.is_empty() {
+            cinfo!(SYNC, "Peer #{} doesn't have the snapshot", from);
+            downloader.back_off(from);
+            return
+        }
+        if let Err(err) = downloader.import(from, &response) {
+            cwarn!(SYNC, "Invalid snapshot from peer #{}: {}", from, err);
+        }
+    }
+
+    fn on_snapshot_body_response(&self, from: &NodeId, hashes: Vec<H256>, bodies: Vec<Vec<UnverifiedParcel>>) {
+        if let Some(SnapshotSync::Restoring(downloader)) = &mut *self.snapshot_sync.lock() {
+            downloader.cancel(from);
+        }
+        for (hash, body) in hashes.into_iter().zip(bodies) {
+            let header = self.client.block_header(BlockId::Hash(hash)).expect("Requested body's header must exist");
+            let parent =
+                self.client.block_header(BlockId::Hash(header.parent_hash())).expect("Parent header must exist");
+            let total_score =
+                self.client.block_total_score(BlockId::Hash(hash)).expect("Imported header has its score");
+            let number = header.number();
+            let block = Block {
+                header: header.decode(),
+                parcels: body,
+            };
+            match self.client.import_bootstrap_block(&block.rlp_bytes(Seal::With), &parent.decode(), total_score) {
+                Ok(_) => {
+                    *self.snapshot_sync.lock() = None;
+                    self.add_body_targets_after(number);
+                }
+                Err(BlockImportError::Import(ImportError::AlreadyInChain)) => {}
+                Err(err) => {
+                    cwarn!(SYNC, "Cannot import the snapshot block {} from peer #{}: {:?}", hash, from, err);
+                    if let Some(SnapshotSync::Restoring(downloader)) = &mut *self.snapshot_sync.lock() {
+                        downloader.back_off(from);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds the bodies of the canonical headers after the block to the download targets.
+    fn add_body_targets_after(&self, number: BlockNumber) {
+        let mut parent = self.client.block_header(BlockId::Number(number)).expect("The best block must exist");
+        let mut body_targets = Vec::new();
+        while let Some(header) = self.client.block_header(BlockId::Number(parent.number() + 1)) {
+            body_targets.push((header.hash(), parent.parcels_root(), header.parcels_root()));
+            parent = header;
+        }
+        self.body_downloader.lock().add_target(body_targets);
+    }
+}
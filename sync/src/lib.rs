@@ -17,12 +17,15 @@
 extern crate parking_lot;
 
 extern crate codechain_core as ccore;
+extern crate codechain_crypto as ccrypto;
 extern crate codechain_merkle as cmerkle;
 #[macro_use]
 extern crate codechain_logger as clogger;
 extern crate codechain_network as cnetwork;
+extern crate codechain_state as cstate;
 extern crate codechain_types as ctypes;
 
+extern crate hashdb;
 extern crate kvdb;
 #[macro_use]
 extern crate log;
@@ -36,10 +39,20 @@ extern crate time;
 mod block;
 mod parcel;
 mod snapshot;
+#[cfg(test)]
+mod tests;
 
 pub use self::block::BlockSyncExtension;
 pub use self::parcel::ParcelSyncExtension;
-pub use self::snapshot::SnapshotService;
+pub use self::snapshot::{
+    create_snapshot, restore as restore_snapshot, Snapshot, SnapshotBlock, SnapshotError, SnapshotService,
+};
 
 #[cfg(test)]
 extern crate codechain_key as ckey;
+#[cfg(test)]
+extern crate journaldb;
+#[cfg(test)]
+extern crate kvdb_memorydb;
+#[cfg(test)]
+extern crate memorydb;
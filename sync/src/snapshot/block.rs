@@ -0,0 +1,89 @@
+// Copyright 2018 Kodebox, Inc.
+// This file is part of CodeChain.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use ccore::{Block, BlockChainClient, BlockId, BlockInfo, Header, Seal};
+use primitives::{H256, U256};
+use rlp::{Decodable, DecoderError, Encodable, RlpStream, UntrustedRlp};
+
+use super::error::Error;
+
+/// The block of a snapshot with what's needed to import it without its ancestors.
+#[derive(Debug, PartialEq)]
+pub struct SnapshotBlock {
+    /// The parent header, which verifies the parcels root of the block.
+    pub parent: Header,
+    pub block: Block,
+    pub total_score: U256,
+}
+
+impl SnapshotBlock {
+    /// Fails if the client doesn't have the block or its parent, so the genesis block can't be a snapshot.
+    pub fn from_client(client: &BlockChainClient, block_hash: &H256) -> Result<Self, Error> {
+        let block = client.block(BlockId::Hash(*block_hash)).ok_or(Error::BlockNotFound)?.decode();
+        let parent = client.block_header(BlockId::Hash(*block.header.parent_hash())).ok_or(Error::BlockNotFound)?;
+        let total_score = client.block_total_score(BlockId::Hash(*block_hash)).ok_or(Error::BlockNotFound)?;
+        Ok(Self {
+            parent: parent.decode(),
+            block,
+            total_score,
+        })
+    }
+}
+
+impl Encodable for SnapshotBlock {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(3);
+        s.append(&self.parent);
+        s.append_raw(&self.block.rlp_bytes(Seal::With), 1);
+        s.append(&self.total_score);
+    }
+}
+
+impl Decodable for SnapshotBlock {
+    fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+        if rlp.item_count()? != 3 {
+            return Err(DecoderError::RlpIncorrectListLen)
+        }
+        Ok(Self {
+            parent: rlp.val_at(0)?,
+            block: rlp.val_at(1)?,
+            total_score: rlp.val_at(2)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_and_decode_snapshot_block() {
+        let mut parent = Header::default();
+        parent.set_number(9);
+        let mut header = Header::default();
+        header.set_number(10);
+        header.set_parent_hash(parent.hash());
+        let snapshot_block = SnapshotBlock {
+            parent,
+            block: Block {
+                header,
+                parcels: Vec::new(),
+            },
+            total_score: 11.into(),
+        };
+        rlp_encode_and_decode_test!(snapshot_block);
+    }
+}
@@ -17,14 +17,24 @@
 use std::fmt::{Display, Formatter, Result as FormatResult};
 use std::io::{Error as FileError, ErrorKind};
 
+use ccore::BlockImportError;
 use kvdb::Error as DBError;
 use primitives::H256;
+use rlp::DecoderError;
 
 #[derive(Debug)]
 pub enum Error {
     NodeNotFound(H256),
+    InvalidNode(H256),
+    InvalidBlock(H256),
+    InvalidManifest(H256),
+    InvalidChunk(H256),
+    ShardNotFound(H256),
+    BlockNotFound,
+    BlockImportError(BlockImportError),
     DBError(DBError),
     FileError(ErrorKind),
+    DecoderError(DecoderError),
 }
 
 impl From<DBError> for Error {
@@ -39,12 +49,32 @@ impl From<FileError> for Error {
     }
 }
 
+impl From<DecoderError> for Error {
+    fn from(error: DecoderError) -> Self {
+        Error::DecoderError(error)
+    }
+}
+
+impl From<BlockImportError> for Error {
+    fn from(error: BlockImportError) -> Self {
+        Error::BlockImportError(error)
+    }
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> FormatResult {
         match self {
             Error::NodeNotFound(key) => write!(f, "State node not found: {:x}", key),
+            Error::InvalidNode(key) => write!(f, "State node doesn't match its key: {:x}", key),
+            Error::InvalidBlock(hash) => write!(f, "The block of the snapshot doesn't match: {:x}", hash),
+            Error::InvalidManifest(hash) => write!(f, "The manifest of the snapshot doesn't match: {:x}", hash),
+            Error::InvalidChunk(root) => write!(f, "The chunk doesn't match the manifest: {:x}", root),
+            Error::ShardNotFound(root) => write!(f, "The shard trie isn't in the snapshot: {:x}", root),
+            Error::BlockNotFound => write!(f, "The block of the snapshot not found"),
+            Error::BlockImportError(error) => write!(f, "Cannot import the block of the snapshot: {:?}", error),
             Error::DBError(error) => write!(f, "DB Error: {:?}", error),
             Error::FileError(kind) => write!(f, "File system error: {:?}", kind),
+            Error::DecoderError(error) => write!(f, "Invalid snapshot file: {}", error),
         }
     }
 }
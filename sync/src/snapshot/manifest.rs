@@ -0,0 +1,126 @@
+// Copyright 2018 Kodebox, Inc.
+// This file is part of CodeChain.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use ccrypto::blake256;
+use ctypes::BlockNumber;
+use primitives::H256;
+use rlp::{Decodable, DecoderError, Encodable, RlpStream, UntrustedRlp};
+
+/// A chunk file of a snapshot.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChunkInfo {
+    /// The root of the subtree in the chunk, which is also the name of the file.
+    pub root: H256,
+    /// The hash of the compressed file.
+    pub hash: H256,
+    /// The size of the compressed file in bytes.
+    pub size: u64,
+}
+
+impl ChunkInfo {
+    pub fn new(root: H256, compressed: &[u8]) -> Self {
+        Self {
+            root,
+            hash: blake256(compressed),
+            size: compressed.len() as u64,
+        }
+    }
+}
+
+impl Encodable for ChunkInfo {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(3);
+        s.append(&self.root);
+        s.append(&self.hash);
+        s.append(&self.size);
+    }
+}
+
+impl Decodable for ChunkInfo {
+    fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+        if rlp.item_count()? != 3 {
+            return Err(DecoderError::RlpIncorrectListLen)
+        }
+        Ok(Self {
+            root: rlp.val_at(0)?,
+            hash: rlp.val_at(1)?,
+            size: rlp.val_at(2)?,
+        })
+    }
+}
+
+/// Describes what's written in a snapshot directory.
+/// It's written after all the other files, so a snapshot without it is incomplete.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Manifest {
+    pub block_number: BlockNumber,
+    pub block_hash: H256,
+    pub state_root: H256,
+    /// The roots of the shard tries, whose chunks follow the chunks of the state trie.
+    pub shard_roots: Vec<H256>,
+    pub chunks: Vec<ChunkInfo>,
+}
+
+impl Manifest {
+    /// The total size of the chunk files in bytes.
+    pub fn chunks_size(&self) -> u64 {
+        self.chunks.iter().map(|chunk| chunk.size).sum()
+    }
+}
+
+impl Encodable for Manifest {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(5);
+        s.append(&self.block_number);
+        s.append(&self.block_hash);
+        s.append(&self.state_root);
+        s.append_list(&self.shard_roots);
+        s.append_list(&self.chunks);
+    }
+}
+
+impl Decodable for Manifest {
+    fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+        if rlp.item_count()? != 5 {
+            return Err(DecoderError::RlpIncorrectListLen)
+        }
+        Ok(Self {
+            block_number: rlp.val_at(0)?,
+            block_hash: rlp.val_at(1)?,
+            state_root: rlp.val_at(2)?,
+            shard_roots: rlp.list_at(3)?,
+            chunks: rlp.list_at(4)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_and_decode_manifest() {
+        let manifest = Manifest {
+            block_number: 100,
+            block_hash: H256::random(),
+            state_root: H256::random(),
+            shard_roots: vec![H256::random()],
+            chunks: vec![ChunkInfo::new(H256::random(), &[1, 2, 3]), ChunkInfo::new(H256::random(), &[4, 5])],
+        };
+        assert_eq!(5, manifest.chunks_size());
+        rlp_encode_and_decode_test!(manifest);
+    }
+}
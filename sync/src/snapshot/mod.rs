@@ -14,7 +14,18 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+mod block;
 mod error;
+mod manifest;
+mod reader;
+mod restorer;
 mod service;
 
-pub use self::service::Service as SnapshotService;
+pub use self::block::SnapshotBlock;
+pub use self::error::Error as SnapshotError;
+pub use self::manifest::{ChunkInfo, Manifest};
+pub use self::reader::{child_keys, shard_root, Snapshot};
+pub use self::restorer::{restore, Restorer};
+pub use self::service::{
+    create_snapshot, remove_incomplete_snapshots, remove_old_snapshots, write_snapshot, Service as SnapshotService,
+};
@@ -0,0 +1,460 @@
+// Copyright 2018 Kodebox, Inc.
+// This file is part of CodeChain.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{read_dir, remove_dir_all, File};
+use std::io::Read;
+use std::iter::once;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use ccrypto::{blake256, BLAKE_NULL_RLP};
+use cmerkle::Node;
+use cstate::Shard;
+use primitives::{Bytes, H256};
+use rlp::{DecoderError, UntrustedRlp};
+use snap;
+
+use super::block::SnapshotBlock;
+use super::error::Error;
+use super::manifest::{ChunkInfo, Manifest};
+
+const HEAD_FILE_NAME: &'static str = "head";
+const BLOCK_FILE_NAME: &'static str = "block";
+const MANIFEST_FILE_NAME: &'static str = "manifest";
+
+/// The snapshot of a block that `Service` wrote in `<root dir>/<block hash>`.
+///
+/// The state is the state trie and the shard tries, whose roots are in the shards that the state trie has.
+/// The head has the roots of the tries with their children, and the roots of the shard tries.
+/// Every grandchild of a trie root is the root of a chunk, which has all the nodes of the subtree under it.
+/// The chunks are the snappy-compressed RLP lists of `[key, node]`,
+/// and the head is the snappy-compressed RLP of `[[[key, node], ...], [shard root, ...]]`.
+/// The block file has the RLP of `SnapshotBlock`, which is needed to restore the snapshot without any other block.
+/// The manifest file has the RLP of `Manifest`. It's written last, so a directory without it is incomplete.
+pub struct Snapshot {
+    block_hash: H256,
+    path: PathBuf,
+}
+
+impl Snapshot {
+    pub fn new<P: AsRef<Path>>(root_dir: P, block_hash: &H256) -> Self {
+        Self {
+            block_hash: *block_hash,
+            path: root_dir.as_ref().join(format!("{:x}", block_hash)),
+        }
+    }
+
+    /// Returns the complete snapshots written in the root directory.
+    pub fn list<P: AsRef<Path>>(root_dir: P) -> Result<Vec<Snapshot>, Error> {
+        Ok(list_dirs(root_dir)?.into_iter().filter(Snapshot::exists).collect())
+    }
+
+    /// Returns the snapshots whose writing was interrupted, e.g. by a crash.
+    pub fn list_incomplete<P: AsRef<Path>>(root_dir: P) -> Result<Vec<Snapshot>, Error> {
+        Ok(list_dirs(root_dir)?.into_iter().filter(|snapshot| !snapshot.exists()).collect())
+    }
+
+    pub fn block_hash(&self) -> H256 {
+        self.block_hash
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns true if the snapshot is completely written.
+    pub fn exists(&self) -> bool {
+        self.manifest_path().is_file()
+    }
+
+    pub fn remove(&self) -> Result<(), Error> {
+        Ok(remove_dir_all(&self.path)?)
+    }
+
+    /// Returns the compressed head as it's written in the file.
+    pub fn read_head(&self) -> Result<Bytes, Error> {
+        read_file(&self.head_path())
+    }
+
+    /// Returns the compressed chunk as it's written in the file.
+    pub fn read_chunk(&self, chunk_root: &H256) -> Result<Bytes, Error> {
+        read_file(&self.chunk_path(chunk_root))
+    }
+
+    pub fn read_block(&self) -> Result<SnapshotBlock, Error> {
+        let snapshot_block: SnapshotBlock = UntrustedRlp::new(&read_file(&self.block_path())?).as_val()?;
+        let header = &snapshot_block.block.header;
+        if header.hash() != self.block_hash || *header.parent_hash() != snapshot_block.parent.hash() {
+            return Err(Error::InvalidBlock(self.block_hash))
+        }
+        Ok(snapshot_block)
+    }
+
+    pub fn read_manifest(&self) -> Result<Manifest, Error> {
+        let manifest: Manifest = UntrustedRlp::new(&read_file(&self.manifest_path())?).as_val()?;
+        if manifest.block_hash != self.block_hash {
+            return Err(Error::InvalidManifest(self.block_hash))
+        }
+        Ok(manifest)
+    }
+
+    /// Checks that every node of the snapshot matches its key and that the nodes make the state of the block.
+    /// Every shard in the state must have its trie in the snapshot, and the manifest must describe the block
+    /// and every chunk file.
+    pub fn verify(&self) -> Result<(), Error> {
+        let manifest = self.read_manifest()?;
+        let header = self.read_block()?.block.header;
+        if manifest.block_number != header.number() || manifest.state_root != *header.state_root() {
+            return Err(Error::InvalidManifest(self.block_hash))
+        }
+
+        let (nodes, shard_roots) = decode_head(&self.read_head()?)?;
+        if shard_roots != manifest.shard_roots {
+            return Err(Error::InvalidManifest(self.block_hash))
+        }
+        let (head, chunk_roots) = split_head(&nodes, &manifest.state_root, &shard_roots)?;
+        if chunk_roots.len() != manifest.chunks.len() {
+            return Err(Error::InvalidManifest(self.block_hash))
+        }
+        let mut reachable_shard_roots: HashSet<_> = head.iter().filter_map(|(_, node)| shard_root(node)).collect();
+        for (chunk_root, chunk_info) in chunk_roots.into_iter().zip(manifest.chunks) {
+            let compressed = self.read_chunk(&chunk_root)?;
+            if ChunkInfo::new(chunk_root, &compressed) != chunk_info {
+                return Err(Error::InvalidChunk(chunk_root))
+            }
+            let nodes = decode_verified_nodes(&compressed)?;
+            let subtree = subtree_nodes(&chunk_root, &nodes)?;
+            check_shard_roots(subtree.iter().map(|(_, node)| *node), &shard_roots)?;
+            reachable_shard_roots.extend(subtree.iter().filter_map(|(_, node)| shard_root(node)));
+        }
+        // The chunks of the shard tries are complete, so no trie is in the snapshot unless a shard has it.
+        if reachable_shard_roots != shard_roots.iter().cloned().collect() {
+            return Err(Error::InvalidManifest(self.block_hash))
+        }
+        Ok(())
+    }
+
+    /// Returns the roots of the chunks, which are the grandchildren of the state root and the shard roots.
+    pub fn chunk_roots(&self) -> Result<Vec<H256>, Error> {
+        let (nodes, shard_roots) = decode_head(&self.read_head()?)?;
+        let (_, chunk_roots) = split_head(&nodes, &self.read_manifest()?.state_root, &shard_roots)?;
+        Ok(chunk_roots)
+    }
+
+    pub fn head_path(&self) -> PathBuf {
+        self.path.join(HEAD_FILE_NAME)
+    }
+
+    pub fn chunk_path(&self, chunk_root: &H256) -> PathBuf {
+        self.path.join(format!("{:x}", chunk_root))
+    }
+
+    pub fn block_path(&self) -> PathBuf {
+        self.path.join(BLOCK_FILE_NAME)
+    }
+
+    pub fn manifest_path(&self) -> PathBuf {
+        self.path.join(MANIFEST_FILE_NAME)
+    }
+}
+
+/// Returns the snapshot directories in the root directory, which are named by their block hashes.
+fn list_dirs<P: AsRef<Path>>(root_dir: P) -> Result<Vec<Snapshot>, Error> {
+    if !root_dir.as_ref().is_dir() {
+        return Ok(Vec::new())
+    }
+    let mut snapshots = Vec::new();
+    for entry in read_dir(&root_dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue
+        }
+        if let Some(block_hash) = entry.file_name().to_str().and_then(|name| H256::from_str(name).ok()) {
+            snapshots.push(Snapshot::new(&root_dir, &block_hash));
+        }
+    }
+    Ok(snapshots)
+}
+
+fn read_file(path: &Path) -> Result<Bytes, Error> {
+    let mut bytes = Vec::new();
+    File::open(path)?.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn decompress(compressed: &[u8]) -> Result<Bytes, Error> {
+    let mut bytes = Vec::new();
+    snap::Reader::new(compressed).read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Decompresses a chunk into its `(key, node)` pairs.
+pub fn decode_nodes(compressed: &[u8]) -> Result<Vec<(H256, Bytes)>, Error> {
+    nodes_of(&UntrustedRlp::new(&decompress(compressed)?))
+}
+
+fn nodes_of(rlp: &UntrustedRlp) -> Result<Vec<(H256, Bytes)>, Error> {
+    if !rlp.is_list() {
+        return Err(DecoderError::RlpExpectedToBeList.into())
+    }
+    let mut nodes = Vec::new();
+    for item in rlp.iter() {
+        if item.item_count()? != 2 {
+            return Err(DecoderError::RlpIncorrectListLen.into())
+        }
+        nodes.push((item.val_at(0)?, item.val_at(1)?));
+    }
+    Ok(nodes)
+}
+
+/// Decompresses a chunk, checking that every node matches its key.
+pub fn decode_verified_nodes(compressed: &[u8]) -> Result<HashMap<H256, Bytes>, Error> {
+    verify_nodes(decode_nodes(compressed)?)
+}
+
+/// Decompresses a head into its nodes and the shard roots, checking that every node matches its key.
+pub fn decode_head(compressed: &[u8]) -> Result<(HashMap<H256, Bytes>, Vec<H256>), Error> {
+    let bytes = decompress(compressed)?;
+    let rlp = UntrustedRlp::new(&bytes);
+    if rlp.item_count()? != 2 {
+        return Err(DecoderError::RlpIncorrectListLen.into())
+    }
+    Ok((verify_nodes(nodes_of(&rlp.at(0)?)?)?, rlp.list_at(1)?))
+}
+
+fn verify_nodes(decoded: Vec<(H256, Bytes)>) -> Result<HashMap<H256, Bytes>, Error> {
+    let mut nodes = HashMap::new();
+    for (key, node) in decoded {
+        if blake256(&node) != key {
+            return Err(Error::InvalidNode(key))
+        }
+        nodes.insert(key, node);
+    }
+    Ok(nodes)
+}
+
+/// Splits the nodes of a head into the trie roots with their children, and the roots of the chunks.
+/// The chunks of the state trie come first, and then the chunks of the shard tries in the order of the shard roots.
+/// Fails if a shard in the head isn't one of the shard roots.
+pub fn split_head(
+    nodes: &HashMap<H256, Bytes>,
+    state_root: &H256,
+    shard_roots: &[H256],
+) -> Result<(Vec<(H256, Bytes)>, Vec<H256>), Error> {
+    let mut head = Vec::new();
+    let mut chunk_roots = Vec::new();
+    // The same subtree can be under more than one node, but it's in only one chunk.
+    let mut known = HashSet::new();
+    for trie_root in once(state_root).chain(shard_roots) {
+        let root = nodes.get(trie_root).ok_or(Error::NodeNotFound(*trie_root))?;
+        head.push((*trie_root, root.clone()));
+        for child in child_keys(root) {
+            let node = nodes.get(&child).ok_or(Error::NodeNotFound(child))?;
+            chunk_roots.extend(child_keys(node).into_iter().filter(|grandchild| known.insert(*grandchild)));
+            head.push((child, node.clone()));
+        }
+    }
+    check_shard_roots(head.iter().map(|(_, node)| node), shard_roots)?;
+    Ok((head, chunk_roots))
+}
+
+/// Fails if a node is a leaf of a shard whose trie isn't one of the shard roots.
+pub fn check_shard_roots<'a, I>(nodes: I, shard_roots: &[H256]) -> Result<(), Error>
+where
+    I: IntoIterator<Item = &'a Bytes>, {
+    for node in nodes {
+        if let Some(root) = shard_root(node) {
+            if !shard_roots.contains(&root) {
+                return Err(Error::ShardNotFound(root))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the nodes of the subtree under the root, failing if any of them is missing.
+pub fn subtree_nodes<'a>(root: &H256, nodes: &'a HashMap<H256, Bytes>) -> Result<Vec<(H256, &'a Bytes)>, Error> {
+    let mut subtree = Vec::new();
+    let mut stack = vec![*root];
+    while let Some(key) = stack.pop() {
+        let node = nodes.get(&key).ok_or(Error::NodeNotFound(key))?;
+        stack.extend(child_keys(node));
+        subtree.push((key, node));
+    }
+    Ok(subtree)
+}
+
+/// Returns the root of the shard trie if the node is a leaf that has a shard.
+/// An empty shard has no trie, so it returns `None` for it.
+pub fn shard_root(node: &[u8]) -> Option<H256> {
+    match Node::decoded(node) {
+        Some(Node::Leaf(_, value)) => match Shard::from_leaf_value(value) {
+            Some(ref shard) if *shard.root() != BLAKE_NULL_RLP => Some(*shard.root()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the keys of the children of a trie node.
+pub fn child_keys(node: &[u8]) -> Vec<H256> {
+    match Node::decoded(node) {
+        None => Vec::new(),
+        Some(Node::Leaf(..)) => Vec::new(),
+        Some(Node::Branch(_, children)) => children.iter().filter_map(|child| *child).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env::temp_dir;
+    use std::fs::{copy, remove_file};
+    use std::io::Write;
+
+    use rlp::{self, RlpStream};
+
+    use super::super::super::tests::helpers::{
+        node_with_shards, node_with_state, temp_root_dir, write_snapshot_of_block, write_snapshot_of_block_in,
+    };
+    use super::*;
+
+    fn compress(nodes: &[(H256, Bytes)]) -> Bytes {
+        let mut stream = RlpStream::new_list(nodes.len());
+        for (key, node) in nodes {
+            stream.begin_list(2).append(key).append(node);
+        }
+        let mut snappy = snap::Writer::new(Vec::new());
+        snappy.write_all(&stream.out()).unwrap();
+        snappy.into_inner().unwrap()
+    }
+
+    #[test]
+    fn decode_compressed_nodes() {
+        let nodes = vec![(H256::random(), vec![0x80]), (H256::random(), vec![0xc2, 0x01, 0x02])];
+        assert_eq!(nodes, decode_nodes(&compress(&nodes)).unwrap());
+    }
+
+    #[test]
+    fn decoding_uncompressed_bytes_fails() {
+        assert!(decode_nodes(&[0xc0]).is_err());
+    }
+
+    #[test]
+    fn snapshot_that_is_not_written_does_not_exist() {
+        let snapshot = Snapshot::new(temp_dir(), &H256::random());
+        assert!(!snapshot.exists());
+        assert!(snapshot.read_head().is_err());
+        assert!(snapshot.read_chunk(&H256::random()).is_err());
+    }
+
+    #[test]
+    fn verify_written_snapshot() {
+        let (db, root) = node_with_state(1000);
+        let snapshot = write_snapshot_of_block(&db, &root);
+        assert!(snapshot.verify().is_ok());
+        assert_eq!(root, *snapshot.read_block().unwrap().block.header.state_root());
+    }
+
+    #[test]
+    fn verifying_snapshot_with_corrupted_chunk_fails() {
+        let (db, root) = node_with_state(1000);
+        let snapshot = write_snapshot_of_block(&db, &root);
+        let chunk_roots = snapshot.chunk_roots().unwrap();
+        copy(snapshot.chunk_path(&chunk_roots[1]), snapshot.chunk_path(&chunk_roots[0])).unwrap();
+        assert!(snapshot.verify().is_err());
+    }
+
+    #[test]
+    fn verifying_snapshot_with_wrong_manifest_fails() {
+        let (db, root) = node_with_state(1000);
+        let snapshot = write_snapshot_of_block(&db, &root);
+        let mut manifest = snapshot.read_manifest().unwrap();
+        manifest.chunks[0].size += 1;
+        File::create(snapshot.manifest_path()).unwrap().write_all(&rlp::encode(&manifest)).unwrap();
+        assert!(snapshot.verify().is_err());
+    }
+
+    #[test]
+    fn manifest_describes_written_snapshot() {
+        let (db, root) = node_with_state(1000);
+        let snapshot = write_snapshot_of_block(&db, &root);
+        let manifest = snapshot.read_manifest().unwrap();
+        assert_eq!(1, manifest.block_number);
+        assert_eq!(snapshot.block_hash(), manifest.block_hash);
+        assert_eq!(root, manifest.state_root);
+        let chunk_roots: Vec<_> = manifest.chunks.iter().map(|chunk| chunk.root).collect();
+        assert_eq!(snapshot.chunk_roots().unwrap(), chunk_roots);
+    }
+
+    #[test]
+    fn snapshot_has_shard_tries() {
+        let (db, root) = node_with_shards(&[1000, 0, 10]);
+        let snapshot = write_snapshot_of_block(&db, &root);
+        assert!(snapshot.verify().is_ok());
+
+        let manifest = snapshot.read_manifest().unwrap();
+        // The empty shard has no trie.
+        assert_eq!(2, manifest.shard_roots.len());
+        let (mut nodes, _) = decode_head(&snapshot.read_head().unwrap()).unwrap();
+        for chunk_root in snapshot.chunk_roots().unwrap() {
+            nodes.extend(decode_verified_nodes(&snapshot.read_chunk(&chunk_root).unwrap()).unwrap());
+        }
+        for shard_root in &manifest.shard_roots {
+            assert!(subtree_nodes(shard_root, &nodes).is_ok());
+        }
+    }
+
+    #[test]
+    fn verifying_snapshot_without_shard_trie_fails() {
+        let (db, root) = node_with_shards(&[1000, 10]);
+        let snapshot = write_snapshot_of_block(&db, &root);
+
+        // Both the head and the manifest leave out the first shard trie, and so its chunks.
+        let (nodes, shard_roots) = decode_head(&snapshot.read_head().unwrap()).unwrap();
+        let mut stream = RlpStream::new_list(2);
+        stream.begin_list(nodes.len());
+        for (key, node) in &nodes {
+            stream.begin_list(2).append(key).append(node);
+        }
+        stream.append_list(&shard_roots[1..]);
+        let mut snappy = snap::Writer::new(Vec::new());
+        snappy.write_all(&stream.out()).unwrap();
+        File::create(snapshot.head_path()).unwrap().write_all(&snappy.into_inner().unwrap()).unwrap();
+
+        let mut manifest = snapshot.read_manifest().unwrap();
+        manifest.shard_roots.remove(0);
+        let (_, chunk_roots) = split_head(&nodes, &root, &manifest.shard_roots).unwrap_or_default();
+        manifest.chunks.retain(|chunk| chunk_roots.contains(&chunk.root));
+        File::create(snapshot.manifest_path()).unwrap().write_all(&rlp::encode(&manifest)).unwrap();
+        assert!(snapshot.verify().is_err());
+    }
+
+    #[test]
+    fn list_written_snapshots() {
+        let (db, root) = node_with_state(10);
+        let root_dir = temp_root_dir();
+        let first = write_snapshot_of_block_in(&root_dir, &db, &root, 1);
+        let second = write_snapshot_of_block_in(&root_dir, &db, &root, 2);
+        remove_file(second.manifest_path()).unwrap();
+
+        let complete: Vec<_> = Snapshot::list(&root_dir).unwrap().iter().map(Snapshot::block_hash).collect();
+        assert_eq!(vec![first.block_hash()], complete);
+        let incomplete: Vec<_> =
+            Snapshot::list_incomplete(&root_dir).unwrap().iter().map(Snapshot::block_hash).collect();
+        assert_eq!(vec![second.block_hash()], incomplete);
+    }
+}
@@ -0,0 +1,275 @@
+// Copyright 2018 Kodebox, Inc.
+// This file is part of CodeChain.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use ccore::{Client, DatabaseClient, Seal, COL_STATE};
+use kvdb::{DBTransaction, KeyValueDB};
+use primitives::{Bytes, H256};
+
+use super::error::Error;
+use super::reader::{check_shard_roots, decode_head, decode_verified_nodes, split_head, subtree_nodes, Snapshot};
+
+/// Writes the state trie and the shard tries of a snapshot into the database.
+///
+/// The chunks are written as soon as they are verified, and the root of the state and its children are written last.
+/// So the state is complete if and only if its root is in the database,
+/// and the chunks written before a restart don't need to be restored again.
+pub struct Restorer {
+    db: Arc<KeyValueDB>,
+    state_root: H256,
+
+    /// The roots of the tries and their children, which are written after all the chunks.
+    head: Option<Vec<(H256, Bytes)>>,
+    /// Every shard in the chunks must be one of them, so that no shard trie is left out.
+    shard_roots: Vec<H256>,
+    chunks: Vec<H256>,
+}
+
+impl Restorer {
+    pub fn new(db: Arc<KeyValueDB>, state_root: H256) -> Self {
+        Self {
+            db,
+            state_root,
+
+            head: None,
+            shard_roots: Vec::new(),
+            chunks: Vec::new(),
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.has_node(&self.state_root).unwrap_or(false)
+    }
+
+    pub fn has_head(&self) -> bool {
+        self.head.is_some()
+    }
+
+    /// Returns the roots of the chunks that are not restored yet.
+    pub fn chunks(&self) -> &[H256] {
+        &self.chunks
+    }
+
+    pub fn import_head(&mut self, compressed: &[u8]) -> Result<(), Error> {
+        if self.head.is_some() {
+            return Ok(())
+        }
+
+        let (nodes, shard_roots) = decode_head(compressed)?;
+        let (head, chunks) = split_head(&nodes, &self.state_root, &shard_roots)?;
+        let mut remaining = Vec::new();
+        for chunk_root in chunks {
+            if !self.has_node(&chunk_root)? {
+                remaining.push(chunk_root);
+            }
+        }
+
+        cinfo!(SNAPSHOT, "Restoring {} chunks of the state {}", remaining.len(), self.state_root);
+        self.head = Some(head);
+        self.shard_roots = shard_roots;
+        self.chunks = remaining;
+        if self.chunks.is_empty() {
+            self.write_head()?;
+        }
+        Ok(())
+    }
+
+    pub fn import_chunk(&mut self, chunk_root: H256, compressed: &[u8]) -> Result<(), Error> {
+        if !self.chunks.contains(&chunk_root) {
+            return Ok(())
+        }
+
+        let nodes = decode_verified_nodes(compressed)?;
+        let subtree = subtree_nodes(&chunk_root, &nodes)?;
+        check_shard_roots(subtree.iter().map(|(_, node)| *node), &self.shard_roots)?;
+        let mut batch = DBTransaction::new();
+        for (key, node) in subtree {
+            batch.put(COL_STATE, &key, node);
+        }
+        self.db.write(batch)?;
+
+        self.chunks.retain(|root| *root != chunk_root);
+        ctrace!(SNAPSHOT, "State chunk {} is restored. {} chunks left", chunk_root, self.chunks.len());
+        if self.chunks.is_empty() {
+            self.write_head()?;
+        }
+        Ok(())
+    }
+
+    fn write_head(&mut self) -> Result<(), Error> {
+        let head = self.head.as_ref().expect("The head is imported before the chunks");
+        let mut batch = DBTransaction::new();
+        // The root goes to the same transaction with its children, so the state is never partially complete.
+        for (key, node) in head {
+            batch.put(COL_STATE, key, node);
+        }
+        self.db.write(batch)?;
+        cinfo!(SNAPSHOT, "The state {} is restored", self.state_root);
+        Ok(())
+    }
+
+    fn has_node(&self, key: &H256) -> Result<bool, Error> {
+        Ok(self.db.get(COL_STATE, key)?.is_some())
+    }
+}
+
+/// Restores the state of the snapshot and makes its block the best block of the client.
+pub fn restore(client: &Client, snapshot: &Snapshot) -> Result<H256, Error> {
+    let snapshot_block = snapshot.read_block()?;
+    let state_root = *snapshot_block.block.header.state_root();
+
+    let mut restorer = Restorer::new(client.database(), state_root);
+    if !restorer.is_complete() {
+        restorer.import_head(&snapshot.read_head()?)?;
+        for chunk_root in restorer.chunks().to_vec() {
+            restorer.import_chunk(chunk_root, &snapshot.read_chunk(&chunk_root)?)?;
+        }
+    }
+    // Every chunk, including the ones of the shard tries, must be in the snapshot.
+    if !restorer.is_complete() {
+        return Err(Error::NodeNotFound(state_root))
+    }
+
+    let bytes = snapshot_block.block.rlp_bytes(Seal::With);
+    Ok(client.import_bootstrap_block(&bytes, &snapshot_block.parent, snapshot_block.total_score)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use kvdb_memorydb;
+    use rlp::RlpStream;
+    use snap;
+
+    use super::super::super::tests::helpers::{
+        assert_same_state, node_with_shards, node_with_state, write_snapshot_of,
+    };
+    use super::*;
+
+    fn restore_head(restorer: &mut Restorer, snapshot: &Snapshot) {
+        restorer.import_head(&snapshot.read_head().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn restore_state_of_snapshot() {
+        let (server, root) = node_with_state(1000);
+        let snapshot = write_snapshot_of(&server, &root);
+
+        let client: Arc<KeyValueDB> = Arc::new(kvdb_memorydb::create(1));
+        let mut restorer = Restorer::new(Arc::clone(&client), root);
+        restore_head(&mut restorer, &snapshot);
+        for chunk_root in restorer.chunks().to_vec() {
+            restorer.import_chunk(chunk_root, &snapshot.read_chunk(&chunk_root).unwrap()).unwrap();
+        }
+        assert!(restorer.is_complete());
+        assert_same_state(&server, &client, &root);
+    }
+
+    #[test]
+    fn resume_restoration_after_restart() {
+        let (server, root) = node_with_state(1000);
+        let snapshot = write_snapshot_of(&server, &root);
+
+        let client: Arc<KeyValueDB> = Arc::new(kvdb_memorydb::create(1));
+        let mut restorer = Restorer::new(Arc::clone(&client), root);
+        restore_head(&mut restorer, &snapshot);
+        let total_chunks = restorer.chunks().len();
+        for chunk_root in restorer.chunks()[..total_chunks / 2].to_vec() {
+            restorer.import_chunk(chunk_root, &snapshot.read_chunk(&chunk_root).unwrap()).unwrap();
+        }
+        assert!(!restorer.is_complete());
+
+        let mut restorer = Restorer::new(Arc::clone(&client), root);
+        restore_head(&mut restorer, &snapshot);
+        assert_eq!(total_chunks - total_chunks / 2, restorer.chunks().len());
+        for chunk_root in restorer.chunks().to_vec() {
+            restorer.import_chunk(chunk_root, &snapshot.read_chunk(&chunk_root).unwrap()).unwrap();
+        }
+        assert!(restorer.is_complete());
+        assert_same_state(&server, &client, &root);
+    }
+
+    #[test]
+    fn reject_head_of_another_state() {
+        let (server, root) = node_with_state(100);
+        let snapshot = write_snapshot_of(&server, &root);
+
+        let client: Arc<KeyValueDB> = Arc::new(kvdb_memorydb::create(1));
+        let mut restorer = Restorer::new(client, H256::random());
+        assert!(restorer.import_head(&snapshot.read_head().unwrap()).is_err());
+        assert!(!restorer.has_head());
+    }
+
+    #[test]
+    fn reject_chunk_of_another_subtree() {
+        let (server, root) = node_with_state(1000);
+        let snapshot = write_snapshot_of(&server, &root);
+
+        let client: Arc<KeyValueDB> = Arc::new(kvdb_memorydb::create(1));
+        let mut restorer = Restorer::new(Arc::clone(&client), root);
+        restore_head(&mut restorer, &snapshot);
+
+        let first = restorer.chunks()[0];
+        let second = restorer.chunks()[1];
+        assert!(restorer.import_chunk(first, &snapshot.read_chunk(&second).unwrap()).is_err());
+        assert!(restorer.chunks().contains(&first));
+        assert_eq!(None, client.get(COL_STATE, &first).unwrap());
+    }
+
+    #[test]
+    fn restore_shard_tries() {
+        let (server, root) = node_with_shards(&[1000, 0, 10]);
+        let snapshot = write_snapshot_of(&server, &root);
+
+        let client: Arc<KeyValueDB> = Arc::new(kvdb_memorydb::create(1));
+        let mut restorer = Restorer::new(Arc::clone(&client), root);
+        restore_head(&mut restorer, &snapshot);
+        for chunk_root in restorer.chunks().to_vec() {
+            restorer.import_chunk(chunk_root, &snapshot.read_chunk(&chunk_root).unwrap()).unwrap();
+        }
+        assert!(restorer.is_complete());
+        assert_same_state(&server, &client, &root);
+    }
+
+    #[test]
+    fn reject_chunk_with_shard_missing_in_head() {
+        let (server, root) = node_with_shards(&[1000]);
+        let snapshot = write_snapshot_of(&server, &root);
+
+        // The head leaves out the shard root, so the chunks of the shard trie are never requested.
+        let (nodes, _) = decode_head(&snapshot.read_head().unwrap()).unwrap();
+        let mut stream = RlpStream::new_list(2);
+        stream.begin_list(nodes.len());
+        for (key, node) in &nodes {
+            stream.begin_list(2).append(key).append(node);
+        }
+        stream.begin_list(0);
+        let mut snappy = snap::Writer::new(Vec::new());
+        snappy.write_all(&stream.out()).unwrap();
+
+        let client: Arc<KeyValueDB> = Arc::new(kvdb_memorydb::create(1));
+        let mut restorer = Restorer::new(Arc::clone(&client), root);
+        restorer.import_head(&snappy.into_inner().unwrap()).unwrap();
+        let mut rejected = false;
+        for chunk_root in restorer.chunks().to_vec() {
+            rejected |= restorer.import_chunk(chunk_root, &snapshot.read_chunk(&chunk_root).unwrap()).is_err();
+        }
+        assert!(rejected);
+        assert!(!restorer.is_complete());
+    }
+}
@@ -14,21 +14,25 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::fs::{create_dir_all, File};
+use std::collections::HashSet;
+use std::fs::{create_dir_all, rename, File};
 use std::io::{ErrorKind, Write};
-use std::path::PathBuf;
+use std::iter::once;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread::spawn;
 
-use ccore::{BlockChainClient, BlockId, BlockInfo, ChainInfo, ChainNotify, Client, DatabaseClient, COL_STATE};
-
-use cmerkle::Node;
-use kvdb::KeyValueDB;
-use primitives::H256;
-use rlp::RlpStream;
+use ccore::{BlockChainClient, BlockId, ChainInfo, ChainNotify, Client};
+use hashdb::HashDB;
+use primitives::{Bytes, H256};
+use rlp::{self, RlpStream};
 use snap;
 
+use super::block::SnapshotBlock;
 use super::error::Error;
+use super::manifest::{ChunkInfo, Manifest};
+use super::reader::{child_keys, shard_root, Snapshot};
 
 pub struct Service {
     client: Arc<Client>,
@@ -36,14 +40,26 @@ pub struct Service {
     root_dir: String,
     /// Snapshot creation period in unit of block numbers
     period: u64,
+    /// The number of the latest snapshots to keep
+    retention: usize,
+    /// Whether a snapshot is being written
+    is_writing: Arc<AtomicBool>,
 }
 
 impl Service {
-    pub fn new(client: Arc<Client>, root_dir: String, period: u64) -> Arc<Self> {
+    pub fn new(client: Arc<Client>, root_dir: String, period: u64, retention: usize) -> Arc<Self> {
+        if let Err(e) = remove_incomplete_snapshots(&root_dir) {
+            cerror!(SNAPSHOT, "Cannot remove the incomplete snapshots: {}", e);
+        }
+        if let Err(e) = remove_old_snapshots(&root_dir, retention) {
+            cerror!(SNAPSHOT, "Cannot remove the old snapshots: {}", e);
+        }
         Arc::new(Self {
             client,
             root_dir,
             period,
+            retention,
+            is_writing: Arc::new(AtomicBool::new(false)),
         })
     }
 }
@@ -64,100 +80,272 @@ impl ChainNotify for Service {
             .iter()
             .map(|hash| self.client.block_number(BlockId::Hash(*hash)).expect("Enacted block must exist"))
             .any(|number| number % self.period == 0);
-        if is_checkpoint && best_number > self.period {
+        // The genesis block can't be a snapshot because it has no parent.
+        if is_checkpoint && best_number >= 2 * self.period {
             let number = (best_number / self.period - 1) * self.period;
-            let header = self.client.block_header(BlockId::Number(number)).expect("Snapshot target must exist");
-
-            let db = self.client.database();
-            let path: PathBuf = [self.root_dir.clone(), format!("{:x}", header.hash())].iter().collect();
-            let root = header.state_root();
-            spawn(move || match write_snapshot(db, path, &root) {
-                Ok(_) => {}
-                Err(Error::FileError(ErrorKind::AlreadyExists)) => {}
-                Err(e) => cerror!(SNAPSHOT, "{}", e),
+            let hash = self.client.block_hash(BlockId::Number(number)).expect("Snapshot target must exist");
+            let snapshot_block = match SnapshotBlock::from_client(&*self.client, &hash) {
+                Ok(snapshot_block) => snapshot_block,
+                Err(e) => {
+                    cerror!(SNAPSHOT, "{}", e);
+                    return
+                }
+            };
+
+            // The pruning history is at least twice the period, so the target isn't pruned until the next checkpoint.
+            // A snapshot that is still being written then fails anyway, so only one snapshot is written at once.
+            if self.is_writing.compare_and_swap(false, true, Ordering::SeqCst) {
+                cwarn!(SNAPSHOT, "Skipping the snapshot #{}: the previous snapshot is still being written", number);
+                return
+            }
+            // The state is read through the journal, since a pruning database keeps the recent nodes in memory.
+            // The clone shares the in-memory nodes with the client instead of copying them.
+            let db = self.client.state_db().journal_db().boxed_clone();
+            let root_dir = self.root_dir.clone();
+            let retention = self.retention;
+            let is_writing = Arc::clone(&self.is_writing);
+            spawn(move || {
+                match create_snapshot(db.as_hashdb(), &root_dir, &snapshot_block) {
+                    Ok(_) => {
+                        if let Err(e) = remove_old_snapshots(&root_dir, retention) {
+                            cerror!(SNAPSHOT, "Cannot remove the old snapshots: {}", e);
+                        }
+                    }
+                    Err(Error::FileError(ErrorKind::AlreadyExists)) => {}
+                    Err(e) => cerror!(SNAPSHOT, "{}", e),
+                }
+                is_writing.store(false, Ordering::SeqCst);
             });
         }
     }
 }
 
-fn write_snapshot(db: Arc<KeyValueDB>, path: PathBuf, root: &H256) -> Result<(), Error> {
-    create_dir_all(&path)?;
-
-    let root_val = get_node(&db, root)?;
-    let children = children_of(&db, &root_val)?;
-    let mut grandchildren = Vec::new();
-    for (_, value) in &children {
-        grandchildren.extend(children_of(&db, value)?);
+/// Writes the state and the block of the snapshot into `<root dir>/<block hash>`, and then its manifest.
+/// Fails with `AlreadyExists` if the snapshot is already complete.
+pub fn create_snapshot(db: &HashDB, root_dir: &str, snapshot_block: &SnapshotBlock) -> Result<Snapshot, Error> {
+    let header = &snapshot_block.block.header;
+    let snapshot = Snapshot::new(root_dir, &header.hash());
+    if snapshot.exists() {
+        return Err(Error::FileError(ErrorKind::AlreadyExists))
+    }
+    if snapshot.path().exists() {
+        cwarn!(SNAPSHOT, "Rewriting the incomplete snapshot {:x}", header.hash());
+        snapshot.remove()?;
     }
 
-    {
-        let file = File::create(path.join("head"))?;
-        let mut snappy = snap::Writer::new(file);
-
-        let mut stream = RlpStream::new();
-        stream.begin_unbounded_list();
-        for (key, value) in vec![(*root, root_val)].iter().chain(&grandchildren).chain(&children) {
-            stream.begin_list(2);
-            stream.append(key);
-            stream.append(value);
-        }
-        stream.complete_unbounded_list();
+    let (chunks, shard_roots) = write_snapshot(db, snapshot.path().to_path_buf(), header.state_root())?;
+    let mut file = File::create(snapshot.block_path())?;
+    file.write_all(&rlp::encode(snapshot_block))?;
 
-        snappy.write(&stream.drain())?;
+    let manifest = Manifest {
+        block_number: header.number(),
+        block_hash: header.hash(),
+        state_root: *header.state_root(),
+        shard_roots,
+        chunks,
+    };
+    write_manifest(&snapshot, &manifest)?;
+    cinfo!(
+        SNAPSHOT,
+        "Snapshot #{} {:x} is written: {} bytes",
+        manifest.block_number,
+        manifest.block_hash,
+        manifest.chunks_size()
+    );
+    Ok(snapshot)
+}
+
+/// Writes the manifest into a temporary file and renames it, so that the manifest is never partially written.
+fn write_manifest(snapshot: &Snapshot, manifest: &Manifest) -> Result<(), Error> {
+    let temp_path = snapshot.manifest_path().with_extension("tmp");
+    {
+        let mut file = File::create(&temp_path)?;
+        file.write_all(&rlp::encode(manifest))?;
+        file.sync_all()?;
     }
+    rename(temp_path, snapshot.manifest_path())?;
+    Ok(())
+}
 
-    for (grandchild, _) in &grandchildren {
-        let nodes = enumerate_subtree(&db, grandchild)?;
-        let file = File::create(path.join(format!("{:x}", grandchild)))?;
-        let mut snappy = snap::Writer::new(file);
+/// Removes the snapshot directories left without the manifest, e.g. by a crash while writing them.
+pub fn remove_incomplete_snapshots(root_dir: &str) -> Result<(), Error> {
+    for snapshot in Snapshot::list_incomplete(root_dir)? {
+        cwarn!(SNAPSHOT, "Removing the incomplete snapshot {:x}", snapshot.block_hash());
+        snapshot.remove()?;
+    }
+    Ok(())
+}
 
-        let mut stream = RlpStream::new();
-        stream.begin_unbounded_list();
-        for (key, value) in nodes {
-            stream.begin_list(2);
-            stream.append(&key);
-            stream.append(&value);
+/// Removes the snapshots except the latest `retention` ones.
+pub fn remove_old_snapshots(root_dir: &str, retention: usize) -> Result<(), Error> {
+    let mut snapshots = Vec::new();
+    for snapshot in Snapshot::list(root_dir)? {
+        match snapshot.read_manifest() {
+            Ok(manifest) => snapshots.push((manifest.block_number, snapshot)),
+            Err(e) => cwarn!(SNAPSHOT, "Cannot read the manifest of {:x}: {}", snapshot.block_hash(), e),
         }
-        stream.complete_unbounded_list();
+    }
+    snapshots.sort_unstable_by(|(a, _), (b, _)| b.cmp(a));
+    for (number, snapshot) in snapshots.into_iter().skip(retention) {
+        cinfo!(SNAPSHOT, "Removing the old snapshot #{} {:x}", number, snapshot.block_hash());
+        snapshot.remove()?;
+    }
+    Ok(())
+}
+
+/// Writes the head and the chunks of the state, and returns the chunks and the shard roots.
+/// The shard tries are found in the leaves of the state trie, and they're split into chunks after it.
+pub fn write_snapshot(db: &HashDB, path: PathBuf, root: &H256) -> Result<(Vec<ChunkInfo>, Vec<H256>), Error> {
+    create_dir_all(&path)?;
 
-        snappy.write(&stream.drain())?;
+    let mut head = Vec::new();
+    let mut chunks = Vec::new();
+    let mut written = HashSet::new();
+    let mut trie_roots = vec![*root];
+    let mut index = 0;
+    while index < trie_roots.len() {
+        let trie_root = trie_roots[index];
+        index += 1;
+
+        let root_val = get_node(db, &trie_root)?;
+        let children = children_of(db, &root_val)?;
+        for (_, node) in &children {
+            for chunk_root in child_keys(node) {
+                // The same subtree can be under more than one node, but it's written only once.
+                if !written.insert(chunk_root) {
+                    continue
+                }
+                let nodes = enumerate_subtree(db, &chunk_root)?;
+                add_shard_roots(&nodes, &mut trie_roots);
+                let mut stream = RlpStream::new();
+                append_nodes(&mut stream, &nodes);
+                let compressed = write_compressed(&path.join(format!("{:x}", chunk_root)), stream.out())?;
+                chunks.push(ChunkInfo::new(chunk_root, &compressed));
+            }
+        }
+        let upper: Vec<_> = once((trie_root, root_val)).chain(children).collect();
+        add_shard_roots(&upper, &mut trie_roots);
+        head.extend(upper);
     }
 
-    Ok(())
+    let shard_roots = trie_roots.split_off(1);
+    let mut stream = RlpStream::new_list(2);
+    append_nodes(&mut stream, &head);
+    stream.append_list(&shard_roots);
+    write_compressed(&path.join("head"), stream.out())?;
+
+    Ok((chunks, shard_roots))
 }
 
-fn get_node(db: &Arc<KeyValueDB>, key: &H256) -> Result<Vec<u8>, Error> {
-    match db.get(COL_STATE, key) {
-        Ok(Some(value)) => Ok(value.to_vec()),
-        Ok(None) => Err(Error::NodeNotFound(*key)),
-        Err(e) => Err(Error::DBError(e)),
+/// Adds the roots of the shard tries in the nodes that aren't added yet.
+fn add_shard_roots(nodes: &[(H256, Vec<u8>)], trie_roots: &mut Vec<H256>) {
+    for root in nodes.iter().filter_map(|(_, node)| shard_root(node)) {
+        if !trie_roots.contains(&root) {
+            trie_roots.push(root);
+        }
     }
 }
 
-fn children_of(db: &Arc<KeyValueDB>, node: &[u8]) -> Result<Vec<(H256, Vec<u8>)>, Error> {
-    let keys = match Node::decoded(node) {
-        None => Vec::new(),
-        Some(Node::Leaf(..)) => Vec::new(),
-        Some(Node::Branch(_, children)) => children.iter().filter_map(|child| *child).collect(),
-    };
+/// Appends the RLP list of `[key, node]`.
+fn append_nodes(stream: &mut RlpStream, nodes: &[(H256, Vec<u8>)]) {
+    stream.begin_list(nodes.len());
+    for (key, value) in nodes {
+        stream.begin_list(2);
+        stream.append(key);
+        stream.append(value);
+    }
+}
+
+/// Writes the snappy-compressed bytes into the file, and returns the written bytes.
+fn write_compressed(path: &Path, bytes: Bytes) -> Result<Bytes, Error> {
+    let mut snappy = snap::Writer::new(Vec::new());
+    snappy.write_all(&bytes)?;
+    let compressed = snappy.into_inner().map_err(|e| Error::FileError(e.error().kind()))?;
+
+    File::create(path)?.write_all(&compressed)?;
+    Ok(compressed)
+}
+
+fn get_node(db: &HashDB, key: &H256) -> Result<Vec<u8>, Error> {
+    db.get(key).map(|node| node.to_vec()).ok_or(Error::NodeNotFound(*key))
+}
 
+fn children_of(db: &HashDB, node: &[u8]) -> Result<Vec<(H256, Vec<u8>)>, Error> {
     let mut result = Vec::new();
-    for key in keys {
+    for key in child_keys(node) {
         result.push((key, get_node(db, &key)?));
     }
     Ok(result)
 }
 
-fn enumerate_subtree(db: &Arc<KeyValueDB>, root: &H256) -> Result<Vec<(H256, Vec<u8>)>, Error> {
+fn enumerate_subtree(db: &HashDB, root: &H256) -> Result<Vec<(H256, Vec<u8>)>, Error> {
     let node = get_node(db, root)?;
-    let children = match Node::decoded(&node) {
-        None => Vec::new(),
-        Some(Node::Leaf(..)) => Vec::new(),
-        Some(Node::Branch(_, children)) => children.iter().filter_map(|child| *child).collect(),
-    };
+    let children = child_keys(&node);
     let mut result: Vec<_> = vec![(*root, node)];
     for child in children {
         result.extend(enumerate_subtree(db, &child)?);
     }
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs::remove_file;
+
+    use super::super::super::tests::helpers::{
+        node_with_state, state_db_of, temp_root_dir, write_snapshot_of_block_in,
+    };
+    use super::*;
+
+    #[test]
+    fn creating_complete_snapshot_again_fails() {
+        let (db, root) = node_with_state(100);
+        let root_dir = temp_root_dir();
+        let snapshot = write_snapshot_of_block_in(&root_dir, &db, &root, 1);
+        match create_snapshot(state_db_of(&db).as_hashdb(), root_dir.to_str().unwrap(), &snapshot.read_block().unwrap())
+        {
+            Err(Error::FileError(ErrorKind::AlreadyExists)) => {}
+            _ => panic!("The snapshot must not be written twice"),
+        }
+    }
+
+    #[test]
+    fn rewrite_incomplete_snapshot() {
+        let (db, root) = node_with_state(1000);
+        let root_dir = temp_root_dir();
+        let snapshot = write_snapshot_of_block_in(&root_dir, &db, &root, 1);
+        let chunk_roots = snapshot.chunk_roots().unwrap();
+        remove_file(snapshot.chunk_path(&chunk_roots[0])).unwrap();
+        remove_file(snapshot.manifest_path()).unwrap();
+
+        let snapshot =
+            create_snapshot(state_db_of(&db).as_hashdb(), root_dir.to_str().unwrap(), &snapshot.read_block().unwrap())
+                .unwrap();
+        assert!(snapshot.verify().is_ok());
+    }
+
+    #[test]
+    fn remove_snapshots_without_manifest() {
+        let (db, root) = node_with_state(10);
+        let root_dir = temp_root_dir();
+        let complete = write_snapshot_of_block_in(&root_dir, &db, &root, 1);
+        let incomplete = write_snapshot_of_block_in(&root_dir, &db, &root, 2);
+        remove_file(incomplete.manifest_path()).unwrap();
+
+        remove_incomplete_snapshots(root_dir.to_str().unwrap()).unwrap();
+        assert!(complete.path().exists());
+        assert!(!incomplete.path().exists());
+    }
+
+    #[test]
+    fn keep_latest_snapshots() {
+        let (db, root) = node_with_state(10);
+        let root_dir = temp_root_dir();
+        let snapshots: Vec<_> =
+            [3, 1, 4, 2].iter().map(|n| write_snapshot_of_block_in(&root_dir, &db, &root, *n)).collect();
+
+        remove_old_snapshots(root_dir.to_str().unwrap(), 2).unwrap();
+        let remaining: Vec<_> = snapshots.iter().map(Snapshot::exists).collect();
+        assert_eq!(vec![true, false, true, false], remaining);
+    }
+}
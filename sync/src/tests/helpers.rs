@@ -0,0 +1,129 @@
+// Copyright 2018 Kodebox, Inc.
+// This file is part of CodeChain.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::env::temp_dir;
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use ccore::{Block, Header, COL_STATE};
+use ccrypto::blake256;
+use cmerkle::{TrieDBMut, TrieMut};
+use cstate::{Shard, ShardAddress};
+use journaldb::{self, Algorithm, JournalDB};
+use kvdb::{DBTransaction, KeyValueDB};
+use kvdb_memorydb;
+use memorydb::MemoryDB;
+use primitives::H256;
+use rlp;
+
+use super::super::snapshot::{child_keys, create_snapshot, shard_root, write_snapshot, Snapshot, SnapshotBlock};
+
+/// Returns the database of a node that has a state with the given number of accounts, and the state root.
+pub fn node_with_state(accounts: usize) -> (Arc<KeyValueDB>, H256) {
+    let mut memory = MemoryDB::new();
+    let mut root = H256::zero();
+    insert_items(&mut memory, &mut root, "account", accounts);
+    (db_of(memory), root)
+}
+
+/// Returns the database of a node whose state has the shards with the given numbers of assets, and the state root.
+/// The state also has 100 accounts, so that the shards are in the chunks.
+pub fn node_with_shards(assets: &[usize]) -> (Arc<KeyValueDB>, H256) {
+    let mut memory = MemoryDB::new();
+    let mut root = H256::zero();
+    insert_items(&mut memory, &mut root, "account", 100);
+    for (shard_id, assets) in assets.iter().enumerate() {
+        let mut shard_root = H256::zero();
+        insert_items(&mut memory, &mut shard_root, &format!("asset of shard {}", shard_id), *assets);
+        let shard = Shard::new(shard_root, Vec::new(), Vec::new());
+        let mut trie = TrieDBMut::from_existing(&mut memory, &mut root).unwrap();
+        trie.insert(ShardAddress::new(shard_id as u16).as_ref(), &rlp::encode(&shard)).unwrap();
+    }
+    (db_of(memory), root)
+}
+
+fn insert_items(memory: &mut MemoryDB, root: &mut H256, name: &str, count: usize) {
+    let mut trie = TrieDBMut::new(memory, root);
+    for i in 0..count {
+        let key = blake256(format!("{} {}", name, i));
+        trie.insert(&key, &blake256(&key)).unwrap();
+    }
+}
+
+fn db_of(mut memory: MemoryDB) -> Arc<KeyValueDB> {
+    let db: Arc<KeyValueDB> = Arc::new(kvdb_memorydb::create(1));
+    let mut batch = DBTransaction::new();
+    // The tries remove the nodes that they replaced.
+    for (key, (value, _)) in memory.drain().into_iter().filter(|(_, (_, rc))| *rc > 0) {
+        batch.put(COL_STATE, &key, &value);
+    }
+    db.write(batch).unwrap();
+    db
+}
+
+/// Returns the archive `JournalDB` over the state of the node.
+pub fn state_db_of(db: &Arc<KeyValueDB>) -> Box<JournalDB> {
+    journaldb::new(Arc::clone(db), Algorithm::Archive, COL_STATE)
+}
+
+/// Writes the snapshot of the state in the temporary directory.
+pub fn write_snapshot_of(db: &Arc<KeyValueDB>, root: &H256) -> Snapshot {
+    let block_hash = H256::random();
+    write_snapshot(state_db_of(db).as_hashdb(), temp_dir().join(format!("{:x}", block_hash)), root).unwrap();
+    Snapshot::new(temp_dir(), &block_hash)
+}
+
+/// Returns a new directory in the temporary directory, so that tests don't share their snapshots.
+pub fn temp_root_dir() -> PathBuf {
+    let root_dir = temp_dir().join(format!("snapshots-{:x}", H256::random()));
+    create_dir_all(&root_dir).unwrap();
+    root_dir
+}
+
+/// Writes the snapshot of a block that has the state in a new temporary directory.
+pub fn write_snapshot_of_block(db: &Arc<KeyValueDB>, root: &H256) -> Snapshot {
+    write_snapshot_of_block_in(&temp_root_dir(), db, root, 1)
+}
+
+/// Writes the snapshot of the block at the number, which has the state.
+pub fn write_snapshot_of_block_in(root_dir: &Path, db: &Arc<KeyValueDB>, root: &H256, number: u64) -> Snapshot {
+    let mut parent = Header::default();
+    parent.set_number(number - 1);
+    let mut header = Header::default();
+    header.set_parent_hash(parent.hash());
+    header.set_number(number);
+    header.set_state_root(*root);
+    header.set_extra_data(H256::random().to_vec());
+    let snapshot_block = SnapshotBlock {
+        parent,
+        block: Block {
+            header,
+            parcels: Vec::new(),
+        },
+        total_score: number.into(),
+    };
+    create_snapshot(state_db_of(db).as_hashdb(), root_dir.to_str().unwrap(), &snapshot_block).unwrap()
+}
+
+/// Compares the tries under the root, including the shard tries.
+pub fn assert_same_state(expected: &Arc<KeyValueDB>, actual: &Arc<KeyValueDB>, root: &H256) {
+    let node = expected.get(COL_STATE, root).unwrap().unwrap();
+    assert_eq!(Some(node.clone()), actual.get(COL_STATE, root).unwrap());
+    for child in child_keys(&node).into_iter().chain(shard_root(&node)) {
+        assert_same_state(expected, actual, &child);
+    }
+}
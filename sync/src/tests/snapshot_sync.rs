@@ -0,0 +1,230 @@
+// Copyright 2018 Kodebox, Inc.
+// This file is part of CodeChain.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::Arc;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use ccore::{
+    AssetClient, BlockChainClient, BlockId, BlockInfo, ChainInfo, Client, ClientConfig, ClientService, DatabaseClient,
+    Miner, MiningBlockChainClient, SignedParcel, Spec,
+};
+use ckey::{Address, Generator, KeyPair, Random};
+use cnetwork::{NodeId, TestNetworkCall, TestNetworkClient, TimerToken};
+use ctypes::parcel::{Action, ChangeShard, Parcel};
+use ctypes::transaction::{AssetMintOutput, Transaction};
+use primitives::H256;
+
+use super::super::block::BlockSyncExtension;
+use super::super::snapshot::{create_snapshot, SnapshotBlock};
+use super::helpers::{assert_same_state, temp_root_dir};
+
+const EXTENSION_NAME: &str = "block-propagation";
+/// The timer of the block sync extension.
+const SYNC_TIMER_TOKEN: TimerToken = 0;
+
+/// The null engine rewards the author of every block, so that every block has a different state.
+/// The owner of the shard is replaced with the address of a key that the test generates.
+const SPEC: &str = r#"{
+    "name": "SnapshotSync",
+    "engine": {
+        "null": {
+            "params": {
+                "blockReward": "0x0d"
+            }
+        }
+    },
+    "params": {
+        "maxExtraDataSize": "0x20",
+        "maxMetadataSize": "0x0400",
+        "networkID": "0x11",
+        "minParcelCost": "10",
+        "maxBodySize": 4194304,
+        "snapshotPeriod": 4,
+        "maxScriptCost": 10000,
+        "useShardValidator": false
+    },
+    "genesis": {
+        "seal": {
+            "generic": "0x0"
+        },
+        "score": "0x20000",
+        "author": "0x0000000000000000000000000000000000000000",
+        "timestamp": "0x00",
+        "parentHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+        "extraData": "0x"
+    },
+    "accounts": {
+        "0000000000000000000000000000000000000001": { "balance": "1000000", "nonce": "0" },
+        "{owner}": { "balance": "1000000", "nonce": "0" }
+    },
+    "shards": {
+        "0": {
+            "nonce": 0,
+            "owner": "0x{owner}",
+            "worlds": [{
+                "nonce": 0,
+                "owners": ["0x{owner}"]
+            }]
+        }
+    }
+}"#;
+
+fn spec(owner: &Address) -> Spec {
+    Spec::load(SPEC.replace("{owner}", &format!("{:x}", owner)).as_bytes()).unwrap()
+}
+
+/// Returns the parcel minting an asset in the world of the shard, and the hash of the mint transaction.
+fn mint_parcel(owner: &KeyPair, nonce: u64) -> (SignedParcel, H256) {
+    let mint = Transaction::AssetMint {
+        network_id: 0x11,
+        shard_id: 0,
+        world_id: 0,
+        metadata: format!("asset {}", nonce),
+        output: AssetMintOutput {
+            lock_script_hash: H256::random(),
+            parameters: vec![],
+            amount: Some(100),
+        },
+        registrar: None,
+        nonce: 0,
+    };
+    let mint_hash = mint.hash();
+    let parcel = Parcel {
+        nonce: nonce.into(),
+        fee: 10.into(),
+        network_id: 0x11,
+        expiration: None,
+        action: Action::ChangeShardState {
+            transactions: vec![mint],
+            changes: vec![ChangeShard {
+                shard_id: 0,
+                pre_root: H256::zero(),
+                post_root: H256::zero(),
+            }],
+            signatures: vec![],
+        },
+    };
+    (SignedParcel::new_with_sign(parcel, owner.private()), mint_hash)
+}
+
+struct Node {
+    _service: ClientService,
+    client: Arc<Client>,
+    network: TestNetworkClient,
+    id: NodeId,
+}
+
+impl Node {
+    fn new(spec: Spec, port: u16, snapshot_dir: Option<String>, snapshot_sync: bool) -> Self {
+        let miner = Arc::new(Miner::with_spec(&spec));
+        let service = ClientService::start(ClientConfig::default(), &spec, &temp_root_dir(), miner).unwrap();
+        let client = service.client();
+        let extension = BlockSyncExtension::new(Arc::clone(&client), snapshot_dir, snapshot_sync);
+        client.add_notify(extension.clone());
+        let mut network = TestNetworkClient::new();
+        network.register_extension(extension);
+        Self {
+            _service: service,
+            client,
+            network,
+            id: NodeId::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), port),
+        }
+    }
+
+    /// Imports a block of a random author with the parcels.
+    fn mine_block(&self, parcels: &[SignedParcel]) -> H256 {
+        let parent = self.client.block_header(BlockId::Latest).unwrap();
+        let producer: &MiningBlockChainClient = &*self.client;
+        let mut block = producer.prepare_open_block(Address::random(), Vec::new());
+        block.push_parcels(parcels).unwrap();
+        let block = block
+            .close_and_lock(parent.parcels_root(), parent.invoices_root())
+            .seal(self.client.engine(), Vec::new())
+            .unwrap();
+        producer.import_sealed_block(block).unwrap()
+    }
+
+    /// Delivers the messages that this node has sent to the other node.
+    fn send_messages_to(&self, other: &Node) {
+        while let Some(call) = self.network.pop_call(EXTENSION_NAME) {
+            if let TestNetworkCall::Send(to, message) = call {
+                assert_eq!(other.id, to);
+                other.network.send_message(EXTENSION_NAME, self.id, &message);
+            }
+        }
+    }
+}
+
+fn connect(a: &Node, b: &Node) {
+    a.network.add_node(EXTENSION_NAME, b.id);
+    b.network.add_node(EXTENSION_NAME, a.id);
+}
+
+#[test]
+fn fresh_node_restores_snapshot_and_downloads_the_following_blocks() {
+    let owner = Random.generate().unwrap();
+    let snapshot_dir = temp_root_dir();
+    let server = Node::new(spec(&owner.address()), 3485, Some(snapshot_dir.to_str().unwrap().to_string()), false);
+    // An asset is minted before the snapshot, and another one is minted after it.
+    let (mint_before, minted_before) = mint_parcel(&owner, 0);
+    let (mint_after, minted_after) = mint_parcel(&owner, 1);
+    let hashes: Vec<_> = (1..11)
+        .map(|number| match number {
+            2 => server.mine_block(&[mint_before.clone()]),
+            6 => server.mine_block(&[mint_after.clone()]),
+            _ => server.mine_block(&[]),
+        })
+        .collect();
+    // The best block is #10, so the snapshot of #4 is the one that the peers have.
+    let snapshot_hash = hashes[3];
+    let snapshot_block = SnapshotBlock::from_client(&*server.client, &snapshot_hash).unwrap();
+    create_snapshot(server.client.state_db().as_hashdb(), snapshot_dir.to_str().unwrap(), &snapshot_block).unwrap();
+
+    let fresh = Node::new(spec(&owner.address()), 3486, None, true);
+    connect(&server, &fresh);
+
+    let best_hash = hashes[9];
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while fresh.client.chain_info().best_block_hash != best_hash {
+        assert!(Instant::now() < deadline, "The fresh node didn't catch up with the server");
+        server.send_messages_to(&fresh);
+        fresh.send_messages_to(&server);
+        fresh.network.call_timeout(EXTENSION_NAME, SYNC_TIMER_TOKEN);
+        sleep(Duration::from_millis(10));
+    }
+
+    // The blocks before the snapshot aren't downloaded.
+    for hash in &hashes[..3] {
+        assert!(fresh.client.block_header(BlockId::Hash(*hash)).is_some());
+        assert!(fresh.client.block_body(BlockId::Hash(*hash)).is_none());
+    }
+    let snapshot_header = server.client.block_header(BlockId::Hash(snapshot_hash)).unwrap();
+    assert_same_state(&server.client.database(), &fresh.client.database(), &snapshot_header.state_root());
+    // The blocks after the snapshot are downloaded and executed.
+    for hash in &hashes[3..] {
+        assert!(fresh.client.block_body(BlockId::Hash(*hash)).is_some());
+    }
+    let best_header = server.client.block_header(BlockId::Hash(best_hash)).unwrap();
+    assert_same_state(&server.client.database(), &fresh.client.database(), &best_header.state_root());
+    // The shard trie is restored, so the assets can be read.
+    for minted in &[minted_before, minted_after] {
+        let asset = fresh.client.get_asset(*minted, 0, BlockId::Latest).unwrap();
+        assert!(asset.is_some());
+        assert_eq!(server.client.get_asset(*minted, 0, BlockId::Latest).unwrap(), asset);
+    }
+}
@@ -19,7 +19,11 @@ use rlp::{Decodable, DecoderError, Encodable, RlpStream, UntrustedRlp};
 /// Information describing execution of a parcel.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Invoice {
+    /// Every change made by the transaction is applied.
+    /// `AssetCompose` has locked all of its inputs into the composite asset,
+    /// and `AssetDecompose` has given back all of the pooled assets.
     Success,
+    /// No change made by the transaction is applied, so the assets that it tried to spend remain unspent.
     Failed,
 }
 
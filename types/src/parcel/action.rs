@@ -27,6 +27,10 @@ const PAYMENT: u8 = 2;
 const SET_REGULAR_KEY: u8 = 3;
 const CREATE_SHARD: u8 = 4;
 const CUSTOM: u8 = 5;
+const SET_SHARD_OWNERS: u8 = 6;
+const SET_SHARD_USERS: u8 = 7;
+const WRAP_CCC: u8 = 8;
+const BATCH: u8 = 9;
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, RlpDecodable, RlpEncodable)]
 #[serde(rename_all = "camelCase")]
@@ -54,7 +58,31 @@ pub enum Action {
         key: Public,
     },
     CreateShard,
-    Custom(Bytes),
+    SetShardOwners {
+        shard_id: ShardId,
+        owners: Vec<Address>,
+    },
+    SetShardUsers {
+        shard_id: ShardId,
+        users: Vec<Address>,
+    },
+    /// Moves CCC from the balance of the sender into an asset of the wrapped CCC in the shard.
+    /// `AssetUnwrapCCC` burns the asset and gives the CCC back.
+    WrapCCC {
+        shard_id: ShardId,
+        lock_script_hash: H256,
+        parameters: Vec<Bytes>,
+        amount: u64,
+    },
+    /// Applies the actions in order. If any of them fails, none of them is applied.
+    Batch {
+        actions: Vec<Action>,
+    },
+    /// Executed by the custom action handler that the chain spec registers with `handler_id`.
+    Custom {
+        handler_id: u64,
+        bytes: Bytes,
+    },
 }
 
 impl Action {
@@ -62,6 +90,28 @@ impl Action {
         let rlp = self.rlp_bytes();
         Blake::blake(rlp)
     }
+
+    /// Returns true if the action can be in a `Batch`.
+    /// Only the actions that have a single invoice can be batched.
+    /// `WrapCCC` isn't allowed either, because its asset is identified by the hash of the parcel.
+    pub fn is_batchable(&self) -> bool {
+        match self {
+            Action::Payment {
+                ..
+            }
+            | Action::SetRegularKey {
+                ..
+            }
+            | Action::CreateShard
+            | Action::SetShardOwners {
+                ..
+            }
+            | Action::SetShardUsers {
+                ..
+            } => true,
+            _ => false,
+        }
+    }
 }
 
 impl Encodable for Action {
@@ -98,9 +148,51 @@ impl Encodable for Action {
                 s.begin_list(1);
                 s.append(&CREATE_SHARD);
             }
-            Action::Custom(bytes) => {
+            Action::SetShardOwners {
+                shard_id,
+                owners,
+            } => {
+                s.begin_list(3);
+                s.append(&SET_SHARD_OWNERS);
+                s.append(shard_id);
+                s.append_list(owners);
+            }
+            Action::SetShardUsers {
+                shard_id,
+                users,
+            } => {
+                s.begin_list(3);
+                s.append(&SET_SHARD_USERS);
+                s.append(shard_id);
+                s.append_list(users);
+            }
+            Action::WrapCCC {
+                shard_id,
+                lock_script_hash,
+                parameters,
+                amount,
+            } => {
+                s.begin_list(5);
+                s.append(&WRAP_CCC);
+                s.append(shard_id);
+                s.append(lock_script_hash);
+                s.append(parameters);
+                s.append(amount);
+            }
+            Action::Batch {
+                actions,
+            } => {
                 s.begin_list(2);
+                s.append(&BATCH);
+                s.append_list(actions);
+            }
+            Action::Custom {
+                handler_id,
+                bytes,
+            } => {
+                s.begin_list(3);
                 s.append(&CUSTOM);
+                s.append(handler_id);
                 s.append(bytes);
             }
         }
@@ -143,11 +235,58 @@ impl Decodable for Action {
                 }
                 Ok(Action::CreateShard)
             }
-            CUSTOM => {
+            SET_SHARD_OWNERS => {
+                if rlp.item_count()? != 3 {
+                    return Err(DecoderError::RlpIncorrectListLen)
+                }
+                Ok(Action::SetShardOwners {
+                    shard_id: rlp.val_at(1)?,
+                    owners: rlp.list_at(2)?,
+                })
+            }
+            SET_SHARD_USERS => {
+                if rlp.item_count()? != 3 {
+                    return Err(DecoderError::RlpIncorrectListLen)
+                }
+                Ok(Action::SetShardUsers {
+                    shard_id: rlp.val_at(1)?,
+                    users: rlp.list_at(2)?,
+                })
+            }
+            WRAP_CCC => {
+                if rlp.item_count()? != 5 {
+                    return Err(DecoderError::RlpIncorrectListLen)
+                }
+                Ok(Action::WrapCCC {
+                    shard_id: rlp.val_at(1)?,
+                    lock_script_hash: rlp.val_at(2)?,
+                    parameters: rlp.val_at(3)?,
+                    amount: rlp.val_at(4)?,
+                })
+            }
+            BATCH => {
                 if rlp.item_count()? != 2 {
                     return Err(DecoderError::RlpIncorrectListLen)
                 }
-                Ok(Action::Custom(rlp.val_at(1)?))
+                let actions = rlp.at(1)?;
+                // A nested batch is rejected before it is decoded, so that a deep nesting can't overflow the stack.
+                for action in actions.iter() {
+                    if action.val_at::<u8>(0)? == BATCH {
+                        return Err(DecoderError::Custom("Nested batch"))
+                    }
+                }
+                Ok(Action::Batch {
+                    actions: actions.as_list()?,
+                })
+            }
+            CUSTOM => {
+                if rlp.item_count()? != 3 {
+                    return Err(DecoderError::RlpIncorrectListLen)
+                }
+                Ok(Action::Custom {
+                    handler_id: rlp.val_at(1)?,
+                    bytes: rlp.val_at(2)?,
+                })
             }
             _ => Err(DecoderError::Custom("Unexpected action prefix")),
         }
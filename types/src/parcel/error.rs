@@ -21,7 +21,8 @@ use primitives::{H256, U256};
 use unexpected::Mismatch;
 
 use super::super::transaction::Error as TransactionError;
-use super::super::ShardId;
+use super::super::{BlockNumber, ShardId};
+use super::Expiration;
 
 #[derive(Debug, PartialEq, Clone)]
 /// Errors concerning parcel processing.
@@ -70,12 +71,36 @@ pub enum Error {
     /// Signature error
     InvalidSignature(String),
     InconsistentShardOutcomes,
+    /// The parcel changes the shard more than once.
+    DuplicatedShardChange(ShardId),
+    /// A transaction is related to a shard that the parcel doesn't change.
+    ShardChangeMissing(ShardId),
     ParcelsTooBig,
     RegularKeyAlreadyInUse,
     RegularKeyAlreadyInUseAsMaster,
     InvalidTransferDestination,
+    /// The sender is not allowed to change the shard or to mint assets in it.
+    InsufficientPermission,
+    /// The sender must remain one of the shard owners.
+    NewOwnersMustContainSender,
     /// Transaction error
     InvalidTransaction(TransactionError),
+    /// The fee payer of a sponsored parcel is the sender itself.
+    InvalidFeePayer,
+    /// The action can't be in a batch.
+    InvalidBatchedAction,
+    /// The batch has no action.
+    EmptyBatch,
+    /// No custom action handler has the id, or the handler can't understand the action.
+    InvalidCustomAction,
+    /// The supply of the wrapped CCC in the shard doesn't match the CCC that is wrapped or given back.
+    InconsistentWrappedCCCSupply(ShardId),
+    /// The parcel is included in a block after its expiration.
+    Expired {
+        expiration: Expiration,
+        block_number: BlockNumber,
+        timestamp: u64,
+    },
 }
 
 impl Display for Error {
@@ -106,11 +131,30 @@ impl Display for Error {
             Error::NotAllowed => "Sender does not have permissions to execute this type of transaction".into(),
             Error::InvalidSignature(err) => format!("Parcel has invalid signature: {}.", err),
             Error::InconsistentShardOutcomes => "Shard outcomes are inconsistent".to_string(),
+            Error::DuplicatedShardChange(shard_id) => format!("Shard {} is changed more than once", shard_id),
+            Error::ShardChangeMissing(shard_id) => format!("The change of shard {} is missing", shard_id),
             Error::ParcelsTooBig => "Parcel size exceeded the body size limit".to_string(),
             Error::RegularKeyAlreadyInUse => "The regular key is already registered to another account".to_string(),
             Error::RegularKeyAlreadyInUseAsMaster => "The regular key is already used as a master account".to_string(),
             Error::InvalidTransferDestination => "Transfer receiver is not valid account".to_string(),
+            Error::InsufficientPermission => "Sender doesn't have a permission".to_string(),
+            Error::NewOwnersMustContainSender => "New owners must contain the sender".to_string(),
             Error::InvalidTransaction(err) => format!("Parcel has an invalid transaction: {}", err).to_string(),
+            Error::InvalidFeePayer => "The fee payer must be different from the sender".to_string(),
+            Error::InvalidBatchedAction => "The action can't be in a batch".to_string(),
+            Error::EmptyBatch => "The batch must have at least one action".to_string(),
+            Error::InvalidCustomAction => "No custom action handler can execute the action".to_string(),
+            Error::InconsistentWrappedCCCSupply(shard_id) => {
+                format!("The supply of the wrapped CCC in shard {} is inconsistent", shard_id)
+            }
+            Error::Expired {
+                expiration,
+                block_number,
+                timestamp,
+            } => format!(
+                "The parcel expired at {} but the block is #{} at timestamp {}",
+                expiration, block_number, timestamp
+            ),
         };
 
         f.write_fmt(format_args!("Parcel error ({})", msg))
@@ -0,0 +1,111 @@
+// Copyright 2018 Kodebox, Inc.
+// This file is part of CodeChain.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::fmt::{Display, Formatter, Result as FormatResult};
+
+use rlp::{Decodable, DecoderError, Encodable, RlpStream, UntrustedRlp};
+
+use super::super::BlockNumber;
+
+const BLOCK_NUMBER: u8 = 1;
+const TIMESTAMP: u8 = 2;
+
+/// The last block in which a parcel can be included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Expiration {
+    /// The parcel can't be included in the blocks whose number is greater than this.
+    BlockNumber(BlockNumber),
+    /// The parcel can't be included in the blocks whose timestamp is greater than this.
+    Timestamp(u64),
+}
+
+impl Expiration {
+    /// Returns true if the block of the number and the timestamp can't include the parcel.
+    pub fn is_expired(&self, block_number: BlockNumber, timestamp: u64) -> bool {
+        match self {
+            Expiration::BlockNumber(expiration) => block_number > *expiration,
+            Expiration::Timestamp(expiration) => timestamp > *expiration,
+        }
+    }
+}
+
+impl Display for Expiration {
+    fn fmt(&self, f: &mut Formatter) -> FormatResult {
+        match self {
+            Expiration::BlockNumber(block_number) => write!(f, "block #{}", block_number),
+            Expiration::Timestamp(timestamp) => write!(f, "timestamp {}", timestamp),
+        }
+    }
+}
+
+impl Encodable for Expiration {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(2);
+        match self {
+            Expiration::BlockNumber(block_number) => {
+                s.append(&BLOCK_NUMBER);
+                s.append(block_number);
+            }
+            Expiration::Timestamp(timestamp) => {
+                s.append(&TIMESTAMP);
+                s.append(timestamp);
+            }
+        }
+    }
+}
+
+impl Decodable for Expiration {
+    fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+        if rlp.item_count()? != 2 {
+            return Err(DecoderError::RlpIncorrectListLen)
+        }
+        match rlp.val_at(0)? {
+            BLOCK_NUMBER => Ok(Expiration::BlockNumber(rlp.val_at(1)?)),
+            TIMESTAMP => Ok(Expiration::Timestamp(rlp.val_at(1)?)),
+            _ => Err(DecoderError::Custom("Unexpected expiration type")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_and_decode_block_number_expiration() {
+        rlp_encode_and_decode_test!(Expiration::BlockNumber(10));
+    }
+
+    #[test]
+    fn encode_and_decode_timestamp_expiration() {
+        rlp_encode_and_decode_test!(Expiration::Timestamp(1_540_000_000));
+    }
+
+    #[test]
+    fn expired_after_the_block_number() {
+        let expiration = Expiration::BlockNumber(10);
+        assert!(!expiration.is_expired(10, ::std::u64::MAX));
+        assert!(expiration.is_expired(11, 0));
+    }
+
+    #[test]
+    fn expired_after_the_timestamp() {
+        let expiration = Expiration::Timestamp(1_540_000_000);
+        assert!(!expiration.is_expired(::std::u64::MAX, 1_540_000_000));
+        assert!(expiration.is_expired(0, 1_540_000_001));
+    }
+}
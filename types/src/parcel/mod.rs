@@ -16,10 +16,12 @@
 
 mod action;
 mod error;
+mod expiration;
 mod outcome;
 mod parcel;
 
 pub use self::action::{Action, ChangeShard};
 pub use self::error::Error;
+pub use self::expiration::Expiration;
 pub use self::outcome::Outcome;
 pub use self::parcel::Parcel;
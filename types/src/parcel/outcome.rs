@@ -25,4 +25,21 @@ pub enum Outcome {
         error: Option<Error>,
     },
     Transactions(Vec<TransactionOutcome>),
+    /// The outcomes of the actions in a `Batch`, which are all failed if any of them has failed.
+    Batch(Vec<Outcome>),
+}
+
+impl Outcome {
+    /// Returns false if the action has failed.
+    /// The transactions of `ChangeShardState` succeed or fail separately, so it never fails as a whole.
+    pub fn is_success(&self) -> bool {
+        match self {
+            Outcome::Single {
+                invoice,
+                ..
+            } => *invoice == Invoice::Success,
+            Outcome::Transactions(_) => true,
+            Outcome::Batch(outcomes) => outcomes.iter().all(Outcome::is_success),
+        }
+    }
 }
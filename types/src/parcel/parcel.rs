@@ -15,11 +15,13 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use ccrypto::blake256;
+use ckey::Address;
 use heapsize::HeapSizeOf;
 use primitives::{H256, U256};
 use rlp::RlpStream;
 
-use super::Action;
+use super::super::BlockNumber;
+use super::{Action, Expiration};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Parcel {
@@ -29,6 +31,8 @@ pub struct Parcel {
     pub fee: U256,
     /// Mainnet or Testnet
     pub network_id: u64,
+    /// The last block in which the parcel can be included.
+    pub expiration: Option<Expiration>,
 
     pub action: Action,
 }
@@ -42,10 +46,11 @@ impl HeapSizeOf for Parcel {
 impl Parcel {
     /// Append object with a without signature into RLP stream
     pub fn rlp_append_unsigned_parcel(&self, s: &mut RlpStream) {
-        s.begin_list(4);
+        s.begin_list(5);
         s.append(&self.nonce);
         s.append(&self.fee);
         s.append(&self.network_id);
+        s.append(&self.expiration);
         s.append(&self.action);
     }
 
@@ -56,6 +61,25 @@ impl Parcel {
         blake256(stream.as_raw())
     }
 
+    /// The message hash that the sender of a sponsored parcel signs.
+    /// It includes the fee payer so that no other account can pay the fee with the signature.
+    pub fn hash_with_fee_payer(&self, fee_payer: &Address) -> H256 {
+        let mut stream = RlpStream::new();
+        stream.begin_list(6);
+        stream.append(&self.nonce);
+        stream.append(&self.fee);
+        stream.append(&self.network_id);
+        stream.append(&self.expiration);
+        stream.append(&self.action);
+        stream.append(fee_payer);
+        blake256(stream.as_raw())
+    }
+
+    /// Returns true if the parcel can't be included in the block of the given number and timestamp.
+    pub fn is_expired(&self, block_number: BlockNumber, timestamp: u64) -> bool {
+        self.expiration.map_or(false, |expiration| expiration.is_expired(block_number, timestamp))
+    }
+
     pub fn iter_transactions<'a>(&'a self) -> Box<Iterator<Item = H256> + 'a> {
         match &self.action {
             Action::ChangeShardState {
@@ -20,6 +20,8 @@ use ckey::Address;
 use primitives::{H256, U256};
 use unexpected::Mismatch;
 
+use super::super::WorldId;
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Error {
     InvalidPaymentSender(Mismatch<Address>),
@@ -48,6 +50,28 @@ pub enum Error {
     InvalidNetworkId(Mismatch<u64>),
     /// Returned when the sum of the transaction's inputs is different from the sum of outputs.
     InconsistentTransactionInOut,
+    /// The world doesn't exist in the shard.
+    InvalidWorldId(WorldId),
+    InvalidShardNonce(Mismatch<u64>),
+    InvalidWorldNonce(Mismatch<u64>),
+    /// The sender is not allowed to send the transaction.
+    InsufficientPermission,
+    /// The amount of assets to issue is zero.
+    ZeroAmount,
+    /// The supply of the asset scheme cannot be increased because it is infinite or it would overflow.
+    CannotIncreaseSupply(H256),
+    /// The asset is frozen by the registrar of its asset scheme.
+    AssetFrozen(H256),
+    /// The transaction has no inputs.
+    EmptyInput,
+    /// An input of the transaction is not in the shard that the transaction belongs to.
+    InconsistentShardOfInputs,
+    /// More than one input of the transaction spends the same asset.
+    DuplicatedPreviousOutput,
+    /// The input of `AssetDecompose` is not a composite asset or it doesn't hold the whole supply.
+    InvalidDecomposedInput(H256),
+    /// The outputs of `AssetDecompose` are different from the assets that were composed.
+    InvalidDecomposedOutput,
 }
 
 impl Display for Error {
@@ -81,6 +105,20 @@ impl Display for Error {
             Error::InconsistentTransactionInOut => {
                 write!(f, "The sum of the transaction's inputs is different from the sum of the transaction's outputs")
             }
+            Error::InvalidWorldId(world_id) => write!(f, "Invalid world id {}", world_id),
+            Error::InvalidShardNonce(mismatch) => write!(f, "Invalid shard nonce. {}", mismatch),
+            Error::InvalidWorldNonce(mismatch) => write!(f, "Invalid world nonce. {}", mismatch),
+            Error::InsufficientPermission => write!(f, "The sender doesn't have the permission"),
+            Error::ZeroAmount => write!(f, "The amount must be larger than zero"),
+            Error::CannotIncreaseSupply(asset_type) => write!(f, "Cannot increase the supply of {}", asset_type),
+            Error::AssetFrozen(addr) => write!(f, "Asset is frozen: {}", addr),
+            Error::EmptyInput => write!(f, "The transaction has no inputs"),
+            Error::InconsistentShardOfInputs => write!(f, "The inputs must be in the shard of the transaction"),
+            Error::DuplicatedPreviousOutput => write!(f, "The same asset is spent more than once"),
+            Error::InvalidDecomposedInput(addr) => write!(f, "Asset {} cannot be decomposed", addr),
+            Error::InvalidDecomposedOutput => {
+                write!(f, "The outputs must be the same as the assets that were composed")
+            }
         }
     }
 }
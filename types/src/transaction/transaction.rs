@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::Cursor;
 
 use byteorder::{BigEndian, ReadBytesExt};
@@ -23,7 +23,7 @@ use ckey::Address;
 use primitives::{Bytes, H256, U128};
 use rlp::{Decodable, DecoderError, Encodable, RlpStream, UntrustedRlp};
 
-use super::super::ShardId;
+use super::super::{ShardId, WorldId};
 use super::error::Error;
 
 #[derive(Debug, Clone, Eq, PartialEq, RlpDecodable, RlpEncodable, Deserialize, Serialize)]
@@ -32,6 +32,8 @@ pub struct AssetOutPoint {
     pub transaction_hash: H256,
     pub index: usize,
     pub asset_type: H256,
+    /// The shard that the asset is in, which can be different from the shard of its asset scheme.
+    pub shard_id: ShardId,
     pub amount: u64,
 }
 
@@ -49,6 +51,8 @@ pub struct AssetTransferOutput {
     pub lock_script_hash: H256,
     pub parameters: Vec<Bytes>,
     pub asset_type: H256,
+    /// The shard that the asset is created in.
+    pub shard_id: ShardId,
     pub amount: u64,
 }
 
@@ -56,10 +60,26 @@ pub struct AssetTransferOutput {
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase", tag = "type", content = "data")]
 pub enum Transaction {
+    #[serde(rename_all = "camelCase")]
+    CreateWorld {
+        network_id: u64,
+        shard_id: ShardId,
+        nonce: u64,
+        owners: Vec<Address>,
+    },
+    #[serde(rename_all = "camelCase")]
+    SetWorldOwners {
+        network_id: u64,
+        shard_id: ShardId,
+        world_id: WorldId,
+        nonce: u64,
+        owners: Vec<Address>,
+    },
     #[serde(rename_all = "camelCase")]
     AssetMint {
         network_id: u64,
         shard_id: ShardId,
+        world_id: WorldId,
         metadata: String,
         registrar: Option<Address>,
         nonce: u64,
@@ -74,6 +94,70 @@ pub enum Transaction {
         outputs: Vec<AssetTransferOutput>,
         nonce: u64,
     },
+    /// Replaces the registrar of the asset scheme. Only the current registrar can send it.
+    #[serde(rename_all = "camelCase")]
+    AssetChangeRegistrar {
+        network_id: u64,
+        asset_type: H256,
+        registrar: Option<Address>,
+        nonce: u64,
+    },
+    /// Issues more assets of a permissioned asset scheme that has a finite amount.
+    #[serde(rename_all = "camelCase")]
+    AssetIncreaseSupply {
+        network_id: u64,
+        asset_type: H256,
+        lock_script_hash: H256,
+        parameters: Vec<Bytes>,
+        amount: u64,
+        nonce: u64,
+    },
+    /// Prevents the asset from being spent until it is unfrozen.
+    #[serde(rename_all = "camelCase")]
+    AssetFreeze {
+        network_id: u64,
+        outpoint: AssetOutPoint,
+        nonce: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    AssetUnfreeze {
+        network_id: u64,
+        outpoint: AssetOutPoint,
+        nonce: u64,
+    },
+    /// Locks the inputs into a composite asset of a new asset scheme.
+    /// Like `AssetMint`, the asset type of the new scheme is derived from the hash of the transaction,
+    /// the shard and the world.
+    #[serde(rename_all = "camelCase")]
+    AssetCompose {
+        network_id: u64,
+        shard_id: ShardId,
+        world_id: WorldId,
+        metadata: String,
+        registrar: Option<Address>,
+        inputs: Vec<AssetTransferInput>,
+        lock_script_hash: H256,
+        parameters: Vec<Bytes>,
+        amount: u64,
+        nonce: u64,
+    },
+    /// Spends the whole supply of a composite asset and gives back the assets that were composed into it.
+    #[serde(rename_all = "camelCase")]
+    AssetDecompose {
+        network_id: u64,
+        input: AssetTransferInput,
+        outputs: Vec<AssetTransferOutput>,
+        nonce: u64,
+    },
+    /// Burns an asset of the wrapped CCC and gives the CCC back to the receiver.
+    /// The asset must be in the shard where it was wrapped.
+    #[serde(rename_all = "camelCase")]
+    AssetUnwrapCCC {
+        network_id: u64,
+        burn: AssetTransferInput,
+        receiver: Address,
+        nonce: u64,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
@@ -94,22 +178,8 @@ impl Transaction {
                 outputs,
                 nonce,
             } => {
-                let new_burns: Vec<_> = burns
-                    .iter()
-                    .map(|input| AssetTransferInput {
-                        prev_out: input.prev_out.clone(),
-                        lock_script: Vec::new(),
-                        unlock_script: Vec::new(),
-                    })
-                    .collect();
-                let new_inputs: Vec<_> = inputs
-                    .iter()
-                    .map(|input| AssetTransferInput {
-                        prev_out: input.prev_out.clone(),
-                        lock_script: Vec::new(),
-                        unlock_script: Vec::new(),
-                    })
-                    .collect();
+                let new_burns: Vec<_> = burns.iter().map(AssetTransferInput::without_script).collect();
+                let new_inputs: Vec<_> = inputs.iter().map(AssetTransferInput::without_script).collect();
                 Transaction::AssetTransfer {
                     network_id: *network_id,
                     burns: new_burns,
@@ -118,6 +188,51 @@ impl Transaction {
                     nonce: *nonce,
                 }
             }
+            Transaction::AssetCompose {
+                network_id,
+                shard_id,
+                world_id,
+                metadata,
+                registrar,
+                inputs,
+                lock_script_hash,
+                parameters,
+                amount,
+                nonce,
+            } => Transaction::AssetCompose {
+                network_id: *network_id,
+                shard_id: *shard_id,
+                world_id: *world_id,
+                metadata: metadata.clone(),
+                registrar: *registrar,
+                inputs: inputs.iter().map(AssetTransferInput::without_script).collect(),
+                lock_script_hash: *lock_script_hash,
+                parameters: parameters.clone(),
+                amount: *amount,
+                nonce: *nonce,
+            },
+            Transaction::AssetDecompose {
+                network_id,
+                input,
+                outputs,
+                nonce,
+            } => Transaction::AssetDecompose {
+                network_id: *network_id,
+                input: input.without_script(),
+                outputs: outputs.clone(),
+                nonce: *nonce,
+            },
+            Transaction::AssetUnwrapCCC {
+                network_id,
+                burn,
+                receiver,
+                nonce,
+            } => Transaction::AssetUnwrapCCC {
+                network_id: *network_id,
+                burn: burn.without_script(),
+                receiver: *receiver,
+                nonce: *nonce,
+            },
             _ => unreachable!(),
         }
     }
@@ -132,6 +247,14 @@ impl Transaction {
 
     pub fn network_id(&self) -> u64 {
         match self {
+            Transaction::CreateWorld {
+                network_id,
+                ..
+            } => *network_id,
+            Transaction::SetWorldOwners {
+                network_id,
+                ..
+            } => *network_id,
             Transaction::AssetTransfer {
                 network_id,
                 ..
@@ -140,6 +263,34 @@ impl Transaction {
                 network_id,
                 ..
             } => *network_id,
+            Transaction::AssetChangeRegistrar {
+                network_id,
+                ..
+            } => *network_id,
+            Transaction::AssetIncreaseSupply {
+                network_id,
+                ..
+            } => *network_id,
+            Transaction::AssetFreeze {
+                network_id,
+                ..
+            } => *network_id,
+            Transaction::AssetUnfreeze {
+                network_id,
+                ..
+            } => *network_id,
+            Transaction::AssetCompose {
+                network_id,
+                ..
+            } => *network_id,
+            Transaction::AssetDecompose {
+                network_id,
+                ..
+            } => *network_id,
+            Transaction::AssetUnwrapCCC {
+                network_id,
+                ..
+            } => *network_id,
         }
     }
 
@@ -148,44 +299,156 @@ impl Transaction {
             Transaction::AssetTransfer {
                 burns,
                 inputs,
+                outputs,
                 ..
             } => {
                 let mut shards: Vec<ShardId> = burns
                     .iter()
                     .map(AssetTransferInput::related_shard)
                     .chain(inputs.iter().map(AssetTransferInput::related_shard))
+                    .chain(outputs.iter().map(|output| output.shard_id))
                     .collect();
                 shards.sort_unstable();
                 shards.dedup();
                 shards
             }
+            Transaction::CreateWorld {
+                shard_id,
+                ..
+            } => vec![*shard_id],
+            Transaction::SetWorldOwners {
+                shard_id,
+                ..
+            } => vec![*shard_id],
             Transaction::AssetMint {
                 shard_id,
                 ..
             } => vec![*shard_id],
+            Transaction::AssetChangeRegistrar {
+                asset_type,
+                ..
+            } => vec![shard_id_of_asset_type(asset_type)],
+            Transaction::AssetIncreaseSupply {
+                asset_type,
+                ..
+            } => vec![shard_id_of_asset_type(asset_type)],
+            Transaction::AssetFreeze {
+                outpoint,
+                ..
+            } => vec![outpoint.related_shard()],
+            Transaction::AssetUnfreeze {
+                outpoint,
+                ..
+            } => vec![outpoint.related_shard()],
+            Transaction::AssetCompose {
+                shard_id,
+                ..
+            } => vec![*shard_id],
+            Transaction::AssetDecompose {
+                input,
+                outputs,
+                ..
+            } => {
+                let mut shards: Vec<ShardId> = outputs
+                    .iter()
+                    .map(|output| output.shard_id)
+                    .chain(::std::iter::once(input.related_shard()))
+                    .collect();
+                shards.sort_unstable();
+                shards.dedup();
+                shards
+            }
+            Transaction::AssetUnwrapCCC {
+                burn,
+                ..
+            } => vec![burn.related_shard()],
         }
     }
 
     pub fn verify(&self) -> Result<(), Error> {
         match self {
             Transaction::AssetTransfer {
+                burns,
                 inputs,
                 outputs,
                 ..
             } => {
+                if has_duplicated_prev_out(burns.iter().chain(inputs.iter())) {
+                    return Err(Error::DuplicatedPreviousOutput)
+                }
                 // FIXME: check burns
                 if !is_input_and_output_consistent(inputs, outputs) {
                     return Err(Error::InconsistentTransactionInOut)
                 }
                 Ok(())
             }
+            Transaction::CreateWorld {
+                ..
+            } => Ok(()),
+            Transaction::SetWorldOwners {
+                ..
+            } => Ok(()),
             Transaction::AssetMint {
                 ..
             } => Ok(()),
+            Transaction::AssetChangeRegistrar {
+                ..
+            } => Ok(()),
+            Transaction::AssetIncreaseSupply {
+                amount,
+                ..
+            } => {
+                if *amount == 0 {
+                    return Err(Error::ZeroAmount)
+                }
+                Ok(())
+            }
+            Transaction::AssetFreeze {
+                ..
+            } => Ok(()),
+            Transaction::AssetUnfreeze {
+                ..
+            } => Ok(()),
+            Transaction::AssetCompose {
+                shard_id,
+                inputs,
+                amount,
+                ..
+            } => {
+                if inputs.is_empty() {
+                    return Err(Error::EmptyInput)
+                }
+                if *amount == 0 {
+                    return Err(Error::ZeroAmount)
+                }
+                if inputs.iter().any(|input| input.related_shard() != *shard_id) {
+                    return Err(Error::InconsistentShardOfInputs)
+                }
+                if has_duplicated_prev_out(inputs.iter()) {
+                    return Err(Error::DuplicatedPreviousOutput)
+                }
+                Ok(())
+            }
+            Transaction::AssetDecompose {
+                ..
+            } => Ok(()),
+            Transaction::AssetUnwrapCCC {
+                ..
+            } => Ok(()),
         }
     }
 }
 
+/// Returns true if more than one input spends the same asset.
+fn has_duplicated_prev_out<'a, I>(inputs: I) -> bool
+where
+    I: Iterator<Item = &'a AssetTransferInput>, {
+    let mut prev_outs = HashSet::new();
+    inputs
+        .map(|input| &input.prev_out)
+        .any(|prev_out| !prev_outs.insert((prev_out.transaction_hash, prev_out.index, prev_out.shard_id)))
+}
+
 fn is_input_and_output_consistent(inputs: &[AssetTransferInput], outputs: &[AssetTransferOutput]) -> bool {
     let mut sum: HashMap<H256, U128> = HashMap::new();
 
@@ -214,27 +477,60 @@ fn is_input_and_output_consistent(inputs: &[AssetTransferInput], outputs: &[Asse
 }
 
 type TransactionId = u8;
+const CREATE_WORLD_ID: TransactionId = 0x01;
+const SET_WORLD_OWNERS_ID: TransactionId = 0x02;
 const ASSET_MINT_ID: TransactionId = 0x03;
 const ASSET_TRANSFER_ID: TransactionId = 0x04;
+const ASSET_CHANGE_REGISTRAR_ID: TransactionId = 0x05;
+const ASSET_INCREASE_SUPPLY_ID: TransactionId = 0x06;
+const ASSET_FREEZE_ID: TransactionId = 0x07;
+const ASSET_UNFREEZE_ID: TransactionId = 0x08;
+const ASSET_COMPOSE_ID: TransactionId = 0x09;
+const ASSET_DECOMPOSE_ID: TransactionId = 0x0a;
+const ASSET_UNWRAP_CCC_ID: TransactionId = 0x0b;
 
 impl Decodable for Transaction {
     fn decode(d: &UntrustedRlp) -> Result<Self, DecoderError> {
         match d.val_at(0)? {
+            CREATE_WORLD_ID => {
+                if d.item_count()? != 5 {
+                    return Err(DecoderError::RlpIncorrectListLen)
+                }
+                Ok(Transaction::CreateWorld {
+                    network_id: d.val_at(1)?,
+                    shard_id: d.val_at(2)?,
+                    nonce: d.val_at(3)?,
+                    owners: d.list_at(4)?,
+                })
+            }
+            SET_WORLD_OWNERS_ID => {
+                if d.item_count()? != 6 {
+                    return Err(DecoderError::RlpIncorrectListLen)
+                }
+                Ok(Transaction::SetWorldOwners {
+                    network_id: d.val_at(1)?,
+                    shard_id: d.val_at(2)?,
+                    world_id: d.val_at(3)?,
+                    nonce: d.val_at(4)?,
+                    owners: d.list_at(5)?,
+                })
+            }
             ASSET_MINT_ID => {
-                if d.item_count()? != 9 {
+                if d.item_count()? != 10 {
                     return Err(DecoderError::RlpIncorrectListLen)
                 }
                 Ok(Transaction::AssetMint {
                     network_id: d.val_at(1)?,
                     shard_id: d.val_at(2)?,
-                    metadata: d.val_at(3)?,
+                    world_id: d.val_at(3)?,
+                    metadata: d.val_at(4)?,
                     output: AssetMintOutput {
-                        lock_script_hash: d.val_at(4)?,
-                        parameters: d.val_at(5)?,
-                        amount: d.val_at(6)?,
+                        lock_script_hash: d.val_at(5)?,
+                        parameters: d.val_at(6)?,
+                        amount: d.val_at(7)?,
                     },
-                    registrar: d.val_at(7)?,
-                    nonce: d.val_at(8)?,
+                    registrar: d.val_at(8)?,
+                    nonce: d.val_at(9)?,
                 })
             }
             ASSET_TRANSFER_ID => {
@@ -249,6 +545,89 @@ impl Decodable for Transaction {
                     nonce: d.val_at(5)?,
                 })
             }
+            ASSET_CHANGE_REGISTRAR_ID => {
+                if d.item_count()? != 5 {
+                    return Err(DecoderError::RlpIncorrectListLen)
+                }
+                Ok(Transaction::AssetChangeRegistrar {
+                    network_id: d.val_at(1)?,
+                    asset_type: d.val_at(2)?,
+                    registrar: d.val_at(3)?,
+                    nonce: d.val_at(4)?,
+                })
+            }
+            ASSET_INCREASE_SUPPLY_ID => {
+                if d.item_count()? != 7 {
+                    return Err(DecoderError::RlpIncorrectListLen)
+                }
+                Ok(Transaction::AssetIncreaseSupply {
+                    network_id: d.val_at(1)?,
+                    asset_type: d.val_at(2)?,
+                    lock_script_hash: d.val_at(3)?,
+                    parameters: d.val_at(4)?,
+                    amount: d.val_at(5)?,
+                    nonce: d.val_at(6)?,
+                })
+            }
+            ASSET_FREEZE_ID => {
+                if d.item_count()? != 4 {
+                    return Err(DecoderError::RlpIncorrectListLen)
+                }
+                Ok(Transaction::AssetFreeze {
+                    network_id: d.val_at(1)?,
+                    outpoint: d.val_at(2)?,
+                    nonce: d.val_at(3)?,
+                })
+            }
+            ASSET_UNFREEZE_ID => {
+                if d.item_count()? != 4 {
+                    return Err(DecoderError::RlpIncorrectListLen)
+                }
+                Ok(Transaction::AssetUnfreeze {
+                    network_id: d.val_at(1)?,
+                    outpoint: d.val_at(2)?,
+                    nonce: d.val_at(3)?,
+                })
+            }
+            ASSET_COMPOSE_ID => {
+                if d.item_count()? != 11 {
+                    return Err(DecoderError::RlpIncorrectListLen)
+                }
+                Ok(Transaction::AssetCompose {
+                    network_id: d.val_at(1)?,
+                    shard_id: d.val_at(2)?,
+                    world_id: d.val_at(3)?,
+                    metadata: d.val_at(4)?,
+                    registrar: d.val_at(5)?,
+                    inputs: d.list_at(6)?,
+                    lock_script_hash: d.val_at(7)?,
+                    parameters: d.val_at(8)?,
+                    amount: d.val_at(9)?,
+                    nonce: d.val_at(10)?,
+                })
+            }
+            ASSET_DECOMPOSE_ID => {
+                if d.item_count()? != 5 {
+                    return Err(DecoderError::RlpIncorrectListLen)
+                }
+                Ok(Transaction::AssetDecompose {
+                    network_id: d.val_at(1)?,
+                    input: d.val_at(2)?,
+                    outputs: d.list_at(3)?,
+                    nonce: d.val_at(4)?,
+                })
+            }
+            ASSET_UNWRAP_CCC_ID => {
+                if d.item_count()? != 5 {
+                    return Err(DecoderError::RlpIncorrectListLen)
+                }
+                Ok(Transaction::AssetUnwrapCCC {
+                    network_id: d.val_at(1)?,
+                    burn: d.val_at(2)?,
+                    receiver: d.val_at(3)?,
+                    nonce: d.val_at(4)?,
+                })
+            }
             _ => Err(DecoderError::Custom("Unexpected transaction")),
         }
     }
@@ -257,9 +636,36 @@ impl Decodable for Transaction {
 impl Encodable for Transaction {
     fn rlp_append(&self, s: &mut RlpStream) {
         match self {
+            Transaction::CreateWorld {
+                network_id,
+                shard_id,
+                nonce,
+                owners,
+            } => s
+                .begin_list(5)
+                .append(&CREATE_WORLD_ID)
+                .append(network_id)
+                .append(shard_id)
+                .append(nonce)
+                .append_list(owners),
+            Transaction::SetWorldOwners {
+                network_id,
+                shard_id,
+                world_id,
+                nonce,
+                owners,
+            } => s
+                .begin_list(6)
+                .append(&SET_WORLD_OWNERS_ID)
+                .append(network_id)
+                .append(shard_id)
+                .append(world_id)
+                .append(nonce)
+                .append_list(owners),
             Transaction::AssetMint {
                 network_id,
                 shard_id,
+                world_id,
                 metadata,
                 output:
                     AssetMintOutput {
@@ -270,10 +676,11 @@ impl Encodable for Transaction {
                 registrar,
                 nonce,
             } => s
-                .begin_list(9)
+                .begin_list(10)
                 .append(&ASSET_MINT_ID)
                 .append(network_id)
                 .append(shard_id)
+                .append(world_id)
                 .append(metadata)
                 .append(lock_script_hash)
                 .append(parameters)
@@ -294,14 +701,105 @@ impl Encodable for Transaction {
                 .append_list(inputs)
                 .append_list(outputs)
                 .append(nonce),
+            Transaction::AssetChangeRegistrar {
+                network_id,
+                asset_type,
+                registrar,
+                nonce,
+            } => s
+                .begin_list(5)
+                .append(&ASSET_CHANGE_REGISTRAR_ID)
+                .append(network_id)
+                .append(asset_type)
+                .append(registrar)
+                .append(nonce),
+            Transaction::AssetIncreaseSupply {
+                network_id,
+                asset_type,
+                lock_script_hash,
+                parameters,
+                amount,
+                nonce,
+            } => s
+                .begin_list(7)
+                .append(&ASSET_INCREASE_SUPPLY_ID)
+                .append(network_id)
+                .append(asset_type)
+                .append(lock_script_hash)
+                .append(parameters)
+                .append(amount)
+                .append(nonce),
+            Transaction::AssetFreeze {
+                network_id,
+                outpoint,
+                nonce,
+            } => s.begin_list(4).append(&ASSET_FREEZE_ID).append(network_id).append(outpoint).append(nonce),
+            Transaction::AssetUnfreeze {
+                network_id,
+                outpoint,
+                nonce,
+            } => s.begin_list(4).append(&ASSET_UNFREEZE_ID).append(network_id).append(outpoint).append(nonce),
+            Transaction::AssetCompose {
+                network_id,
+                shard_id,
+                world_id,
+                metadata,
+                registrar,
+                inputs,
+                lock_script_hash,
+                parameters,
+                amount,
+                nonce,
+            } => s
+                .begin_list(11)
+                .append(&ASSET_COMPOSE_ID)
+                .append(network_id)
+                .append(shard_id)
+                .append(world_id)
+                .append(metadata)
+                .append(registrar)
+                .append_list(inputs)
+                .append(lock_script_hash)
+                .append(parameters)
+                .append(amount)
+                .append(nonce),
+            Transaction::AssetDecompose {
+                network_id,
+                input,
+                outputs,
+                nonce,
+            } => s
+                .begin_list(5)
+                .append(&ASSET_DECOMPOSE_ID)
+                .append(network_id)
+                .append(input)
+                .append_list(outputs)
+                .append(nonce),
+            Transaction::AssetUnwrapCCC {
+                network_id,
+                burn,
+                receiver,
+                nonce,
+            } => s
+                .begin_list(5)
+                .append(&ASSET_UNWRAP_CCC_ID)
+                .append(network_id)
+                .append(burn)
+                .append(receiver)
+                .append(nonce),
         };
     }
 }
 
+/// Returns the id of the shard that the asset scheme of `asset_type` belongs to.
+fn shard_id_of_asset_type(asset_type: &H256) -> ShardId {
+    debug_assert_eq!(::std::mem::size_of::<u16>(), ::std::mem::size_of::<ShardId>());
+    Cursor::new(&asset_type[2..4]).read_u16::<BigEndian>().unwrap()
+}
+
 impl AssetOutPoint {
     pub fn related_shard(&self) -> ShardId {
-        debug_assert_eq!(::std::mem::size_of::<u16>(), ::std::mem::size_of::<ShardId>());
-        Cursor::new(&self.asset_type[2..4]).read_u16::<BigEndian>().unwrap()
+        self.shard_id
     }
 }
 
@@ -309,6 +807,14 @@ impl AssetTransferInput {
     pub fn related_shard(&self) -> ShardId {
         self.prev_out.related_shard()
     }
+
+    fn without_script(&self) -> Self {
+        AssetTransferInput {
+            prev_out: self.prev_out.clone(),
+            lock_script: Vec::new(),
+            unlock_script: Vec::new(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -317,13 +823,11 @@ mod tests {
 
     #[test]
     fn related_shard_of_asset_out_point() {
-        let mut asset_type = H256::new();
-        asset_type[2..4].clone_from_slice(&[0xBE, 0xEF]);
-
         let p = AssetOutPoint {
             transaction_hash: H256::random(),
             index: 3,
-            asset_type,
+            asset_type: H256::random(),
+            shard_id: 0xBEEF,
             amount: 34,
         };
 
@@ -332,13 +836,11 @@ mod tests {
 
     #[test]
     fn related_shard_of_asset_transfer_input() {
-        let mut asset_type = H256::new();
-        asset_type[2..4].clone_from_slice(&[0xBE, 0xEF]);
-
         let prev_out = AssetOutPoint {
             transaction_hash: H256::random(),
             index: 3,
-            asset_type,
+            asset_type: H256::random(),
+            shard_id: 0xBEEF,
             amount: 34,
         };
 
@@ -351,6 +853,108 @@ mod tests {
         assert_eq!(0xBEEF, input.related_shard());
     }
 
+    #[test]
+    fn compose_without_inputs_is_invalid() {
+        let compose = Transaction::AssetCompose {
+            network_id: 200,
+            shard_id: 0,
+            world_id: 0,
+            metadata: "".to_string(),
+            registrar: None,
+            inputs: vec![],
+            lock_script_hash: H256::random(),
+            parameters: vec![],
+            amount: 1,
+            nonce: 0,
+        };
+        assert_eq!(Err(Error::EmptyInput), compose.verify());
+    }
+
+    #[test]
+    fn compose_inputs_must_be_in_the_shard() {
+        let compose = Transaction::AssetCompose {
+            network_id: 200,
+            shard_id: 0,
+            world_id: 0,
+            metadata: "".to_string(),
+            registrar: None,
+            inputs: vec![AssetTransferInput {
+                prev_out: AssetOutPoint {
+                    transaction_hash: H256::random(),
+                    index: 0,
+                    asset_type: H256::random(),
+                    shard_id: 0xBEEF,
+                    amount: 30,
+                },
+                lock_script: vec![],
+                unlock_script: vec![],
+            }],
+            lock_script_hash: H256::random(),
+            parameters: vec![],
+            amount: 1,
+            nonce: 0,
+        };
+        assert_eq!(Err(Error::InconsistentShardOfInputs), compose.verify());
+    }
+
+    #[test]
+    fn compose_cannot_spend_the_same_asset_twice() {
+        let input = AssetTransferInput {
+            prev_out: AssetOutPoint {
+                transaction_hash: H256::random(),
+                index: 0,
+                asset_type: H256::random(),
+                shard_id: 0,
+                amount: 30,
+            },
+            lock_script: vec![],
+            unlock_script: vec![],
+        };
+        let compose = Transaction::AssetCompose {
+            network_id: 200,
+            shard_id: 0,
+            world_id: 0,
+            metadata: "".to_string(),
+            registrar: None,
+            inputs: vec![input.clone(), input],
+            lock_script_hash: H256::random(),
+            parameters: vec![],
+            amount: 1,
+            nonce: 0,
+        };
+        assert_eq!(Err(Error::DuplicatedPreviousOutput), compose.verify());
+    }
+
+    #[test]
+    fn transfer_cannot_spend_the_same_asset_twice() {
+        let asset_type = H256::random();
+        let input = AssetTransferInput {
+            prev_out: AssetOutPoint {
+                transaction_hash: H256::random(),
+                index: 0,
+                asset_type,
+                shard_id: 0,
+                amount: 30,
+            },
+            lock_script: vec![],
+            unlock_script: vec![],
+        };
+        let transfer = Transaction::AssetTransfer {
+            network_id: 200,
+            burns: vec![input.clone()],
+            inputs: vec![input],
+            outputs: vec![AssetTransferOutput {
+                lock_script_hash: H256::random(),
+                parameters: vec![],
+                asset_type,
+                shard_id: 0,
+                amount: 30,
+            }],
+            nonce: 0,
+        };
+        assert_eq!(Err(Error::DuplicatedPreviousOutput), transfer.verify());
+    }
+
     #[test]
     fn test_is_input_and_output_consistent() {
         let asset_type = H256::random();
@@ -362,6 +966,7 @@ mod tests {
                     transaction_hash: H256::random(),
                     index: 0,
                     asset_type,
+                    shard_id: 0,
                     amount,
                 },
                 lock_script: vec![],
@@ -371,6 +976,7 @@ mod tests {
                 lock_script_hash: H256::random(),
                 parameters: vec![],
                 asset_type,
+                shard_id: 0,
                 amount,
             }]
         ));
@@ -396,6 +1002,7 @@ mod tests {
                         transaction_hash: H256::random(),
                         index: 0,
                         asset_type: asset_type1,
+                        shard_id: 0,
                         amount: amount1,
                     },
                     lock_script: vec![],
@@ -406,6 +1013,7 @@ mod tests {
                         transaction_hash: H256::random(),
                         index: 0,
                         asset_type: asset_type2,
+                        shard_id: 0,
                         amount: amount2,
                     },
                     lock_script: vec![],
@@ -417,12 +1025,14 @@ mod tests {
                     lock_script_hash: H256::random(),
                     parameters: vec![],
                     asset_type: asset_type1,
+                    shard_id: 0,
                     amount: amount1,
                 },
                 AssetTransferOutput {
                     lock_script_hash: H256::random(),
                     parameters: vec![],
                     asset_type: asset_type2,
+                    shard_id: 0,
                     amount: amount2,
                 },
             ]
@@ -449,6 +1059,7 @@ mod tests {
                         transaction_hash: H256::random(),
                         index: 0,
                         asset_type: asset_type1,
+                        shard_id: 0,
                         amount: amount1,
                     },
                     lock_script: vec![],
@@ -459,6 +1070,7 @@ mod tests {
                         transaction_hash: H256::random(),
                         index: 0,
                         asset_type: asset_type2,
+                        shard_id: 0,
                         amount: amount2,
                     },
                     lock_script: vec![],
@@ -470,12 +1082,14 @@ mod tests {
                     lock_script_hash: H256::random(),
                     parameters: vec![],
                     asset_type: asset_type2,
+                    shard_id: 0,
                     amount: amount2,
                 },
                 AssetTransferOutput {
                     lock_script_hash: H256::random(),
                     parameters: vec![],
                     asset_type: asset_type1,
+                    shard_id: 0,
                     amount: amount1,
                 },
             ]
@@ -497,6 +1111,7 @@ mod tests {
                 lock_script_hash: H256::random(),
                 parameters: vec![],
                 asset_type,
+                shard_id: 0,
                 amount: output_amount,
             }]
         ));
@@ -513,6 +1128,7 @@ mod tests {
                     transaction_hash: H256::random(),
                     index: 0,
                     asset_type,
+                    shard_id: 0,
                     amount: input_amount,
                 },
                 lock_script: vec![],
@@ -534,6 +1150,7 @@ mod tests {
                     transaction_hash: H256::random(),
                     index: 0,
                     asset_type,
+                    shard_id: 0,
                     amount: input_amount,
                 },
                 lock_script: vec![],
@@ -543,6 +1160,7 @@ mod tests {
                 lock_script_hash: H256::random(),
                 parameters: vec![],
                 asset_type,
+                shard_id: 0,
                 amount: output_amount,
             }]
         ));
@@ -560,6 +1178,7 @@ mod tests {
                     transaction_hash: H256::random(),
                     index: 0,
                     asset_type,
+                    shard_id: 0,
                     amount: input_amount,
                 },
                 lock_script: vec![],
@@ -569,6 +1188,7 @@ mod tests {
                 lock_script_hash: H256::random(),
                 parameters: vec![],
                 asset_type,
+                shard_id: 0,
                 amount: output_amount,
             }]
         ));
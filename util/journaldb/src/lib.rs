@@ -19,6 +19,7 @@
 extern crate hashdb;
 extern crate kvdb;
 extern crate memorydb;
+extern crate parking_lot;
 extern crate primitives;
 extern crate rlp;
 extern crate util_error as error;
@@ -26,12 +27,15 @@ extern crate util_error as error;
 #[cfg(test)]
 extern crate codechain_crypto as crypto;
 #[cfg(test)]
+extern crate codechain_merkle as cmerkle;
+#[cfg(test)]
 extern crate kvdb_memorydb;
 
 use std::sync::Arc;
 use std::{fmt, str};
 
 mod archivedb;
+mod overlayrecentdb;
 /// Export the journaldb module.
 mod traits;
 
@@ -43,6 +47,13 @@ pub use self::traits::JournalDB;
 pub enum Algorithm {
     /// Keep all keys forever.
     Archive,
+
+    /// Ancient and recent history maintained separately; recent history lasts for particular
+    /// number of blocks.
+    ///
+    /// Inserts go into memory overlay, which is tried for key fetches. Memory overlay gets
+    /// flushed in backing only at end of recent history.
+    OverlayRecent,
 }
 
 impl Default for Algorithm {
@@ -57,6 +68,7 @@ impl str::FromStr for Algorithm {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "archive" => Ok(Algorithm::Archive),
+            "fast" => Ok(Algorithm::OverlayRecent),
             e => Err(format!("Invalid algorithm: {}", e)),
         }
     }
@@ -67,6 +79,7 @@ impl Algorithm {
     pub fn as_str(&self) -> &'static str {
         match *self {
             Algorithm::Archive => "archive",
+            Algorithm::OverlayRecent => "fast",
         }
     }
 
@@ -74,19 +87,20 @@ impl Algorithm {
     pub fn as_internal_name_str(&self) -> &'static str {
         match *self {
             Algorithm::Archive => "archive",
+            Algorithm::OverlayRecent => "overlayrecent",
         }
     }
 
     /// Returns true if pruning strategy is stable
     pub fn is_stable(&self) -> bool {
         match *self {
-            Algorithm::Archive => true,
+            Algorithm::Archive | Algorithm::OverlayRecent => true,
         }
     }
 
     /// Returns all algorithm types.
     pub fn all_types() -> Vec<Algorithm> {
-        vec![Algorithm::Archive]
+        vec![Algorithm::Archive, Algorithm::OverlayRecent]
     }
 }
 
@@ -100,6 +114,7 @@ impl fmt::Display for Algorithm {
 pub fn new(backing: Arc<::kvdb::KeyValueDB>, algorithm: Algorithm, col: Option<u32>) -> Box<JournalDB> {
     match algorithm {
         Algorithm::Archive => Box::new(archivedb::ArchiveDB::new(backing, col)),
+        Algorithm::OverlayRecent => Box::new(overlayrecentdb::OverlayRecentDB::new(backing, col)),
     }
 }
 
@@ -114,16 +129,19 @@ mod tests {
     #[test]
     fn test_journal_algorithm_parsing() {
         assert_eq!(Algorithm::Archive, "archive".parse().unwrap());
+        assert_eq!(Algorithm::OverlayRecent, "fast".parse().unwrap());
     }
 
     #[test]
     fn test_journal_algorithm_printing() {
         assert_eq!(Algorithm::Archive.to_string(), "archive".to_string());
+        assert_eq!(Algorithm::OverlayRecent.to_string(), "fast".to_string());
     }
 
     #[test]
     fn test_journal_algorithm_is_stable() {
         assert!(Algorithm::Archive.is_stable());
+        assert!(Algorithm::OverlayRecent.is_stable());
     }
 
     #[test]
@@ -135,13 +153,16 @@ mod tests {
     fn test_journal_algorithm_all_types() {
         // compiling should fail if some cases are not covered
         let mut archive = 0;
+        let mut overlayrecent = 0;
 
         for a in &Algorithm::all_types() {
             match *a {
                 Algorithm::Archive => archive += 1,
+                Algorithm::OverlayRecent => overlayrecent += 1,
             }
         }
 
         assert_eq!(archive, 1);
+        assert_eq!(overlayrecent, 1);
     }
 }
@@ -0,0 +1,921 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! `JournalDB` over in-memory overlay
+
+use super::memorydb::*;
+use super::{DB_PREFIX_LEN, LATEST_ERA_KEY};
+use error::{BaseDataError, UtilError};
+use hashdb::*;
+use kvdb::{DBTransaction, KeyValueDB};
+use parking_lot::RwLock;
+use primitives::{Bytes, H256};
+use rlp::{decode, encode, Rlp, RlpStream};
+use std::collections::HashMap;
+use std::iter::repeat;
+use std::sync::Arc;
+use traits::JournalDB;
+
+/// Implementation of the `JournalDB` trait for a disk-backed database with a memory overlay
+/// and, possibly, latent-removal semantics.
+///
+/// Like `OverlayDB`, there is a memory overlay; `commit()` must be called in order to
+/// write operations out to disk. Unlike `OverlayDB`, `remove()` operations do not take effect
+/// immediately. Rather some age (based on a linear but arbitrary metric) must pass before
+/// the removals actually take effect.
+///
+/// There are two memory overlays:
+/// - Transaction overlay contains current transaction data. It is merged with with history
+/// overlay on each `commit()`
+/// - History overlay contains all data inserted during the history period. When the node
+/// in the overlay becomes ancient it is written to disk on `commit()`
+///
+/// There is also a journal maintained in memory and on the disk as well which lists insertions
+/// and removals for each commit during the history period. This is used to track
+/// data nodes that go out of history scope and must be written to disk.
+///
+/// Commit workflow:
+/// 1. Create a new journal record from the transaction overlay.
+/// 2. Insert each node from the transaction overlay into the History overlay increasing reference
+/// count if it is already there. Note that the reference counting is managed by `MemoryDB`
+/// 3. Clear the transaction overlay.
+/// 4. For a canonical journal record that becomes ancient add its insertions to the reference counts
+/// on the disk, writing the nodes that weren't on the disk.
+/// 5. For each journal record that goes out of the history scope (becomes ancient) remove its
+/// insertions from the history overlay, decreasing the reference counter and removing entry if
+/// if reaches zero.
+/// 6. For a canonical journal record that becomes ancient subtract its removals from the reference counts
+/// on the disk, deleting the nodes that aren't referenced anymore.
+/// 7. Delete ancient record from memory and disk.
+///
+/// The tries remove the nodes that they replace, so the state of a block is deleted from the disk once the block
+/// becomes ancient, except for the nodes that the newer states still reference.
+/// A node can be referenced more than once, e.g. by two tries that have the same item. The disk keeps the reference
+/// count of such a node under `refs_key`, and a node on the disk without it is referenced once.
+pub struct OverlayRecentDB {
+    transaction_overlay: MemoryDB,
+    backing: Arc<KeyValueDB>,
+    journal_overlay: Arc<RwLock<JournalOverlay>>,
+    column: Option<u32>,
+}
+
+#[derive(PartialEq)]
+struct JournalOverlay {
+    /// Nodes added in the history period
+    backing_overlay: MemoryDB,
+    /// Nodes being transfered from backing_overlay to backing db
+    pending_overlay: HashMap<H256, DBValue>,
+    journal: HashMap<u64, Vec<JournalEntry>>,
+    latest_era: Option<u64>,
+    earliest_era: Option<u64>,
+    /// Cumulative size of all the nodes in the history overlay
+    cumulative_size: usize,
+}
+
+#[derive(PartialEq)]
+struct JournalEntry {
+    id: H256,
+    insertions: Vec<H256>,
+    deletions: Vec<H256>,
+}
+
+impl Clone for OverlayRecentDB {
+    fn clone(&self) -> OverlayRecentDB {
+        OverlayRecentDB {
+            transaction_overlay: self.transaction_overlay.clone(),
+            backing: self.backing.clone(),
+            journal_overlay: self.journal_overlay.clone(),
+            column: self.column.clone(),
+        }
+    }
+}
+
+/// Every key of the journal is at least `DB_PREFIX_LEN` bytes long, so that it doesn't collide with a node.
+const PADDING: [u8; 10] = [0u8; 10];
+
+fn journal_key(era: u64, index: usize) -> Bytes {
+    let mut stream = RlpStream::new_list(3);
+    stream.append(&era);
+    stream.append(&index);
+    stream.append(&&PADDING[..]);
+    stream.out()
+}
+
+/// The key of the reference count of a node that is referenced more than once.
+fn refs_key(key: &H256) -> Bytes {
+    let mut stream = RlpStream::new_list(2);
+    stream.append(&&b"refs"[..]);
+    stream.append(key);
+    stream.out()
+}
+
+impl OverlayRecentDB {
+    /// Create a new instance.
+    pub fn new(backing: Arc<KeyValueDB>, col: Option<u32>) -> OverlayRecentDB {
+        let journal_overlay = Arc::new(RwLock::new(OverlayRecentDB::read_overlay(&*backing, col)));
+        OverlayRecentDB {
+            transaction_overlay: MemoryDB::new(),
+            backing,
+            journal_overlay,
+            column: col,
+        }
+    }
+
+    #[cfg(test)]
+    fn can_reconstruct_refs(&self) -> bool {
+        let reconstructed = OverlayRecentDB::read_overlay(&*self.backing, self.column);
+        let journal_overlay = self.journal_overlay.read();
+        journal_overlay.backing_overlay == reconstructed.backing_overlay
+            && journal_overlay.pending_overlay == reconstructed.pending_overlay
+            && journal_overlay.journal == reconstructed.journal
+            && journal_overlay.latest_era == reconstructed.latest_era
+            && journal_overlay.cumulative_size == reconstructed.cumulative_size
+    }
+
+    fn payload(&self, key: &H256) -> Option<DBValue> {
+        self.backing.get(self.column, key).expect("Low-level database error. Some issue with your hard disk?")
+    }
+
+    /// Returns the number of the references to the node on the disk.
+    fn disk_refs(&self, key: &H256) -> Result<i32, UtilError> {
+        Ok(match self.backing.get(self.column, &refs_key(key))? {
+            Some(refs) => decode::<u32>(&refs) as i32,
+            None if self.backing.get(self.column, key)?.is_some() => 1,
+            None => 0,
+        })
+    }
+
+    /// Changes the reference count of the node on the disk, writing the node if it wasn't on the disk
+    /// and deleting it if it isn't referenced anymore.
+    fn add_disk_refs(
+        &self,
+        batch: &mut DBTransaction,
+        key: &H256,
+        value: Option<&DBValue>,
+        delta: i32,
+    ) -> Result<(), UtilError> {
+        let refs = self.disk_refs(key)?;
+        let new_refs = refs + delta;
+        if new_refs <= 0 {
+            if refs > 0 {
+                batch.delete(self.column, key);
+            }
+            if refs > 1 {
+                batch.delete(self.column, &refs_key(key));
+            }
+            return Ok(())
+        }
+
+        if refs == 0 {
+            let value = value.expect("A node that isn't on the disk is referenced only by its insertion");
+            batch.put(self.column, key, value);
+        }
+        if new_refs > 1 {
+            batch.put(self.column, &refs_key(key), &encode(&(new_refs as u32)));
+        } else if refs > 1 {
+            batch.delete(self.column, &refs_key(key));
+        }
+        Ok(())
+    }
+
+    fn read_overlay(db: &KeyValueDB, col: Option<u32>) -> JournalOverlay {
+        let mut journal = HashMap::new();
+        let mut overlay = MemoryDB::new();
+        let mut latest_era = None;
+        let mut earliest_era = None;
+        let mut cumulative_size = 0;
+        if let Some(val) = db.get(col, &LATEST_ERA_KEY).expect("Low-level database error.") {
+            let mut era = decode::<u64>(&val);
+            latest_era = Some(era);
+            loop {
+                let mut index = 0usize;
+                while let Some(rlp_data) = db.get(col, &journal_key(era, index)).expect("Low-level database error.") {
+                    let rlp = Rlp::new(&rlp_data);
+                    let id: H256 = rlp.val_at(0);
+                    let insertions = rlp.at(1);
+                    let deletions: Vec<H256> = rlp.list_at(2);
+                    let mut inserted_keys = Vec::new();
+                    for r in insertions.iter() {
+                        let k: H256 = r.val_at(0);
+                        let v = r.at(1).data();
+                        let count: u32 = r.val_at(2);
+                        if !overlay.contains(&k) {
+                            cumulative_size += v.len();
+                        }
+                        for _ in 0..count {
+                            overlay.emplace(k, DBValue::from_slice(v));
+                            inserted_keys.push(k);
+                        }
+                    }
+                    journal.entry(era).or_insert_with(Vec::new).push(JournalEntry {
+                        id,
+                        insertions: inserted_keys,
+                        deletions,
+                    });
+                    index += 1;
+                    earliest_era = Some(era);
+                }
+                if index == 0 || era == 0 {
+                    break
+                }
+                era -= 1;
+            }
+        }
+        JournalOverlay {
+            backing_overlay: overlay,
+            pending_overlay: HashMap::new(),
+            journal,
+            latest_era,
+            earliest_era,
+            cumulative_size,
+        }
+    }
+}
+
+impl JournalDB for OverlayRecentDB {
+    fn boxed_clone(&self) -> Box<JournalDB> {
+        Box::new(self.clone())
+    }
+
+    fn mem_used(&self) -> usize {
+        let journal_overlay = self.journal_overlay.read();
+        self.transaction_overlay.mem_used() + journal_overlay.backing_overlay.mem_used()
+    }
+
+    fn journal_size(&self) -> usize {
+        self.journal_overlay.read().cumulative_size
+    }
+
+    fn is_empty(&self) -> bool {
+        self.backing.get(self.column, &LATEST_ERA_KEY).expect("Low level database error").is_none()
+    }
+
+    fn latest_era(&self) -> Option<u64> {
+        self.journal_overlay.read().latest_era
+    }
+
+    fn earliest_era(&self) -> Option<u64> {
+        self.journal_overlay.read().earliest_era
+    }
+
+    fn state(&self, key: &H256) -> Option<Bytes> {
+        let journal_overlay = self.journal_overlay.read();
+        journal_overlay
+            .backing_overlay
+            .get(key)
+            .map(|v| v.into_vec())
+            .or_else(|| journal_overlay.pending_overlay.get(key).map(|d| d.clone().into_vec()))
+            .or_else(|| self.backing.get_by_prefix(self.column, &key[0..DB_PREFIX_LEN]).map(|b| b.into_vec()))
+    }
+
+    fn journal_under(&mut self, batch: &mut DBTransaction, now: u64, id: &H256) -> Result<u32, UtilError> {
+        let mut journal_overlay = self.journal_overlay.write();
+        // flush previous changes
+        journal_overlay.pending_overlay.clear();
+
+        let mut tx = self.transaction_overlay.drain();
+        let ops = tx.values().filter(|(_, rc)| *rc != 0).count();
+        let insertion_count = tx.values().filter(|(_, rc)| *rc > 0).count();
+
+        // A key is journaled as many times as it is inserted or removed, since each of them is a reference.
+        let mut inserted_keys = Vec::new();
+        let mut removed_keys = Vec::new();
+        let mut stream = RlpStream::new_list(3);
+        stream.append(id);
+        stream.begin_list(insertion_count);
+        for (k, (v, rc)) in tx.drain() {
+            if rc < 0 {
+                removed_keys.extend(repeat(k).take(-rc as usize));
+                continue
+            }
+            if rc == 0 {
+                continue
+            }
+            // Increase counter for each inserted key no matter if the block is canonical or not.
+            stream.begin_list(3).append(&k).append(&&*v).append(&(rc as u32));
+            if !journal_overlay.backing_overlay.contains(&k) {
+                journal_overlay.cumulative_size += v.len();
+            }
+            for _ in 0..rc {
+                journal_overlay.backing_overlay.emplace(k, v.clone());
+                inserted_keys.push(k);
+            }
+        }
+        stream.append_list(&removed_keys);
+
+        let index = journal_overlay.journal.get(&now).map_or(0, |entries| entries.len());
+        batch.put_vec(self.column, &journal_key(now, index), stream.out());
+        if journal_overlay.latest_era.map_or(true, |e| now > e) {
+            batch.put(self.column, &LATEST_ERA_KEY, &encode(&now));
+            journal_overlay.latest_era = Some(now);
+        }
+        if journal_overlay.earliest_era.map_or(true, |e| now < e) {
+            journal_overlay.earliest_era = Some(now);
+        }
+
+        journal_overlay.journal.entry(now).or_insert_with(Vec::new).push(JournalEntry {
+            id: *id,
+            insertions: inserted_keys,
+            deletions: removed_keys,
+        });
+        Ok(ops as u32)
+    }
+
+    fn mark_canonical(&mut self, batch: &mut DBTransaction, end_era: u64, canon_id: &H256) -> Result<u32, UtilError> {
+        let mut journal_overlay = self.journal_overlay.write();
+        let journal_overlay = &mut *journal_overlay;
+
+        let mut ops = 0;
+        // apply old commits' details
+        if let Some(records) = journal_overlay.journal.remove(&end_era) {
+            let mut canon_insertions: Vec<(H256, DBValue)> = Vec::new();
+            let mut canon_deletions: Vec<H256> = Vec::new();
+            let mut overlay_deletions: Vec<H256> = Vec::new();
+            for (index, mut journal) in records.into_iter().enumerate() {
+                // delete the record from the db
+                batch.delete(self.column, &journal_key(end_era, index));
+                if *canon_id == journal.id {
+                    for h in &journal.insertions {
+                        if let Some((d, rc)) = journal_overlay.backing_overlay.raw(h) {
+                            if rc > 0 {
+                                canon_insertions.push((*h, d));
+                            }
+                        }
+                    }
+                    canon_deletions = journal.deletions;
+                }
+                overlay_deletions.append(&mut journal.insertions);
+            }
+
+            ops += canon_insertions.len();
+            ops += canon_deletions.len();
+
+            // apply canon inserts and deletions to the reference counts on the disk
+            let mut changes: HashMap<H256, (i32, Option<DBValue>)> = HashMap::new();
+            for (k, v) in canon_insertions {
+                let change = changes.entry(k).or_insert((0, None));
+                change.0 += 1;
+                change.1 = Some(v);
+            }
+            for k in canon_deletions {
+                changes.entry(k).or_insert((0, None)).0 -= 1;
+            }
+            for (k, (delta, value)) in changes {
+                self.add_disk_refs(batch, &k, value.as_ref(), delta)?;
+                if let Some(v) = value {
+                    journal_overlay.pending_overlay.insert(k, v);
+                }
+            }
+            // update the overlay
+            for k in overlay_deletions {
+                if let Some(val) = journal_overlay.backing_overlay.remove_and_purge(&k) {
+                    journal_overlay.cumulative_size -= val.len();
+                }
+            }
+        }
+
+        if !journal_overlay.journal.is_empty() {
+            journal_overlay.earliest_era = Some(end_era + 1);
+        }
+
+        Ok(ops as u32)
+    }
+
+    fn flush(&self) {
+        self.journal_overlay.write().pending_overlay.clear();
+    }
+
+    fn inject(&mut self, batch: &mut DBTransaction) -> Result<u32, UtilError> {
+        let mut ops = 0;
+        for (key, (value, rc)) in self.transaction_overlay.drain() {
+            if rc == 0 {
+                continue
+            }
+            ops += 1;
+
+            if rc < 0 && self.backing.get(self.column, &key)?.is_none() {
+                return Err(BaseDataError::NegativelyReferencedHash(key).into())
+            }
+            self.add_disk_refs(batch, &key, Some(&value), rc)?;
+        }
+
+        Ok(ops)
+    }
+
+    fn backing(&self) -> &Arc<KeyValueDB> {
+        &self.backing
+    }
+
+    fn consolidate(&mut self, with: MemoryDB) {
+        self.transaction_overlay.consolidate(with);
+    }
+}
+
+impl HashDB for OverlayRecentDB {
+    fn keys(&self) -> HashMap<H256, i32> {
+        let mut ret: HashMap<H256, i32> = self
+            .backing
+            .iter(self.column)
+            .filter(|(key, _)| key.len() == 32)
+            .map(|(key, _)| (H256::from_slice(&*key), 1))
+            .collect();
+
+        for (key, refs) in self.transaction_overlay.keys() {
+            *ret.entry(key).or_insert(0) += refs;
+        }
+        ret
+    }
+
+    fn get(&self, key: &H256) -> Option<DBValue> {
+        if let Some((d, rc)) = self.transaction_overlay.raw(key) {
+            if rc > 0 {
+                return Some(d)
+            }
+        }
+        let v = {
+            let journal_overlay = self.journal_overlay.read();
+            journal_overlay.backing_overlay.get(key).or_else(|| journal_overlay.pending_overlay.get(key).cloned())
+        };
+        v.or_else(|| self.payload(key))
+    }
+
+    fn contains(&self, key: &H256) -> bool {
+        self.get(key).is_some()
+    }
+
+    fn insert(&mut self, value: &[u8]) -> H256 {
+        self.transaction_overlay.insert(value)
+    }
+
+    fn emplace(&mut self, key: H256, value: DBValue) {
+        self.transaction_overlay.emplace(key, value);
+    }
+
+    fn remove(&mut self, key: &H256) {
+        self.transaction_overlay.remove(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cmerkle::{Trie, TrieDB, TrieDBMut, TrieMut};
+    use crypto::blake256;
+    use hashdb::{DBValue, HashDB};
+    use {kvdb_memorydb, JournalDB};
+
+    fn new_db() -> OverlayRecentDB {
+        let backing = Arc::new(kvdb_memorydb::create(0));
+        OverlayRecentDB::new(backing, None)
+    }
+
+    #[test]
+    fn insert_same_in_fork() {
+        // history is 1
+        let mut jdb = new_db();
+
+        let x = jdb.insert(b"X");
+        jdb.commit_batch(1, &blake256(b"1"), None).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        jdb.commit_batch(2, &blake256(b"2"), None).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        jdb.commit_batch(3, &blake256(b"1002a"), Some((1, blake256(b"1")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        jdb.commit_batch(4, &blake256(b"1003a"), Some((2, blake256(b"2")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+
+        jdb.remove(&x);
+        jdb.commit_batch(3, &blake256(b"1002b"), Some((1, blake256(b"1")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        let x = jdb.insert(b"X");
+        jdb.commit_batch(4, &blake256(b"1003b"), Some((2, blake256(b"2")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+
+        jdb.commit_batch(5, &blake256(b"1004a"), Some((3, blake256(b"1002a")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        jdb.commit_batch(6, &blake256(b"1005a"), Some((4, blake256(b"1003a")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+
+        assert!(jdb.contains(&x));
+    }
+
+    #[test]
+    fn removing_node_from_trie_keeps_it_for_other_trie() {
+        // history is 1
+        let mut jdb = new_db();
+
+        let mut first = H256::zero();
+        {
+            let mut trie = TrieDBMut::new(&mut jdb, &mut first);
+            trie.insert(b"shared", b"value").unwrap();
+        }
+        jdb.commit_batch(0, &blake256(b"0"), None).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+
+        // The second trie has the same node as the first trie.
+        let mut second = H256::zero();
+        {
+            let mut trie = TrieDBMut::new(&mut jdb, &mut second);
+            trie.insert(b"shared", b"value").unwrap();
+        }
+        assert_eq!(first, second);
+        jdb.commit_batch(1, &blake256(b"1"), None).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+
+        {
+            let mut trie = TrieDBMut::from_existing(&mut jdb, &mut first).unwrap();
+            trie.remove(b"shared").unwrap();
+        }
+        jdb.commit_batch(2, &blake256(b"2"), Some((0, blake256(b"0")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        jdb.commit_batch(3, &blake256(b"3"), Some((1, blake256(b"1")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        jdb.commit_batch(4, &blake256(b"4"), Some((2, blake256(b"2")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+
+        assert!(jdb.backing().get(None, &second).unwrap().is_some());
+        let trie = TrieDB::new(&jdb, &second).unwrap();
+        assert_eq!(Some(DBValue::from_slice(b"value")), trie.get(b"shared").unwrap());
+    }
+
+    #[test]
+    fn ancient_canonical_state_is_deleted() {
+        // history is 2
+        let mut jdb = new_db();
+
+        let mut root = H256::zero();
+        {
+            let mut trie = TrieDBMut::new(&mut jdb, &mut root);
+            for i in 0..10u8 {
+                trie.insert(&[i], &[i]).unwrap();
+            }
+        }
+        jdb.commit_batch(0, &blake256(b"0"), None).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+
+        let old_root = root;
+        {
+            let mut trie = TrieDBMut::from_existing(&mut jdb, &mut root).unwrap();
+            trie.insert(&[0], b"changed").unwrap();
+        }
+        jdb.commit_batch(1, &blake256(b"1"), None).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        jdb.commit_batch(2, &blake256(b"2"), Some((0, blake256(b"0")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        assert!(TrieDB::new(&jdb, &old_root).is_ok());
+
+        jdb.commit_batch(3, &blake256(b"3"), Some((1, blake256(b"1")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        assert!(!jdb.contains(&old_root));
+        let trie = TrieDB::new(&jdb, &root).unwrap();
+        assert_eq!(Some(DBValue::from_slice(b"changed")), trie.get(&[0]).unwrap());
+        for i in 1..10u8 {
+            assert_eq!(Some(DBValue::from_slice(&[i])), trie.get(&[i]).unwrap());
+        }
+
+        // Only the nodes of the latest state are left on the disk.
+        let mut latest = MemoryDB::new();
+        let mut latest_root = H256::zero();
+        {
+            let mut trie = TrieDBMut::new(&mut latest, &mut latest_root);
+            trie.insert(&[0], b"changed").unwrap();
+            for i in 1..10u8 {
+                trie.insert(&[i], &[i]).unwrap();
+            }
+        }
+        assert_eq!(latest_root, root);
+        let nodes_on_disk = jdb.backing().iter(None).filter(|(key, _)| key.len() == 32).count();
+        assert_eq!(latest.keys().len(), nodes_on_disk);
+    }
+
+    #[test]
+    fn long_history() {
+        // history is 3
+        let mut jdb = new_db();
+        let h = jdb.insert(b"foo");
+        jdb.commit_batch(0, &blake256(b"0"), None).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        assert!(jdb.contains(&h));
+        jdb.remove(&h);
+        jdb.commit_batch(1, &blake256(b"1"), None).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        assert!(jdb.contains(&h));
+        jdb.commit_batch(2, &blake256(b"2"), None).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        assert!(jdb.contains(&h));
+        jdb.commit_batch(3, &blake256(b"3"), Some((0, blake256(b"0")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        assert!(jdb.contains(&h));
+        jdb.commit_batch(4, &blake256(b"4"), Some((1, blake256(b"1")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        assert!(!jdb.contains(&h));
+    }
+
+    #[test]
+    fn complex() {
+        // history is 1
+        let mut jdb = new_db();
+
+        let foo = jdb.insert(b"foo");
+        let bar = jdb.insert(b"bar");
+        jdb.commit_batch(0, &blake256(b"0"), None).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        assert!(jdb.contains(&foo));
+        assert!(jdb.contains(&bar));
+
+        jdb.remove(&foo);
+        jdb.remove(&bar);
+        let baz = jdb.insert(b"baz");
+        jdb.commit_batch(1, &blake256(b"1"), Some((0, blake256(b"0")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        assert!(jdb.contains(&foo));
+        assert!(jdb.contains(&bar));
+        assert!(jdb.contains(&baz));
+
+        let foo = jdb.insert(b"foo");
+        jdb.remove(&baz);
+        jdb.commit_batch(2, &blake256(b"2"), Some((1, blake256(b"1")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        assert!(jdb.contains(&foo));
+        assert!(!jdb.contains(&bar));
+        assert!(jdb.contains(&baz));
+
+        jdb.remove(&foo);
+        jdb.commit_batch(3, &blake256(b"3"), Some((2, blake256(b"2")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        assert!(jdb.contains(&foo));
+        assert!(!jdb.contains(&bar));
+        assert!(!jdb.contains(&baz));
+
+        jdb.commit_batch(4, &blake256(b"4"), Some((3, blake256(b"3")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        assert!(!jdb.contains(&foo));
+        assert!(!jdb.contains(&bar));
+        assert!(!jdb.contains(&baz));
+    }
+
+    #[test]
+    fn fork() {
+        // history is 1
+        let mut jdb = new_db();
+
+        let foo = jdb.insert(b"foo");
+        let bar = jdb.insert(b"bar");
+        jdb.commit_batch(0, &blake256(b"0"), None).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        assert!(jdb.contains(&foo));
+        assert!(jdb.contains(&bar));
+
+        jdb.remove(&foo);
+        let baz = jdb.insert(b"baz");
+        jdb.commit_batch(1, &blake256(b"1a"), Some((0, blake256(b"0")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+
+        jdb.remove(&bar);
+        jdb.commit_batch(1, &blake256(b"1b"), Some((0, blake256(b"0")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+
+        assert!(jdb.contains(&foo));
+        assert!(jdb.contains(&bar));
+        assert!(jdb.contains(&baz));
+
+        jdb.commit_batch(2, &blake256(b"2b"), Some((1, blake256(b"1b")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        assert!(jdb.contains(&foo));
+        assert!(!jdb.contains(&baz));
+        assert!(!jdb.contains(&bar));
+    }
+
+    #[test]
+    fn overwrite() {
+        // history is 1
+        let mut jdb = new_db();
+
+        let foo = jdb.insert(b"foo");
+        jdb.commit_batch(0, &blake256(b"0"), None).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        assert!(jdb.contains(&foo));
+
+        jdb.remove(&foo);
+        jdb.commit_batch(1, &blake256(b"1"), Some((0, blake256(b"0")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        jdb.insert(b"foo");
+        assert!(jdb.contains(&foo));
+        jdb.commit_batch(2, &blake256(b"2"), Some((1, blake256(b"1")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        assert!(jdb.contains(&foo));
+        jdb.commit_batch(3, &blake256(b"2"), Some((0, blake256(b"2")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        assert!(jdb.contains(&foo));
+    }
+
+    #[test]
+    fn fork_same_key_one() {
+        let mut jdb = new_db();
+        jdb.commit_batch(0, &blake256(b"0"), None).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+
+        let foo = jdb.insert(b"foo");
+        jdb.commit_batch(1, &blake256(b"1a"), Some((0, blake256(b"0")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+
+        jdb.insert(b"foo");
+        jdb.commit_batch(1, &blake256(b"1b"), Some((0, blake256(b"0")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+
+        jdb.insert(b"foo");
+        jdb.commit_batch(1, &blake256(b"1c"), Some((0, blake256(b"0")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+
+        assert!(jdb.contains(&foo));
+
+        jdb.commit_batch(2, &blake256(b"2a"), Some((1, blake256(b"1a")))).unwrap();
+        assert!(jdb.can_reconstruct_refs());
+        assert!(jdb.contains(&foo));
+    }
+
+    #[test]
+    fn reopen() {
+        let shared_db = Arc::new(kvdb_memorydb::create(0));
+        let bar = H256::random();
+
+        let foo = {
+            let mut jdb = OverlayRecentDB::new(shared_db.clone(), None);
+            // history is 1
+            let foo = jdb.insert(b"foo");
+            jdb.emplace(bar.clone(), DBValue::from_slice(b"bar"));
+            jdb.commit_batch(0, &blake256(b"0"), None).unwrap();
+            assert!(jdb.can_reconstruct_refs());
+            foo
+        };
+
+        {
+            let mut jdb = OverlayRecentDB::new(shared_db.clone(), None);
+            jdb.remove(&foo);
+            jdb.commit_batch(1, &blake256(b"1"), Some((0, blake256(b"0")))).unwrap();
+            assert!(jdb.can_reconstruct_refs());
+        }
+
+        {
+            let mut jdb = OverlayRecentDB::new(shared_db.clone(), None);
+            assert!(jdb.contains(&foo));
+            assert!(jdb.contains(&bar));
+            jdb.commit_batch(2, &blake256(b"2"), Some((1, blake256(b"1")))).unwrap();
+            assert!(jdb.can_reconstruct_refs());
+            assert!(!jdb.contains(&foo));
+        }
+    }
+
+    #[test]
+    fn reopen_remove_three() {
+        let shared_db = Arc::new(kvdb_memorydb::create(0));
+        let foo = blake256(b"foo");
+
+        {
+            let mut jdb = OverlayRecentDB::new(shared_db.clone(), None);
+            // history is 1
+            jdb.insert(b"foo");
+            jdb.commit_batch(0, &blake256(b"0"), None).unwrap();
+            assert!(jdb.can_reconstruct_refs());
+            jdb.commit_batch(1, &blake256(b"1"), None).unwrap();
+            assert!(jdb.can_reconstruct_refs());
+
+            // foo is ancient history.
+
+            jdb.remove(&foo);
+            jdb.commit_batch(2, &blake256(b"2"), Some((0, blake256(b"0")))).unwrap();
+            assert!(jdb.can_reconstruct_refs());
+            assert!(jdb.contains(&foo));
+
+            jdb.insert(b"foo");
+            jdb.commit_batch(3, &blake256(b"3"), Some((1, blake256(b"1")))).unwrap();
+            assert!(jdb.can_reconstruct_refs());
+            assert!(jdb.contains(&foo));
+
+            // reopen the db after the removal of foo is journalled.
+        }
+
+        {
+            let mut jdb = OverlayRecentDB::new(shared_db.clone(), None);
+            jdb.remove(&foo);
+            jdb.commit_batch(4, &blake256(b"4"), Some((2, blake256(b"2")))).unwrap();
+            assert!(jdb.can_reconstruct_refs());
+            assert!(jdb.contains(&foo));
+        }
+
+        {
+            let mut jdb = OverlayRecentDB::new(shared_db.clone(), None);
+            jdb.commit_batch(5, &blake256(b"5"), Some((3, blake256(b"3")))).unwrap();
+            assert!(jdb.can_reconstruct_refs());
+            assert!(jdb.contains(&foo));
+        }
+
+        {
+            let mut jdb = OverlayRecentDB::new(shared_db, None);
+            jdb.commit_batch(6, &blake256(b"6"), Some((4, blake256(b"4")))).unwrap();
+            assert!(jdb.can_reconstruct_refs());
+            assert!(!jdb.contains(&foo));
+        }
+    }
+
+    #[test]
+    fn reopen_fork() {
+        let shared_db = Arc::new(kvdb_memorydb::create(0));
+
+        let (foo, bar, baz) = {
+            let mut jdb = OverlayRecentDB::new(shared_db.clone(), None);
+            // history is 1
+            let foo = jdb.insert(b"foo");
+            let bar = jdb.insert(b"bar");
+            jdb.commit_batch(0, &blake256(b"0"), None).unwrap();
+            assert!(jdb.can_reconstruct_refs());
+            jdb.remove(&foo);
+            let baz = jdb.insert(b"baz");
+            jdb.commit_batch(1, &blake256(b"1a"), Some((0, blake256(b"0")))).unwrap();
+            assert!(jdb.can_reconstruct_refs());
+
+            jdb.remove(&bar);
+            jdb.commit_batch(1, &blake256(b"1b"), Some((0, blake256(b"0")))).unwrap();
+            assert!(jdb.can_reconstruct_refs());
+            (foo, bar, baz)
+        };
+
+        {
+            let mut jdb = OverlayRecentDB::new(shared_db, None);
+            jdb.commit_batch(2, &blake256(b"2b"), Some((1, blake256(b"1b")))).unwrap();
+            assert!(jdb.can_reconstruct_refs());
+            assert!(jdb.contains(&foo));
+            assert!(!jdb.contains(&baz));
+            assert!(!jdb.contains(&bar));
+        }
+    }
+
+    #[test]
+    fn earliest_era() {
+        let shared_db = Arc::new(kvdb_memorydb::create(0));
+
+        // empty DB
+        let mut jdb = OverlayRecentDB::new(shared_db.clone(), None);
+        assert!(jdb.earliest_era().is_none());
+
+        // single journalled era.
+        let _key = jdb.insert(b"hello!");
+        let mut batch = jdb.backing().transaction();
+        jdb.journal_under(&mut batch, 0, &blake256(b"0")).unwrap();
+        jdb.backing().write_buffered(batch);
+
+        assert_eq!(jdb.earliest_era(), Some(0));
+
+        // second journalled era.
+        let mut batch = jdb.backing().transaction();
+        jdb.journal_under(&mut batch, 1, &blake256(b"1")).unwrap();
+        jdb.backing().write_buffered(batch);
+
+        assert_eq!(jdb.earliest_era(), Some(0));
+
+        // single journalled era.
+        let mut batch = jdb.backing().transaction();
+        jdb.mark_canonical(&mut batch, 0, &blake256(b"0")).unwrap();
+        jdb.backing().write_buffered(batch);
+
+        assert_eq!(jdb.earliest_era(), Some(1));
+
+        // no journalled eras.
+        let mut batch = jdb.backing().transaction();
+        jdb.mark_canonical(&mut batch, 1, &blake256(b"1")).unwrap();
+        jdb.backing().write_buffered(batch);
+
+        assert_eq!(jdb.earliest_era(), Some(1));
+
+        // reconstructed: no journal entries.
+        drop(jdb);
+        let jdb = OverlayRecentDB::new(shared_db, None);
+        assert_eq!(jdb.earliest_era(), None);
+    }
+
+    #[test]
+    fn inject() {
+        let mut jdb = new_db();
+        let key = jdb.insert(b"dog");
+        jdb.inject_batch().unwrap();
+
+        assert_eq!(jdb.get(&key).unwrap(), DBValue::from_slice(b"dog"));
+        jdb.remove(&key);
+        jdb.inject_batch().unwrap();
+
+        assert!(jdb.get(&key).is_none());
+    }
+}
@@ -78,11 +78,12 @@ impl<'a> TrieDBMut<'a> {
                         if partial == path {
                             let node = RlpNode::Leaf(path, insert_value);
                             let node_rlp = RlpNode::encoded(node);
-                            let hash = self.db.insert(&node_rlp);
+                            let new_hash = self.db.insert(&node_rlp);
+                            self.db.remove(&hash);
 
                             *old_val = Some(DBValue::from_slice(value));
 
-                            Ok(hash)
+                            Ok(new_hash)
                         } else {
                             // Make branch node and insert Leaves
                             let common = partial.common_prefix(&path);
@@ -104,9 +105,10 @@ impl<'a> TrieDBMut<'a> {
                             )?);
 
                             let node_rlp = RlpNode::encoded_until(RlpNode::Branch(partial, new_child), common);
-                            let hash = self.db.insert(&node_rlp);
+                            let new_hash = self.db.insert(&node_rlp);
+                            self.db.remove(&hash);
 
-                            Ok(hash)
+                            Ok(new_hash)
                         }
                     }
                     Some(RlpNode::Branch(partial, mut children)) => {
@@ -131,9 +133,10 @@ impl<'a> TrieDBMut<'a> {
                             )?);
 
                             node_rlp = RlpNode::encoded_until(RlpNode::Branch(partial, new_child), common);
-                            let hash = self.db.insert(&node_rlp);
+                            let new_hash = self.db.insert(&node_rlp);
+                            self.db.remove(&hash);
 
-                            Ok(hash)
+                            Ok(new_hash)
                         } else {
                             // Insert leaf into the branch node
                             let new_path = path.mid(common);
@@ -147,9 +150,10 @@ impl<'a> TrieDBMut<'a> {
 
                             let new_branch = RlpNode::Branch(partial, children);
                             let node_rlp = RlpNode::encoded(new_branch);
-                            let hash = self.db.insert(&node_rlp);
+                            let new_hash = self.db.insert(&node_rlp);
+                            self.db.remove(&hash);
 
-                            Ok(hash)
+                            Ok(new_hash)
                         }
                     }
                     None => {
@@ -186,6 +190,7 @@ impl<'a> TrieDBMut<'a> {
                     Some(RlpNode::Leaf(partial, value)) => {
                         if path == partial {
                             *old_val = Some(DBValue::from_slice(&value));
+                            self.db.remove(&hash);
 
                             Ok(None)
                         } else {
@@ -205,6 +210,7 @@ impl<'a> TrieDBMut<'a> {
                                 match child_count {
                                     16 => {
                                         // Branch can be removed
+                                        self.db.remove(&hash);
                                         return Ok(None)
                                     }
                                     15 => {
@@ -234,6 +240,9 @@ impl<'a> TrieDBMut<'a> {
                                                 );
                                                 let mut node_rlp = RlpNode::encoded(new_leaf);
                                                 let new_hash = self.db.insert(&node_rlp);
+                                                // The child is merged into the new node.
+                                                self.db.remove(&new_leaf_hash);
+                                                self.db.remove(&hash);
 
                                                 Ok(Some(new_hash))
                                             }
@@ -249,6 +258,9 @@ impl<'a> TrieDBMut<'a> {
                                                 );
                                                 let mut node_rlp = RlpNode::encoded(new_branch);
                                                 let new_hash = self.db.insert(&node_rlp);
+                                                // The child is merged into the new node.
+                                                self.db.remove(&new_leaf_hash);
+                                                self.db.remove(&hash);
 
                                                 Ok(Some(new_hash))
                                             }
@@ -258,6 +270,7 @@ impl<'a> TrieDBMut<'a> {
                                         let new_branch = RlpNode::Branch(partial, children);
                                         let mut node_rlp = RlpNode::encoded(new_branch);
                                         let new_hash = self.db.insert(&node_rlp);
+                                        self.db.remove(&hash);
 
                                         Ok(Some(new_hash))
                                     }
@@ -266,6 +279,7 @@ impl<'a> TrieDBMut<'a> {
                                 let new_branch = RlpNode::Branch(partial, children);
                                 let mut node_rlp = RlpNode::encoded(new_branch);
                                 let new_hash = self.db.insert(&node_rlp);
+                                self.db.remove(&hash);
 
                                 Ok(Some(new_hash))
                             }
@@ -406,6 +420,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn replaced_nodes_are_removed() {
+        let mut memdb = MemoryDB::new();
+        let mut root = H256::new();
+        {
+            let mut t = TrieDBMut::new(&mut memdb, &mut root);
+            for i in 0..100u8 {
+                t.insert(&[i], &[i, i]).unwrap();
+            }
+            for i in 0..50u8 {
+                t.remove(&[i]).unwrap();
+            }
+            t.insert(&[99], &[1]).unwrap();
+        }
+
+        let mut expected_db = MemoryDB::new();
+        let mut expected_root = H256::new();
+        {
+            let mut t = TrieDBMut::new(&mut expected_db, &mut expected_root);
+            for i in 50..99u8 {
+                t.insert(&[i], &[i, i]).unwrap();
+            }
+            t.insert(&[99], &[1]).unwrap();
+        }
+        assert_eq!(expected_root, root);
+        assert_eq!(expected_db.keys(), memdb.keys());
+
+        {
+            let mut t = TrieDBMut::from_existing(&mut memdb, &mut root).unwrap();
+            for i in 50..100u8 {
+                t.remove(&[i]).unwrap();
+            }
+        }
+        assert_eq!(BLAKE_NULL_RLP, root);
+        assert!(memdb.keys().is_empty());
+    }
+
     #[test]
     fn init() {
         let mut memdb = MemoryDB::new();
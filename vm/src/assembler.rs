@@ -0,0 +1,122 @@
+// Copyright 2018 Kodebox, Inc.
+// This file is part of CodeChain.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use ckey::hex::FromHex;
+
+use decoder::{decode, DecoderError};
+use encoder::{encode, EncoderError};
+use instruction::Instruction;
+
+#[derive(Debug, PartialEq)]
+pub enum AssemblerError {
+    UnknownMnemonic(String),
+    MissingOperand(String),
+    InvalidOperand(String),
+    Encoder(EncoderError),
+}
+
+/// Assembles the text form of a script into bytecode.
+///
+/// Instructions and their operands are separated by whitespace. An integer operand is written in decimal or in
+/// hexadecimal with the `0x` prefix, and a blob operand is written in hexadecimal with the `0x` prefix.
+/// `#` starts a comment which lasts until the end of the line.
+pub fn assemble(source: &str) -> Result<Vec<u8>, AssemblerError> {
+    let mut tokens = source.lines().flat_map(|line| line.split('#').next().unwrap_or("").split_whitespace());
+    let mut script = Vec::new();
+    while let Some(mnemonic) = tokens.next() {
+        let instruction = match mnemonic.to_uppercase().as_str() {
+            "NOP" => Instruction::Nop,
+            "BURN" => Instruction::Burn,
+            "SUCCESS" => Instruction::Success,
+            "FAIL" => Instruction::Fail,
+            "NOT" => Instruction::Not,
+            "EQ" => Instruction::Eq,
+            "LT" => Instruction::Lt,
+            "GT" => Instruction::Gt,
+            "LE" => Instruction::Le,
+            "GE" => Instruction::Ge,
+            "JMP" => Instruction::Jmp(parse_integer(mnemonic, tokens.next())?),
+            "JNZ" => Instruction::Jnz(parse_integer(mnemonic, tokens.next())?),
+            "JZ" => Instruction::Jz(parse_integer(mnemonic, tokens.next())?),
+            "PUSH" => Instruction::Push(parse_integer(mnemonic, tokens.next())?),
+            "POP" => Instruction::Pop,
+            "PUSHB" => Instruction::PushB(parse_blob(mnemonic, tokens.next())?),
+            "DUP" => Instruction::Dup,
+            "SWAP" => Instruction::Swap,
+            "COPY" => Instruction::Copy(parse_integer(mnemonic, tokens.next())?),
+            "DROP" => Instruction::Drop(parse_integer(mnemonic, tokens.next())?),
+            "ADD" => Instruction::Add,
+            "SUB" => Instruction::Sub,
+            "MUL" => Instruction::Mul,
+            "DIV" => Instruction::Div,
+            "MOD" => Instruction::Mod,
+            "MIN" => Instruction::Min,
+            "MAX" => Instruction::Max,
+            "CHKSIG" => Instruction::ChkSig,
+            "CHKMULTISIG" => Instruction::ChkMultiSig,
+            "CHKSCHNORRSIG" => Instruction::ChkSchnorrSig,
+            "CHKAGGSCHNORRSIG" => Instruction::ChkAggSchnorrSig,
+            "BLAKE256" => Instruction::Blake256,
+            "SHA256" => Instruction::Sha256,
+            "RIPEMD160" => Instruction::Ripemd160,
+            "KECCAK256" => Instruction::Keccak256,
+            "INPUTCOUNT" => Instruction::InputCount,
+            "OUTPUTCOUNT" => Instruction::OutputCount,
+            "INPUTAMOUNT" => Instruction::InputAmount,
+            "INPUTASSETTYPE" => Instruction::InputAssetType,
+            "OUTPUTAMOUNT" => Instruction::OutputAmount,
+            "OUTPUTASSETTYPE" => Instruction::OutputAssetType,
+            "OUTPUTLOCKHASH" => Instruction::OutputLockHash,
+            "CURAMOUNT" => Instruction::CurAmount,
+            "CURASSETTYPE" => Instruction::CurAssetType,
+            "CURLOCKHASH" => Instruction::CurLockHash,
+            "CHKTIMELOCK" => Instruction::ChkTimelock(parse_integer(mnemonic, tokens.next())?),
+            _ => return Err(AssemblerError::UnknownMnemonic(mnemonic.to_string())),
+        };
+        script.push(instruction);
+    }
+    encode(&script).map_err(AssemblerError::Encoder)
+}
+
+/// Disassembles bytecode into the text form accepted by `assemble`, one instruction per line.
+pub fn disassemble(bytes: &[u8]) -> Result<String, DecoderError> {
+    let lines: Vec<_> = decode(bytes)?.iter().map(ToString::to_string).collect();
+    Ok(lines.join("\n"))
+}
+
+fn parse_integer(mnemonic: &str, operand: Option<&str>) -> Result<u8, AssemblerError> {
+    let operand = operand.ok_or_else(|| AssemblerError::MissingOperand(mnemonic.to_string()))?;
+    let parsed = if operand.starts_with("0x") {
+        u8::from_str_radix(&operand[2..], 16)
+    } else {
+        operand.parse()
+    };
+    parsed.map_err(|_| AssemblerError::InvalidOperand(operand.to_string()))
+}
+
+fn parse_blob(mnemonic: &str, operand: Option<&str>) -> Result<Vec<u8>, AssemblerError> {
+    let operand = operand.ok_or_else(|| AssemblerError::MissingOperand(mnemonic.to_string()))?;
+    if !operand.starts_with("0x") {
+        return Err(AssemblerError::InvalidOperand(operand.to_string()))
+    }
+    match operand[2..].from_hex() {
+        Ok(ref blob) if blob.len() > ::std::u8::MAX as usize => {
+            Err(AssemblerError::InvalidOperand(operand.to_string()))
+        }
+        Ok(blob) => Ok(blob),
+        Err(_) => Err(AssemblerError::InvalidOperand(operand.to_string())),
+    }
+}
@@ -34,6 +34,10 @@ pub fn decode(bytes: &[u8]) -> Result<Vec<Instruction>, DecoderError> {
             opcode::FAIL => result.push(Instruction::Fail),
             opcode::NOT => result.push(Instruction::Not),
             opcode::EQ => result.push(Instruction::Eq),
+            opcode::LT => result.push(Instruction::Lt),
+            opcode::GT => result.push(Instruction::Gt),
+            opcode::LE => result.push(Instruction::Le),
+            opcode::GE => result.push(Instruction::Ge),
             opcode::JMP => {
                 let val = *iter.next().ok_or(DecoderError::ScriptTooShort)?;
                 result.push(Instruction::Jmp(val));
@@ -70,11 +74,35 @@ pub fn decode(bytes: &[u8]) -> Result<Vec<Instruction>, DecoderError> {
                 let val = *iter.next().ok_or(DecoderError::ScriptTooShort)?;
                 result.push(Instruction::Drop(val));
             }
+            opcode::ADD => result.push(Instruction::Add),
+            opcode::SUB => result.push(Instruction::Sub),
+            opcode::MUL => result.push(Instruction::Mul),
+            opcode::DIV => result.push(Instruction::Div),
+            opcode::MOD => result.push(Instruction::Mod),
+            opcode::MIN => result.push(Instruction::Min),
+            opcode::MAX => result.push(Instruction::Max),
             opcode::CHKSIG => result.push(Instruction::ChkSig),
+            opcode::CHKMULTISIG => result.push(Instruction::ChkMultiSig),
+            opcode::CHKSCHNORRSIG => result.push(Instruction::ChkSchnorrSig),
+            opcode::CHKAGGSCHNORRSIG => result.push(Instruction::ChkAggSchnorrSig),
             opcode::BLAKE256 => result.push(Instruction::Blake256),
             opcode::SHA256 => result.push(Instruction::Sha256),
             opcode::RIPEMD160 => result.push(Instruction::Ripemd160),
             opcode::KECCAK256 => result.push(Instruction::Keccak256),
+            opcode::INPUTCOUNT => result.push(Instruction::InputCount),
+            opcode::OUTPUTCOUNT => result.push(Instruction::OutputCount),
+            opcode::INPUTAMOUNT => result.push(Instruction::InputAmount),
+            opcode::INPUTASSETTYPE => result.push(Instruction::InputAssetType),
+            opcode::OUTPUTAMOUNT => result.push(Instruction::OutputAmount),
+            opcode::OUTPUTASSETTYPE => result.push(Instruction::OutputAssetType),
+            opcode::OUTPUTLOCKHASH => result.push(Instruction::OutputLockHash),
+            opcode::CURAMOUNT => result.push(Instruction::CurAmount),
+            opcode::CURASSETTYPE => result.push(Instruction::CurAssetType),
+            opcode::CURLOCKHASH => result.push(Instruction::CurLockHash),
+            opcode::CHKTIMELOCK => {
+                let val = *iter.next().ok_or(DecoderError::ScriptTooShort)?;
+                result.push(Instruction::ChkTimelock(val));
+            }
             invalid_opcode => return Err(DecoderError::InvalidOpCode(invalid_opcode)),
         }
     }
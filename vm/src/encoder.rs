@@ -0,0 +1,87 @@
+// Copyright 2018 Kodebox, Inc.
+// This file is part of CodeChain.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use instruction::Instruction;
+use opcode;
+
+#[derive(Debug, PartialEq)]
+pub enum EncoderError {
+    /// The length of the blob, which is longer than 255 bytes
+    BlobTooLong(usize),
+}
+
+/// Encodes instructions into bytecode.
+/// Fails if a blob of `PushB` is longer than 255 bytes.
+pub fn encode(script: &[Instruction]) -> Result<Vec<u8>, EncoderError> {
+    let mut result = Vec::new();
+    for instruction in script {
+        match instruction {
+            Instruction::Nop => result.push(opcode::NOP),
+            Instruction::Burn => result.push(opcode::BURN),
+            Instruction::Success => result.push(opcode::SUCCESS),
+            Instruction::Fail => result.push(opcode::FAIL),
+            Instruction::Not => result.push(opcode::NOT),
+            Instruction::Eq => result.push(opcode::EQ),
+            Instruction::Lt => result.push(opcode::LT),
+            Instruction::Gt => result.push(opcode::GT),
+            Instruction::Le => result.push(opcode::LE),
+            Instruction::Ge => result.push(opcode::GE),
+            Instruction::Jmp(val) => result.extend_from_slice(&[opcode::JMP, *val]),
+            Instruction::Jnz(val) => result.extend_from_slice(&[opcode::JNZ, *val]),
+            Instruction::Jz(val) => result.extend_from_slice(&[opcode::JZ, *val]),
+            Instruction::Push(val) => result.extend_from_slice(&[opcode::PUSH, *val]),
+            Instruction::Pop => result.push(opcode::POP),
+            Instruction::PushB(blob) => {
+                if blob.len() > ::std::u8::MAX as usize {
+                    return Err(EncoderError::BlobTooLong(blob.len()))
+                }
+                result.extend_from_slice(&[opcode::PUSHB, blob.len() as u8]);
+                result.extend_from_slice(blob);
+            }
+            Instruction::Dup => result.push(opcode::DUP),
+            Instruction::Swap => result.push(opcode::SWAP),
+            Instruction::Copy(val) => result.extend_from_slice(&[opcode::COPY, *val]),
+            Instruction::Drop(val) => result.extend_from_slice(&[opcode::DROP, *val]),
+            Instruction::Add => result.push(opcode::ADD),
+            Instruction::Sub => result.push(opcode::SUB),
+            Instruction::Mul => result.push(opcode::MUL),
+            Instruction::Div => result.push(opcode::DIV),
+            Instruction::Mod => result.push(opcode::MOD),
+            Instruction::Min => result.push(opcode::MIN),
+            Instruction::Max => result.push(opcode::MAX),
+            Instruction::ChkSig => result.push(opcode::CHKSIG),
+            Instruction::ChkMultiSig => result.push(opcode::CHKMULTISIG),
+            Instruction::ChkSchnorrSig => result.push(opcode::CHKSCHNORRSIG),
+            Instruction::ChkAggSchnorrSig => result.push(opcode::CHKAGGSCHNORRSIG),
+            Instruction::Blake256 => result.push(opcode::BLAKE256),
+            Instruction::Sha256 => result.push(opcode::SHA256),
+            Instruction::Ripemd160 => result.push(opcode::RIPEMD160),
+            Instruction::Keccak256 => result.push(opcode::KECCAK256),
+            Instruction::InputCount => result.push(opcode::INPUTCOUNT),
+            Instruction::OutputCount => result.push(opcode::OUTPUTCOUNT),
+            Instruction::InputAmount => result.push(opcode::INPUTAMOUNT),
+            Instruction::InputAssetType => result.push(opcode::INPUTASSETTYPE),
+            Instruction::OutputAmount => result.push(opcode::OUTPUTAMOUNT),
+            Instruction::OutputAssetType => result.push(opcode::OUTPUTASSETTYPE),
+            Instruction::OutputLockHash => result.push(opcode::OUTPUTLOCKHASH),
+            Instruction::CurAmount => result.push(opcode::CURAMOUNT),
+            Instruction::CurAssetType => result.push(opcode::CURASSETTYPE),
+            Instruction::CurLockHash => result.push(opcode::CURLOCKHASH),
+            Instruction::ChkTimelock(val) => result.extend_from_slice(&[opcode::CHKTIMELOCK, *val]),
+        }
+    }
+    Ok(result)
+}
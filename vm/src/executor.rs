@@ -15,25 +15,54 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use ccrypto::{blake256, keccak256, ripemd160, sha256};
-use ckey::{verify, Public, Signature, SIGNATURE_LENGTH};
+use ckey::{
+    aggregate_schnorr_publics, verify, verify_schnorr, Public, SchnorrSignature, Signature, SCHNORR_SIGNATURE_LENGTH,
+    SIGNATURE_LENGTH,
+};
+use ctypes::transaction::{AssetTransferInput, AssetTransferOutput, Transaction};
 use primitives::H256;
 
 use instruction::{is_valid_unlock_script, Instruction};
+use sighash::sighash;
 
 const DEFAULT_MAX_MEMORY: usize = 1024;
+const DEFAULT_MAX_COST: u64 = 10_000;
+
+/// The cost of each public key of `ChkMultiSig`, which is the cost of a signature verification.
+const MULTISIG_KEY_COST: u64 = 100;
+/// The cost of weighting and adding each public key of `ChkAggSchnorrSig`.
+const AGGREGATED_KEY_COST: u64 = 50;
+
+pub const TIMELOCK_TYPE_BLOCK: u8 = 0x01;
+pub const TIMELOCK_TYPE_TIME: u8 = 0x02;
+pub const TIMELOCK_TYPE_BLOCK_AGE: u8 = 0x03;
 
 pub struct Config {
     pub max_memory: usize,
+    /// The maximum total cost of the instructions executed in both the unlock and the lock script.
+    pub max_cost: u64,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             max_memory: DEFAULT_MAX_MEMORY,
+            max_cost: DEFAULT_MAX_COST,
         }
     }
 }
 
+/// The chain context that timelock instructions are checked against.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ChainTimeInfo {
+    /// The number of the block that includes the transaction.
+    pub block_number: u64,
+    /// The timestamp of the block that includes the transaction.
+    pub block_timestamp: u64,
+    /// The number of the block in which the spent output was created.
+    pub output_block_number: u64,
+}
+
 #[derive(Debug, PartialEq)]
 pub enum ScriptResult {
     Fail,
@@ -41,12 +70,42 @@ pub enum ScriptResult {
     Burnt,
 }
 
+/// The frame in which an instruction is executed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Frame {
+    Unlock,
+    Lock,
+}
+
+/// An executed instruction with the stack right before its execution.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceStep {
+    pub frame: Frame,
+    pub pc: usize,
+    pub instruction: Instruction,
+    /// Items of the stack from the bottom to the top.
+    pub stack: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ScriptTrace {
+    pub steps: Vec<TraceStep>,
+    pub result: Result<ScriptResult, RuntimeError>,
+}
+
 #[derive(Debug, PartialEq)]
 pub enum RuntimeError {
     OutOfMemory,
     IndexOutOfBound,
     StackUnderflow,
     TypeMismatch,
+    InvalidJump,
+    InvalidTimelockType,
+    InvalidSigCount,
+    OutOfGas,
+    IntegerOverflow,
+    DivisionByZero,
+    InvalidSigHashType,
 }
 
 #[derive(Clone)]
@@ -64,6 +123,14 @@ impl Item {
             Err(RuntimeError::TypeMismatch)
         }
     }
+
+    /// Interprets the item as a big-endian unsigned integer of at most 8 bytes.
+    fn to_u64(&self) -> Result<u64, RuntimeError> {
+        if self.len() > 8 {
+            return Err(RuntimeError::TypeMismatch)
+        }
+        Ok(self.as_ref().iter().fold(0, |acc, b| (acc << 8) | u64::from(*b)))
+    }
 }
 
 impl AsRef<[u8]> for Item {
@@ -82,6 +149,14 @@ impl From<bool> for Item {
     }
 }
 
+impl From<u64> for Item {
+    /// Encodes the integer in big-endian without leading zeros, so zero is an empty item.
+    fn from(val: u64) -> Item {
+        let len = 8 - val.leading_zeros() as usize / 8;
+        Item((0..len).rev().map(|i| (val >> (i * 8)) as u8).collect())
+    }
+}
+
 impl Into<bool> for Item {
     fn into(self) -> bool {
         self.as_ref().iter().find(|b| **b != 0).is_some()
@@ -139,30 +214,168 @@ impl Stack {
     }
 }
 
+/// The transaction and its input whose scripts are executed.
+struct TransactionInfo<'a> {
+    tx: &'a Transaction,
+    /// The hash of the transaction without scripts, which is the message of signatures without a sighash type.
+    hash: H256,
+    inputs: &'a [AssetTransferInput],
+    outputs: &'a [AssetTransferOutput],
+    cur: &'a AssetTransferInput,
+}
+
+impl<'a> TransactionInfo<'a> {
+    /// Splits a signature item into the signature and the message it signs.
+    /// The item may carry a sighash type as its last byte, otherwise the signature signs the whole transaction.
+    fn split_signature(&self, item: Item, signature_length: usize) -> Result<(Vec<u8>, H256), RuntimeError> {
+        let mut signature = item.0;
+        if signature.len() == signature_length {
+            Ok((signature, self.hash))
+        } else if signature.len() == signature_length + 1 {
+            let sighash_type = signature.pop().expect("The signature is not empty");
+            Ok((signature, sighash(self.tx, self.cur, sighash_type)?))
+        } else {
+            Err(RuntimeError::TypeMismatch)
+        }
+    }
+}
+
+trait Tracer {
+    fn record(&mut self, frame: Frame, pc: usize, instruction: &Instruction, stack: &Stack);
+}
+
+impl Tracer for () {
+    fn record(&mut self, _frame: Frame, _pc: usize, _instruction: &Instruction, _stack: &Stack) {}
+}
+
+impl Tracer for Vec<TraceStep> {
+    fn record(&mut self, frame: Frame, pc: usize, instruction: &Instruction, stack: &Stack) {
+        self.push(TraceStep {
+            frame,
+            pc,
+            instruction: instruction.clone(),
+            stack: stack.stack.iter().map(|item| item.0.clone()).collect(),
+        });
+    }
+}
+
 pub fn execute(
     unlock: &[Instruction],
     params: &[Vec<u8>],
     lock: &[Instruction],
-    tx_hash: H256,
+    tx: &Transaction,
+    cur: &AssetTransferInput,
+    chain_time: ChainTimeInfo,
     config: Config,
 ) -> Result<ScriptResult, RuntimeError> {
-    // FIXME: don't merge scripts
+    execute_with_tracer(unlock, params, lock, tx, cur, chain_time, config, &mut ())
+}
+
+/// Executes scripts like `execute`, recording every executed instruction.
+pub fn trace(
+    unlock: &[Instruction],
+    params: &[Vec<u8>],
+    lock: &[Instruction],
+    tx: &Transaction,
+    cur: &AssetTransferInput,
+    chain_time: ChainTimeInfo,
+    config: Config,
+) -> ScriptTrace {
+    let mut steps = Vec::new();
+    let result = execute_with_tracer(unlock, params, lock, tx, cur, chain_time, config, &mut steps);
+    ScriptTrace {
+        steps,
+        result,
+    }
+}
 
+fn execute_with_tracer<T: Tracer>(
+    unlock: &[Instruction],
+    params: &[Vec<u8>],
+    lock: &[Instruction],
+    tx: &Transaction,
+    cur: &AssetTransferInput,
+    chain_time: ChainTimeInfo,
+    config: Config,
+    tracer: &mut T,
+) -> Result<ScriptResult, RuntimeError> {
     if !is_valid_unlock_script(unlock) {
         return Ok(ScriptResult::Fail)
     }
 
-    let param_scripts: Vec<_> = params.iter().map(|p| Instruction::PushB(p.clone())).rev().collect();
-    let script = [unlock, &param_scripts, lock].concat();
+    let (inputs, outputs): (&[_], &[_]) = match tx {
+        Transaction::AssetTransfer {
+            inputs,
+            outputs,
+            ..
+        } => (inputs, outputs),
+        Transaction::AssetCompose {
+            inputs,
+            ..
+        } => (inputs, &[]),
+        Transaction::AssetDecompose {
+            input,
+            outputs,
+            ..
+        } => (::std::slice::from_ref(input), outputs),
+        Transaction::AssetUnwrapCCC {
+            burn,
+            ..
+        } => (::std::slice::from_ref(burn), &[]),
+        _ => (&[], &[]),
+    };
+    let tx = TransactionInfo {
+        tx,
+        hash: tx.hash_without_script(),
+        inputs,
+        outputs,
+        cur,
+    };
 
+    let mut remaining_cost = config.max_cost;
     let mut stack = Stack::new(config);
+
+    // The unlock script runs in its own frame. Only the resulting stack is handed over to the lock script.
+    if let Some(result) = run(unlock, Frame::Unlock, &mut stack, &mut remaining_cost, &tx, &chain_time, tracer)? {
+        return Ok(result)
+    }
+
+    for param in params.iter().rev() {
+        stack.push(Item(param.clone()))?;
+    }
+
+    if let Some(result) = run(lock, Frame::Lock, &mut stack, &mut remaining_cost, &tx, &chain_time, tracer)? {
+        return Ok(result)
+    }
+
+    let result = stack.pop()?;
+    if result.into() && stack.len() == 0 {
+        Ok(ScriptResult::Unlocked)
+    } else {
+        Ok(ScriptResult::Fail)
+    }
+}
+
+/// Runs a script in its own frame.
+/// Returns the result if the script terminates explicitly, or `None` if it runs to the end.
+fn run<T: Tracer>(
+    script: &[Instruction],
+    frame: Frame,
+    stack: &mut Stack,
+    remaining_cost: &mut u64,
+    tx: &TransactionInfo,
+    chain_time: &ChainTimeInfo,
+    tracer: &mut T,
+) -> Result<Option<ScriptResult>, RuntimeError> {
     let mut pc = 0;
     while pc < script.len() {
+        tracer.record(frame, pc, &script[pc], stack);
+        *remaining_cost = remaining_cost.checked_sub(cost(&script[pc])).ok_or(RuntimeError::OutOfGas)?;
         match &script[pc] {
             Instruction::Nop => {}
-            Instruction::Burn => return Ok(ScriptResult::Burnt),
-            Instruction::Success => return Ok(ScriptResult::Unlocked),
-            Instruction::Fail => return Ok(ScriptResult::Fail),
+            Instruction::Burn => return Ok(Some(ScriptResult::Burnt)),
+            Instruction::Success => return Ok(Some(ScriptResult::Unlocked)),
+            Instruction::Fail => return Ok(Some(ScriptResult::Fail)),
             Instruction::Not => {
                 let value: bool = stack.pop()?.into();
                 stack.push(Item::from(!value))?;
@@ -172,18 +385,22 @@ pub fn execute(
                 let second = stack.pop()?;
                 stack.push(Item::from(first.as_ref() == second.as_ref()))?;
             }
+            Instruction::Lt => apply_integer_op(stack, |lhs, rhs| Ok((lhs < rhs).into()))?,
+            Instruction::Gt => apply_integer_op(stack, |lhs, rhs| Ok((lhs > rhs).into()))?,
+            Instruction::Le => apply_integer_op(stack, |lhs, rhs| Ok((lhs <= rhs).into()))?,
+            Instruction::Ge => apply_integer_op(stack, |lhs, rhs| Ok((lhs >= rhs).into()))?,
             Instruction::Jmp(val) => {
-                pc += *val as usize;
+                pc = jump(pc, *val, script.len())?;
             }
             Instruction::Jnz(val) => {
                 if stack.pop()?.into() {
-                    pc += *val as usize;
+                    pc = jump(pc, *val, script.len())?;
                 }
             }
             Instruction::Jz(val) => {
                 let condition: bool = stack.pop()?.into();
                 if !condition {
-                    pc += *val as usize;
+                    pc = jump(pc, *val, script.len())?;
                 }
             }
             Instruction::Push(val) => stack.push(Item(vec![*val]))?,
@@ -209,15 +426,93 @@ pub fn execute(
             Instruction::Drop(index) => {
                 stack.remove(*index as usize)?;
             }
+            Instruction::Add => apply_integer_op(stack, |lhs, rhs| {
+                lhs.checked_add(rhs).map(Item::from).ok_or(RuntimeError::IntegerOverflow)
+            })?,
+            Instruction::Sub => apply_integer_op(stack, |lhs, rhs| {
+                lhs.checked_sub(rhs).map(Item::from).ok_or(RuntimeError::IntegerOverflow)
+            })?,
+            Instruction::Mul => apply_integer_op(stack, |lhs, rhs| {
+                lhs.checked_mul(rhs).map(Item::from).ok_or(RuntimeError::IntegerOverflow)
+            })?,
+            Instruction::Div => apply_integer_op(stack, |lhs, rhs| {
+                lhs.checked_div(rhs).map(Item::from).ok_or(RuntimeError::DivisionByZero)
+            })?,
+            Instruction::Mod => apply_integer_op(stack, |lhs, rhs| {
+                lhs.checked_rem(rhs).map(Item::from).ok_or(RuntimeError::DivisionByZero)
+            })?,
+            Instruction::Min => apply_integer_op(stack, |lhs, rhs| Ok(lhs.min(rhs).into()))?,
+            Instruction::Max => apply_integer_op(stack, |lhs, rhs| Ok(lhs.max(rhs).into()))?,
             Instruction::ChkSig => {
                 let pubkey = Public::from_slice(stack.pop()?.assert_len(64)?.as_ref());
-                let signature = Signature::from(Signature::from(stack.pop()?.assert_len(SIGNATURE_LENGTH)?.as_ref()));
-                let result = match verify(&pubkey, &signature, &tx_hash) {
+                let (signature, message) = tx.split_signature(stack.pop()?, SIGNATURE_LENGTH)?;
+                let result = match verify(&pubkey, &Signature::from(&signature[..]), &message) {
                     Ok(true) => 1,
                     _ => 0,
                 };
                 stack.push(Item(vec![result]))?;
             }
+            Instruction::ChkMultiSig => {
+                let n = stack.pop()?.to_u64()?;
+                // Every public key may be tried against a signature.
+                charge(remaining_cost, n, MULTISIG_KEY_COST)?;
+                let n = n as usize;
+                let mut pubkeys = Vec::with_capacity(n.min(stack.len()));
+                for _ in 0..n {
+                    pubkeys.push(Public::from_slice(stack.pop()?.assert_len(64)?.as_ref()));
+                }
+                pubkeys.reverse();
+
+                let m = stack.pop()?.to_u64()? as usize;
+                if m == 0 || m > n {
+                    return Err(RuntimeError::InvalidSigCount)
+                }
+                let mut signatures = Vec::with_capacity(m);
+                for _ in 0..m {
+                    let (signature, message) = tx.split_signature(stack.pop()?, SIGNATURE_LENGTH)?;
+                    signatures.push((Signature::from(&signature[..]), message));
+                }
+                signatures.reverse();
+
+                // Signatures must be given in the same order as the public keys they match.
+                let mut pubkeys = pubkeys.iter();
+                let result = signatures.iter().all(|(signature, message)| {
+                    pubkeys.any(|pubkey| match verify(pubkey, signature, message) {
+                        Ok(true) => true,
+                        _ => false,
+                    })
+                });
+                stack.push(Item::from(result))?;
+            }
+            Instruction::ChkSchnorrSig => {
+                let pubkey = Public::from_slice(stack.pop()?.assert_len(64)?.as_ref());
+                let (signature, message) = tx.split_signature(stack.pop()?, SCHNORR_SIGNATURE_LENGTH)?;
+                let result = match verify_schnorr(&pubkey, &SchnorrSignature::from(&signature[..]), &message) {
+                    Ok(true) => true,
+                    _ => false,
+                };
+                stack.push(Item::from(result))?;
+            }
+            Instruction::ChkAggSchnorrSig => {
+                let n = stack.pop()?.to_u64()?;
+                charge(remaining_cost, n, AGGREGATED_KEY_COST)?;
+                let n = n as usize;
+                let mut pubkeys = Vec::with_capacity(n.min(stack.len()));
+                for _ in 0..n {
+                    pubkeys.push(Public::from_slice(stack.pop()?.assert_len(64)?.as_ref()));
+                }
+                pubkeys.reverse();
+                let (signature, message) = tx.split_signature(stack.pop()?, SCHNORR_SIGNATURE_LENGTH)?;
+                let signature = SchnorrSignature::from(&signature[..]);
+
+                // The signature must be made with the sum of the shares of the private keys.
+                let aggregated = aggregate_schnorr_publics(&pubkeys);
+                let result = match aggregated.and_then(|pubkey| verify_schnorr(&pubkey, &signature, &message)) {
+                    Ok(true) => true,
+                    _ => false,
+                };
+                stack.push(Item::from(result))?;
+            }
             Instruction::Blake256 => {
                 let value = stack.pop()?;
                 stack.push(Item(blake256(value).to_vec()))?;
@@ -234,14 +529,92 @@ pub fn execute(
                 let value = stack.pop()?;
                 stack.push(Item(keccak256(value).to_vec()))?;
             }
+            Instruction::InputCount => stack.push(Item::from(tx.inputs.len() as u64))?,
+            Instruction::OutputCount => stack.push(Item::from(tx.outputs.len() as u64))?,
+            Instruction::InputAmount => {
+                let input = get_by_index(tx.inputs, stack.pop()?.to_u64()?)?;
+                stack.push(Item::from(input.prev_out.amount))?;
+            }
+            Instruction::InputAssetType => {
+                let input = get_by_index(tx.inputs, stack.pop()?.to_u64()?)?;
+                stack.push(Item(input.prev_out.asset_type.to_vec()))?;
+            }
+            Instruction::OutputAmount => {
+                let output = get_by_index(tx.outputs, stack.pop()?.to_u64()?)?;
+                stack.push(Item::from(output.amount))?;
+            }
+            Instruction::OutputAssetType => {
+                let output = get_by_index(tx.outputs, stack.pop()?.to_u64()?)?;
+                stack.push(Item(output.asset_type.to_vec()))?;
+            }
+            Instruction::OutputLockHash => {
+                let output = get_by_index(tx.outputs, stack.pop()?.to_u64()?)?;
+                stack.push(Item(output.lock_script_hash.to_vec()))?;
+            }
+            Instruction::CurAmount => stack.push(Item::from(tx.cur.prev_out.amount))?,
+            Instruction::CurAssetType => stack.push(Item(tx.cur.prev_out.asset_type.to_vec()))?,
+            Instruction::CurLockHash => stack.push(Item(blake256(&tx.cur.lock_script).to_vec()))?,
+            Instruction::ChkTimelock(timelock_type) => {
+                let value = stack.pop()?.to_u64()?;
+                let result = match *timelock_type {
+                    TIMELOCK_TYPE_BLOCK => chain_time.block_number >= value,
+                    TIMELOCK_TYPE_TIME => chain_time.block_timestamp >= value,
+                    TIMELOCK_TYPE_BLOCK_AGE => {
+                        chain_time.block_number.saturating_sub(chain_time.output_block_number) >= value
+                    }
+                    _ => return Err(RuntimeError::InvalidTimelockType),
+                };
+                stack.push(Item::from(result))?;
+            }
         }
         pc += 1;
     }
+    Ok(None)
+}
 
-    let result = stack.pop()?;
-    if result.into() && stack.len() == 0 {
-        Ok(ScriptResult::Unlocked)
-    } else {
-        Ok(ScriptResult::Fail)
+/// Pops the right-hand side and then the left-hand side as integers, and pushes the result of `op`.
+fn apply_integer_op<F>(stack: &mut Stack, op: F) -> Result<(), RuntimeError>
+where
+    F: FnOnce(u64, u64) -> Result<Item, RuntimeError>, {
+    let rhs = stack.pop()?.to_u64()?;
+    let lhs = stack.pop()?.to_u64()?;
+    stack.push(op(lhs, rhs)?)
+}
+
+/// Returns the item at the index, which is given as an integer item.
+fn get_by_index<T>(items: &[T], index: u64) -> Result<&T, RuntimeError> {
+    if index >= items.len() as u64 {
+        return Err(RuntimeError::IndexOutOfBound)
+    }
+    Ok(&items[index as usize])
+}
+
+/// Returns the cost of executing the instruction.
+/// The instructions that take a number of public keys are charged for each key when the number is popped.
+fn cost(instruction: &Instruction) -> u64 {
+    match instruction {
+        Instruction::Blake256 | Instruction::Sha256 | Instruction::Ripemd160 | Instruction::Keccak256 => 10,
+        Instruction::ChkSig => 100,
+        Instruction::ChkMultiSig => 1,
+        Instruction::ChkSchnorrSig => 100,
+        Instruction::ChkAggSchnorrSig => 100,
+        _ => 1,
+    }
+}
+
+/// Takes `count` times `unit_cost` from the remaining cost.
+fn charge(remaining_cost: &mut u64, count: u64, unit_cost: u64) -> Result<(), RuntimeError> {
+    let cost = count.checked_mul(unit_cost).ok_or(RuntimeError::OutOfGas)?;
+    *remaining_cost = remaining_cost.checked_sub(cost).ok_or(RuntimeError::OutOfGas)?;
+    Ok(())
+}
+
+/// Returns the new program counter, which must stay within the running script.
+/// Jumping to the end of the script is allowed and terminates the frame.
+fn jump(pc: usize, offset: u8, script_len: usize) -> Result<usize, RuntimeError> {
+    let destination = pc + offset as usize;
+    if destination + 1 > script_len {
+        return Err(RuntimeError::InvalidJump)
     }
+    Ok(destination)
 }
@@ -14,6 +14,10 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use std::fmt;
+
+use ckey::hex::ToHex;
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Instruction {
     Nop,
@@ -22,6 +26,10 @@ pub enum Instruction {
     Fail,
     Not,
     Eq,
+    Lt,
+    Gt,
+    Le,
+    Ge,
     Jmp(u8),
     Jnz(u8),
     Jz(u8),
@@ -32,11 +40,85 @@ pub enum Instruction {
     Swap,
     Copy(u8),
     Drop(u8),
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Min,
+    Max,
     ChkSig,
+    ChkMultiSig,
+    ChkSchnorrSig,
+    ChkAggSchnorrSig,
     Blake256,
     Sha256,
     Ripemd160,
     Keccak256,
+    InputCount,
+    OutputCount,
+    InputAmount,
+    InputAssetType,
+    OutputAmount,
+    OutputAssetType,
+    OutputLockHash,
+    CurAmount,
+    CurAssetType,
+    CurLockHash,
+    ChkTimelock(u8),
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Instruction::Nop => write!(f, "NOP"),
+            Instruction::Burn => write!(f, "BURN"),
+            Instruction::Success => write!(f, "SUCCESS"),
+            Instruction::Fail => write!(f, "FAIL"),
+            Instruction::Not => write!(f, "NOT"),
+            Instruction::Eq => write!(f, "EQ"),
+            Instruction::Lt => write!(f, "LT"),
+            Instruction::Gt => write!(f, "GT"),
+            Instruction::Le => write!(f, "LE"),
+            Instruction::Ge => write!(f, "GE"),
+            Instruction::Jmp(val) => write!(f, "JMP {}", val),
+            Instruction::Jnz(val) => write!(f, "JNZ {}", val),
+            Instruction::Jz(val) => write!(f, "JZ {}", val),
+            Instruction::Push(val) => write!(f, "PUSH {}", val),
+            Instruction::Pop => write!(f, "POP"),
+            Instruction::PushB(blob) => write!(f, "PUSHB 0x{}", blob.to_hex()),
+            Instruction::Dup => write!(f, "DUP"),
+            Instruction::Swap => write!(f, "SWAP"),
+            Instruction::Copy(val) => write!(f, "COPY {}", val),
+            Instruction::Drop(val) => write!(f, "DROP {}", val),
+            Instruction::Add => write!(f, "ADD"),
+            Instruction::Sub => write!(f, "SUB"),
+            Instruction::Mul => write!(f, "MUL"),
+            Instruction::Div => write!(f, "DIV"),
+            Instruction::Mod => write!(f, "MOD"),
+            Instruction::Min => write!(f, "MIN"),
+            Instruction::Max => write!(f, "MAX"),
+            Instruction::ChkSig => write!(f, "CHKSIG"),
+            Instruction::ChkMultiSig => write!(f, "CHKMULTISIG"),
+            Instruction::ChkSchnorrSig => write!(f, "CHKSCHNORRSIG"),
+            Instruction::ChkAggSchnorrSig => write!(f, "CHKAGGSCHNORRSIG"),
+            Instruction::Blake256 => write!(f, "BLAKE256"),
+            Instruction::Sha256 => write!(f, "SHA256"),
+            Instruction::Ripemd160 => write!(f, "RIPEMD160"),
+            Instruction::Keccak256 => write!(f, "KECCAK256"),
+            Instruction::InputCount => write!(f, "INPUTCOUNT"),
+            Instruction::OutputCount => write!(f, "OUTPUTCOUNT"),
+            Instruction::InputAmount => write!(f, "INPUTAMOUNT"),
+            Instruction::InputAssetType => write!(f, "INPUTASSETTYPE"),
+            Instruction::OutputAmount => write!(f, "OUTPUTAMOUNT"),
+            Instruction::OutputAssetType => write!(f, "OUTPUTASSETTYPE"),
+            Instruction::OutputLockHash => write!(f, "OUTPUTLOCKHASH"),
+            Instruction::CurAmount => write!(f, "CURAMOUNT"),
+            Instruction::CurAssetType => write!(f, "CURASSETTYPE"),
+            Instruction::CurLockHash => write!(f, "CURLOCKHASH"),
+            Instruction::ChkTimelock(val) => write!(f, "CHKTIMELOCK {}", val),
+        }
+    }
 }
 
 pub fn is_valid_unlock_script(instrs: &[Instruction]) -> bool {
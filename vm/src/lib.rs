@@ -16,18 +16,31 @@
 
 extern crate codechain_crypto as ccrypto;
 extern crate codechain_key as ckey;
+extern crate codechain_types as ctypes;
 extern crate primitives;
 
 #[cfg(test)]
 extern crate secp256k1;
 
+mod assembler;
 mod decoder;
+mod encoder;
 mod executor;
 mod instruction;
 mod opcode;
+mod sighash;
+mod template;
 
 #[cfg(test)]
 mod tests;
 
+pub use assembler::{assemble, disassemble, AssemblerError};
 pub use decoder::{decode, DecoderError};
-pub use executor::{execute, Config as VMConfig, RuntimeError, ScriptResult};
+pub use encoder::{encode, EncoderError};
+pub use executor::{
+    execute, trace, ChainTimeInfo, Config as VMConfig, Frame, RuntimeError, ScriptResult, ScriptTrace, TraceStep,
+    TIMELOCK_TYPE_BLOCK, TIMELOCK_TYPE_BLOCK_AGE, TIMELOCK_TYPE_TIME,
+};
+pub use instruction::Instruction;
+pub use sighash::{sighash, SIGHASH_ALL, SIGHASH_ANYONECANPAY, SIGHASH_OUTPUTS_ONLY, SIGHASH_SINGLE};
+pub use template::{multisig_lock_script, multisig_unlock_script};
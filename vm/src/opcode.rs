@@ -20,6 +20,10 @@ pub const SUCCESS: u8 = 0x02;
 pub const FAIL: u8 = 0x03;
 pub const NOT: u8 = 0x10;
 pub const EQ: u8 = 0x11;
+pub const LT: u8 = 0x12;
+pub const GT: u8 = 0x13;
+pub const LE: u8 = 0x14;
+pub const GE: u8 = 0x15;
 pub const JMP: u8 = 0x20;
 pub const JNZ: u8 = 0x21;
 pub const JZ: u8 = 0x22;
@@ -30,8 +34,29 @@ pub const DUP: u8 = 0x33;
 pub const SWAP: u8 = 0x34;
 pub const COPY: u8 = 0x35;
 pub const DROP: u8 = 0x36;
+pub const ADD: u8 = 0x40;
+pub const SUB: u8 = 0x41;
+pub const MUL: u8 = 0x42;
+pub const DIV: u8 = 0x43;
+pub const MOD: u8 = 0x44;
+pub const MIN: u8 = 0x45;
+pub const MAX: u8 = 0x46;
 pub const CHKSIG: u8 = 0x80;
+pub const CHKMULTISIG: u8 = 0x81;
+pub const CHKSCHNORRSIG: u8 = 0x82;
+pub const CHKAGGSCHNORRSIG: u8 = 0x83;
 pub const BLAKE256: u8 = 0x90;
 pub const SHA256: u8 = 0x91;
 pub const RIPEMD160: u8 = 0x92;
 pub const KECCAK256: u8 = 0x93;
+pub const INPUTCOUNT: u8 = 0xa1;
+pub const OUTPUTCOUNT: u8 = 0xa2;
+pub const INPUTAMOUNT: u8 = 0xa3;
+pub const INPUTASSETTYPE: u8 = 0xa4;
+pub const OUTPUTAMOUNT: u8 = 0xa5;
+pub const OUTPUTASSETTYPE: u8 = 0xa6;
+pub const OUTPUTLOCKHASH: u8 = 0xa7;
+pub const CURAMOUNT: u8 = 0xa8;
+pub const CURASSETTYPE: u8 = 0xa9;
+pub const CURLOCKHASH: u8 = 0xaa;
+pub const CHKTIMELOCK: u8 = 0xb0;
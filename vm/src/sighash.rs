@@ -0,0 +1,76 @@
+// Copyright 2018 Kodebox, Inc.
+// This file is part of CodeChain.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use ccrypto::blake256_with_key;
+use ctypes::transaction::{AssetTransferInput, Transaction};
+use primitives::H256;
+
+use executor::RuntimeError;
+
+/// Signs all the outputs.
+pub const SIGHASH_ALL: u8 = 0x01;
+/// Signs only the output at the same index as the input being unlocked.
+pub const SIGHASH_SINGLE: u8 = 0x02;
+/// Signs no input, so that the signature stays valid whatever inputs are spent.
+pub const SIGHASH_OUTPUTS_ONLY: u8 = 0x40;
+/// Signs only the input being unlocked, so that other parties can add their own inputs.
+pub const SIGHASH_ANYONECANPAY: u8 = 0x80;
+
+const INPUT_FLAGS: u8 = SIGHASH_OUTPUTS_ONLY | SIGHASH_ANYONECANPAY;
+
+/// Returns the message that a signature with the given sighash type signs when unlocking `cur`.
+///
+/// The message is the hash of the transaction without scripts, where the inputs and the outputs
+/// that the sighash type doesn't cover are removed, keyed with the sighash type itself.
+pub fn sighash(tx: &Transaction, cur: &AssetTransferInput, sighash_type: u8) -> Result<H256, RuntimeError> {
+    let (network_id, burns, inputs, outputs, nonce) = match tx {
+        Transaction::AssetTransfer {
+            network_id,
+            burns,
+            inputs,
+            outputs,
+            nonce,
+        } => (*network_id, burns, inputs, outputs, *nonce),
+        _ => return Err(RuntimeError::InvalidSigHashType),
+    };
+
+    let (signed_burns, signed_inputs) = match sighash_type & INPUT_FLAGS {
+        0 => (burns.clone(), inputs.clone()),
+        SIGHASH_ANYONECANPAY => (
+            burns.iter().filter(|burn| *burn == cur).cloned().collect(),
+            inputs.iter().filter(|input| *input == cur).cloned().collect(),
+        ),
+        SIGHASH_OUTPUTS_ONLY => (Vec::new(), Vec::new()),
+        _ => return Err(RuntimeError::InvalidSigHashType),
+    };
+    let signed_outputs = match sighash_type & !INPUT_FLAGS {
+        SIGHASH_ALL => outputs.clone(),
+        SIGHASH_SINGLE => {
+            let index = inputs.iter().position(|input| input == cur).ok_or(RuntimeError::InvalidSigHashType)?;
+            vec![outputs.get(index).cloned().ok_or(RuntimeError::IndexOutOfBound)?]
+        }
+        _ => return Err(RuntimeError::InvalidSigHashType),
+    };
+
+    let partial = Transaction::AssetTransfer {
+        network_id,
+        burns: signed_burns,
+        inputs: signed_inputs,
+        outputs: signed_outputs,
+        nonce,
+    };
+    Ok(blake256_with_key(partial.hash_without_script(), &[sighash_type]))
+}
@@ -0,0 +1,46 @@
+// Copyright 2018 Kodebox, Inc.
+// This file is part of CodeChain.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use ckey::{Public, Signature};
+
+use opcode;
+
+/// Creates a lock script which requires `required` signatures of the given public keys.
+pub fn multisig_lock_script(required: u8, pubkeys: &[Public]) -> Vec<u8> {
+    debug_assert!(required > 0 && required as usize <= pubkeys.len());
+    debug_assert!(pubkeys.len() <= ::std::u8::MAX as usize);
+
+    let mut script = vec![opcode::PUSH, required];
+    for pubkey in pubkeys {
+        script.push(opcode::PUSHB);
+        script.push(pubkey.len() as u8);
+        script.extend_from_slice(pubkey);
+    }
+    script.extend_from_slice(&[opcode::PUSH, pubkeys.len() as u8, opcode::CHKMULTISIG]);
+    script
+}
+
+/// Creates an unlock script for `multisig_lock_script`.
+/// The signatures must be in the same order as the public keys they match.
+pub fn multisig_unlock_script(signatures: &[Signature]) -> Vec<u8> {
+    let mut script = Vec::new();
+    for signature in signatures {
+        script.push(opcode::PUSHB);
+        script.push(signature.len() as u8);
+        script.extend_from_slice(signature);
+    }
+    script
+}
@@ -0,0 +1,92 @@
+// Copyright 2018 Kodebox, Inc.
+// This file is part of CodeChain.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use assembler::{assemble, disassemble, AssemblerError};
+use decoder::decode;
+use encoder::{encode, EncoderError};
+use instruction::Instruction;
+use opcode;
+
+#[test]
+fn assemble_pay_to_public_key() {
+    let source = "PUSHB 0x0102030405 # public key\nCHKSIG";
+    assert_eq!(assemble(source), Ok(vec![opcode::PUSHB, 5, 1, 2, 3, 4, 5, opcode::CHKSIG]));
+}
+
+#[test]
+fn assemble_integer_operands() {
+    assert_eq!(assemble("PUSH 10 JMP 0x0a"), Ok(vec![opcode::PUSH, 10, opcode::JMP, 10]));
+    assert_eq!(assemble("push 1 chktimelock 2"), Ok(vec![opcode::PUSH, 1, opcode::CHKTIMELOCK, 2]));
+}
+
+#[test]
+fn assemble_invalid_source() {
+    assert_eq!(assemble("PUSHC 1"), Err(AssemblerError::UnknownMnemonic("PUSHC".to_string())));
+    assert_eq!(assemble("PUSH"), Err(AssemblerError::MissingOperand("PUSH".to_string())));
+    assert_eq!(assemble("PUSH 256"), Err(AssemblerError::InvalidOperand("256".to_string())));
+    assert_eq!(assemble("PUSHB 0102"), Err(AssemblerError::InvalidOperand("0102".to_string())));
+    assert_eq!(assemble("PUSHB 0x012"), Err(AssemblerError::InvalidOperand("0x012".to_string())));
+
+    let too_long_blob = format!("PUSHB 0x{}", "00".repeat(256));
+    assert!(assemble(&too_long_blob).is_err());
+}
+
+#[test]
+fn encoding_too_long_blob_fails() {
+    assert_eq!(encode(&[Instruction::PushB(vec![0; 255])]).map(|bytes| bytes.len()), Ok(257));
+    assert_eq!(encode(&[Instruction::PushB(vec![0; 256])]), Err(EncoderError::BlobTooLong(256)));
+}
+
+#[test]
+fn disassemble_script() {
+    let bytes = vec![opcode::PUSHB, 2, 0xab, 0xcd, opcode::BLAKE256, opcode::EQ, opcode::JNZ, 1, opcode::BURN];
+    assert_eq!(disassemble(&bytes), Ok("PUSHB 0xabcd\nBLAKE256\nEQ\nJNZ 1\nBURN".to_string()));
+}
+
+#[test]
+fn round_trip() {
+    let script = vec![
+        Instruction::Nop,
+        Instruction::Burn,
+        Instruction::Success,
+        Instruction::Fail,
+        Instruction::Not,
+        Instruction::Eq,
+        Instruction::Jmp(1),
+        Instruction::Jnz(2),
+        Instruction::Jz(3),
+        Instruction::Push(4),
+        Instruction::Pop,
+        Instruction::PushB(vec![]),
+        Instruction::PushB(vec![0xde, 0xad, 0xbe, 0xef]),
+        Instruction::Dup,
+        Instruction::Swap,
+        Instruction::Copy(5),
+        Instruction::Drop(6),
+        Instruction::ChkSig,
+        Instruction::ChkMultiSig,
+        Instruction::Blake256,
+        Instruction::Sha256,
+        Instruction::Ripemd160,
+        Instruction::Keccak256,
+        Instruction::ChkTimelock(7),
+    ];
+    let bytes = encode(&script).unwrap();
+    assert_eq!(decode(&bytes), Ok(script));
+
+    let source = disassemble(&bytes).unwrap();
+    assert_eq!(assemble(&source), Ok(bytes));
+}
@@ -54,6 +54,10 @@ test_no_argument_opcode!(SUCCESS, Success);
 test_no_argument_opcode!(FAIL, Fail);
 test_no_argument_opcode!(NOT, Not);
 test_no_argument_opcode!(EQ, Eq);
+test_no_argument_opcode!(LT, Lt);
+test_no_argument_opcode!(GT, Gt);
+test_no_argument_opcode!(LE, Le);
+test_no_argument_opcode!(GE, Ge);
 test_one_argument_opcode!(JMP, Jmp);
 test_one_argument_opcode!(JNZ, Jnz);
 test_one_argument_opcode!(JZ, Jz);
@@ -63,11 +67,32 @@ test_no_argument_opcode!(DUP, Dup);
 test_no_argument_opcode!(SWAP, Swap);
 test_one_argument_opcode!(COPY, Copy);
 test_one_argument_opcode!(DROP, Drop);
+test_no_argument_opcode!(ADD, Add);
+test_no_argument_opcode!(SUB, Sub);
+test_no_argument_opcode!(MUL, Mul);
+test_no_argument_opcode!(DIV, Div);
+test_no_argument_opcode!(MOD, Mod);
+test_no_argument_opcode!(MIN, Min);
+test_no_argument_opcode!(MAX, Max);
 test_no_argument_opcode!(CHKSIG, ChkSig);
+test_no_argument_opcode!(CHKMULTISIG, ChkMultiSig);
+test_no_argument_opcode!(CHKSCHNORRSIG, ChkSchnorrSig);
+test_no_argument_opcode!(CHKAGGSCHNORRSIG, ChkAggSchnorrSig);
 test_no_argument_opcode!(BLAKE256, Blake256);
 test_no_argument_opcode!(SHA256, Sha256);
 test_no_argument_opcode!(RIPEMD160, Ripemd160);
 test_no_argument_opcode!(KECCAK256, Keccak256);
+test_no_argument_opcode!(INPUTCOUNT, InputCount);
+test_no_argument_opcode!(OUTPUTCOUNT, OutputCount);
+test_no_argument_opcode!(INPUTAMOUNT, InputAmount);
+test_no_argument_opcode!(INPUTASSETTYPE, InputAssetType);
+test_no_argument_opcode!(OUTPUTAMOUNT, OutputAmount);
+test_no_argument_opcode!(OUTPUTASSETTYPE, OutputAssetType);
+test_no_argument_opcode!(OUTPUTLOCKHASH, OutputLockHash);
+test_no_argument_opcode!(CURAMOUNT, CurAmount);
+test_no_argument_opcode!(CURASSETTYPE, CurAssetType);
+test_no_argument_opcode!(CURLOCKHASH, CurLockHash);
+test_one_argument_opcode!(CHKTIMELOCK, ChkTimelock);
 
 #[test]
 #[allow(non_snake_case)]
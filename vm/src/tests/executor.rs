@@ -15,42 +15,132 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use ccrypto::{blake256, BLAKE_EMPTY, BLAKE_NULL_RLP};
-use ckey::{sign, KeyPair, Private, Signature};
+use ckey::{
+    aggregate_schnorr_private_share, sign, sign_schnorr, KeyPair, Private, Signature, SECP256K1, SIGNATURE_LENGTH,
+};
+use ctypes::transaction::{AssetOutPoint, AssetTransferInput, AssetTransferOutput, Transaction};
 use primitives::{H160, H256};
 
 use secp256k1::key::{SecretKey, MINUS_ONE_KEY, ONE_KEY};
 
-use executor::{execute, Config, RuntimeError, ScriptResult};
+use decoder::decode;
+use executor::{
+    execute, trace, ChainTimeInfo, Config, Frame, RuntimeError, ScriptResult, TraceStep, TIMELOCK_TYPE_BLOCK,
+    TIMELOCK_TYPE_BLOCK_AGE, TIMELOCK_TYPE_TIME,
+};
 use instruction::Instruction;
+use sighash::{sighash, SIGHASH_ALL, SIGHASH_ANYONECANPAY, SIGHASH_OUTPUTS_ONLY, SIGHASH_SINGLE};
+use template::multisig_lock_script;
+
+pub fn dummy_input() -> AssetTransferInput {
+    AssetTransferInput {
+        prev_out: AssetOutPoint {
+            transaction_hash: H256::default(),
+            index: 0,
+            asset_type: H256::default(),
+            shard_id: 0,
+            amount: 0,
+        },
+        lock_script: Vec::new(),
+        unlock_script: Vec::new(),
+    }
+}
+
+pub fn dummy_tx() -> Transaction {
+    Transaction::AssetTransfer {
+        network_id: 0,
+        burns: Vec::new(),
+        inputs: vec![dummy_input()],
+        outputs: Vec::new(),
+        nonce: 0,
+    }
+}
 
 #[test]
 fn simple_success() {
     assert_eq!(
-        execute(&[], &[], &[Instruction::Push(1)], H256::default(), Config::default()),
+        execute(
+            &[],
+            &[],
+            &[Instruction::Push(1)],
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
         Ok(ScriptResult::Unlocked)
     );
 
     assert_eq!(
-        execute(&[], &[], &[Instruction::Success], H256::default(), Config::default()),
+        execute(
+            &[],
+            &[],
+            &[Instruction::Success],
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
         Ok(ScriptResult::Unlocked)
     );
 }
 
 #[test]
 fn simple_failure() {
-    assert_eq!(execute(&[Instruction::Push(0)], &[], &[], H256::default(), Config::default()), Ok(ScriptResult::Fail));
-    assert_eq!(execute(&[], &[], &[Instruction::Fail], H256::default(), Config::default()), Ok(ScriptResult::Fail));
+    assert_eq!(
+        execute(
+            &[Instruction::Push(0)],
+            &[],
+            &[],
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Ok(ScriptResult::Fail)
+    );
+    assert_eq!(
+        execute(
+            &[],
+            &[],
+            &[Instruction::Fail],
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Ok(ScriptResult::Fail)
+    );
 }
 
 #[test]
 fn simple_burn() {
-    assert_eq!(execute(&[], &[], &[Instruction::Burn], H256::default(), Config::default()), Ok(ScriptResult::Burnt));
+    assert_eq!(
+        execute(
+            &[],
+            &[],
+            &[Instruction::Burn],
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Ok(ScriptResult::Burnt)
+    );
 }
 
 #[test]
 fn underflow() {
     assert_eq!(
-        execute(&[], &[], &[Instruction::Pop], H256::default(), Config::default()),
+        execute(
+            &[],
+            &[],
+            &[Instruction::Pop],
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
         Err(RuntimeError::StackUnderflow)
     );
 }
@@ -59,53 +149,135 @@ fn underflow() {
 fn out_of_memory() {
     let config = Config {
         max_memory: 2,
+        ..Config::default()
     };
     assert_eq!(
-        execute(&[Instruction::Push(0), Instruction::Push(1), Instruction::Push(2)], &[], &[], H256::default(), config),
+        execute(
+            &[Instruction::Push(0), Instruction::Push(1), Instruction::Push(2)],
+            &[],
+            &[],
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            config
+        ),
         Err(RuntimeError::OutOfMemory)
     );
 }
 
+#[test]
+fn out_of_gas() {
+    let lock = [Instruction::Blake256, Instruction::Blake256, Instruction::Blake256];
+    let config = Config {
+        max_cost: 30,
+        ..Config::default()
+    };
+    assert_eq!(
+        execute(&[Instruction::Push(1)], &[], &lock, &dummy_tx(), &dummy_input(), ChainTimeInfo::default(), config),
+        Err(RuntimeError::OutOfGas)
+    );
+
+    let config = Config {
+        max_cost: 31,
+        ..Config::default()
+    };
+    assert_eq!(
+        execute(&[Instruction::Push(1)], &[], &lock, &dummy_tx(), &dummy_input(), ChainTimeInfo::default(), config),
+        Ok(ScriptResult::Unlocked)
+    );
+}
+
 #[test]
 fn invalid_unlock_script() {
-    assert_eq!(execute(&[Instruction::Nop], &[], &[], H256::default(), Config::default()), Ok(ScriptResult::Fail));
+    assert_eq!(
+        execute(
+            &[Instruction::Nop],
+            &[],
+            &[],
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Ok(ScriptResult::Fail)
+    );
 }
 
 #[test]
 fn valid_pay_to_public_key() {
     let keypair = KeyPair::from_private(Private::from(SecretKey::from(ONE_KEY))).unwrap();
     let pubkey = <&[u8]>::from(keypair.public()).to_vec();
-    let message = blake256("asdf");
+    let transaction = dummy_tx();
+    let message = transaction.hash_without_script();
     let signature = Signature::from(sign(keypair.private(), &message).unwrap()).to_vec();
     let unlock_script = vec![Instruction::PushB(signature)];
     let lock_script = vec![Instruction::PushB(pubkey), Instruction::ChkSig];
 
-    assert_eq!(execute(&unlock_script, &[], &lock_script, message, Config::default()), Ok(ScriptResult::Unlocked));
+    assert_eq!(
+        execute(
+            &unlock_script,
+            &[],
+            &lock_script,
+            &transaction,
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Ok(ScriptResult::Unlocked)
+    );
 }
 
 #[test]
 fn invalid_pay_to_public_key() {
     let keypair = KeyPair::from_private(Private::from(SecretKey::from(ONE_KEY))).unwrap();
     let pubkey = <&[u8]>::from(keypair.public()).to_vec();
-    let message = blake256("asdf");
+    let transaction = dummy_tx();
+    let message = transaction.hash_without_script();
     let lock_script = vec![Instruction::PushB(pubkey), Instruction::ChkSig];
 
     let invalid_keypair = KeyPair::from_private(Private::from(SecretKey::from(MINUS_ONE_KEY))).unwrap();
     let invalid_signature = Signature::from(sign(invalid_keypair.private(), &message).unwrap()).to_vec();
     let unlock_script = vec![Instruction::PushB(invalid_signature)];
 
-    assert_eq!(execute(&unlock_script[..], &[], &lock_script, message, Config::default()), Ok(ScriptResult::Fail));
+    assert_eq!(
+        execute(
+            &unlock_script[..],
+            &[],
+            &lock_script,
+            &transaction,
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Ok(ScriptResult::Fail)
+    );
 }
 
 #[test]
 fn conditional_burn() {
     let lock_script = vec![Instruction::Eq, Instruction::Dup, Instruction::Jnz(1), Instruction::Burn];
     assert_eq!(
-        execute(&[Instruction::Push(0)], &[vec![0]], &lock_script, H256::default(), Config::default()),
+        execute(
+            &[Instruction::Push(0)],
+            &[vec![0]],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
         Ok(ScriptResult::Unlocked)
     );
     assert_eq!(
-        execute(&[Instruction::Push(0)], &[vec![1]], &lock_script, H256::default(), Config::default()),
+        execute(
+            &[Instruction::Push(0)],
+            &[vec![1]],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
         Ok(ScriptResult::Burnt)
     );
 }
@@ -114,19 +286,51 @@ fn conditional_burn() {
 fn test_blake256() {
     let lock_script = vec![Instruction::Blake256, Instruction::Eq];
     assert_eq!(
-        execute(&[], &[vec![], BLAKE_EMPTY.to_vec()], &lock_script, H256::default(), Config::default()),
+        execute(
+            &[],
+            &[vec![], BLAKE_EMPTY.to_vec()],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
         Ok(ScriptResult::Unlocked)
     );
     assert_eq!(
-        execute(&[], &[vec![], BLAKE_NULL_RLP.to_vec()], &lock_script, H256::default(), Config::default()),
+        execute(
+            &[],
+            &[vec![], BLAKE_NULL_RLP.to_vec()],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
         Ok(ScriptResult::Fail)
     );
     assert_eq!(
-        execute(&[], &[vec![0x80], BLAKE_NULL_RLP.to_vec()], &lock_script, H256::default(), Config::default()),
+        execute(
+            &[],
+            &[vec![0x80], BLAKE_NULL_RLP.to_vec()],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
         Ok(ScriptResult::Unlocked)
     );
     assert_eq!(
-        execute(&[], &[vec![0x80], BLAKE_EMPTY.to_vec()], &lock_script, H256::default(), Config::default()),
+        execute(
+            &[],
+            &[vec![0x80], BLAKE_EMPTY.to_vec()],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
         Ok(ScriptResult::Fail)
     );
 }
@@ -143,19 +347,51 @@ fn test_ripemd160() {
     ]);
     let lock_script = vec![Instruction::Ripemd160, Instruction::Eq];
     assert_eq!(
-        execute(&[], &[vec![], RIPEMD160_EMPTY.to_vec()], &lock_script, H256::default(), Config::default()),
+        execute(
+            &[],
+            &[vec![], RIPEMD160_EMPTY.to_vec()],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
         Ok(ScriptResult::Unlocked)
     );
     assert_eq!(
-        execute(&[], &[vec![], RIPEMD160_NULL_RLP.to_vec()], &lock_script, H256::default(), Config::default()),
+        execute(
+            &[],
+            &[vec![], RIPEMD160_NULL_RLP.to_vec()],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
         Ok(ScriptResult::Fail)
     );
     assert_eq!(
-        execute(&[], &[vec![0x80], RIPEMD160_NULL_RLP.to_vec()], &lock_script, H256::default(), Config::default()),
+        execute(
+            &[],
+            &[vec![0x80], RIPEMD160_NULL_RLP.to_vec()],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
         Ok(ScriptResult::Unlocked)
     );
     assert_eq!(
-        execute(&[], &[vec![0x80], RIPEMD160_EMPTY.to_vec()], &lock_script, H256::default(), Config::default()),
+        execute(
+            &[],
+            &[vec![0x80], RIPEMD160_EMPTY.to_vec()],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
         Ok(ScriptResult::Fail)
     );
 }
@@ -172,19 +408,51 @@ fn test_sha256() {
     ]);
     let lock_script = vec![Instruction::Sha256, Instruction::Eq];
     assert_eq!(
-        execute(&[], &[vec![], SHA256_EMPTY.to_vec()], &lock_script, H256::default(), Config::default()),
+        execute(
+            &[],
+            &[vec![], SHA256_EMPTY.to_vec()],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
         Ok(ScriptResult::Unlocked)
     );
     assert_eq!(
-        execute(&[], &[vec![], SHA256_NULL_RLP.to_vec()], &lock_script, H256::default(), Config::default()),
+        execute(
+            &[],
+            &[vec![], SHA256_NULL_RLP.to_vec()],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
         Ok(ScriptResult::Fail)
     );
     assert_eq!(
-        execute(&[], &[vec![0x80], SHA256_NULL_RLP.to_vec()], &lock_script, H256::default(), Config::default()),
+        execute(
+            &[],
+            &[vec![0x80], SHA256_NULL_RLP.to_vec()],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
         Ok(ScriptResult::Unlocked)
     );
     assert_eq!(
-        execute(&[], &[vec![0x80], SHA256_EMPTY.to_vec()], &lock_script, H256::default(), Config::default()),
+        execute(
+            &[],
+            &[vec![0x80], SHA256_EMPTY.to_vec()],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
         Ok(ScriptResult::Fail)
     );
 }
@@ -201,19 +469,923 @@ fn test_keccak256() {
     ]);
     let lock_script = vec![Instruction::Keccak256, Instruction::Eq];
     assert_eq!(
-        execute(&[], &[vec![], KECCAK256_EMPTY.to_vec()], &lock_script, H256::default(), Config::default()),
+        execute(
+            &[],
+            &[vec![], KECCAK256_EMPTY.to_vec()],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Ok(ScriptResult::Unlocked)
+    );
+    assert_eq!(
+        execute(
+            &[],
+            &[vec![], KECCAK256_NULL_RLP.to_vec()],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Ok(ScriptResult::Fail)
+    );
+    assert_eq!(
+        execute(
+            &[],
+            &[vec![0x80], KECCAK256_NULL_RLP.to_vec()],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Ok(ScriptResult::Unlocked)
+    );
+    assert_eq!(
+        execute(
+            &[],
+            &[vec![0x80], KECCAK256_EMPTY.to_vec()],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Ok(ScriptResult::Fail)
+    );
+}
+
+#[test]
+fn jump_out_of_lock_script() {
+    let lock_script = vec![Instruction::Jmp(2), Instruction::Nop];
+    assert_eq!(
+        execute(
+            &[Instruction::Push(1)],
+            &[],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Err(RuntimeError::InvalidJump)
+    );
+
+    let lock_script = vec![Instruction::Jnz(1), Instruction::Fail];
+    assert_eq!(
+        execute(
+            &[Instruction::Push(1), Instruction::Push(1)],
+            &[],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Ok(ScriptResult::Unlocked)
+    );
+}
+
+#[test]
+fn unlock_script_does_not_affect_lock_script_jump() {
+    let lock_script = vec![Instruction::Jz(1), Instruction::Fail, Instruction::Success];
+    let short_unlock = vec![Instruction::Push(0)];
+    let long_unlock = vec![Instruction::Push(1), Instruction::Push(1), Instruction::Push(1), Instruction::Push(0)];
+
+    assert_eq!(
+        execute(
+            &short_unlock,
+            &[],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Ok(ScriptResult::Unlocked)
+    );
+    assert_eq!(
+        execute(
+            &long_unlock,
+            &[],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Ok(ScriptResult::Unlocked)
+    );
+}
+
+#[test]
+fn unlock_script_stack_is_handed_to_lock_script() {
+    let unlock_script = vec![Instruction::PushB(vec![1, 2, 3])];
+    let lock_script = vec![Instruction::Eq];
+    assert_eq!(
+        execute(
+            &unlock_script,
+            &[vec![1, 2, 3]],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Ok(ScriptResult::Unlocked)
+    );
+    assert_eq!(
+        execute(
+            &unlock_script,
+            &[vec![3, 2, 1]],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Ok(ScriptResult::Fail)
+    );
+}
+
+#[test]
+fn timelock_block_number() {
+    let lock_script = vec![Instruction::ChkTimelock(TIMELOCK_TYPE_BLOCK)];
+    let chain_time = ChainTimeInfo {
+        block_number: 10,
+        block_timestamp: 0,
+        output_block_number: 0,
+    };
+    assert_eq!(
+        execute(&[], &[vec![10]], &lock_script, &dummy_tx(), &dummy_input(), chain_time, Config::default()),
+        Ok(ScriptResult::Unlocked)
+    );
+    assert_eq!(
+        execute(&[], &[vec![11]], &lock_script, &dummy_tx(), &dummy_input(), chain_time, Config::default()),
+        Ok(ScriptResult::Fail)
+    );
+}
+
+#[test]
+fn timelock_timestamp() {
+    let lock_script = vec![Instruction::ChkTimelock(TIMELOCK_TYPE_TIME)];
+    let chain_time = ChainTimeInfo {
+        block_number: 0,
+        block_timestamp: 0x5B6A_2C00,
+        output_block_number: 0,
+    };
+    assert_eq!(
+        execute(
+            &[],
+            &[vec![0x5B, 0x6A, 0x2B, 0xFF]],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            chain_time,
+            Config::default()
+        ),
+        Ok(ScriptResult::Unlocked)
+    );
+    assert_eq!(
+        execute(
+            &[],
+            &[vec![0x5B, 0x6A, 0x2C, 0x01]],
+            &lock_script,
+            &dummy_tx(),
+            &dummy_input(),
+            chain_time,
+            Config::default()
+        ),
+        Ok(ScriptResult::Fail)
+    );
+}
+
+#[test]
+fn timelock_block_age() {
+    let lock_script = vec![Instruction::ChkTimelock(TIMELOCK_TYPE_BLOCK_AGE)];
+    let chain_time = ChainTimeInfo {
+        block_number: 15,
+        block_timestamp: 0,
+        output_block_number: 10,
+    };
+    assert_eq!(
+        execute(&[], &[vec![5]], &lock_script, &dummy_tx(), &dummy_input(), chain_time, Config::default()),
+        Ok(ScriptResult::Unlocked)
+    );
+    assert_eq!(
+        execute(&[], &[vec![6]], &lock_script, &dummy_tx(), &dummy_input(), chain_time, Config::default()),
+        Ok(ScriptResult::Fail)
+    );
+}
+
+#[test]
+fn timelock_invalid_operand() {
+    assert_eq!(
+        execute(
+            &[],
+            &[vec![0]],
+            &[Instruction::ChkTimelock(0)],
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Err(RuntimeError::InvalidTimelockType)
+    );
+    assert_eq!(
+        execute(
+            &[],
+            &[vec![0; 9]],
+            &[Instruction::ChkTimelock(TIMELOCK_TYPE_BLOCK)],
+            &dummy_tx(),
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Err(RuntimeError::TypeMismatch)
+    );
+}
+
+pub fn multisig_keypairs() -> Vec<KeyPair> {
+    vec![
+        KeyPair::from_private(Private::from(SecretKey::from(ONE_KEY))).unwrap(),
+        KeyPair::from_private(Private::from(SecretKey::from(MINUS_ONE_KEY))).unwrap(),
+        KeyPair::from_private(Private::from(H256::from(3))).unwrap(),
+    ]
+}
+
+fn multisig_lock_script_of(required: u8, keypairs: &[KeyPair]) -> Vec<Instruction> {
+    let pubkeys: Vec<_> = keypairs.iter().map(|keypair| *keypair.public()).collect();
+    decode(&multisig_lock_script(required, &pubkeys)).unwrap()
+}
+
+#[test]
+fn valid_multisig() {
+    let keypairs = multisig_keypairs();
+    let transaction = dummy_tx();
+    let message = transaction.hash_without_script();
+    let signatures: Vec<_> =
+        keypairs.iter().map(|keypair| Signature::from(sign(keypair.private(), &message).unwrap()).to_vec()).collect();
+    let lock_script = multisig_lock_script_of(2, &keypairs);
+
+    let unlock_script = vec![Instruction::PushB(signatures[0].clone()), Instruction::PushB(signatures[1].clone())];
+    assert_eq!(
+        execute(
+            &unlock_script,
+            &[],
+            &lock_script,
+            &transaction,
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Ok(ScriptResult::Unlocked)
+    );
+
+    let unlock_script = vec![Instruction::PushB(signatures[0].clone()), Instruction::PushB(signatures[2].clone())];
+    assert_eq!(
+        execute(
+            &unlock_script,
+            &[],
+            &lock_script,
+            &transaction,
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Ok(ScriptResult::Unlocked)
+    );
+}
+
+#[test]
+fn multisig_with_unordered_signatures() {
+    let keypairs = multisig_keypairs();
+    let transaction = dummy_tx();
+    let message = transaction.hash_without_script();
+    let signatures: Vec<_> =
+        keypairs.iter().map(|keypair| Signature::from(sign(keypair.private(), &message).unwrap()).to_vec()).collect();
+    let lock_script = multisig_lock_script_of(2, &keypairs);
+
+    let unlock_script = vec![Instruction::PushB(signatures[1].clone()), Instruction::PushB(signatures[0].clone())];
+    assert_eq!(
+        execute(
+            &unlock_script,
+            &[],
+            &lock_script,
+            &transaction,
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Ok(ScriptResult::Fail)
+    );
+}
+
+#[test]
+fn multisig_with_duplicated_signatures() {
+    let keypairs = multisig_keypairs();
+    let transaction = dummy_tx();
+    let message = transaction.hash_without_script();
+    let signature = Signature::from(sign(keypairs[0].private(), &message).unwrap()).to_vec();
+    let lock_script = multisig_lock_script_of(2, &keypairs);
+
+    let unlock_script = vec![Instruction::PushB(signature.clone()), Instruction::PushB(signature)];
+    assert_eq!(
+        execute(
+            &unlock_script,
+            &[],
+            &lock_script,
+            &transaction,
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Ok(ScriptResult::Fail)
+    );
+}
+
+#[test]
+fn multisig_with_invalid_sig_count() {
+    let keypairs = multisig_keypairs();
+    let transaction = dummy_tx();
+    let message = transaction.hash_without_script();
+    let signature = Signature::from(sign(keypairs[0].private(), &message).unwrap()).to_vec();
+
+    let unlock_script = vec![Instruction::PushB(signature)];
+    let mut lock_script = multisig_lock_script_of(1, &keypairs);
+    lock_script[0] = Instruction::Push(0);
+    assert_eq!(
+        execute(
+            &unlock_script,
+            &[],
+            &lock_script,
+            &transaction,
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Err(RuntimeError::InvalidSigCount)
+    );
+    lock_script[0] = Instruction::Push(4);
+    assert_eq!(
+        execute(
+            &unlock_script,
+            &[],
+            &lock_script,
+            &transaction,
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Err(RuntimeError::InvalidSigCount)
+    );
+}
+
+#[test]
+fn multisig_is_charged_for_each_public_key() {
+    let keypairs = multisig_keypairs();
+    let transaction = dummy_tx();
+    let message = transaction.hash_without_script();
+    let signatures: Vec<_> =
+        keypairs.iter().map(|keypair| Signature::from(sign(keypair.private(), &message).unwrap()).to_vec()).collect();
+    let unlock_script = vec![Instruction::PushB(signatures[0].clone()), Instruction::PushB(signatures[1].clone())];
+    let lock_script = multisig_lock_script_of(2, &keypairs);
+
+    // 8 instructions and 3 public keys
+    let config = Config {
+        max_cost: 307,
+        ..Config::default()
+    };
+    assert_eq!(
+        execute(&unlock_script, &[], &lock_script, &transaction, &dummy_input(), ChainTimeInfo::default(), config),
+        Err(RuntimeError::OutOfGas)
+    );
+
+    let config = Config {
+        max_cost: 308,
+        ..Config::default()
+    };
+    assert_eq!(
+        execute(&unlock_script, &[], &lock_script, &transaction, &dummy_input(), ChainTimeInfo::default(), config),
+        Ok(ScriptResult::Unlocked)
+    );
+}
+
+#[test]
+fn too_many_public_keys_run_out_of_gas() {
+    for instruction in &[Instruction::ChkMultiSig, Instruction::ChkAggSchnorrSig] {
+        let lock_script = vec![Instruction::Push(255), instruction.clone()];
+        assert_eq!(
+            execute(&[], &[], &lock_script, &dummy_tx(), &dummy_input(), ChainTimeInfo::default(), Config::default()),
+            Err(RuntimeError::OutOfGas)
+        );
+    }
+}
+
+#[test]
+fn more_public_keys_than_stack_items_underflow() {
+    for instruction in &[Instruction::ChkMultiSig, Instruction::ChkAggSchnorrSig] {
+        let lock_script = vec![Instruction::Push(255), instruction.clone()];
+        let config = Config {
+            max_cost: 100_000,
+            ..Config::default()
+        };
+        assert_eq!(
+            execute(&[], &[], &lock_script, &dummy_tx(), &dummy_input(), ChainTimeInfo::default(), config),
+            Err(RuntimeError::StackUnderflow)
+        );
+    }
+}
+
+#[test]
+fn trace_records_steps_of_both_frames() {
+    let unlock = [Instruction::Push(1)];
+    let lock = [Instruction::Push(2), Instruction::Eq];
+    let result =
+        trace(&unlock, &[vec![2]], &lock, &dummy_tx(), &dummy_input(), ChainTimeInfo::default(), Config::default());
+    assert_eq!(result.result, Ok(ScriptResult::Fail));
+    assert_eq!(
+        result.steps,
+        vec![
+            TraceStep {
+                frame: Frame::Unlock,
+                pc: 0,
+                instruction: Instruction::Push(1),
+                stack: vec![],
+            },
+            TraceStep {
+                frame: Frame::Lock,
+                pc: 0,
+                instruction: Instruction::Push(2),
+                stack: vec![vec![1], vec![2]],
+            },
+            TraceStep {
+                frame: Frame::Lock,
+                pc: 1,
+                instruction: Instruction::Eq,
+                stack: vec![vec![1], vec![2], vec![2]],
+            },
+        ]
+    );
+}
+
+#[test]
+fn trace_stops_at_runtime_error() {
+    let lock = [Instruction::Jmp(0), Instruction::Pop, Instruction::Push(1)];
+    let result = trace(&[], &[], &lock, &dummy_tx(), &dummy_input(), ChainTimeInfo::default(), Config::default());
+    assert_eq!(result.result, Err(RuntimeError::StackUnderflow));
+    let pcs: Vec<_> = result.steps.iter().map(|step| step.pc).collect();
+    assert_eq!(pcs, vec![0, 1]);
+}
+
+fn execute_lock(lock: &[Instruction]) -> Result<ScriptResult, RuntimeError> {
+    execute(&[], &[], lock, &dummy_tx(), &dummy_input(), ChainTimeInfo::default(), Config::default())
+}
+
+#[test]
+fn arithmetic() {
+    let cases = vec![
+        (Instruction::Add, 7, 2, 9),
+        (Instruction::Sub, 7, 2, 5),
+        (Instruction::Mul, 7, 2, 14),
+        (Instruction::Div, 7, 2, 3),
+        (Instruction::Mod, 7, 2, 1),
+        (Instruction::Min, 7, 2, 2),
+        (Instruction::Max, 7, 2, 7),
+    ];
+    for (op, lhs, rhs, result) in cases {
+        let lock = [Instruction::Push(lhs), Instruction::Push(rhs), op, Instruction::Push(result), Instruction::Eq];
+        assert_eq!(execute_lock(&lock), Ok(ScriptResult::Unlocked));
+    }
+}
+
+#[test]
+fn arithmetic_result_is_minimal_big_endian() {
+    let lock = [
+        Instruction::PushB(vec![0x00, 0xff]),
+        Instruction::Push(1),
+        Instruction::Add,
+        Instruction::PushB(vec![0x01, 0x00]),
+        Instruction::Eq,
+    ];
+    assert_eq!(execute_lock(&lock), Ok(ScriptResult::Unlocked));
+
+    let lock =
+        [Instruction::Push(3), Instruction::Push(3), Instruction::Sub, Instruction::PushB(vec![]), Instruction::Eq];
+    assert_eq!(execute_lock(&lock), Ok(ScriptResult::Unlocked));
+}
+
+#[test]
+fn comparison() {
+    let cases = vec![
+        (Instruction::Lt, 1, 2, true),
+        (Instruction::Lt, 2, 2, false),
+        (Instruction::Gt, 2, 1, true),
+        (Instruction::Gt, 2, 2, false),
+        (Instruction::Le, 2, 2, true),
+        (Instruction::Le, 3, 2, false),
+        (Instruction::Ge, 2, 2, true),
+        (Instruction::Ge, 1, 2, false),
+    ];
+    for (op, lhs, rhs, result) in cases {
+        let expected = if result {
+            ScriptResult::Unlocked
+        } else {
+            ScriptResult::Fail
+        };
+        assert_eq!(execute_lock(&[Instruction::Push(lhs), Instruction::Push(rhs), op]), Ok(expected));
+    }
+}
+
+#[test]
+fn integer_overflow() {
+    let max = Instruction::PushB(vec![0xff; 8]);
+    assert_eq!(
+        execute_lock(&[max.clone(), Instruction::Push(1), Instruction::Add]),
+        Err(RuntimeError::IntegerOverflow)
+    );
+    assert_eq!(
+        execute_lock(&[max.clone(), Instruction::Push(2), Instruction::Mul]),
+        Err(RuntimeError::IntegerOverflow)
+    );
+    assert_eq!(
+        execute_lock(&[Instruction::Push(1), Instruction::Push(2), Instruction::Sub]),
+        Err(RuntimeError::IntegerOverflow)
+    );
+}
+
+#[test]
+fn division_by_zero() {
+    assert_eq!(
+        execute_lock(&[Instruction::Push(1), Instruction::Push(0), Instruction::Div]),
+        Err(RuntimeError::DivisionByZero)
+    );
+    assert_eq!(
+        execute_lock(&[Instruction::Push(1), Instruction::Push(0), Instruction::Mod]),
+        Err(RuntimeError::DivisionByZero)
+    );
+}
+
+#[test]
+fn integer_longer_than_8_bytes() {
+    assert_eq!(
+        execute_lock(&[Instruction::PushB(vec![0; 9]), Instruction::Push(1), Instruction::Add]),
+        Err(RuntimeError::TypeMismatch)
+    );
+}
+
+fn introspection_tx() -> (Transaction, AssetTransferInput) {
+    let asset_type = H256::random();
+    let input = |amount, lock_script: Vec<u8>| AssetTransferInput {
+        prev_out: AssetOutPoint {
+            transaction_hash: H256::random(),
+            index: 0,
+            asset_type,
+            shard_id: 0,
+            amount,
+        },
+        lock_script,
+        unlock_script: Vec::new(),
+    };
+    let cur = input(30, vec![0x02]);
+    let change_lock_script_hash = blake256(&cur.lock_script);
+    let transaction = Transaction::AssetTransfer {
+        network_id: 0,
+        burns: Vec::new(),
+        inputs: vec![cur.clone(), input(5, vec![0x03])],
+        outputs: vec![
+            AssetTransferOutput {
+                lock_script_hash: H256::random(),
+                parameters: Vec::new(),
+                asset_type,
+                shard_id: 0,
+                amount: 25,
+            },
+            AssetTransferOutput {
+                lock_script_hash: change_lock_script_hash,
+                parameters: Vec::new(),
+                asset_type,
+                shard_id: 0,
+                amount: 10,
+            },
+        ],
+        nonce: 0,
+    };
+    (transaction, cur)
+}
+
+fn execute_introspection(lock: &[Instruction]) -> Result<ScriptResult, RuntimeError> {
+    let (transaction, cur) = introspection_tx();
+    execute(&[], &[], lock, &transaction, &cur, ChainTimeInfo::default(), Config::default())
+}
+
+#[test]
+fn input_and_output_count() {
+    let lock = [Instruction::InputCount, Instruction::Push(2), Instruction::Eq];
+    assert_eq!(execute_introspection(&lock), Ok(ScriptResult::Unlocked));
+    let lock = [Instruction::OutputCount, Instruction::Push(2), Instruction::Eq];
+    assert_eq!(execute_introspection(&lock), Ok(ScriptResult::Unlocked));
+}
+
+#[test]
+fn amounts_of_inputs_and_outputs() {
+    // inputs[0] + inputs[1] == outputs[0] + outputs[1]
+    let lock = [
+        Instruction::Push(0),
+        Instruction::InputAmount,
+        Instruction::Push(1),
+        Instruction::InputAmount,
+        Instruction::Add,
+        Instruction::Push(0),
+        Instruction::OutputAmount,
+        Instruction::Push(1),
+        Instruction::OutputAmount,
+        Instruction::Add,
+        Instruction::Eq,
+    ];
+    assert_eq!(execute_introspection(&lock), Ok(ScriptResult::Unlocked));
+
+    let lock = [Instruction::CurAmount, Instruction::Push(30), Instruction::Eq];
+    assert_eq!(execute_introspection(&lock), Ok(ScriptResult::Unlocked));
+}
+
+#[test]
+fn asset_types_of_inputs_and_outputs() {
+    let lock = [Instruction::Push(1), Instruction::InputAssetType, Instruction::CurAssetType, Instruction::Eq];
+    assert_eq!(execute_introspection(&lock), Ok(ScriptResult::Unlocked));
+    let lock = [Instruction::Push(0), Instruction::OutputAssetType, Instruction::CurAssetType, Instruction::Eq];
+    assert_eq!(execute_introspection(&lock), Ok(ScriptResult::Unlocked));
+}
+
+#[test]
+fn change_must_be_paid_back_to_the_same_lock() {
+    let lock_of_output =
+        |index| [Instruction::Push(index), Instruction::OutputLockHash, Instruction::CurLockHash, Instruction::Eq];
+    assert_eq!(execute_introspection(&lock_of_output(1)), Ok(ScriptResult::Unlocked));
+    assert_eq!(execute_introspection(&lock_of_output(0)), Ok(ScriptResult::Fail));
+}
+
+#[test]
+fn introspection_out_of_bound() {
+    assert_eq!(
+        execute_introspection(&[Instruction::Push(2), Instruction::InputAmount]),
+        Err(RuntimeError::IndexOutOfBound)
+    );
+    assert_eq!(
+        execute_introspection(&[Instruction::Push(2), Instruction::OutputLockHash]),
+        Err(RuntimeError::IndexOutOfBound)
+    );
+}
+
+#[test]
+fn valid_pay_to_schnorr_public_key() {
+    let keypair = KeyPair::from_private(Private::from(H256::from(2))).unwrap();
+    let transaction = dummy_tx();
+    let signature = sign_schnorr(keypair.private(), &transaction.hash_without_script()).unwrap().to_vec();
+    let lock_script = vec![Instruction::PushB(keypair.public().to_vec()), Instruction::ChkSchnorrSig];
+
+    assert_eq!(
+        execute(
+            &[Instruction::PushB(signature)],
+            &[],
+            &lock_script,
+            &transaction,
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
         Ok(ScriptResult::Unlocked)
     );
+}
+
+#[test]
+fn invalid_pay_to_schnorr_public_key() {
+    let keypair = KeyPair::from_private(Private::from(H256::from(2))).unwrap();
+    let invalid_keypair = KeyPair::from_private(Private::from(H256::from(3))).unwrap();
+    let transaction = dummy_tx();
+    let signature = sign_schnorr(invalid_keypair.private(), &transaction.hash_without_script()).unwrap().to_vec();
+    let lock_script = vec![Instruction::PushB(keypair.public().to_vec()), Instruction::ChkSchnorrSig];
+
     assert_eq!(
-        execute(&[], &[vec![], KECCAK256_NULL_RLP.to_vec()], &lock_script, H256::default(), Config::default()),
+        execute(
+            &[Instruction::PushB(signature)],
+            &[],
+            &lock_script,
+            &transaction,
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
         Ok(ScriptResult::Fail)
     );
+}
+
+#[test]
+fn aggregated_schnorr_signature() {
+    let keypairs = [
+        KeyPair::from_private(Private::from(H256::from(2))).unwrap(),
+        KeyPair::from_private(Private::from(H256::from(3))).unwrap(),
+    ];
+    let lock_script = vec![
+        Instruction::PushB(keypairs[0].public().to_vec()),
+        Instruction::PushB(keypairs[1].public().to_vec()),
+        Instruction::Push(2),
+        Instruction::ChkAggSchnorrSig,
+    ];
+    let transaction = dummy_tx();
+    let message = transaction.hash_without_script();
+    let publics = [*keypairs[0].public(), *keypairs[1].public()];
+
+    let shares: Vec<_> = keypairs
+        .iter()
+        .map(|keypair| {
+            let share = aggregate_schnorr_private_share(&publics, keypair.private()).unwrap();
+            SecretKey::from_slice(&SECP256K1, &share).unwrap()
+        })
+        .collect();
+    let mut sum = shares[0];
+    sum.add_assign(&SECP256K1, &shares[1]).unwrap();
+    let signature = sign_schnorr(&Private::from(sum), &message).unwrap().to_vec();
     assert_eq!(
-        execute(&[], &[vec![0x80], KECCAK256_NULL_RLP.to_vec()], &lock_script, H256::default(), Config::default()),
+        execute(
+            &[Instruction::PushB(signature)],
+            &[],
+            &lock_script,
+            &transaction,
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
         Ok(ScriptResult::Unlocked)
     );
+
+    let signature = sign_schnorr(keypairs[0].private(), &message).unwrap().to_vec();
     assert_eq!(
-        execute(&[], &[vec![0x80], KECCAK256_EMPTY.to_vec()], &lock_script, H256::default(), Config::default()),
+        execute(
+            &[Instruction::PushB(signature)],
+            &[],
+            &lock_script,
+            &transaction,
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
         Ok(ScriptResult::Fail)
     );
 }
+
+fn sign_with_sighash(transaction: &Transaction, cur: &AssetTransferInput, sighash_type: u8) -> Vec<u8> {
+    let keypair = KeyPair::from_private(Private::from(SecretKey::from(ONE_KEY))).unwrap();
+    let message = sighash(transaction, cur, sighash_type).unwrap();
+    let mut signature = Signature::from(sign(keypair.private(), &message).unwrap()).to_vec();
+    signature.push(sighash_type);
+    signature
+}
+
+fn execute_pay_to_public_key(
+    signature: Vec<u8>,
+    transaction: &Transaction,
+    cur: &AssetTransferInput,
+) -> Result<ScriptResult, RuntimeError> {
+    let keypair = KeyPair::from_private(Private::from(SecretKey::from(ONE_KEY))).unwrap();
+    let lock_script = vec![Instruction::PushB(keypair.public().to_vec()), Instruction::ChkSig];
+    execute(
+        &[Instruction::PushB(signature)],
+        &[],
+        &lock_script,
+        transaction,
+        cur,
+        ChainTimeInfo::default(),
+        Config::default(),
+    )
+}
+
+fn modify_transfer<F>(transaction: &Transaction, f: F) -> Transaction
+where
+    F: FnOnce(&mut Vec<AssetTransferInput>, &mut Vec<AssetTransferOutput>), {
+    let mut transaction = transaction.clone();
+    if let Transaction::AssetTransfer {
+        inputs,
+        outputs,
+        ..
+    } = &mut transaction
+    {
+        f(inputs, outputs);
+    }
+    transaction
+}
+
+fn another_input() -> AssetTransferInput {
+    AssetTransferInput {
+        prev_out: AssetOutPoint {
+            transaction_hash: H256::random(),
+            index: 1,
+            asset_type: H256::random(),
+            shard_id: 0,
+            amount: 100,
+        },
+        lock_script: vec![0x04],
+        unlock_script: Vec::new(),
+    }
+}
+
+fn another_output() -> AssetTransferOutput {
+    AssetTransferOutput {
+        lock_script_hash: H256::random(),
+        parameters: Vec::new(),
+        asset_type: H256::random(),
+        shard_id: 0,
+        amount: 100,
+    }
+}
+
+#[test]
+fn sighash_all() {
+    let (transaction, cur) = introspection_tx();
+    let signature = sign_with_sighash(&transaction, &cur, SIGHASH_ALL);
+    assert_eq!(execute_pay_to_public_key(signature.clone(), &transaction, &cur), Ok(ScriptResult::Unlocked));
+
+    let added_input = modify_transfer(&transaction, |inputs, _| inputs.push(another_input()));
+    assert_eq!(execute_pay_to_public_key(signature.clone(), &added_input, &cur), Ok(ScriptResult::Fail));
+    let added_output = modify_transfer(&transaction, |_, outputs| outputs.push(another_output()));
+    assert_eq!(execute_pay_to_public_key(signature, &added_output, &cur), Ok(ScriptResult::Fail));
+}
+
+#[test]
+fn signature_with_sighash_type_does_not_sign_the_whole_transaction() {
+    let (transaction, cur) = introspection_tx();
+    let keypair = KeyPair::from_private(Private::from(SecretKey::from(ONE_KEY))).unwrap();
+    let mut signature = Signature::from(sign(keypair.private(), &transaction.hash_without_script()).unwrap()).to_vec();
+    assert_eq!(execute_pay_to_public_key(signature.clone(), &transaction, &cur), Ok(ScriptResult::Unlocked));
+    signature.push(SIGHASH_ALL);
+    assert_eq!(execute_pay_to_public_key(signature, &transaction, &cur), Ok(ScriptResult::Fail));
+}
+
+#[test]
+fn sighash_anyone_can_pay() {
+    let (transaction, cur) = introspection_tx();
+    let signature = sign_with_sighash(&transaction, &cur, SIGHASH_ALL | SIGHASH_ANYONECANPAY);
+
+    let added_input = modify_transfer(&transaction, |inputs, _| inputs.push(another_input()));
+    assert_eq!(execute_pay_to_public_key(signature.clone(), &added_input, &cur), Ok(ScriptResult::Unlocked));
+    let removed_input = modify_transfer(&transaction, |inputs, _| {
+        inputs.pop();
+    });
+    assert_eq!(execute_pay_to_public_key(signature.clone(), &removed_input, &cur), Ok(ScriptResult::Unlocked));
+    let added_output = modify_transfer(&transaction, |_, outputs| outputs.push(another_output()));
+    assert_eq!(execute_pay_to_public_key(signature, &added_output, &cur), Ok(ScriptResult::Fail));
+}
+
+#[test]
+fn sighash_single() {
+    let (transaction, cur) = introspection_tx();
+    // The current input is at index 0, so only outputs[0] is signed.
+    let signature = sign_with_sighash(&transaction, &cur, SIGHASH_SINGLE | SIGHASH_ANYONECANPAY);
+
+    let swapped = modify_transfer(&transaction, |inputs, outputs| {
+        inputs.push(another_input());
+        outputs[1].amount = 5;
+        outputs.push(another_output());
+    });
+    assert_eq!(execute_pay_to_public_key(signature.clone(), &swapped, &cur), Ok(ScriptResult::Unlocked));
+    let modified_output = modify_transfer(&transaction, |_, outputs| outputs[0].amount = 24);
+    assert_eq!(execute_pay_to_public_key(signature, &modified_output, &cur), Ok(ScriptResult::Fail));
+}
+
+#[test]
+fn sighash_single_without_matching_output() {
+    let (transaction, cur) = introspection_tx();
+    let transaction = modify_transfer(&transaction, |_, outputs| {
+        outputs.clear();
+    });
+    let mut signature = vec![0; SIGNATURE_LENGTH];
+    signature.push(SIGHASH_SINGLE);
+    assert_eq!(execute_pay_to_public_key(signature, &transaction, &cur), Err(RuntimeError::IndexOutOfBound));
+}
+
+#[test]
+fn sighash_outputs_only() {
+    let (transaction, cur) = introspection_tx();
+    let signature = sign_with_sighash(&transaction, &cur, SIGHASH_ALL | SIGHASH_OUTPUTS_ONLY);
+
+    let replaced_inputs = modify_transfer(&transaction, |inputs, _| {
+        inputs.truncate(1);
+        inputs.push(another_input());
+    });
+    assert_eq!(execute_pay_to_public_key(signature.clone(), &replaced_inputs, &cur), Ok(ScriptResult::Unlocked));
+    let added_output = modify_transfer(&transaction, |_, outputs| outputs.push(another_output()));
+    assert_eq!(execute_pay_to_public_key(signature, &added_output, &cur), Ok(ScriptResult::Fail));
+}
+
+#[test]
+fn invalid_sighash_type() {
+    let (transaction, cur) = introspection_tx();
+    for sighash_type in &[0x00, 0x03, SIGHASH_ALL | SIGHASH_ANYONECANPAY | SIGHASH_OUTPUTS_ONLY] {
+        let mut signature = vec![0; SIGNATURE_LENGTH];
+        signature.push(*sighash_type);
+        assert_eq!(execute_pay_to_public_key(signature, &transaction, &cur), Err(RuntimeError::InvalidSigHashType));
+    }
+}
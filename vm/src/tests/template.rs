@@ -0,0 +1,83 @@
+// Copyright 2018 Kodebox, Inc.
+// This file is part of CodeChain.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use ckey::{sign, KeyPair, Private, Signature};
+
+use secp256k1::key::{SecretKey, MINUS_ONE_KEY, ONE_KEY};
+
+use decoder::decode;
+use executor::{execute, ChainTimeInfo, Config, ScriptResult};
+use instruction::Instruction;
+use template::{multisig_lock_script, multisig_unlock_script};
+
+use super::executor::{dummy_input, dummy_tx, multisig_keypairs};
+
+#[test]
+fn decode_multisig_lock_script() {
+    let keypair1 = KeyPair::from_private(Private::from(SecretKey::from(ONE_KEY))).unwrap();
+    let keypair2 = KeyPair::from_private(Private::from(SecretKey::from(MINUS_ONE_KEY))).unwrap();
+    let script = multisig_lock_script(1, &[*keypair1.public(), *keypair2.public()]);
+
+    assert_eq!(
+        decode(&script),
+        Ok(vec![
+            Instruction::Push(1),
+            Instruction::PushB(keypair1.public().to_vec()),
+            Instruction::PushB(keypair2.public().to_vec()),
+            Instruction::Push(2),
+            Instruction::ChkMultiSig,
+        ])
+    );
+}
+
+#[test]
+fn unlock_multisig_lock_script() {
+    let keypairs = multisig_keypairs();
+    let pubkeys: Vec<_> = keypairs.iter().map(|keypair| *keypair.public()).collect();
+    let transaction = dummy_tx();
+    let message = transaction.hash_without_script();
+    let signatures: Vec<Signature> =
+        keypairs.iter().map(|keypair| sign(keypair.private(), &message).unwrap()).collect();
+
+    let lock_script = decode(&multisig_lock_script(2, &pubkeys)).unwrap();
+    let unlock_script = decode(&multisig_unlock_script(&[signatures[1].clone(), signatures[2].clone()])).unwrap();
+    assert_eq!(
+        execute(
+            &unlock_script,
+            &[],
+            &lock_script,
+            &transaction,
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Ok(ScriptResult::Unlocked)
+    );
+
+    let unlock_script = decode(&multisig_unlock_script(&[signatures[2].clone(), signatures[1].clone()])).unwrap();
+    assert_eq!(
+        execute(
+            &unlock_script,
+            &[],
+            &lock_script,
+            &transaction,
+            &dummy_input(),
+            ChainTimeInfo::default(),
+            Config::default()
+        ),
+        Ok(ScriptResult::Fail)
+    );
+}